@@ -0,0 +1,188 @@
+//! Total-size budgets for embeds: the repo-wide `RUST_SILOS_MAX_TOTAL` environment variable
+//! and the per-call `max_total_size` option.
+
+/// Caps the bytes any single `embed_silo!` may embed, read at expansion time.
+pub const MAX_TOTAL_ENV: &str = "RUST_SILOS_MAX_TOTAL";
+
+/// Where the limit that applies to an embed came from.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LimitSource {
+    /// `RUST_SILOS_MAX_TOTAL`, with its value as written.
+    Env(String),
+    /// The call's own `max_total_size`.
+    CallSite,
+}
+
+/// The limit an embed is checked against.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Limit {
+    pub bytes: u64,
+    pub source: LimitSource,
+}
+
+/// Parses a byte count with an optional case-insensitive suffix: `B`, decimal `KB`/`MB`/`GB`
+/// (powers of 1000), or binary `K`/`M`/`G` and `KiB`/`MiB`/`GiB` (powers of 1024).
+/// Whitespace between the number and the suffix is allowed.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, suffix) = value.split_at(split);
+    let invalid =
+        || format!("invalid size {value:?}: expected a byte count such as 500000, 512KiB or 20MB");
+    let number: u64 = digits.parse().map_err(|_| invalid())?;
+    let multiplier: u64 = match suffix.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000 * 1000,
+        "gb" => 1000 * 1000 * 1000,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        _ => return Err(invalid()),
+    };
+    number.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// The stricter of the environment budget (`env`, the raw variable if set) and the call's
+/// `max_total_size`. `allow_over_budget` opts the call out of the environment budget only;
+/// its own `max_total_size` still applies.
+pub fn effective_limit(
+    env: Option<&str>,
+    max_total_size: Option<u64>,
+    allow_over_budget: bool,
+) -> Result<Option<Limit>, String> {
+    let env = match env.filter(|_| !allow_over_budget) {
+        Some(raw) => {
+            let bytes = parse_size(raw).map_err(|err| format!("{MAX_TOTAL_ENV}: {err}"))?;
+            Some(Limit {
+                bytes,
+                source: LimitSource::Env(raw.trim().to_owned()),
+            })
+        }
+        None => None,
+    };
+    let call = max_total_size.map(|bytes| Limit {
+        bytes,
+        source: LimitSource::CallSite,
+    });
+    Ok(match (env, call) {
+        (Some(env), Some(call)) if call.bytes <= env.bytes => Some(call),
+        (Some(env), _) => Some(env),
+        (None, call) => call,
+    })
+}
+
+/// Checks `total` embedded bytes from `dir` against `limit`, returning the build error if it
+/// is exceeded.
+pub fn check(dir: &str, total: u64, limit: Option<&Limit>) -> Result<(), String> {
+    let Some(limit) = limit.filter(|limit| total > limit.bytes) else {
+        return Ok(());
+    };
+    Err(match &limit.source {
+        LimitSource::Env(raw) => format!(
+            "embed_silo!(\"{dir}\") would embed {total} bytes, over the limit of {} bytes set by {MAX_TOTAL_ENV}={raw}; \
+             add `allow_over_budget = true` to this call to opt out",
+            limit.bytes
+        ),
+        LimitSource::CallSite => {
+            format!(
+                "embed_silo!(\"{dir}\") would embed {total} bytes, over its max_total_size of {} bytes",
+                limit.bytes
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sizes parse with decimal and binary suffixes, case-insensitively.
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1234"), Ok(1234));
+        assert_eq!(parse_size(" 10 B "), Ok(10));
+        assert_eq!(parse_size("2KB"), Ok(2000));
+        assert_eq!(parse_size("2kib"), Ok(2048));
+        assert_eq!(parse_size("3M"), Ok(3 << 20));
+        assert_eq!(parse_size("3 MB"), Ok(3_000_000));
+        assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+        assert!(parse_size("").is_err());
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("1.5MB").is_err());
+        assert!(parse_size("10 TB").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("99999999999999999999G").is_err());
+    }
+
+    /// The stricter limit wins, and opting out only drops the environment budget.
+    #[test]
+    fn test_effective_limit_precedence() {
+        let env = |bytes: u64, raw: &str| {
+            Some(Limit {
+                bytes,
+                source: LimitSource::Env(raw.to_owned()),
+            })
+        };
+        let call = |bytes: u64| {
+            Some(Limit {
+                bytes,
+                source: LimitSource::CallSite,
+            })
+        };
+        assert_eq!(effective_limit(None, None, false), Ok(None));
+        assert_eq!(
+            effective_limit(Some("1K"), None, false),
+            Ok(env(1024, "1K"))
+        );
+        assert_eq!(effective_limit(Some("1K"), Some(100), false), Ok(call(100)));
+        assert_eq!(
+            effective_limit(Some("1K"), Some(5000), false),
+            Ok(env(1024, "1K"))
+        );
+        assert_eq!(effective_limit(Some("1K"), None, true), Ok(None));
+        assert_eq!(
+            effective_limit(Some("1K"), Some(5000), true),
+            Ok(call(5000))
+        );
+        assert_eq!(effective_limit(None, Some(7), false), Ok(call(7)));
+        assert!(
+            effective_limit(Some("lots"), None, false)
+                .unwrap_err()
+                .contains("RUST_SILOS_MAX_TOTAL")
+        );
+        assert_eq!(effective_limit(Some("lots"), None, true), Ok(None));
+    }
+
+    /// The failure names the directory, the measured size and the limit with its source.
+    #[test]
+    fn test_budget_messages() {
+        let env = Limit {
+            bytes: 1000,
+            source: LimitSource::Env("1KB".to_owned()),
+        };
+        assert_eq!(check("assets", 1000, Some(&env)), Ok(()));
+        assert_eq!(check("assets", 5000, None), Ok(()));
+        let err = check("assets", 1001, Some(&env)).unwrap_err();
+        assert!(
+            err.contains("embed_silo!(\"assets\")") && err.contains("1001 bytes"),
+            "{err}"
+        );
+        assert!(
+            err.contains("1000 bytes set by RUST_SILOS_MAX_TOTAL=1KB")
+                && err.contains("allow_over_budget = true"),
+            "{err}"
+        );
+        let call = Limit {
+            bytes: 10,
+            source: LimitSource::CallSite,
+        };
+        assert!(
+            check("assets", 11, Some(&call))
+                .unwrap_err()
+                .contains("max_total_size of 10 bytes")
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! `build_id = true`: a 128-bit identifier stamped into each embedded silo at expansion.
+
+use std::hash::{BuildHasher, Hasher};
+
+const OFFSET_BASIS: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// An identifier unique to this expansion, from the time, the process and the standard
+/// library's per-process random hasher keys.
+pub fn random() -> [u8; 16] {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let half = |salt: u64| {
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(now.as_nanos());
+        hasher.write_u32(std::process::id());
+        hasher.write_u64(salt);
+        hasher.finish()
+    };
+    let id = (u128::from(half(0)) << 64) | u128::from(half(1));
+    id.to_be_bytes()
+}
+
+/// An identifier derived from the embedded contents: 128-bit FNV-1a over each file's
+/// relative path and content hash, in path order. The same files always give the same id,
+/// on any machine and toolchain.
+pub fn deterministic<'a>(files: impl IntoIterator<Item = (&'a str, u64)>) -> [u8; 16] {
+    let mut files: Vec<(&str, u64)> = files.into_iter().collect();
+    files.sort();
+    let mut hash = OFFSET_BASIS;
+    let mut update = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash = (hash ^ u128::from(byte)).wrapping_mul(PRIME);
+        }
+    };
+    for (path, content_hash) in files {
+        update(path.as_bytes());
+        update(&[0]);
+        update(&content_hash.to_le_bytes());
+    }
+    hash.to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Content-derived ids depend only on the set of files, not on the order they were walked in.
+    #[test]
+    fn test_deterministic_build_id() {
+        let id = deterministic([("a.txt", 1), ("b/c.txt", 2)]);
+        assert_eq!(id, deterministic([("b/c.txt", 2), ("a.txt", 1)]));
+        assert_ne!(id, deterministic([("a.txt", 1), ("b/c.txt", 3)]));
+        assert_ne!(id, deterministic([("a.txt", 1)]));
+        assert_eq!(
+            deterministic([]),
+            0x6c62_272e_07bb_0142_62b8_2175_6295_c58du128.to_be_bytes()
+        );
+    }
+
+    /// Random ids differ between expansions.
+    #[test]
+    fn test_random_build_id() {
+        assert_ne!(random(), random());
+    }
+}
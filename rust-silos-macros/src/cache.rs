@@ -0,0 +1,296 @@
+//! Expansion cache for `cache = true`: the collected entry list of a directory, stored under
+//! `target/` and reused while a metadata-only digest of the tree is unchanged.
+
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Set to any value to ignore and not write the cache.
+pub const NO_CACHE_ENV: &str = "RUST_SILOS_NO_CACHE";
+
+/// First line of every cache file; a different macro version never reads another's cache.
+const HEADER: &str = concat!("rust-silos-cache 2 ", env!("CARGO_PKG_VERSION"));
+
+/// A collected file, as stored in the cache.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CachedEntry {
+    pub rel_path: String,
+    pub abs_path: String,
+    pub size: usize,
+    pub modified: u64,
+    pub content_hash: u64,
+    pub mode: u32,
+}
+
+/// Permission bits of a file (`st_mode & 0o7777`) on Unix, 0 elsewhere.
+pub fn file_mode(meta: &std::fs::Metadata) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        meta.permissions().mode() & 0o7777
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        0
+    }
+}
+
+/// Whether the cache is bypassed through [`NO_CACHE_ENV`].
+pub fn disabled() -> bool {
+    std::env::var_os(NO_CACHE_ENV).is_some()
+}
+
+/// Directory holding the cache files: `rust-silos-cache` under `CARGO_TARGET_DIR`, else under
+/// the nearest `target` directory above the crate (the workspace's), else `<crate>/target`.
+pub fn cache_dir(manifest_dir: &Path) -> PathBuf {
+    let target = match std::env::var_os("CARGO_TARGET_DIR") {
+        Some(dir) => manifest_dir.join(dir),
+        None => manifest_dir
+            .ancestors()
+            .map(|dir| dir.join("target"))
+            .find(|dir| dir.is_dir())
+            .unwrap_or_else(|| manifest_dir.join("target")),
+    };
+    target.join("rust-silos-cache")
+}
+
+/// Cache file for a directory walked with the given options.
+pub fn cache_file(
+    cache_dir: &Path,
+    root: &Path,
+    follow_symlinks: bool,
+    skip_unreadable: bool,
+) -> PathBuf {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (root, follow_symlinks, skip_unreadable).hash(&mut hasher);
+    cache_dir.join(format!("{:016x}.cache", hasher.finish()))
+}
+
+/// Digest of the names, types, sizes, mtimes and modes (and symlink targets) under `root`, without
+/// reading any contents. `None` if any part of the tree cannot be inspected, in which case
+/// the cache is not used.
+pub fn tree_digest(root: &Path, follow_symlinks: bool) -> Option<u64> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for entry in WalkDir::new(root)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
+    {
+        let entry = entry.ok()?;
+        let meta = entry.metadata().ok()?;
+        entry.path().strip_prefix(root).ok()?.hash(&mut hasher);
+        (meta.is_dir(), meta.is_file(), meta.len(), file_mode(&meta)).hash(&mut hasher);
+        meta.modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .hash(&mut hasher);
+        if entry.path_is_symlink() {
+            std::fs::read_link(entry.path()).ok()?.hash(&mut hasher);
+        }
+    }
+    Some(hasher.finish())
+}
+
+/// Reads the entries cached in `file` if they were stored for `digest`. A missing, stale,
+/// truncated or otherwise malformed file yields `None`.
+pub fn load(file: &Path, digest: u64) -> Option<Vec<CachedEntry>> {
+    let text = std::fs::read_to_string(file).ok()?;
+    let mut lines = text.lines();
+    if lines.next()? != HEADER || lines.next()? != format!("digest {digest:016x}") {
+        return None;
+    }
+    let mut entries = Vec::new();
+    for line in lines {
+        if let Some(count) = line.strip_prefix("end ") {
+            return (count.parse::<usize>().ok()? == entries.len()).then_some(entries);
+        }
+        let mut fields = line.split('\t');
+        let entry = CachedEntry {
+            size: fields.next()?.parse().ok()?,
+            modified: fields.next()?.parse().ok()?,
+            content_hash: u64::from_str_radix(fields.next()?, 16).ok()?,
+            mode: u32::from_str_radix(fields.next()?, 8).ok()?,
+            rel_path: fields.next()?.to_owned(),
+            abs_path: fields.next()?.to_owned(),
+        };
+        if fields.next().is_some() {
+            return None;
+        }
+        entries.push(entry);
+    }
+    None
+}
+
+/// Writes `entries` to `file` for `digest`, replacing it atomically. Entries whose paths
+/// contain tabs or line breaks cannot be stored, and nothing is written.
+pub fn store(file: &Path, digest: u64, entries: &[CachedEntry]) -> std::io::Result<()> {
+    let unstorable = |path: &str| path.contains(['\t', '\n', '\r']);
+    if entries
+        .iter()
+        .any(|entry| unstorable(&entry.rel_path) || unstorable(&entry.abs_path))
+    {
+        return Ok(());
+    }
+    let mut text = format!("{HEADER}\ndigest {digest:016x}\n");
+    for entry in entries {
+        text.push_str(&format!(
+            "{}\t{}\t{:016x}\t{:o}\t{}\t{}\n",
+            entry.size,
+            entry.modified,
+            entry.content_hash,
+            entry.mode,
+            entry.rel_path,
+            entry.abs_path
+        ));
+    }
+    text.push_str(&format!("end {}\n", entries.len()));
+    let dir = file.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let temp = file.with_extension(format!("tmp{}", std::process::id()));
+    let result = std::fs::File::create(&temp)
+        .and_then(|mut out| out.write_all(text.as_bytes()))
+        .and_then(|_| std::fs::rename(&temp, file));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime};
+
+    fn write(path: &Path, contents: &str, age: u64) {
+        std::fs::write(path, contents).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    fn tree() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tmp.path().join("sub")).unwrap();
+        write(&tmp.path().join("a.txt"), "aa", 60);
+        write(&tmp.path().join("sub/b.txt"), "bbb", 60);
+        tmp
+    }
+
+    fn entries() -> Vec<CachedEntry> {
+        ["a.txt", "sub/b.txt"]
+            .iter()
+            .enumerate()
+            .map(|(i, path)| CachedEntry {
+                rel_path: path.to_string(),
+                abs_path: format!("/src/{path}"),
+                size: i + 2,
+                modified: 1_700_000_000,
+                content_hash: 0xfeed_0000 + i as u64,
+                mode: if i == 0 { 0o644 } else { 0o755 },
+            })
+            .collect()
+    }
+
+    /// An unchanged tree reads back exactly what was stored.
+    #[test]
+    fn test_cache_hit() {
+        let tmp = tree();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let file = cache_file(cache_dir.path(), tmp.path(), false, false);
+        let digest = tree_digest(tmp.path(), false).unwrap();
+        assert_eq!(load(&file, digest), None);
+        store(&file, digest, &entries()).unwrap();
+        assert_eq!(tree_digest(tmp.path(), false), Some(digest));
+        assert_eq!(load(&file, digest), Some(entries()));
+        assert_ne!(file, cache_file(cache_dir.path(), tmp.path(), true, false));
+    }
+
+    /// Changing a size, an mtime or the set of files changes the digest, so the cache is not used.
+    #[test]
+    fn test_cache_stale() {
+        let tmp = tree();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let file = cache_file(cache_dir.path(), tmp.path(), false, false);
+        let digest = tree_digest(tmp.path(), false).unwrap();
+        store(&file, digest, &entries()).unwrap();
+
+        write(&tmp.path().join("a.txt"), "aa", 30);
+        let touched = tree_digest(tmp.path(), false).unwrap();
+        assert_ne!(touched, digest);
+        assert_eq!(load(&file, touched), None);
+
+        write(&tmp.path().join("a.txt"), "aaaa", 30);
+        let resized = tree_digest(tmp.path(), false).unwrap();
+        assert_ne!(resized, touched);
+
+        write(&tmp.path().join("sub/c.txt"), "c", 60);
+        let added = tree_digest(tmp.path(), false).unwrap();
+        assert_ne!(added, resized);
+        std::fs::remove_file(tmp.path().join("sub/c.txt")).unwrap();
+        assert_ne!(tree_digest(tmp.path(), false).unwrap(), added);
+    }
+
+    /// Making a file executable changes its recorded mode and the digest, though neither its
+    /// size nor its mtime moves.
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tree();
+        let script = tmp.path().join("a.txt");
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let digest = tree_digest(tmp.path(), false).unwrap();
+        assert_eq!(file_mode(&std::fs::metadata(&script).unwrap()), 0o644);
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(file_mode(&std::fs::metadata(&script).unwrap()), 0o755);
+        assert_ne!(tree_digest(tmp.path(), false).unwrap(), digest);
+    }
+
+    /// Garbage, truncated files and a different header are all treated as a miss.
+    #[test]
+    fn test_cache_corrupt() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let file = cache_dir.path().join("x.cache");
+        store(&file, 7, &entries()).unwrap();
+        let valid = std::fs::read_to_string(&file).unwrap();
+
+        std::fs::write(&file, b"\xff\xfe not a cache").unwrap();
+        assert_eq!(load(&file, 7), None);
+
+        let without_end = valid.lines().take(3).collect::<Vec<_>>().join("\n");
+        std::fs::write(&file, without_end).unwrap();
+        assert_eq!(load(&file, 7), None);
+
+        std::fs::write(&file, valid.replacen("2\t", "two\t", 1)).unwrap();
+        assert_eq!(load(&file, 7), None);
+
+        std::fs::write(&file, valid.replace("end 2", "end 3")).unwrap();
+        assert_eq!(load(&file, 7), None);
+
+        std::fs::write(
+            &file,
+            valid.replacen("rust-silos-cache 2", "rust-silos-cache 1", 1),
+        )
+        .unwrap();
+        assert_eq!(load(&file, 7), None);
+
+        // A corrupt file is simply replaced by the next store.
+        store(&file, 7, &entries()).unwrap();
+        assert_eq!(load(&file, 7), Some(entries()));
+    }
+
+    /// Paths the line format cannot hold are never written.
+    #[test]
+    fn test_cache_skips_unstorable_paths() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let file = cache_dir.path().join("x.cache");
+        let mut odd = entries();
+        odd[0].rel_path = "tab\there".to_owned();
+        store(&file, 1, &odd).unwrap();
+        assert!(!file.exists());
+    }
+}
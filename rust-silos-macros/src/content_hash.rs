@@ -0,0 +1,11 @@
+//! Embed-time content hash; mirrors `rust-silos/src/content_hash.rs` (64-bit FNV-1a).
+//! Keep the two in sync so embedded and dynamic files hash identically.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
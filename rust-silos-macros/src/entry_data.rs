@@ -0,0 +1,105 @@
+//! The data static behind each generated `EmbedEntry`, and the `size` that goes with it.
+//!
+//! Sizes are never taken from file metadata: `include_bytes!` reads the file again after
+//! the macro has looked at it (and rustc may reuse an old expansion against newer bytes),
+//! so a recorded length could disagree with the contents. Instead the size is the length
+//! of the embedded slice, or of the bytes the macro itself emitted.
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// Where the bytes of an embedded file come from.
+pub enum Data {
+    /// Included from the file with `include_bytes!`.
+    Include,
+    /// Byte string literal of contents the macro read and rewrote (transcoded to UTF-8).
+    Literal(Vec<u8>),
+    /// Base64 string literal of contents the macro read.
+    Base64(Vec<u8>),
+}
+
+/// Generates the static `ident` holding `data` for the file at `abs_path`, and the
+/// expression for its entry's `size`. Literal and base64 data keep an unused
+/// `include_bytes!` so that editing the file still triggers a rebuild.
+pub fn data_static(
+    ident: &proc_macro2::Ident,
+    abs_path: &str,
+    data: &Data,
+    crate_root: &TokenStream,
+) -> (TokenStream, TokenStream) {
+    let abs_path_lit = syn::LitStr::new(abs_path, Span::call_site());
+    match data {
+        Data::Include => (
+            quote! { static #ident: &[u8] = include_bytes!(#abs_path_lit); },
+            quote! { #ident.len() },
+        ),
+        Data::Literal(bytes) => {
+            let bytes_lit = syn::LitByteStr::new(bytes, Span::call_site());
+            (
+                quote! {
+                    const _: &[u8] = include_bytes!(#abs_path_lit);
+                    static #ident: &[u8] = #bytes_lit;
+                },
+                quote! { #ident.len() },
+            )
+        }
+        Data::Base64(bytes) => {
+            let text_lit = syn::LitStr::new(&crate::base64::encode(bytes), Span::call_site());
+            let size_lit = syn::LitInt::new(&bytes.len().to_string(), Span::call_site());
+            (
+                quote! {
+                    const _: &[u8] = include_bytes!(#abs_path_lit);
+                    static #ident: #crate_root::EmbedBase64 = #crate_root::EmbedBase64::new(#text_lit);
+                },
+                quote! { #size_lit },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    /// A file rewritten between the metadata snapshot and expansion still gets a size that
+    /// matches the bytes that end up embedded, never the stale metadata length.
+    #[test]
+    fn test_size_follows_embedded_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("asset.txt");
+        std::fs::write(&path, "v1").unwrap();
+        let stale = std::fs::metadata(&path).unwrap().len();
+        std::fs::write(&path, "version two").unwrap();
+        let abs_path = path.to_str().unwrap();
+        let ident = quote::format_ident!("__EMBED_DATA_0");
+        let crate_root = quote! { ::rust_silos };
+
+        let (item, size) = data_static(&ident, abs_path, &Data::Include, &crate_root);
+        assert_eq!(
+            size.to_string(),
+            quote! { __EMBED_DATA_0.len() }.to_string()
+        );
+        assert_eq!(
+            item.to_string(),
+            quote! { static __EMBED_DATA_0: &[u8] = include_bytes!(#abs_path); }.to_string()
+        );
+
+        let read = std::fs::read(&path).unwrap();
+        let (item, size) = data_static(&ident, abs_path, &Data::Base64(read.clone()), &crate_root);
+        assert_eq!(size.to_string(), "11");
+        assert_ne!(size.to_string(), stale.to_string());
+        assert!(
+            item.to_string()
+                .contains(&format!("{:?}", crate::base64::encode(&read)))
+        );
+        assert!(item.to_string().contains("include_bytes"));
+
+        let (item, size) = data_static(&ident, abs_path, &Data::Literal(read), &crate_root);
+        assert_eq!(
+            size.to_string(),
+            quote! { __EMBED_DATA_0.len() }.to_string()
+        );
+        assert!(item.to_string().contains("b\"version two\""));
+    }
+}
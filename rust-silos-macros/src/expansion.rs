@@ -0,0 +1,108 @@
+//! `emit_expansion`: writes the code generated by `embed_silo!` to a file for auditing.
+//!
+//! Only active when `RUST_SILOS_EMIT_EXPANSION=1` is set, so the option can stay in the
+//! source and only take effect in audit builds. The output is `prettyplease`-formatted, so
+//! the same input always renders to the same text.
+
+/// Environment variable that turns `emit_expansion` on.
+pub const ENV: &str = "RUST_SILOS_EMIT_EXPANSION";
+
+/// Whether the value of [`ENV`] turns emission on: only `1` does.
+pub fn enabled(value: Option<&str>) -> bool {
+    value.is_some_and(|value| value.trim() == "1")
+}
+
+/// What was embedded, for the header of the emitted file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Totals {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Renders `items`, the generated code as a sequence of items, behind a header comment naming
+/// the macro call `macro_name!(args)` and what it embedded.
+pub fn render(
+    macro_name: &str,
+    args: &str,
+    totals: Totals,
+    items: proc_macro2::TokenStream,
+) -> syn::Result<String> {
+    let file: syn::File = syn::parse2(items)?;
+    let mut out = format!(
+        "// Expansion of `{macro_name}!`, written because {ENV}=1. Do not edit.\n//\n// Arguments:\n"
+    );
+    for line in args.lines() {
+        out.push_str(&format!("//     {line}\n"));
+    }
+    out.push_str(&format!(
+        "//\n// Embedded files: {}\n// Total size: {} bytes\n\n",
+        totals.files, totals.bytes
+    ));
+    out.push_str(&prettyplease::unparse(&file));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    /// The emitted file parses with `syn`, starts with the header and renders identically twice.
+    #[test]
+    fn test_render_parses_and_is_stable() {
+        let items = || {
+            quote! {
+                fn embed_silo() -> ::rust_silos::Silo {
+                    {
+                        static __EMBED_DATA_0: &[u8] = include_bytes!("/src/assets/a.txt");
+                        static __EMBED_MAP: ::rust_silos::phf::Map<&'static str, ::rust_silos::EmbedEntry> = ::rust_silos::phf::phf_map! {
+                            "a.txt" => ::rust_silos::EmbedEntry { path: "a.txt", contents: &__EMBED_DATA_0, size: __EMBED_DATA_0.len() },
+                        };
+                        ::rust_silos::Silo::from_embedded(&__EMBED_MAP, "/src/assets")
+                    }
+                }
+            }
+        };
+        let args = "\"assets\" , force = true , emit_expansion = \"audit/assets.rs\"";
+        let totals = Totals {
+            files: 1,
+            bytes: 42,
+        };
+        let rendered = render("embed_silo", args, totals, items()).unwrap();
+        assert_eq!(
+            rendered,
+            render("embed_silo", args, totals, items()).unwrap()
+        );
+
+        let file = syn::parse_file(&rendered).unwrap();
+        assert_eq!(file.items.len(), 1);
+        assert!(matches!(&file.items[0], syn::Item::Fn(item) if item.sig.ident == "embed_silo"));
+        let header: Vec<&str> = rendered
+            .lines()
+            .take_while(|line| line.starts_with("//"))
+            .collect();
+        assert!(header[0].starts_with("// Expansion of `embed_silo!`"));
+        assert!(
+            header.contains(
+                &"//     \"assets\" , force = true , emit_expansion = \"audit/assets.rs\""
+            )
+        );
+        assert!(header.contains(&"// Embedded files: 1"));
+        assert!(header.contains(&"// Total size: 42 bytes"));
+    }
+
+    /// Tokens that are not a sequence of items are reported, not written.
+    #[test]
+    fn test_render_rejects_non_items() {
+        assert!(render("embed_silo", "", Totals::default(), quote! { 1 + 1 }).is_err());
+    }
+
+    /// Only `RUST_SILOS_EMIT_EXPANSION=1` turns emission on.
+    #[test]
+    fn test_enabled() {
+        assert!(enabled(Some("1")));
+        for value in [None, Some(""), Some("0"), Some("true"), Some("yes")] {
+            assert!(!enabled(value), "{value:?}");
+        }
+    }
+}
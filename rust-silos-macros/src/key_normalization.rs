@@ -0,0 +1,135 @@
+//! `key_normalization`: embedded keys normalized at compile time, mirroring
+//! `rust_silos::KeyNormalization`.
+
+use std::collections::BTreeMap;
+
+/// A `key_normalization = "..."` option.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Keys {
+    None,
+    Lowercase,
+    Nfc,
+    NfcLowercase,
+}
+
+impl Keys {
+    pub fn parse(value: &str) -> Option<Keys> {
+        match value {
+            "none" => Some(Keys::None),
+            "lowercase" => Some(Keys::Lowercase),
+            "nfc" => Some(Keys::Nfc),
+            "nfc_lowercase" => Some(Keys::NfcLowercase),
+            _ => None,
+        }
+    }
+
+    /// Name of the matching `rust_silos::KeyNormalization` variant.
+    pub fn variant(self) -> &'static str {
+        match self {
+            Keys::None => "None",
+            Keys::Lowercase => "Lowercase",
+            Keys::Nfc => "Nfc",
+            Keys::NfcLowercase => "NfcLowercase",
+        }
+    }
+
+    /// Returns `true` for the forms that need the `unicode` feature.
+    pub fn needs_unicode(self) -> bool {
+        matches!(self, Keys::Nfc | Keys::NfcLowercase)
+    }
+
+    pub fn apply(self, key: &str) -> String {
+        match self {
+            Keys::None => key.to_owned(),
+            Keys::Lowercase => key.to_lowercase(),
+            Keys::Nfc => nfc(key),
+            Keys::NfcLowercase => nfc(key).to_lowercase(),
+        }
+    }
+}
+
+#[cfg(feature = "unicode")]
+fn nfc(key: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    key.nfc().collect()
+}
+
+// The NFC options are rejected when parsed without the feature.
+#[cfg(not(feature = "unicode"))]
+fn nfc(_key: &str) -> String {
+    unreachable!("NFC key normalization without the `unicode` feature")
+}
+
+/// Groups of paths that normalize to the same key, as `(key, paths)`, sorted by key.
+pub fn collisions<'a>(
+    keys: Keys,
+    paths: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, Vec<&'a str>)> {
+    let mut by_key: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for path in paths {
+        by_key.entry(keys.apply(path)).or_default().push(path);
+    }
+    by_key
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lowercasing leaves decomposed names decomposed; option values are matched exactly.
+    #[test]
+    fn test_normalized_keys() {
+        let decomposed = "Cafe\u{301}/Men\u{303}u.txt";
+        assert_eq!(Keys::None.apply(decomposed), decomposed);
+        assert_eq!(
+            Keys::Lowercase.apply(decomposed),
+            "cafe\u{301}/men\u{303}u.txt"
+        );
+        assert_eq!(Keys::parse("nfc_lowercase"), Some(Keys::NfcLowercase));
+        assert_eq!(Keys::parse("NFC"), None);
+        assert!(Keys::Nfc.needs_unicode() && !Keys::Lowercase.needs_unicode());
+    }
+
+    /// Decomposed names compose under NFC, and lowercasing applies after composition.
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_nfc_keys() {
+        let decomposed = "Cafe\u{301}/Men\u{303}u.txt";
+        assert_eq!(Keys::Nfc.apply(decomposed), "Caf\u{e9}/Me\u{f1}u.txt");
+        assert_eq!(
+            Keys::NfcLowercase.apply(decomposed),
+            "caf\u{e9}/me\u{f1}u.txt"
+        );
+    }
+
+    /// Only paths that become equal once normalized are reported.
+    #[test]
+    fn test_key_collisions() {
+        let paths = [
+            "caf\u{e9}.txt",
+            "cafe\u{301}.txt",
+            "Readme.md",
+            "README.md",
+            "other.md",
+        ];
+        assert!(collisions(Keys::None, paths).is_empty());
+        assert_eq!(
+            collisions(Keys::Lowercase, paths),
+            [("readme.md".to_owned(), vec!["Readme.md", "README.md"])]
+        );
+        #[cfg(feature = "unicode")]
+        {
+            assert_eq!(
+                collisions(Keys::Nfc, paths),
+                [(
+                    "caf\u{e9}.txt".to_owned(),
+                    vec!["caf\u{e9}.txt", "cafe\u{301}.txt"]
+                )]
+            );
+            assert_eq!(collisions(Keys::NfcLowercase, paths).len(), 2);
+        }
+    }
+}
@@ -4,30 +4,143 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use syn::{
     parse::{Parse, ParseStream},
     parse_macro_input, LitStr, Token,
 };
 use walkdir::WalkDir;
 
-type EmbedMeta = (String, String, usize, u64);
-type CollectResult = (Vec<EmbedMeta>, Vec<proc_macro2::TokenStream>);
+mod base64;
+mod budget;
+mod build_id;
+mod cache;
+mod content_hash;
+mod entry_data;
+mod expansion;
+mod key_normalization;
+mod report;
+mod scan;
+mod transcode;
+mod variants;
+
+/// Layout version of the generated `EmbedEntry` code. Must match `rust_silos::SILO_ABI`.
+const SILO_ABI: u32 = 5;
+
+/// A file collected for embedding.
+struct EmbedMeta {
+    rel_path: String,
+    abs_path: String,
+    size: usize,
+    modified: u64,
+    /// UTF-8 contents and the original encoding, if the file was transcoded.
+    transcoded: Option<(Vec<u8>, &'static str)>,
+    /// Stable hash of the embedded contents, see `content_hash.rs`.
+    content_hash: u64,
+    /// Permission bits on Unix, 0 elsewhere, see `cache::file_mode`.
+    mode: u32,
+}
+/// (entries, compile errors, warnings for skipped files)
+type CollectResult = (Vec<EmbedMeta>, Vec<proc_macro2::TokenStream>, Vec<String>);
+
+/// Options controlling how `collect_embed_entries` walks the directory.
+#[derive(Clone, Copy, Default)]
+struct CollectOptions {
+    follow_symlinks: bool,
+    /// Skip unreadable files with a warning instead of failing the build.
+    skip_unreadable: bool,
+}
+
+/// Options for `transcode = "utf8"`.
+#[derive(Clone)]
+struct TranscodeOptions {
+    extensions: Vec<String>,
+    /// Fail the build on unrecognized encodings instead of warning and embedding as-is.
+    fail: bool,
+}
 
 /// Internal: Macro input parser for `silo!` macro. Accepts a path and optional force argument.
 /// Path must be a string literal. Force is a bool literal.
+#[derive(Clone)]
 struct SiloMacroInput {
     path: LitStr,
+    /// All directories of the `["base", "theme"]` form, lowest precedence first.
+    roots: Option<Vec<LitStr>>,
     force: Option<(syn::Ident, syn::LitBool)>,
     crate_path: Option<syn::Path>,
+    follow_symlinks: bool,
+    skip_unreadable: bool,
+    redact_root: bool,
+    /// Embed contents as base64 string literals instead of byte arrays.
+    base64: bool,
+    transcode: Option<TranscodeOptions>,
+    /// Fail the build if the first 64 KiB of any file contain one of these literal patterns.
+    deny_containing: Option<(syn::Ident, Vec<String>)>,
+    /// Crate-relative path of the license audit report to write.
+    audit: Option<LitStr>,
+    /// Reuse the collected entry list from `target/` while the tree is unchanged.
+    cache: bool,
+    /// Crate-relative path of the duplicate and size report to write.
+    report_json: Option<LitStr>,
+    /// Files larger than this many bytes are listed as over budget in the report.
+    size_budget: Option<syn::LitInt>,
+    /// Fail the build if the embedded contents exceed this many bytes.
+    max_total_size: Option<u64>,
+    /// Exempt this call from the `RUST_SILOS_MAX_TOTAL` budget.
+    allow_over_budget: bool,
+    /// Stamp embedded silos with a 128-bit build id.
+    build_id: bool,
+    /// Derive the build id from the contents instead of generating a random one.
+    deterministic: Option<(syn::Ident, bool)>,
+    /// Normalize keys at compile time and lookups at runtime.
+    key_normalization: Option<key_normalization::Keys>,
+    /// Doc comment for the static generated by `static_embed_silo!`.
+    doc: Option<LitStr>,
+    /// Crate-relative path to write the generated code to, see `expansion.rs`.
+    emit_expansion: Option<LitStr>,
 }
 
 /// Parse implementation for macro input. Handles path and optional force argument.
 impl Parse for SiloMacroInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let path: LitStr = input.parse()?;
+        let (path, roots) = if input.peek(syn::token::Bracket) {
+            let content;
+            let brackets = syn::bracketed!(content in input);
+            let roots: Vec<LitStr> = content
+                .parse_terminated(|p| p.parse::<LitStr>(), Token![,])?
+                .into_iter()
+                .collect();
+            let Some(first) = roots.first() else {
+                return Err(syn::Error::new(
+                    brackets.span.join(),
+                    "embed_silo!: expected at least one directory",
+                ));
+            };
+            (first.clone(), Some(roots))
+        } else {
+            (input.parse()?, None)
+        };
         let mut force = None;
         let mut crate_path = None;
+        let mut follow_symlinks = false;
+        let mut skip_unreadable = false;
+        let mut redact_root = false;
+        let mut base64 = false;
+        let mut doc = None;
+        let mut transcode = false;
+        let mut transcode_extensions = None;
+        let mut transcode_failure = None;
+        let mut deny_containing = None;
+        let mut audit = None;
+        let mut cache = false;
+        let mut report_json = None;
+        let mut size_budget = None;
+        let mut build_id = false;
+        let mut deterministic = None;
+        let mut max_total_size = None;
+        let mut allow_over_budget = false;
+        let mut key_normalization = None;
+        let mut emit_expansion = None;
         while input.peek(Token![,]) {
             input.parse::<Token![,]>()?;
             let ident: syn::Ident = input.parse()?;
@@ -38,11 +151,168 @@ impl Parse for SiloMacroInput {
             } else if ident == "crate" {
                 let path: syn::Path = input.parse()?;
                 crate_path = Some(path);
+            } else if ident == "follow_symlinks" {
+                let value: syn::LitBool = input.parse()?;
+                follow_symlinks = value.value();
+            } else if ident == "skip_unreadable" {
+                let value: syn::LitBool = input.parse()?;
+                skip_unreadable = value.value();
+            } else if ident == "redact_root" {
+                let value: syn::LitBool = input.parse()?;
+                redact_root = value.value();
+            } else if ident == "base64" {
+                let value: syn::LitBool = input.parse()?;
+                base64 = value.value();
+            } else if ident == "doc" {
+                doc = Some(input.parse::<LitStr>()?);
+            } else if ident == "transcode" {
+                let value: LitStr = input.parse()?;
+                if value.value() != "utf8" {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "embed_silo!: only transcode = \"utf8\" is supported",
+                    ));
+                }
+                transcode = true;
+            } else if ident == "transcode_extensions" {
+                let content;
+                syn::bracketed!(content in input);
+                let list = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                transcode_extensions = Some((
+                    ident,
+                    list.iter().map(|ext| ext.value()).collect::<Vec<_>>(),
+                ));
+            } else if ident == "transcode_failure" {
+                let value: LitStr = input.parse()?;
+                let fail = match value.value().as_str() {
+                    "warn" => false,
+                    "error" => true,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "embed_silo!: transcode_failure must be \"warn\" or \"error\"",
+                        ));
+                    }
+                };
+                transcode_failure = Some((ident, fail));
+            } else if ident == "deny_containing" {
+                let content;
+                syn::bracketed!(content in input);
+                let list = content.parse_terminated(|p| p.parse::<LitStr>(), Token![,])?;
+                if let Some(empty) = list.iter().find(|pattern| pattern.value().is_empty()) {
+                    return Err(syn::Error::new(
+                        empty.span(),
+                        "embed_silo!: deny_containing patterns must not be empty",
+                    ));
+                }
+                deny_containing =
+                    Some((ident, list.iter().map(|pattern| pattern.value()).collect()));
+            } else if ident == "audit" {
+                audit = Some(input.parse::<LitStr>()?);
+            } else if ident == "cache" {
+                let value: syn::LitBool = input.parse()?;
+                cache = value.value();
+            } else if ident == "report_json" {
+                report_json = Some(input.parse::<LitStr>()?);
+            } else if ident == "size_budget" {
+                let value: syn::LitInt = input.parse()?;
+                value.base10_parse::<usize>()?;
+                size_budget = Some(value);
+            } else if ident == "max_total_size" {
+                let bytes = if input.peek(LitStr) {
+                    let value: LitStr = input.parse()?;
+                    budget::parse_size(&value.value()).map_err(|err| {
+                        syn::Error::new(value.span(), format!("embed_silo!: max_total_size: {err}"))
+                    })?
+                } else {
+                    input.parse::<syn::LitInt>()?.base10_parse()?
+                };
+                max_total_size = Some(bytes);
+            } else if ident == "allow_over_budget" {
+                let value: syn::LitBool = input.parse()?;
+                allow_over_budget = value.value();
+            } else if ident == "build_id" {
+                let value: syn::LitBool = input.parse()?;
+                build_id = value.value();
+            } else if ident == "deterministic" {
+                let value: syn::LitBool = input.parse()?;
+                deterministic = Some((ident, value.value()));
+            } else if ident == "key_normalization" {
+                let value: LitStr = input.parse()?;
+                let keys = key_normalization::Keys::parse(&value.value()).ok_or_else(|| {
+                    syn::Error::new(value.span(), "embed_silo!: key_normalization must be \"none\", \"nfc\", \"lowercase\" or \"nfc_lowercase\"")
+                })?;
+                if keys.needs_unicode() && !cfg!(feature = "unicode") {
+                    return Err(syn::Error::new(
+                        value.span(),
+                        "embed_silo!: the NFC key normalizations need the `unicode` feature of rust-silos",
+                    ));
+                }
+                key_normalization = Some(keys);
+            } else if ident == "emit_expansion" {
+                emit_expansion = Some(input.parse::<LitStr>()?);
             } else {
                 return Err(syn::Error::new(ident.span(), "Unknown argument to embed_silo!"));
             }
         }
-        Ok(SiloMacroInput { path, force, crate_path })
+        if !transcode {
+            let stray = transcode_extensions
+                .as_ref()
+                .map(|(ident, _)| ident)
+                .or(transcode_failure.as_ref().map(|(ident, _)| ident));
+            if let Some(ident) = stray {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("embed_silo!: {ident} requires transcode = \"utf8\""),
+                ));
+            }
+        }
+        if let (Some(budget), None) = (&size_budget, &report_json) {
+            return Err(syn::Error::new(
+                budget.span(),
+                "embed_silo!: size_budget requires report_json",
+            ));
+        }
+        if let (Some((ident, _)), false) = (&deterministic, build_id) {
+            return Err(syn::Error::new(
+                ident.span(),
+                "embed_silo!: deterministic requires build_id = true",
+            ));
+        }
+        let transcode = transcode.then(|| TranscodeOptions {
+            extensions: transcode_extensions
+                .map(|(_, exts)| exts)
+                .unwrap_or_else(|| {
+                    transcode::DEFAULT_EXTENSIONS
+                        .iter()
+                        .map(|ext| ext.to_string())
+                        .collect()
+                }),
+            fail: transcode_failure.is_none_or(|(_, fail)| fail),
+        });
+        Ok(SiloMacroInput {
+            path,
+            roots,
+            force,
+            crate_path,
+            follow_symlinks,
+            skip_unreadable,
+            redact_root,
+            base64,
+            transcode,
+            deny_containing,
+            audit,
+            cache,
+            report_json,
+            size_budget,
+            max_total_size,
+            allow_over_budget,
+            build_id,
+            deterministic,
+            key_normalization,
+            doc,
+            emit_expansion,
+        })
     }
 }
 
@@ -51,46 +321,416 @@ impl Parse for SiloMacroInput {
 /// Usage: `let silo = embed_silo!("assets");` or `let silo = embed_silo!("assets", force = true);`
 /// In debug mode, uses dynamic loading unless `force = true`.
 /// Directory path must exist at build time for embedding.
+///
+/// With `follow_symlinks = true`, symlinked files are embedded under their own relative path;
+/// links that resolve to the same target share a single copy of the bytes. Without it,
+/// symlinks are skipped, and the dynamic silo of a debug build or `into_dynamic()` uses
+/// `SymlinkPolicy::Deny` (`SymlinkPolicy::AllowAll` with the option) to match.
+///
+/// With `skip_unreadable = true`, files that cannot be read (permission errors, broken
+/// symlinks) are skipped with a compiler warning instead of failing the build.
+///
+/// With `transcode = "utf8"`, text files that are not UTF-8 (detected by BOM, UTF-16 byte
+/// patterns, or else decoded as windows-1252) are embedded transcoded to UTF-8, and
+/// `File::original_encoding()` reports what they were. Only files whose extension is in
+/// `transcode_extensions = ["txt", ...]` are considered (a default list of common text
+/// formats otherwise). `transcode_failure = "warn"` embeds undecodable files untouched with a
+/// warning instead of the default compile error. In dynamic mode the silo transcodes on read.
+///
+/// With `redact_root = true`, embedded silos do not record the absolute source directory, so
+/// build-machine paths do not end up in release binaries. Such silos cannot be converted with
+/// `into_dynamic()`/`auto_dynamic()`, which return `Error::RootRedacted`. Dynamic mode still
+/// needs the absolute path and is unaffected.
+///
+/// With `base64 = true`, embedded contents are emitted as base64 `&'static str` literals
+/// instead of byte arrays, for code generation pipelines that mishandle large byte arrays.
+/// Each file is decoded on its first read and cached; `size()` reports the decoded size.
+///
+/// With `deny_containing = ["DO NOT DISTRIBUTE", ...]`, the first 64 KiB of every file
+/// (binary files included, before any transcoding) are searched for the literal patterns,
+/// and the build fails with one error listing each offending file and what it contains.
+/// With `audit = "licenses.json"`, a JSON report of the license headers and
+/// `SPDX-License-Identifier` tags detected in each file is written to that path, relative to
+/// the crate root; it is only rewritten when its contents change. Neither option changes what
+/// is embedded, and both also run in dynamic mode so violations show up in debug builds.
+///
+/// With `cache = true`, the collected file list (paths, sizes, mtimes, content hashes) is
+/// stored under `target/rust-silos-cache/` and reused by later expansions as long as a digest
+/// of the names, sizes and mtimes in the tree is unchanged, which skips reading every file.
+/// Any difference, or a missing or corrupt cache file, falls back to a full walk. Set the
+/// `RUST_SILOS_NO_CACHE` environment variable to bypass the cache.
+///
+/// `embed_silo!(["base", "theme"], ...)` expands to a `SiloSet` with one silo per directory,
+/// later directories taking precedence, and the remaining arguments applied to each. Embedded
+/// and dynamic builds layer the directories identically, so debug builds resolve overrides
+/// exactly as release builds do.
+///
+/// With `report_json = "silos-report.json"`, a JSON report for CI is written to that path,
+/// relative to the crate root: groups of byte-identical files (`duplicates`), files larger
+/// than `size_budget = <bytes>` (`over_budget`, empty without a budget) and `totals`. Sizes
+/// are those of the embedded contents. The file is replaced atomically, only when it changes,
+/// and like `audit` it is also written in dynamic mode.
+///
+/// With `max_total_size = <bytes>` (an integer, or a string such as `"20MB"` or `"512KiB"`),
+/// the build fails if the embedded contents add up to more. Independently, the
+/// `RUST_SILOS_MAX_TOTAL` environment variable (same size syntax, read when the macro expands)
+/// caps every embed in the build; the stricter of the two applies, and
+/// `allow_over_budget = true` exempts a call from the environment budget. Cargo does not track
+/// the variable, so changing it only affects crates that are rebuilt. Dynamic silos embed
+/// nothing and are never checked.
+///
+/// With `build_id = true`, embedded silos carry a 128-bit identifier generated at expansion,
+/// returned by `Silo::build_id()` and recorded in the manifest. It is random unless
+/// `deterministic = true`, which derives it from the embedded paths and contents so identical
+/// assets always get the same id. Dynamic silos have no build id.
+///
+/// With `key_normalization = "nfc"` (or `"lowercase"`, `"nfc_lowercase"`; `"none"` is the
+/// default), embedded keys are normalized at compile time and the silo normalizes lookup
+/// paths at runtime, see `Silo::with_key_normalization`, so a file named in decomposed form
+/// on macOS is found by its composed name. Files whose keys collide once normalized fail the
+/// build, in dynamic mode too. The NFC forms need the `unicode` feature of rust-silos.
+///
+/// With `emit_expansion = "audit/assets.rs"`, the code this call generates is written to that
+/// path, relative to the crate root, formatted with `prettyplease` behind a header comment
+/// listing the macro arguments and the number and total size of the embedded files (0 in
+/// dynamic mode). It is a no-op unless the `RUST_SILOS_EMIT_EXPANSION` environment variable
+/// is `1`, so the option can be committed and only take effect in audit builds; Cargo does
+/// not track the variable, so force a rebuild of the crate to emit. The output is the same
+/// across runs of the same input (except for a random `build_id`), and the file is replaced
+/// atomically, only when it changes. `static_embed_silo!` accepts it too.
 #[proc_macro]
 pub fn embed_silo(input: TokenStream) -> TokenStream {
-    let SiloMacroInput { path, force, crate_path } = parse_macro_input!(input as SiloMacroInput);
-    let dir_path = path.value();
+    let args = input.to_string();
+    let mut input = parse_macro_input!(input as SiloMacroInput);
+    if let Some(doc) = &input.doc {
+        return compile_error(
+            "embed_silo!: doc is only supported by static_embed_silo!",
+            doc.span(),
+        );
+    }
+    let emit = input.emit_expansion.take();
+    let crate_root = input
+        .crate_path
+        .as_ref()
+        .map(|p| quote! { #p })
+        .unwrap_or_else(|| quote! { ::rust_silos });
+    let mut totals = expansion::Totals::default();
+    let (expanded, ty) = match input.roots.clone() {
+        Some(roots) => (
+            expand_silo_set(input, roots, &mut totals),
+            quote! { #crate_root::SiloSet },
+        ),
+        None => (
+            expand_embed_silo(input, &mut totals),
+            quote! { #crate_root::Silo },
+        ),
+    };
+    let Some(emit) = emit else {
+        return expanded;
+    };
+    let expr = proc_macro2::TokenStream::from(expanded);
+    let items = quote! {
+        fn embed_silo() -> #ty {
+            #expr
+        }
+    };
+    match emit_expansion("embed_silo", &emit, &args, totals, items) {
+        Ok(()) => expr.into(),
+        Err(error) => error,
+    }
+}
+
+/// Expands the `["base", "theme"]` form to a `SiloSet` with one silo per directory, in the
+/// given order, so later directories override earlier ones whether the silos are embedded or
+/// read from disk.
+fn expand_silo_set(
+    input: SiloMacroInput,
+    roots: Vec<LitStr>,
+    totals: &mut expansion::Totals,
+) -> TokenStream {
+    if let Some(report) = input.audit.as_ref().or(input.report_json.as_ref()) {
+        return compile_error(
+            "embed_silo!: audit and report_json take a single directory",
+            report.span(),
+        );
+    }
+    let crate_root = input
+        .crate_path
+        .as_ref()
+        .map(|p| quote! { #p })
+        .unwrap_or_else(|| quote! { ::rust_silos });
+    let silos = roots.into_iter().map(|path| {
+        let silo = SiloMacroInput {
+            path,
+            roots: None,
+            ..input.clone()
+        };
+        proc_macro2::TokenStream::from(expand_embed_silo(silo, totals))
+    });
+    quote! {
+        #crate_root::SiloSet::new(::std::vec![#(#silos),*])
+    }
+    .into()
+}
+
+/// Internal: input of `static_embed_silo!`: an optionally `pub` name followed by the
+/// arguments of `embed_silo!`.
+struct StaticSiloInput {
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    silo: SiloMacroInput,
+}
+
+impl Parse for StaticSiloInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let silo = input.parse()?;
+        Ok(StaticSiloInput { vis, ident, silo })
+    }
+}
+
+/// Declares a `static` holding an embedded silo, optionally documented.
+///
+/// Usage: `static_embed_silo!(pub ASSETS, "assets", doc = "UI assets");` expands to
+/// `#[doc = "UI assets"] pub static ASSETS: Silo = embed_silo!("assets");`. All other
+/// arguments are passed on to [`embed_silo!`].
+#[proc_macro]
+pub fn static_embed_silo(input: TokenStream) -> TokenStream {
+    let args = input.to_string();
+    let StaticSiloInput {
+        vis,
+        ident,
+        mut silo,
+    } = parse_macro_input!(input as StaticSiloInput);
+    if silo.roots.is_some() {
+        return compile_error(
+            "static_embed_silo!: takes a single directory; build a SiloSet at runtime instead",
+            silo.path.span(),
+        );
+    }
+    let doc = silo
+        .doc
+        .iter()
+        .map(|doc| quote! { #[doc = #doc] })
+        .collect::<Vec<_>>();
+    let crate_root = silo
+        .crate_path
+        .as_ref()
+        .map(|p| quote! { #p })
+        .unwrap_or_else(|| quote! { ::rust_silos });
+    let emit = silo.emit_expansion.take();
+    let mut totals = expansion::Totals::default();
+    let expr = proc_macro2::TokenStream::from(expand_embed_silo(silo, &mut totals));
+    let items = quote! {
+        #(#doc)*
+        #vis static #ident: #crate_root::Silo = #expr;
+    };
+    if let Some(emit) = emit
+        && let Err(error) = emit_expansion("static_embed_silo", &emit, &args, totals, items.clone())
+    {
+        return error;
+    }
+    items.into()
+}
+
+/// Internal: input of `embed_silo_enum!`: attributes, an optionally `pub` enum name, the
+/// directory, then `nested = "error" | "flatten"` and `crate = path`.
+struct SiloEnumInput {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    ident: syn::Ident,
+    path: LitStr,
+    nested: variants::Nested,
+    crate_path: Option<syn::Path>,
+}
+
+impl Parse for SiloEnumInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let ident = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let path = input.parse()?;
+        let mut nested = variants::Nested::Error;
+        let mut crate_path = None;
+        while input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            let ident: syn::Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            if ident == "nested" {
+                let value: LitStr = input.parse()?;
+                nested = variants::Nested::parse(&value.value()).ok_or_else(|| {
+                    syn::Error::new(
+                        value.span(),
+                        "embed_silo_enum!: nested must be \"error\" or \"flatten\"",
+                    )
+                })?;
+            } else if ident == "crate" {
+                crate_path = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "Unknown argument to embed_silo_enum!",
+                ));
+            }
+        }
+        Ok(SiloEnumInput {
+            attrs,
+            vis,
+            ident,
+            path,
+            nested,
+            crate_path,
+        })
+    }
+}
+
+/// Declares an enum with one variant per file in a directory, for matching exhaustively on a
+/// known set of assets.
+///
+/// Usage: `embed_silo_enum!(pub Icon, "assets/icons");` declares
+/// `pub enum Icon { ArrowLeft, Home, ... }` deriving `Clone`, `Copy`, `PartialEq`, `Eq`,
+/// `PartialOrd`, `Ord`, `Hash` and `Debug`, with `Icon::ALL` listing every variant in path
+/// order, `path()` returning the file's relative path and `file(&silo)` looking it up in a
+/// silo of the same directory, such as `embed_silo!("assets/icons")`. Attributes before the
+/// name are put on the enum. The directory is resolved as by `embed_silo!`.
+///
+/// Variants are named after the file stem in `UpperCamelCase`: `arrow-left.svg` becomes
+/// `ArrowLeft`. Files whose names contain anything but ASCII letters, digits, `-`, `_`, `.`
+/// and spaces, start with a digit or name `Self` fail the build, as do two files that map to
+/// the same variant (such as `home.svg` and `home.png`). Files in subdirectories fail the
+/// build unless `nested = "flatten"`, which prefixes the variant with the directory names
+/// (`ui/home.svg` becomes `UiHome`). An empty directory fails the build too.
+///
+/// Every file is referenced with `include_bytes!`, so renaming, deleting or editing one
+/// rebuilds the crate and re-derives the enum; use sites of a removed variant then fail to
+/// compile. The contents are not kept, but adding a file alone does not trigger a rebuild.
+#[proc_macro]
+pub fn embed_silo_enum(input: TokenStream) -> TokenStream {
+    let SiloEnumInput {
+        attrs,
+        vis,
+        ident,
+        path,
+        nested,
+        crate_path,
+    } = parse_macro_input!(input as SiloEnumInput);
     let call_span = path.span();
-    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| String::new());
-    if manifest_dir.is_empty() {
-        return compile_error("embed_silo!: CARGO_MANIFEST_DIR not set", call_span);
+    let abs_path = match resolve_dir("embed_silo_enum!", &path.value(), call_span) {
+        Ok((_, abs_path)) => abs_path,
+        Err(error) => return error,
+    };
+    let Some(abs_path_str) = abs_path.to_str() else {
+        return compile_error("embed_silo_enum!: path must be valid UTF-8", call_span);
+    };
+    let (entries, errors, _) =
+        collect_embed_entries(abs_path_str, call_span, CollectOptions::default());
+    if !errors.is_empty() {
+        return quote! { #(#errors)* }.into();
     }
-    let manifest_dir_canon = match Path::new(&manifest_dir).canonicalize() {
-        Ok(p) => p,
-        Err(_) => return compile_error("embed_silo!: failed to resolve CARGO_MANIFEST_DIR", call_span),
+    if entries.is_empty() {
+        let msg = format!(
+            "embed_silo_enum!: {} has no files to name variants after",
+            path.value()
+        );
+        return quote_spanned! {call_span=> compile_error!(#msg); }.into();
+    }
+    let paths: Vec<&str> = entries
+        .iter()
+        .map(|entry| entry.rel_path.as_str())
+        .collect();
+    let named = match variants::variants(&paths, nested) {
+        Ok(named) => named,
+        Err(errors) => {
+            let errors = errors.iter().map(|msg| {
+                let msg = format!("embed_silo_enum!: {msg}");
+                quote_spanned! {call_span=> compile_error!(#msg); }
+            });
+            return quote! { #(#errors)* }.into();
+        }
     };
+    let crate_root = crate_path
+        .map(|p| quote! { #p })
+        .unwrap_or_else(|| quote! { ::rust_silos });
+    let variants: Vec<syn::Ident> = named
+        .iter()
+        .map(|(name, _)| syn::Ident::new(name, call_span))
+        .collect();
+    let docs = named.iter().map(|(_, path)| format!("`{path}`"));
+    let rel_paths = named.iter().map(|(_, path)| path);
+    let abs_paths = entries.iter().map(|entry| &entry.abs_path);
+    let missing = format!("{ident}::file: silo has no file for this variant");
+    quote! {
+        #(#attrs)*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        #vis enum #ident {
+            #(#[doc = #docs] #variants,)*
+        }
 
-    let abs_path = manifest_dir_canon.join(&dir_path);
-    let abs_path = match abs_path.canonicalize() {
-        Ok(p) => p,
-        Err(_) => {
-            return compile_error(
-                format!("embed_silo!: failed to resolve path: {}", dir_path),
-                call_span,
-            )
+        impl #ident {
+            /// Every variant, in path order.
+            #vis const ALL: &'static [#ident] = &[#(#ident::#variants),*];
+
+            /// Relative path of the file this variant names.
+            #vis const fn path(&self) -> &'static str {
+                const _: () = { #(let _ = include_bytes!(#abs_paths);)* };
+                match self {
+                    #(#ident::#variants => #rel_paths,)*
+                }
+            }
+
+            /// Looks the file up in `silo`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `silo` has no file at [`Self::path`], which only happens for a silo of
+            /// a different directory.
+            #vis fn file(&self, silo: &#crate_root::Silo) -> #crate_root::File {
+                silo.get_file(self.path()).expect(#missing)
+            }
         }
+    }
+    .into()
+}
+
+/// Expands a single-directory `embed_silo!`, adding what it embeds to `totals`.
+fn expand_embed_silo(input: SiloMacroInput, totals: &mut expansion::Totals) -> TokenStream {
+    let SiloMacroInput {
+        path,
+        roots: _,
+        force,
+        crate_path,
+        follow_symlinks,
+        skip_unreadable,
+        redact_root,
+        base64,
+        transcode,
+        deny_containing,
+        audit,
+        cache,
+        report_json,
+        size_budget,
+        max_total_size,
+        allow_over_budget,
+        build_id,
+        deterministic,
+        key_normalization,
+        doc: _,
+        emit_expansion: _,
+    } = input;
+    let dir_path = path.value();
+    let call_span = path.span();
+    let (manifest_dir_canon, abs_path) = match resolve_dir("embed_silo!", &dir_path, call_span) {
+        Ok(resolved) => resolved,
+        Err(error) => return error,
     };
     let abs_path_str = match abs_path.to_str() {
         Some(p) => p,
         None => return compile_error("embed_silo!: path must be valid UTF-8", call_span),
     };
 
-    // Path-safe containment check (avoid prefix-string bugs like /foo/bar matching /foo/bar2).
-    if !abs_path.starts_with(&manifest_dir_canon) {
-        let msg = format!(
-            "embed_silo!: directory not found:\n  {}\n  expected to be inside crate root:\n  {}\n  relative path: {}",
-            abs_path_str,
-            manifest_dir_canon.display(),
-            dir_path
-        );
-        return compile_error(&msg, call_span);
-    }
-
     let force_embed = force.as_ref().is_some_and(|(_, v)| v.value());
     let debug = cfg!(debug_assertions);
     let use_embed = force_embed || !debug;
@@ -100,50 +740,271 @@ pub fn embed_silo(input: TokenStream) -> TokenStream {
 
     // Keep a stable absolute root for dynamic fallback and for `into_dynamic()` conversions.
     let abs_root_lit = syn::LitStr::new(abs_path_str, call_span);
+    let options = CollectOptions {
+        follow_symlinks,
+        skip_unreadable,
+    };
+    let collect = || {
+        if cache && !cache::disabled() {
+            collect_cached(abs_path_str, call_span, options, &manifest_dir_canon)
+        } else {
+            collect_embed_entries(abs_path_str, call_span, options)
+        }
+    };
+    let scan = |entries: &[EmbedMeta]| {
+        scan_entries(
+            entries,
+            deny_containing.as_ref(),
+            audit.as_ref(),
+            &dir_path,
+            &manifest_dir_canon,
+        )?;
+        match &report_json {
+            Some(report_path) => write_report(
+                entries,
+                report_path,
+                size_budget.as_ref(),
+                &dir_path,
+                &manifest_dir_canon,
+            ),
+            None => Ok(()),
+        }
+    };
+    // Colliding keys fail the build in dynamic mode too, so debug builds do not accept a
+    // tree that release builds reject.
+    let keys = key_normalization.filter(|keys| *keys != key_normalization::Keys::None);
+    let normalize_keys = |entries: &mut Vec<EmbedMeta>| -> Result<(), TokenStream> {
+        let Some(keys) = keys else {
+            return Ok(());
+        };
+        let collisions = key_normalization::collisions(
+            keys,
+            entries.iter().map(|entry| entry.rel_path.as_str()),
+        );
+        if !collisions.is_empty() {
+            let errors = collisions.iter().map(|(key, paths)| {
+                let msg = format!(
+                    "embed_silo!: {} all normalize to the key {key:?}; rename all but one",
+                    paths.join(", ")
+                );
+                quote_spanned! {call_span=> compile_error!(#msg); }
+            });
+            return Err(quote! { #(#errors)* }.into());
+        }
+        for entry in entries.iter_mut() {
+            entry.rel_path = keys.apply(&entry.rel_path);
+        }
+        entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+        Ok(())
+    };
+    let keys_call = keys.map(|keys| {
+        let variant = quote::format_ident!("{}", keys.variant());
+        quote! { .with_key_normalization(#crate_root::KeyNormalization::#variant) }
+    });
+    // Dynamic forms (debug builds, `into_dynamic`) see the same symlinks as the walk above.
+    let symlinks_call = if follow_symlinks {
+        quote! { .with_symlink_policy(#crate_root::SymlinkPolicy::AllowAll) }
+    } else {
+        quote! { .with_symlink_policy(#crate_root::SymlinkPolicy::Deny) }
+    };
+    if !use_embed
+        && (deny_containing.is_some() || audit.is_some() || report_json.is_some() || keys.is_some())
+    {
+        let (mut entries, errors, _) = collect();
+        if !errors.is_empty() {
+            return quote! { #(#errors)* }.into();
+        }
+        if let Err(error) = normalize_keys(&mut entries) {
+            return error;
+        }
+        if let Err(error) = scan(&entries) {
+            return error.into();
+        }
+    }
     if use_embed {
         // Generate PHF map at compile time
-        let (entries, errors) = collect_embed_entries(abs_path_str, call_span);
+        let (mut entries, mut errors, mut warnings) = collect();
+        if let Some(transcode) = &transcode {
+            transcode_entries(
+                &mut entries,
+                transcode,
+                call_span,
+                &mut errors,
+                &mut warnings,
+            );
+        }
         if !errors.is_empty() {
             return quote! { #(#errors)* }.into();
         }
-        let phf_pairs = generate_phf_map(&entries, &crate_root);
+        if let Err(error) = normalize_keys(&mut entries) {
+            return error;
+        }
+        let env_budget = std::env::var(budget::MAX_TOTAL_ENV).ok();
+        let total: u64 = entries.iter().map(|entry| entry.size as u64).sum();
+        totals.files += entries.len();
+        totals.bytes += total;
+        let checked =
+            budget::effective_limit(env_budget.as_deref(), max_total_size, allow_over_budget)
+                .and_then(|limit| budget::check(&dir_path, total, limit.as_ref()));
+        if let Err(msg) = checked {
+            return compile_error(msg, call_span);
+        }
+        if let Err(error) = scan(&entries) {
+            return error.into();
+        }
+        let warnings = warnings.iter().map(|msg| emit_warning(msg, call_span));
+        let (data_statics, phf_pairs) = match generate_phf_map(&entries, &crate_root, base64) {
+            Ok(generated) => generated,
+            Err(msg) => return compile_error(msg, call_span),
+        };
         // Use a hash of the absolute path for uniqueness
         let mut hasher = std::collections::hash_map::DefaultHasher::new();
         use std::hash::{Hash, Hasher};
         abs_path_str.hash(&mut hasher);
         let hash = hasher.finish();
         let map_ident = quote::format_ident!("__EMBED_MAP_{:x}", hash);
+        let ext_ident = quote::format_ident!("__EMBED_EXTENSIONS_{:x}", hash);
+        let keys_ident = quote::format_ident!("__EMBED_KEYS_{:x}", hash);
+        let ext_pairs = generate_extension_index(&entries);
+        let mut sorted_keys: Vec<&str> = entries.iter().map(|e| e.rel_path.as_str()).collect();
+        sorted_keys.sort_unstable();
+        let abi_lit = syn::LitInt::new(&SILO_ABI.to_string(), call_span);
+        // The absolute path is only needed by `include_bytes!`, which does not keep it.
+        let embed_root_lit = if redact_root {
+            syn::LitStr::new("", call_span)
+        } else {
+            abs_root_lit
+        };
+        let build_id = build_id.then(|| {
+            let id = match deterministic {
+                Some((_, true)) => build_id::deterministic(
+                    entries
+                        .iter()
+                        .map(|entry| (entry.rel_path.as_str(), entry.content_hash)),
+                ),
+                _ => build_id::random(),
+            };
+            quote! { .with_build_id([#(#id),*]) }
+        });
         let expanded = quote! {
             {
+                const _: () = assert!(
+                    #crate_root::SILO_ABI == #abi_lit,
+                    "embed_silo!: generated code does not match the rust-silos EmbedEntry layout; regenerate it with matching rust-silos and rust-silos-macros versions"
+                );
+                #(#warnings)*
+                #data_statics
                 static #map_ident: #crate_root::phf::Map<&'static str, #crate_root::EmbedEntry> = #crate_root::phf::phf_map! {
                     #phf_pairs
                 };
-                #crate_root::Silo::from_embedded(&#map_ident, #abs_root_lit)
+                static #ext_ident: #crate_root::phf::Map<&'static str, &'static [&'static str]> = #crate_root::phf::phf_map! {
+                    #ext_pairs
+                };
+                static #keys_ident: &[&str] = &[#(#sorted_keys),*];
+                #crate_root::Silo::from_embedded_with_extensions(&#map_ident, &#ext_ident, #embed_root_lit).with_sorted_keys(#keys_ident)#build_id #keys_call #symlinks_call
             }
         };
         expanded.into()
+    } else if let Some(transcode) = &transcode {
+        let extensions = &transcode.extensions;
+        let on_failure = if transcode.fail {
+            quote! { #crate_root::TranscodeFailure::Error }
+        } else {
+            quote! { #crate_root::TranscodeFailure::Skip }
+        };
+        let expanded = quote! {
+            #crate_root::Silo::from_static_transcoded(
+                #abs_root_lit,
+                #crate_root::Transcoding { extensions: &[#(#extensions),*], on_failure: #on_failure },
+            ) #keys_call #symlinks_call
+        };
+        expanded.into()
     } else {
         let expanded = quote! {
-            #crate_root::Silo::from_static(#abs_root_lit)
+            #crate_root::Silo::from_static(#abs_root_lit) #keys_call #symlinks_call
         };
         expanded.into()
     }
 }
 
+/// Resolves `dir_path` against the crate root, which it must stay inside. Returns the
+/// canonical crate root and directory.
+fn resolve_dir(
+    macro_name: &str,
+    dir_path: &str,
+    call_span: proc_macro2::Span,
+) -> Result<(PathBuf, PathBuf), TokenStream> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| String::new());
+    if manifest_dir.is_empty() {
+        return Err(compile_error(
+            format!("{macro_name}: CARGO_MANIFEST_DIR not set"),
+            call_span,
+        ));
+    }
+    let manifest_dir_canon = match Path::new(&manifest_dir).canonicalize() {
+        Ok(p) => p,
+        Err(_) => {
+            return Err(compile_error(
+                format!("{macro_name}: failed to resolve CARGO_MANIFEST_DIR"),
+                call_span,
+            ));
+        }
+    };
+    let abs_path = match manifest_dir_canon.join(dir_path).canonicalize() {
+        Ok(p) => p,
+        Err(_) => {
+            return Err(compile_error(
+                format!("{macro_name}: failed to resolve path: {dir_path}"),
+                call_span,
+            ));
+        }
+    };
+    // Path-safe containment check (avoid prefix-string bugs like /foo/bar matching /foo/bar2).
+    if !abs_path.starts_with(&manifest_dir_canon) {
+        let msg = format!(
+            "{macro_name}: directory not found:\n  {}\n  expected to be inside crate root:\n  {}\n  relative path: {}",
+            abs_path.display(),
+            manifest_dir_canon.display(),
+            dir_path
+        );
+        return Err(compile_error(&msg, call_span));
+    }
+    Ok((manifest_dir_canon, abs_path))
+}
+
 /// Recursively collects all files in the given directory for embedding.
 /// Returns (entries, errors):
 ///   - entries: Vec<(relative_path, abs_path, size, modified)>
 ///   - errors: Vec<TokenStream> for compile_error!s
-fn collect_embed_entries(dir: &str, span: proc_macro2::Span) -> CollectResult {
+fn collect_embed_entries(
+    dir: &str,
+    span: proc_macro2::Span,
+    options: CollectOptions,
+) -> CollectResult {
     let mut entries = Vec::new();
     let mut errors = Vec::new();
+    let mut warnings = Vec::new();
     let root = Path::new(dir);
-    for entry in WalkDir::new(root).into_iter() {
+    // Unreadable files are either hard errors or, with `skip_unreadable`, warnings.
+    let unreadable = |msg: String, errors: &mut Vec<_>, warnings: &mut Vec<_>| {
+        if options.skip_unreadable {
+            warnings.push(format!("{msg} (skipped)"));
+        } else {
+            errors.push(quote_spanned! {span=> compile_error!(#msg); });
+        }
+    };
+    for entry in WalkDir::new(root)
+        .follow_links(options.follow_symlinks)
+        .into_iter()
+    {
         let entry = match entry {
             Ok(e) => e,
             Err(e) => {
-                let msg = format!("embed_silo!: failed to read entry: {}", e);
-                errors.push(quote_spanned! {span=> compile_error!(#msg); });
+                unreadable(
+                    format!("embed_silo!: failed to read entry: {}", e),
+                    &mut errors,
+                    &mut warnings,
+                );
                 continue;
             }
         };
@@ -160,14 +1021,31 @@ fn collect_embed_entries(dir: &str, span: proc_macro2::Span) -> CollectResult {
             let abs_path = match path.canonicalize() {
                 Ok(p) => p.to_string_lossy().to_string(),
                 Err(_) => {
-                    let msg = format!("embed_silo!: failed to canonicalize file: {}", path.display());
-                    errors.push(quote_spanned! {span=> compile_error!(#msg); });
+                    unreadable(
+                        format!(
+                            "embed_silo!: failed to canonicalize file: {}",
+                            path.display()
+                        ),
+                        &mut errors,
+                        &mut warnings,
+                    );
                     continue;
                 }
             };
-            let size = match fs::metadata(path) {
-                Ok(meta) => meta.len() as usize,
-                Err(_) => 0,
+            let content_hash = match fs::read(path) {
+                Ok(bytes) => content_hash::content_hash(&bytes),
+                Err(e) => {
+                    unreadable(
+                        format!("embed_silo!: failed to read file {}: {}", path.display(), e),
+                        &mut errors,
+                        &mut warnings,
+                    );
+                    continue;
+                }
+            };
+            let (size, mode) = match fs::metadata(path) {
+                Ok(meta) => (meta.len() as usize, cache::file_mode(&meta)),
+                Err(_) => (0, 0),
             };
             let modified = match fs::metadata(path)
                 .and_then(|m| m.modified())
@@ -177,17 +1055,276 @@ fn collect_embed_entries(dir: &str, span: proc_macro2::Span) -> CollectResult {
                 Some(d) => d.as_secs(),
                 None => 0,
             };
-            entries.push((rel_path, abs_path, size, modified));
+            entries.push(EmbedMeta {
+                rel_path,
+                abs_path,
+                size,
+                modified,
+                transcoded: None,
+                content_hash,
+                mode,
+            });
         }
     }
 
     // Make builds more reproducible across platforms/filesystems.
-    entries.sort_by(|(a, _, _, _), (b, _, _, _)| a.cmp(b));
-    (entries, errors)
+    entries.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    (entries, errors, warnings)
+}
+
+/// Like `collect_embed_entries`, but reuses the entry list cached for this directory while the
+/// metadata digest of the tree matches. Any mismatch or unreadable cache falls back to a full
+/// walk; only walks without errors or warnings are cached, so those are reported every time.
+fn collect_cached(
+    dir: &str,
+    span: proc_macro2::Span,
+    options: CollectOptions,
+    manifest_dir: &Path,
+) -> CollectResult {
+    let root = Path::new(dir);
+    let file = cache::cache_file(
+        &cache::cache_dir(manifest_dir),
+        root,
+        options.follow_symlinks,
+        options.skip_unreadable,
+    );
+    let Some(digest) = cache::tree_digest(root, options.follow_symlinks) else {
+        return collect_embed_entries(dir, span, options);
+    };
+    if let Some(cached) = cache::load(&file, digest) {
+        let entries = cached
+            .into_iter()
+            .map(|entry| EmbedMeta {
+                rel_path: entry.rel_path,
+                abs_path: entry.abs_path,
+                size: entry.size,
+                modified: entry.modified,
+                transcoded: None,
+                content_hash: entry.content_hash,
+                mode: entry.mode,
+            })
+            .collect();
+        return (entries, Vec::new(), Vec::new());
+    }
+    let (entries, errors, warnings) = collect_embed_entries(dir, span, options);
+    // The tree may have changed during the walk; only store what the digest still describes.
+    // Files modified in the last couple of seconds could change again within the same mtime
+    // tick without changing the digest, so those trees are not cached yet.
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let settled = entries.iter().all(|entry| entry.modified + 2 < now);
+    if errors.is_empty()
+        && warnings.is_empty()
+        && settled
+        && cache::tree_digest(root, options.follow_symlinks) == Some(digest)
+    {
+        let cached: Vec<_> = entries
+            .iter()
+            .map(|entry| cache::CachedEntry {
+                rel_path: entry.rel_path.clone(),
+                abs_path: entry.abs_path.clone(),
+                size: entry.size,
+                modified: entry.modified,
+                content_hash: entry.content_hash,
+                mode: entry.mode,
+            })
+            .collect();
+        // A cache that cannot be written only costs the next expansion a walk.
+        let _ = cache::store(&file, digest, &cached);
+    }
+    (entries, errors, warnings)
+}
+
+/// Transcodes the text files among `entries` to UTF-8 in place. Files outside the extension
+/// list and files that are already UTF-8 are left to `include_bytes!`.
+fn transcode_entries(
+    entries: &mut [EmbedMeta],
+    options: &TranscodeOptions,
+    span: proc_macro2::Span,
+    errors: &mut Vec<proc_macro2::TokenStream>,
+    warnings: &mut Vec<String>,
+) {
+    for entry in entries {
+        let is_text = Path::new(&entry.rel_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                options
+                    .extensions
+                    .iter()
+                    .any(|e| e.eq_ignore_ascii_case(ext))
+            });
+        if !is_text {
+            continue;
+        }
+        let bytes = match fs::read(&entry.abs_path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let msg = format!("embed_silo!: failed to read file {}: {}", entry.abs_path, e);
+                errors.push(quote_spanned! {span=> compile_error!(#msg); });
+                continue;
+            }
+        };
+        match transcode::decode(&bytes) {
+            transcode::Decoded::Utf8 => {}
+            transcode::Decoded::Transcoded(utf8, encoding) => {
+                entry.size = utf8.len();
+                entry.content_hash = content_hash::content_hash(&utf8);
+                entry.transcoded = Some((utf8, encoding));
+            }
+            transcode::Decoded::Unknown => {
+                let msg = format!(
+                    "embed_silo!: could not detect the text encoding of {}",
+                    entry.rel_path
+                );
+                if options.fail {
+                    errors.push(quote_spanned! {span=> compile_error!(#msg); });
+                } else {
+                    warnings.push(format!("{msg} (embedded untouched)"));
+                }
+            }
+        }
+    }
+}
+
+/// Runs the `deny_containing` and `audit` scans over the collected files. The audit report is
+/// written first, so it is available even when a deny rule fails the build.
+fn scan_entries(
+    entries: &[EmbedMeta],
+    deny: Option<&(syn::Ident, Vec<String>)>,
+    audit: Option<&LitStr>,
+    dir_path: &str,
+    manifest_dir: &Path,
+) -> Result<(), proc_macro2::TokenStream> {
+    let mut report = Vec::new();
+    let mut offending = Vec::new();
+    for entry in entries {
+        let head = scan::read_head(&entry.abs_path).map_err(|e| {
+            let span = deny
+                .map(|(ident, _)| ident.span())
+                .or(audit.map(LitStr::span))
+                .unwrap_or_else(proc_macro2::Span::call_site);
+            let msg = format!("embed_silo!: failed to scan file {}: {}", entry.abs_path, e);
+            quote_spanned! {span=> compile_error!(#msg) }
+        })?;
+        if let Some((_, patterns)) = deny {
+            let hits = scan::denied(&head, patterns);
+            if !hits.is_empty() {
+                offending.push(format!(
+                    "  {}: {}",
+                    entry.rel_path,
+                    hits.iter()
+                        .map(|p| format!("{p:?}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        if audit.is_some() {
+            report.push((entry.rel_path.clone(), scan::detect_licenses(&head)));
+        }
+    }
+    if let Some(audit) = audit {
+        let path = manifest_dir.join(audit.value());
+        let contents = scan::audit_report(dir_path, &report);
+        if let Err(e) = report::write_if_changed(&path, &contents) {
+            let msg = format!(
+                "embed_silo!: failed to write audit report {}: {}",
+                path.display(),
+                e
+            );
+            return Err(quote_spanned! {audit.span()=> compile_error!(#msg) });
+        }
+    }
+    if let Some((ident, _)) = deny.filter(|_| !offending.is_empty()) {
+        let msg = format!(
+            "embed_silo!: {} file(s) contain patterns denied by deny_containing:\n{}",
+            offending.len(),
+            offending.join("\n")
+        );
+        return Err(quote_spanned! {ident.span()=> compile_error!(#msg) });
+    }
+    Ok(())
+}
+
+/// Writes the `report_json` report of duplicate files and files over `size_budget`.
+fn write_report(
+    entries: &[EmbedMeta],
+    report_path: &LitStr,
+    size_budget: Option<&syn::LitInt>,
+    dir_path: &str,
+    manifest_dir: &Path,
+) -> Result<(), proc_macro2::TokenStream> {
+    let path = manifest_dir.join(report_path.value());
+    let budget = size_budget.and_then(|budget| budget.base10_parse().ok());
+    let report_entries: Vec<_> = entries
+        .iter()
+        .map(|entry| report::ReportEntry {
+            rel_path: &entry.rel_path,
+            abs_path: &entry.abs_path,
+            size: entry.size,
+            content_hash: entry.content_hash,
+            transcoded: entry.transcoded.as_ref().map(|(bytes, _)| bytes.as_slice()),
+        })
+        .collect();
+    let written = report::render(dir_path, &report_entries, budget)
+        .and_then(|contents| report::write_if_changed(&path, &contents));
+    if let Err(e) = written {
+        let msg = format!(
+            "embed_silo!: failed to write report {}: {}",
+            path.display(),
+            e
+        );
+        return Err(quote_spanned! {report_path.span()=> compile_error!(#msg) });
+    }
+    Ok(())
+}
+
+/// Writes the `emit_expansion` file for `items`, the code `macro_name!(args)` generated, if
+/// the environment enables it. Like `report_json`, the file is replaced atomically and only
+/// when its contents change.
+fn emit_expansion(
+    macro_name: &str,
+    path: &LitStr,
+    args: &str,
+    totals: expansion::Totals,
+    items: proc_macro2::TokenStream,
+) -> Result<(), TokenStream> {
+    if !expansion::enabled(std::env::var(expansion::ENV).ok().as_deref()) {
+        return Ok(());
+    }
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let out = Path::new(&manifest_dir).join(path.value());
+    let written = expansion::render(macro_name, args, totals, items)
+        .map_err(|e| format!("cannot format generated code: {e}"))
+        .and_then(|contents| report::write_if_changed(&out, &contents).map_err(|e| e.to_string()));
+    written.map_err(|e| {
+        compile_error(
+            format!(
+                "{macro_name}!: failed to write expansion {}: {e}",
+                out.display()
+            ),
+            path.span(),
+        )
+    })
 }
 
 // emit_compile_error removed; use quote_spanned! inline instead
 
+/// Emits a compiler warning from an expression-position expansion by referencing a
+/// deprecated constant (proc macros have no stable warning API).
+fn emit_warning(msg: &str, span: proc_macro2::Span) -> proc_macro2::TokenStream {
+    let lit = syn::LitStr::new(msg, span);
+    quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #lit)]
+            const EMBED_SILO_WARNING: () = ();
+            EMBED_SILO_WARNING
+        };
+    }
+}
+
 /// Emit compile_error! and return from macro expansion.
 fn compile_error<S: AsRef<str>>(msg: S, span: proc_macro2::Span) -> proc_macro::TokenStream {
     let lit = syn::LitStr::new(msg.as_ref(), span);
@@ -195,24 +1332,90 @@ fn compile_error<S: AsRef<str>>(msg: S, span: proc_macro2::Span) -> proc_macro::
     tokens.into()
 }
 
-/// Generates a PHF map token stream from the collected entries.
-/// Used internally by the macro. Expects (rel_path, abs_path, size, modified) tuples.
-fn generate_phf_map(entries: &[EmbedMeta], crate_root: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    let pairs = entries.iter().map(|(rel_path, abs_path, size, modified)| {
-        let rel_path_lit = syn::LitStr::new(rel_path, proc_macro2::Span::call_site());
-        let abs_path_lit = syn::LitStr::new(abs_path, proc_macro2::Span::call_site());
-        let size_lit = syn::LitInt::new(&size.to_string(), proc_macro2::Span::call_site());
-        let mod_lit = syn::LitInt::new(&modified.to_string(), proc_macro2::Span::call_site());
+/// Groups the entry keys by lowercase extension (`""` for none) for `Silo::by_extension`.
+/// Mirrors `extension_key` in rust-silos.
+fn generate_extension_index(entries: &[EmbedMeta]) -> proc_macro2::TokenStream {
+    let mut groups: std::collections::BTreeMap<String, Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for entry in entries {
+        let ext = Path::new(&entry.rel_path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        groups.entry(ext).or_default().push(&entry.rel_path);
+    }
+    let pairs = groups
+        .iter()
+        .map(|(ext, keys)| quote! { #ext => &[#(#keys),*], });
+    quote! { #(#pairs)* }
+}
+
+/// Generates the data statics and PHF map token streams from the collected entries.
+/// Used internally by the macro.
+///
+/// Each distinct canonical file gets one `include_bytes!` static, so entries that resolve to the
+/// same target (e.g. symlinks) share their bytes while keeping separate keys. Transcoded files
+/// are emitted as byte string literals instead, and with `base64` every file is emitted as a
+/// base64 string literal; see `entry_data.rs`, which also derives each entry's `size` from
+/// the emitted bytes rather than from metadata.
+fn generate_phf_map(
+    entries: &[EmbedMeta],
+    crate_root: &proc_macro2::TokenStream,
+    base64: bool,
+) -> Result<(proc_macro2::TokenStream, proc_macro2::TokenStream), String> {
+    let mut data_idents = std::collections::BTreeMap::new();
+    let mut statics = Vec::new();
+    for entry in entries {
+        if !data_idents.contains_key(&entry.abs_path) {
+            let ident = quote::format_ident!("__EMBED_DATA_{}", data_idents.len());
+            let data = match &entry.transcoded {
+                _ if base64 => entry_data::Data::Base64(match &entry.transcoded {
+                    Some((utf8, _)) => utf8.clone(),
+                    None => fs::read(&entry.abs_path).map_err(|e| {
+                        format!("embed_silo!: failed to read file {}: {}", entry.abs_path, e)
+                    })?,
+                }),
+                None => entry_data::Data::Include,
+                Some((utf8, _)) => entry_data::Data::Literal(utf8.clone()),
+            };
+            let (item, size) = entry_data::data_static(&ident, &entry.abs_path, &data, crate_root);
+            statics.push(item);
+            data_idents.insert(entry.abs_path.clone(), (ident, size));
+        }
+    }
+    let pairs = entries.iter().map(|entry| {
+        let rel_path_lit = syn::LitStr::new(&entry.rel_path, proc_macro2::Span::call_site());
+        let (data_ident, size) = &data_idents[&entry.abs_path];
+        let mod_lit = syn::LitInt::new(&entry.modified.to_string(), proc_macro2::Span::call_site());
+        let hash_lit = syn::LitInt::new(
+            &format!("{:#x}", entry.content_hash),
+            proc_macro2::Span::call_site(),
+        );
+        let mode_lit = syn::LitInt::new(
+            &format!("{:#o}", entry.mode),
+            proc_macro2::Span::call_site(),
+        );
+        let encoding = match &entry.transcoded {
+            Some((_, encoding)) => quote! { Some(#encoding) },
+            None => quote! { None },
+        };
+        let (contents, base64_text) = if base64 {
+            (quote! { &[] }, quote! { Some(&#data_ident) })
+        } else {
+            (quote! { #data_ident }, quote! { None })
+        };
         quote! {
             #rel_path_lit => #crate_root::EmbedEntry {
                 path: #rel_path_lit,
-                contents: include_bytes!(#abs_path_lit),
-                size: #size_lit,
+                contents: #contents,
+                size: #size,
                 modified: #mod_lit,
+                original_encoding: #encoding,
+                content_hash: #hash_lit,
+                base64: #base64_text,
+                mode: #mode_lit,
             },
         }
     });
-    quote! {
-        #(#pairs)*
-    }
+    Ok((quote! { #(#statics)* }, quote! { #(#pairs)* }))
 }
@@ -0,0 +1,270 @@
+//! `report_json`: a machine-readable build report of byte-identical duplicates and files over
+//! a size budget, for CI.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Bumped whenever a field of the report is renamed, removed or changes type.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A file as seen by the report.
+pub struct ReportEntry<'a> {
+    pub rel_path: &'a str,
+    pub abs_path: &'a str,
+    /// Embedded size, after any transcoding.
+    pub size: usize,
+    pub content_hash: u64,
+    /// Embedded contents if they differ from the file on disk.
+    pub transcoded: Option<&'a [u8]>,
+}
+
+/// Renders the report for `entries`, which must be sorted by path.
+///
+/// Files are grouped as duplicates by size and content hash, and each group is confirmed
+/// byte for byte, so a hash collision never reports distinct files as duplicates.
+pub fn render(
+    root: &str,
+    entries: &[ReportEntry],
+    size_budget: Option<usize>,
+) -> std::io::Result<String> {
+    let mut candidates: BTreeMap<(usize, u64), Vec<&ReportEntry>> = BTreeMap::new();
+    for entry in entries {
+        candidates
+            .entry((entry.size, entry.content_hash))
+            .or_default()
+            .push(entry);
+    }
+    let mut duplicates: Vec<(usize, Vec<&str>)> = Vec::new();
+    for ((size, _), group) in candidates.into_iter().filter(|(_, group)| group.len() > 1) {
+        let mut distinct: Vec<(Vec<u8>, Vec<&str>)> = Vec::new();
+        for entry in group {
+            let bytes = match entry.transcoded {
+                Some(bytes) => bytes.to_vec(),
+                None => std::fs::read(entry.abs_path)?,
+            };
+            match distinct.iter_mut().find(|(seen, _)| *seen == bytes) {
+                Some((_, paths)) => paths.push(entry.rel_path),
+                None => distinct.push((bytes, vec![entry.rel_path])),
+            }
+        }
+        duplicates.extend(
+            distinct
+                .into_iter()
+                .filter(|(_, paths)| paths.len() > 1)
+                .map(|(_, paths)| (size, paths)),
+        );
+    }
+    duplicates.sort_by(|a, b| a.1.cmp(&b.1));
+    let over_budget: Vec<&ReportEntry> = match size_budget {
+        Some(budget) => entries.iter().filter(|entry| entry.size > budget).collect(),
+        None => Vec::new(),
+    };
+
+    let total_bytes: usize = entries.iter().map(|entry| entry.size).sum();
+    let duplicate_files: usize = duplicates.iter().map(|(_, paths)| paths.len() - 1).sum();
+    let duplicate_bytes: usize = duplicates
+        .iter()
+        .map(|(size, paths)| size * (paths.len() - 1))
+        .sum();
+    let budget = size_budget.map_or_else(|| "null".to_owned(), |budget| budget.to_string());
+
+    let mut out = format!(
+        "{{\n  \"schema\": {SCHEMA_VERSION},\n  \"root\": {},\n  \"size_budget\": {budget},\n",
+        json_string(root)
+    );
+    out.push_str("  \"duplicates\": [");
+    for (i, (_, paths)) in duplicates.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        let paths = paths
+            .iter()
+            .map(|path| json_string(path))
+            .collect::<Vec<_>>()
+            .join(", ");
+        out.push_str(&format!("{sep}\n    [{paths}]"));
+    }
+    out.push_str(if duplicates.is_empty() {
+        "],\n"
+    } else {
+        "\n  ],\n"
+    });
+    out.push_str("  \"over_budget\": [");
+    for (i, entry) in over_budget.iter().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        out.push_str(&format!(
+            "{sep}\n    {{ \"path\": {}, \"size\": {} }}",
+            json_string(entry.rel_path),
+            entry.size
+        ));
+    }
+    out.push_str(if over_budget.is_empty() {
+        "],\n"
+    } else {
+        "\n  ],\n"
+    });
+    out.push_str(&format!(
+        "  \"totals\": {{ \"files\": {}, \"bytes\": {total_bytes}, \"duplicate_files\": {duplicate_files}, \"duplicate_bytes\": {duplicate_bytes}, \"over_budget_files\": {} }}\n}}\n",
+        entries.len(),
+        over_budget.len()
+    ));
+    Ok(out)
+}
+
+/// Writes `contents` to `path` unless it already holds exactly that, replacing the file
+/// atomically so readers never see a partial report.
+pub fn write_if_changed(path: &Path, contents: &str) -> std::io::Result<()> {
+    if std::fs::read_to_string(path).ok().as_deref() == Some(contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = path.with_file_name(format!(".{name}.tmp{}", std::process::id()));
+    let result = std::fs::write(&temp, contents).and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+pub fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn entry<'a>(
+        rel_path: &'a str,
+        abs_path: &'a str,
+        size: usize,
+        content_hash: u64,
+    ) -> ReportEntry<'a> {
+        ReportEntry {
+            rel_path,
+            abs_path,
+            size,
+            content_hash,
+            transcoded: None,
+        }
+    }
+
+    /// The report keeps its field names and types; changing them must bump `SCHEMA_VERSION`.
+    #[test]
+    fn test_report_schema() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = |name: &str| tmp.path().join(name).to_str().unwrap().to_owned();
+        std::fs::write(path("a"), "same").unwrap();
+        std::fs::write(path("b"), "same").unwrap();
+        std::fs::write(path("c"), "diff").unwrap();
+        std::fs::write(path("big"), "0123456789").unwrap();
+        let (a, b, c, big) = (path("a"), path("b"), path("c"), path("big"));
+        // `c` collides with `a` and `b` on size and hash but differs in content.
+        let entries = [
+            entry("a.txt", &a, 4, 1),
+            entry("big.bin", &big, 10, 2),
+            entry("dir/b.txt", &b, 4, 1),
+            entry("dir/c.txt", &c, 4, 1),
+        ];
+        let json: Value =
+            serde_json::from_str(&render("assets", &entries, Some(8)).unwrap()).unwrap();
+
+        let keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "duplicates",
+                "over_budget",
+                "root",
+                "schema",
+                "size_budget",
+                "totals"
+            ]
+        );
+        assert_eq!(json["schema"], SCHEMA_VERSION);
+        assert_eq!(json["root"], "assets");
+        assert_eq!(json["size_budget"], 8);
+        assert_eq!(
+            json["duplicates"],
+            serde_json::json!([["a.txt", "dir/b.txt"]])
+        );
+        assert_eq!(
+            json["over_budget"],
+            serde_json::json!([{ "path": "big.bin", "size": 10 }])
+        );
+        assert_eq!(
+            json["totals"],
+            serde_json::json!({ "files": 4, "bytes": 22, "duplicate_files": 1, "duplicate_bytes": 4, "over_budget_files": 1 })
+        );
+    }
+
+    /// Without a budget or duplicates the sections are empty, not missing.
+    #[test]
+    fn test_report_empty_sections() {
+        let json: Value = serde_json::from_str(&render("x\"y", &[], None).unwrap()).unwrap();
+        assert_eq!(json["root"], "x\"y");
+        assert_eq!(json["size_budget"], Value::Null);
+        assert_eq!(json["duplicates"], serde_json::json!([]));
+        assert_eq!(json["over_budget"], serde_json::json!([]));
+        assert_eq!(json["totals"]["files"], 0);
+    }
+
+    /// Transcoded contents are compared instead of the bytes on disk.
+    #[test]
+    fn test_report_compares_transcoded_contents() {
+        let entries = [
+            ReportEntry {
+                transcoded: Some(b"caf\xc3\xa9"),
+                ..entry("a.txt", "/nonexistent/a", 5, 9)
+            },
+            ReportEntry {
+                transcoded: Some(b"caf\xc3\xa9"),
+                ..entry("b.txt", "/nonexistent/b", 5, 9)
+            },
+        ];
+        let json: Value = serde_json::from_str(&render("t", &entries, None).unwrap()).unwrap();
+        assert_eq!(json["duplicates"], serde_json::json!([["a.txt", "b.txt"]]));
+    }
+
+    /// Writing leaves no temporary file behind and skips identical contents.
+    #[test]
+    fn test_report_write_if_changed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let target = tmp.path().join("out/report.json");
+        write_if_changed(&target, "{}\n").unwrap();
+        let modified = std::fs::metadata(&target).unwrap().modified().unwrap();
+        write_if_changed(&target, "{}\n").unwrap();
+        assert_eq!(
+            std::fs::metadata(&target).unwrap().modified().unwrap(),
+            modified
+        );
+        assert_eq!(
+            std::fs::read_dir(tmp.path().join("out")).unwrap().count(),
+            1
+        );
+    }
+}
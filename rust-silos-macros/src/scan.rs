@@ -0,0 +1,118 @@
+//! Embed-time content scanning for `deny_containing` and `audit`: literal byte search over
+//! the start of each file, binary files included.
+
+use crate::report::json_string;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of each file; markers and license headers sit at the top.
+pub const SCAN_LIMIT: u64 = 64 * 1024;
+
+/// License header phrases, checked in order. More specific phrases come first so that e.g.
+/// the LGPL is not also reported as the GPL.
+const LICENSE_HEADERS: &[(&str, &str)] = &[
+    ("AGPL", "GNU Affero General Public License"),
+    ("LGPL", "GNU Lesser General Public License"),
+    ("LGPL", "GNU Library General Public License"),
+    ("GPL", "GNU General Public License"),
+    (
+        "Apache-2.0",
+        "Licensed under the Apache License, Version 2.0",
+    ),
+    ("MPL-2.0", "Mozilla Public License, v. 2.0"),
+    ("MIT", "Permission is hereby granted, free of charge"),
+    ("BSD", "Redistribution and use in source and binary forms"),
+    (
+        "Unlicense",
+        "This is free and unencumbered software released into the public domain",
+    ),
+];
+
+const SPDX_TAG: &[u8] = b"SPDX-License-Identifier:";
+
+/// Reads up to [`SCAN_LIMIT`] bytes from the start of `path`.
+pub fn read_head(path: &str) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    File::open(Path::new(path))?
+        .take(SCAN_LIMIT)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Byte offset of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The patterns from `deny` that occur in `head`.
+pub fn denied<'a>(head: &[u8], deny: &'a [String]) -> Vec<&'a str> {
+    deny.iter()
+        .filter(|pattern| find(head, pattern.as_bytes()).is_some())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Licenses whose headers occur in `head`, sorted and deduplicated. `SPDX-License-Identifier:`
+/// tags contribute the expression that follows them.
+pub fn detect_licenses(head: &[u8]) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = head;
+    while let Some(start) = find(rest, SPDX_TAG) {
+        rest = &rest[start + SPDX_TAG.len()..];
+        let line = rest.split(|&b| b == b'\n').next().unwrap_or_default();
+        let expr = String::from_utf8_lossy(line);
+        let expr = expr
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim();
+        if !expr.is_empty() {
+            found.push(expr.to_owned());
+        }
+    }
+    let mut matched = Vec::new();
+    for (name, phrase) in LICENSE_HEADERS {
+        if let Some(at) = find(head, phrase.as_bytes()) {
+            // A more specific header already claimed this spot (the GPL phrase inside "Lesser").
+            if matched.iter().any(|&(start, end)| at >= start && at < end) {
+                continue;
+            }
+            matched.push((at, at + phrase.len()));
+            found.push((*name).to_owned());
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Renders the audit report: every file with the licenses detected in it.
+pub fn audit_report(root: &str, files: &[(String, Vec<String>)]) -> String {
+    let mut out = format!(
+        "{{\n  \"root\": {},\n  \"scan_limit\": {SCAN_LIMIT},\n  \"files\": [",
+        json_string(root)
+    );
+    for (i, (path, licenses)) in files.iter().enumerate() {
+        let licenses = licenses
+            .iter()
+            .map(|l| json_string(l))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sep = if i == 0 { "" } else { "," };
+        out.push_str(&format!(
+            "{sep}\n    {{ \"path\": {}, \"licenses\": [{licenses}] }}",
+            json_string(path)
+        ));
+    }
+    if !files.is_empty() {
+        out.push_str("\n  ");
+    }
+    out.push_str("]\n}\n");
+    out
+}
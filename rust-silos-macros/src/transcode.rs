@@ -0,0 +1,87 @@
+//! Embed-time normalization of legacy text encodings to UTF-8.
+//!
+//! The detection rules mirror `rust-silos/src/transcode.rs`, which applies them to dynamic
+//! files at runtime; keep the two in sync so dev mode decodes exactly like embedded builds.
+
+/// Extensions transcoded when `transcode = "utf8"` is given without `transcode_extensions`.
+pub const DEFAULT_EXTENSIONS: &[&str] = &[
+    "txt", "csv", "tsv", "md", "html", "htm", "xml", "json", "css", "js", "svg",
+];
+
+/// Outcome of inspecting a text file.
+pub enum Decoded {
+    /// Already UTF-8 without a BOM; the bytes are used as-is.
+    Utf8,
+    /// Decoded from the named encoding.
+    Transcoded(Vec<u8>, &'static str),
+    /// Binary content or an unrecognized encoding.
+    Unknown,
+}
+
+/// Detects the encoding of `bytes` and decodes them to UTF-8.
+///
+/// Recognizes a UTF-8 BOM (which is stripped), UTF-16 with a BOM or with the NUL byte
+/// pattern of mostly-ASCII text (checked first, since such text can be valid UTF-8), plain
+/// UTF-8, and otherwise falls back to windows-1252 (the superset of Latin-1 that legacy
+/// "Latin-1" files actually use). Content with NUL bytes that is not UTF-16 is treated as
+/// binary.
+pub fn decode(bytes: &[u8]) -> Decoded {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return match std::str::from_utf8(rest) {
+            Ok(text) => Decoded::Transcoded(text.as_bytes().to_vec(), "UTF-8"),
+            Err(_) => Decoded::Unknown,
+        };
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return decode_utf16(rest, u16::from_le_bytes, "UTF-16LE");
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return decode_utf16(rest, u16::from_be_bytes, "UTF-16BE");
+    }
+    if bytes.len().is_multiple_of(2) {
+        let pairs = bytes.len() / 2;
+        let even_nuls = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd_nuls = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        if odd_nuls * 2 > pairs && even_nuls * 10 < pairs {
+            return decode_utf16(bytes, u16::from_le_bytes, "UTF-16LE");
+        }
+        if even_nuls * 2 > pairs && odd_nuls * 10 < pairs {
+            return decode_utf16(bytes, u16::from_be_bytes, "UTF-16BE");
+        }
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return Decoded::Utf8;
+    }
+    if bytes.contains(&0) {
+        return Decoded::Unknown;
+    }
+    let text: String = bytes.iter().map(|&b| windows_1252(b)).collect();
+    Decoded::Transcoded(text.into_bytes(), "windows-1252")
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16, label: &'static str) -> Decoded {
+    if !bytes.len().is_multiple_of(2) {
+        return Decoded::Unknown;
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    match char::decode_utf16(units).collect::<Result<String, _>>() {
+        Ok(text) => Decoded::Transcoded(text.into_bytes(), label),
+        Err(_) => Decoded::Unknown,
+    }
+}
+
+/// Maps a windows-1252 byte to its character. Bytes 0x80..=0x9F differ from Latin-1;
+/// the five undefined ones map to the C1 controls, as in the WHATWG encoding standard.
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}',
+        '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}',
+        '\u{178}',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
+    }
+}
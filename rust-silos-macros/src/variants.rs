@@ -0,0 +1,185 @@
+//! Variant names for `embed_silo_enum!`: one `UpperCamelCase` identifier per file, with
+//! collisions and names that cannot become identifiers reported instead of guessed around.
+
+use std::collections::BTreeMap;
+
+/// How files below the top level of the directory are named.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Nested {
+    /// Reject files in subdirectories.
+    Error,
+    /// Prefix the variant with the directory names: `ui/home.svg` becomes `UiHome`.
+    Flatten,
+}
+
+impl Nested {
+    pub fn parse(value: &str) -> Option<Nested> {
+        match value {
+            "error" => Some(Nested::Error),
+            "flatten" => Some(Nested::Flatten),
+            _ => None,
+        }
+    }
+}
+
+/// The variant name for the file at `rel_path`.
+///
+/// The stem (the file name without its last extension) and, when flattening, each directory
+/// name is split at `-`, `_`, `.` and spaces, and the words are joined with their first letter
+/// uppercased: `arrow-left.svg` is `ArrowLeft`, `jquery.min.js` is `JqueryMin` and
+/// `.gitkeep` is `Gitkeep`. Any other character, a leading digit, or a result of `Self` is
+/// an error.
+pub fn variant_name(rel_path: &str, nested: Nested) -> Result<String, String> {
+    let (dirs, file_name) = match rel_path.rsplit_once('/') {
+        Some((dirs, file_name)) => (Some(dirs), file_name),
+        None => (None, rel_path),
+    };
+    if dirs.is_some() && nested == Nested::Error {
+        return Err(format!(
+            "`{rel_path}` is in a subdirectory; use nested = \"flatten\" to include it"
+        ));
+    }
+    let stem = match file_name.rfind('.') {
+        Some(0) | None => file_name,
+        Some(dot) => &file_name[..dot],
+    };
+    let mut name = String::new();
+    for part in dirs
+        .into_iter()
+        .flat_map(|dirs| dirs.split('/'))
+        .chain([stem])
+    {
+        for word in part.split(['-', '_', '.', ' ']) {
+            if let Some(bad) = word.chars().find(|c| !c.is_ascii_alphanumeric()) {
+                return Err(format!(
+                    "`{rel_path}` contains {bad:?}, which cannot be part of a variant name"
+                ));
+            }
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.push(first.to_ascii_uppercase());
+                name.extend(chars);
+            }
+        }
+    }
+    match name.chars().next() {
+        None => Err(format!(
+            "`{rel_path}` has no letters or digits to name a variant after"
+        )),
+        Some(first) if first.is_ascii_digit() => Err(format!(
+            "`{rel_path}` would start variant `{name}` with a digit"
+        )),
+        Some(_) if name == "Self" => Err(format!(
+            "`{rel_path}` would name a variant `Self`, which is reserved"
+        )),
+        Some(_) => Ok(name),
+    }
+}
+
+/// Names every path in `rel_paths`, returning `(variant, path)` pairs in input order, or one
+/// message per file that cannot be named and per group of files that map to the same name.
+pub fn variants<'a>(
+    rel_paths: &[&'a str],
+    nested: Nested,
+) -> Result<Vec<(String, &'a str)>, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut named = Vec::new();
+    let mut by_name: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for &path in rel_paths {
+        match variant_name(path, nested) {
+            Ok(name) => {
+                by_name.entry(name.clone()).or_default().push(path);
+                named.push((name, path));
+            }
+            Err(err) => errors.push(err),
+        }
+    }
+    for (name, paths) in by_name.iter().filter(|(_, paths)| paths.len() > 1) {
+        let paths = paths
+            .iter()
+            .map(|path| format!("`{path}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        errors.push(format!(
+            "{paths} all map to variant `{name}`; rename one of them"
+        ));
+    }
+    if errors.is_empty() {
+        Ok(named)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// File stems become `UpperCamelCase` variants, keeping the case inside each word.
+    #[test]
+    fn test_variant_names() {
+        let name = |path| variant_name(path, Nested::Flatten);
+        assert_eq!(name("home.svg").unwrap(), "Home");
+        assert_eq!(name("arrow-left.svg").unwrap(), "ArrowLeft");
+        assert_eq!(name("user_avatar 2x.png").unwrap(), "UserAvatar2x");
+        assert_eq!(name("jquery.min.js").unwrap(), "JqueryMin");
+        assert_eq!(name("README").unwrap(), "README");
+        assert_eq!(name("iconSet.svg").unwrap(), "IconSet");
+        assert_eq!(name(".gitkeep").unwrap(), "Gitkeep");
+        assert_eq!(name("ui/nav-bar/home.svg").unwrap(), "UiNavBarHome");
+    }
+
+    /// Names that cannot become identifiers are errors rather than being mangled further.
+    #[test]
+    fn test_unnameable_files() {
+        let name = |path| variant_name(path, Nested::Flatten);
+        assert!(name("2fa.svg").unwrap_err().contains("digit"));
+        assert!(name("café.svg").unwrap_err().contains("'é'"));
+        assert!(name("a+b.svg").unwrap_err().contains("'+'"));
+        assert!(name("-.svg").unwrap_err().contains("no letters"));
+        assert!(name("self.svg").unwrap_err().contains("Self"));
+        assert!(
+            variant_name("ui/home.svg", Nested::Error)
+                .unwrap_err()
+                .contains("nested = \"flatten\"")
+        );
+    }
+
+    /// Files mapping to the same variant are reported together, alongside unnameable ones.
+    #[test]
+    fn test_variant_collisions() {
+        let named = variants(&["arrow-left.svg", "home.svg"], Nested::Error).unwrap();
+        assert_eq!(
+            named,
+            [
+                ("ArrowLeft".to_owned(), "arrow-left.svg"),
+                ("Home".to_owned(), "home.svg")
+            ]
+        );
+
+        let errors = variants(
+            &[
+                "2fa.svg",
+                "arrow-left.svg",
+                "arrow_left.png",
+                "home.png",
+                "home.svg",
+            ],
+            Nested::Error,
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("2fa.svg"));
+        assert_eq!(
+            errors[1],
+            "`arrow-left.svg`, `arrow_left.png` all map to variant `ArrowLeft`; rename one of them"
+        );
+        assert!(errors[2].contains("`home.png`, `home.svg`"));
+
+        let errors = variants(&["ui-home.svg", "ui/home.svg"], Nested::Flatten).unwrap_err();
+        assert_eq!(
+            errors,
+            ["`ui-home.svg`, `ui/home.svg` all map to variant `UiHome`; rename one of them"]
+        );
+    }
+}
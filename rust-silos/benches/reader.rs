@@ -0,0 +1,57 @@
+//! Compares `FileReader::read_to_end`, which forwards to the inner reader, with the generic
+//! chunked `read` loop it replaced, for in-memory and on-disk files.
+//!
+//! Run with `cargo bench -p rust-silos --bench reader`.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use rust_silos::Silo;
+use std::hint::black_box;
+use std::io::Read;
+
+const SIZE: usize = 16 << 20;
+
+/// Hides every `Read` method but `read`, so `read_to_end` takes the default chunked loop.
+struct ReadOnly<R>(R);
+
+impl<R: Read> Read for ReadOnly<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+fn bench_read_to_end(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let contents: Vec<u8> = (0..SIZE).map(|i| i as u8).collect();
+    std::fs::write(dir.path().join("big.bin"), &contents).unwrap();
+    let dynamic = Silo::new(dir.path().to_str().unwrap());
+    let memory = dynamic
+        .transform(|f| f.contents().ok().map(|c| c.into_owned()))
+        .unwrap();
+
+    let mut group = c.benchmark_group("read_to_end");
+    group.throughput(Throughput::Bytes(SIZE as u64));
+    group.sample_size(20);
+    for (name, silo) in [("memory", &memory), ("dynamic", &dynamic)] {
+        let file = silo.get_file("big.bin").unwrap();
+        group.bench_function(format!("{name}/forwarded"), |b| {
+            b.iter(|| {
+                let mut buf = Vec::new();
+                file.reader().unwrap().read_to_end(&mut buf).unwrap();
+                black_box(buf)
+            })
+        });
+        group.bench_function(format!("{name}/chunked"), |b| {
+            b.iter(|| {
+                let mut buf = Vec::new();
+                ReadOnly(file.reader().unwrap())
+                    .read_to_end(&mut buf)
+                    .unwrap();
+                black_box(buf)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_to_end);
+criterion_main!(benches);
@@ -0,0 +1,342 @@
+//! Browses the embedded `tests/data` directory over HTTP: directory listings, ETags and
+//! conditional requests, byte ranges and a single-page-app fallback, using only the public
+//! API. The inventory (`SiloStats` and the manifest) is printed at startup.
+//!
+//! ```text
+//! cargo run --example asset_browser                  # http://127.0.0.1:8080/
+//! cargo run --example asset_browser -- 0.0.0.0:3000
+//! cargo run --example asset_browser -- --smoke       # request every kind of route, then exit
+//! ```
+//!
+//! The crate is framework-neutral, so connections are handled with `std::net` and each
+//! request is answered from [`Silo::serve`] the way a web framework's handler would.
+
+use rust_silos::{Entry, HttpFileResponse, RequestHeaders, Silo, SiloLike, embed_silo};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+
+/// Answers extensionless paths that match no file or directory, as a client-side router's
+/// entry point would. `tests/data` has no `index.html`, so a text file stands in.
+const SPA_ENTRY: &str = "/alpha.txt";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let assets = embed_silo!("tests/data", force = true);
+    print_inventory(&assets)?;
+    match std::env::args().nth(1).as_deref() {
+        Some("--smoke") => smoke(assets),
+        addr => {
+            let listener = TcpListener::bind(addr.unwrap_or("127.0.0.1:8080"))?;
+            println!("browsing on http://{}/", listener.local_addr()?);
+            serve_forever(listener, &assets);
+            Ok(())
+        }
+    }
+}
+
+fn print_inventory(assets: &Silo) -> Result<(), rust_silos::Error> {
+    let stats = assets.stats()?;
+    println!("{} files, {} bytes", stats.files, stats.bytes);
+    for entry in assets.manifest()?.entries {
+        println!(
+            "  {:016x}  {:>6}  {}",
+            entry.hash.unwrap_or_default(),
+            entry.size,
+            entry.path
+        );
+    }
+    Ok(())
+}
+
+/// A fully buffered response.
+struct Reply {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+fn respond(assets: &Silo, target: &str, headers: &RequestHeaders) -> Reply {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path.ends_with('/') {
+        return listing(assets, path);
+    }
+    let response = assets.serve(target, headers);
+    if response.status != 404 {
+        return buffer(response);
+    }
+    if list_entries(assets, path).is_some_and(|entries| !entries.is_empty()) {
+        let mut reply = Reply::empty(301);
+        reply.headers.push(("Location", format!("{path}/")));
+        return reply;
+    }
+    let name = path.rsplit('/').next().unwrap_or_default();
+    if name.contains('.') {
+        return Reply::empty(404);
+    }
+    buffer(assets.serve(SPA_ENTRY, headers))
+}
+
+/// Entries of the directory at the URL `path`, or `None` for paths that must not be listed.
+fn list_entries(assets: &Silo, path: &str) -> Option<Vec<Entry>> {
+    let dir = path.trim_matches('/');
+    if dir
+        .split('/')
+        .any(|segment| matches!(segment, "." | "..") || segment.contains(['%', '\\']))
+    {
+        return None;
+    }
+    Some(assets.read_dir(dir).collect())
+}
+
+fn listing(assets: &Silo, path: &str) -> Reply {
+    let entries = match list_entries(assets, path) {
+        Some(entries) if !entries.is_empty() || path == "/" => entries,
+        _ => return Reply::empty(404),
+    };
+    let mut html = format!(
+        "<!doctype html>\n<title>Index of {0}</title>\n<h1>Index of {0}</h1>\n<ul>\n",
+        escape(path)
+    );
+    if path != "/" {
+        html.push_str("<li><a href=\"../\">../</a></li>\n");
+    }
+    for entry in entries {
+        let name = match entry {
+            Entry::File(file) => file
+                .path()
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Entry::Dir(dir) => format!("{}/", dir.rsplit('/').next().unwrap_or_default()),
+        };
+        html.push_str(&format!(
+            "<li><a href=\"{0}\">{0}</a></li>\n",
+            escape(&name)
+        ));
+    }
+    html.push_str("</ul>\n");
+    Reply {
+        status: 200,
+        headers: vec![("Content-Type", "text/html; charset=utf-8".to_owned())],
+        body: html.into_bytes(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn buffer(response: HttpFileResponse) -> Reply {
+    let mut body = Vec::new();
+    if let Some(mut reader) = response.body
+        && reader.read_to_end(&mut body).is_err()
+    {
+        return Reply::empty(500);
+    }
+    Reply {
+        status: response.status,
+        headers: response.headers,
+        body,
+    }
+}
+
+fn serve_forever(listener: TcpListener, assets: &Silo) {
+    for stream in listener.incoming().flatten() {
+        if let Err(err) = handle(&stream, assets) {
+            eprintln!("connection failed: {err}");
+        }
+    }
+}
+
+fn handle(stream: &TcpStream, assets: &Silo) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let mut pairs = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            pairs.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+    let reply = match method {
+        "GET" | "HEAD" => respond(assets, target, &pairs.into_iter().collect()),
+        _ => Reply::empty(405),
+    };
+    let mut out = format!(
+        "HTTP/1.1 {} {}\r\nConnection: close\r\n",
+        reply.status,
+        reason(reply.status)
+    );
+    for (name, value) in &reply.headers {
+        out.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if !reply
+        .headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        && reply.status != 304
+    {
+        out.push_str(&format!("Content-Length: {}\r\n", reply.body.len()));
+    }
+    out.push_str("\r\n");
+    let mut writer = stream;
+    writer.write_all(out.as_bytes())?;
+    if method != "HEAD" {
+        writer.write_all(&reply.body)?;
+    }
+    writer.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        304 => "Not Modified",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        412 => "Precondition Failed",
+        416 => "Range Not Satisfiable",
+        _ => "Internal Server Error",
+    }
+}
+
+/// A response as received by [`fetch`].
+struct Fetched {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Fetched {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn fetch(
+    addr: SocketAddr,
+    target: &str,
+    headers: &[(&str, &str)],
+) -> Result<Fetched, Box<dyn Error>> {
+    let mut stream = TcpStream::connect(addr)?;
+    let mut request = format!("GET {target} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+    let mut raw = String::new();
+    stream.read_to_string(&mut raw)?;
+    let (head, body) = raw.split_once("\r\n\r\n").ok_or("truncated response")?;
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or("no status line")?
+        .parse()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_owned(), value.trim().to_owned()))
+        .collect();
+    Ok(Fetched {
+        status,
+        headers,
+        body: body.to_owned(),
+    })
+}
+
+/// Serves on an ephemeral port and checks one request of every kind against it.
+fn smoke(assets: Silo) -> Result<(), Box<dyn Error>> {
+    let alpha = String::from_utf8(assets.read("alpha.txt")?.into_owned())?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    std::thread::spawn(move || serve_forever(listener, &assets));
+    let check = |ok: bool, what: &str| {
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("smoke check failed: {what}"))
+        }
+    };
+
+    let root = fetch(addr, "/", &[])?;
+    check(
+        root.status == 200 && root.body.contains("alpha.txt") && root.body.contains("subdir/"),
+        "root listing",
+    )?;
+    let redirect = fetch(addr, "/subdir", &[])?;
+    check(
+        redirect.status == 301 && redirect.header("Location") == Some("/subdir/"),
+        "directory redirect",
+    )?;
+    let subdir = fetch(addr, "/subdir/", &[])?;
+    check(
+        subdir.status == 200 && subdir.body.contains("gamma.txt"),
+        "subdirectory listing",
+    )?;
+
+    let file = fetch(addr, "/alpha.txt", &[])?;
+    check(file.status == 200 && file.body == alpha, "file contents")?;
+    let etag = file.header("ETag").ok_or("no ETag")?;
+    check(
+        fetch(addr, "/alpha.txt", &[("If-None-Match", etag)])?.status == 304,
+        "conditional request",
+    )?;
+    let range = fetch(addr, "/alpha.txt", &[("Range", "bytes=0-4")])?;
+    check(
+        range.status == 206 && range.body == alpha[..5],
+        "byte range",
+    )?;
+    check(
+        fetch(
+            addr,
+            "/alpha.txt",
+            &[("Range", "bytes=0-4"), ("If-Range", "\"stale\"")],
+        )?
+        .status
+            == 200,
+        "stale If-Range",
+    )?;
+
+    let spa = fetch(addr, "/app/settings", &[])?;
+    check(spa.status == 200 && spa.body == alpha, "SPA fallback")?;
+    check(
+        fetch(addr, "/missing.txt", &[])?.status == 404,
+        "missing file",
+    )?;
+    check(
+        fetch(addr, "/../Cargo.toml", &[])?.status == 404,
+        "traversal",
+    )?;
+    check(
+        fetch(addr, "/subdir/../", &[])?.status == 404,
+        "listing traversal",
+    )?;
+    println!("smoke test passed");
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Silos shared by the fuzz targets: the fixture embedded as-is and scoped with key
+//! normalization, and a copy of it on disk.
+//!
+//! Not every target uses every helper.
+
+#![allow(dead_code)]
+
+use rust_silos::{KeyNormalization, Silo};
+use std::sync::LazyLock;
+
+static DISK: LazyLock<tempfile::TempDir> = LazyLock::new(|| {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("nested")).unwrap();
+    for path in ["alpha.txt", "index.html", "nested/Data.json"] {
+        let source = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixture")
+            .join(path);
+        std::fs::copy(source, dir.path().join(path)).unwrap();
+    }
+    dir
+});
+
+static SILOS: LazyLock<Vec<Silo>> = LazyLock::new(|| {
+    let disk = DISK.path().to_str().unwrap();
+    vec![
+        rust_silos::embed_silo!("fixture", force = true).with_index("index.html"),
+        rust_silos::embed_silo!("fixture", force = true)
+            .scope("nested")
+            .with_key_normalization(KeyNormalization::Lowercase),
+        Silo::new(disk).with_index("index.html"),
+        Silo::new(disk).with_key_normalization(KeyNormalization::Lowercase),
+    ]
+});
+
+pub fn silos() -> &'static [Silo] {
+    &SILOS
+}
+
+/// Panics if a lookup resolved to a file outside the fixture.
+pub fn assert_contained(file: &rust_silos::File) {
+    let path = file.path().to_string_lossy();
+    assert!(!path.split('/').any(|segment| segment == ".."), "{path}");
+    if let Some(absolute) = file.absolute_path() {
+        assert!(
+            absolute.starts_with(DISK.path().canonicalize().unwrap()),
+            "{}",
+            absolute.display()
+        );
+    }
+}
@@ -0,0 +1,19 @@
+//! Arbitrary lookup paths through `get_file`, `read_dir` and the extension fallback.
+
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|path: &str| {
+    for silo in common::silos() {
+        if let Some(file) = silo.get_file(path) {
+            common::assert_contained(&file);
+        }
+        if let Some(file) = silo.get_file_with_exts(path, &["txt", "json"]) {
+            common::assert_contained(&file);
+        }
+        let _ = silo.read_dir(path);
+    }
+});
@@ -0,0 +1,23 @@
+//! Arbitrary path globs through search filtering and header rules.
+
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+use rust_silos::{RequestHeaders, SearchOptions};
+
+fuzz_target!(|input: (&str, &[u8])| {
+    let (glob, needle) = input;
+    for silo in common::silos() {
+        let options = SearchOptions {
+            path_glob: Some(glob.to_owned()),
+            ..SearchOptions::default()
+        };
+        let _ = silo.find_bytes(needle, options).count();
+        let _ = silo
+            .clone()
+            .with_headers_for(glob, &[("X-Fuzz", "1")])
+            .serve("/alpha.txt", &RequestHeaders::new());
+    }
+});
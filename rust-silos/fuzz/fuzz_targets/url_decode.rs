@@ -0,0 +1,24 @@
+//! Arbitrary request targets and `Range` headers through the percent-decoder and `serve`.
+
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+use rust_silos::RequestHeaders;
+
+fuzz_target!(|input: (&str, Option<&str>)| {
+    let (target, range) = input;
+    let mut headers = RequestHeaders::new();
+    headers.range = range.map(str::to_owned);
+    for silo in common::silos() {
+        if let Some(file) = silo.get_file_url(target) {
+            common::assert_contained(&file);
+        }
+        let response = silo.serve(target, &headers);
+        if response.status == 206 {
+            let length: usize = response.header("Content-Length").unwrap().parse().unwrap();
+            assert!(length > 0);
+        }
+    }
+});
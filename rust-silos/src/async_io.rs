@@ -0,0 +1,62 @@
+//! Reading files from async code without blocking the runtime (`tokio` feature). The
+//! concurrent read helpers are only used by the `tera` integration and built with it.
+
+use crate::{Error, File, FileKind};
+use std::borrow::Cow;
+
+/// Most reads [`read_all`] keeps in flight at once.
+#[cfg(feature = "tera")]
+pub(crate) const MAX_CONCURRENT_READS: usize = 16;
+
+impl File {
+    /// Like [`File::contents`], but reads dynamic files with `tokio::fs`, so the calling task
+    /// never blocks its runtime. Transcoding applies as usual; the read coalescer does not.
+    /// Embedded and in-memory contents are already in memory and returned directly.
+    pub async fn contents_async(&self) -> Result<Cow<'static, [u8]>, Error> {
+        match &self.inner {
+            FileKind::Dynamic(dyn_file) => {
+                let raw = tokio::fs::read(dyn_file.absolute_path()).await?;
+                Ok(Cow::Owned(dyn_file.decode(raw)?))
+            }
+            _ => self.contents(),
+        }
+    }
+}
+
+/// Reads `files` concurrently with [`File::contents_async`], at most
+/// [`MAX_CONCURRENT_READS`] at a time, returning the contents in the order given.
+#[cfg(feature = "tera")]
+pub(crate) async fn read_all(files: Vec<File>) -> Result<Vec<Cow<'static, [u8]>>, Error> {
+    let mut contents: Vec<Cow<'static, [u8]>> = vec![Cow::Borrowed(&[]); files.len()];
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, file) in files.into_iter().enumerate() {
+        if tasks.len() >= MAX_CONCURRENT_READS
+            && let Some(joined) = tasks.join_next().await
+        {
+            let (index, result) = joined.map_err(join_error)?;
+            contents[index] = result?;
+        }
+        tasks.spawn(async move { (index, file.contents_async().await) });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        let (index, result) = joined.map_err(join_error)?;
+        contents[index] = result?;
+    }
+    Ok(contents)
+}
+
+/// Runs filesystem work that has no async counterpart, such as walking a silo, on tokio's
+/// blocking pool.
+#[cfg(feature = "tera")]
+pub(crate) async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, Error> {
+    tokio::task::spawn_blocking(work).await.map_err(join_error)
+}
+
+#[cfg(feature = "tera")]
+fn join_error(err: tokio::task::JoinError) -> Error {
+    Error::IoError {
+        source: std::io::Error::other(err),
+    }
+}
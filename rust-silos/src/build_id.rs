@@ -0,0 +1,79 @@
+//! Identity of an embed, stamped by `embed_silo!(..., build_id = true)`.
+
+use crate::{HttpFileResponse, InnerSilo, Silo};
+
+/// A 128-bit identifier of the embed that produced a silo, see [`Silo::build_id`].
+/// Displays as 32 lowercase hex digits; with the `serde` feature it is (de)serialized as
+/// that string.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BuildId(pub [u8; 16]);
+
+impl std::fmt::Display for BuildId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl std::str::FromStr for BuildId {
+    type Err = ();
+
+    /// Parses the 32 hex digits printed by `Display`.
+    fn from_str(hex: &str) -> Result<Self, ()> {
+        if hex.len() != 32 || !hex.is_ascii() {
+            return Err(());
+        }
+        let mut id = [0u8; 16];
+        for (byte, pair) in id.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).map_err(|_| ())?, 16)
+                .map_err(|_| ())?;
+        }
+        Ok(BuildId(id))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for BuildId {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for BuildId {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        hex.parse()
+            .map_err(|()| ::serde::de::Error::custom("expected a build id of 32 hex digits"))
+    }
+}
+
+impl Silo {
+    /// The identifier stamped by `embed_silo!(..., build_id = true)`, for correlating crash
+    /// reports with the exact embed. `None` for silos embedded without it and for dynamic
+    /// and in-memory silos.
+    pub fn build_id(&self) -> Option<BuildId> {
+        match &self.inner {
+            InnerSilo::Embed(embed) => embed.build_id.map(BuildId),
+            _ => None,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Stamps an embedded silo with its build id; used by `embed_silo!`.
+    pub const fn with_build_id(mut self, id: [u8; 16]) -> Self {
+        if let InnerSilo::Embed(embed) = &mut self.inner {
+            embed.build_id = Some(id);
+        }
+        self
+    }
+}
+
+impl HttpFileResponse {
+    /// Adds an `X-Asset-Build` header carrying `id`, if there is one.
+    pub fn with_build_id(mut self, id: Option<BuildId>) -> Self {
+        if let Some(id) = id {
+            self.headers.push(("X-Asset-Build", id.to_string()));
+        }
+        self
+    }
+}
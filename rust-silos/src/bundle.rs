@@ -0,0 +1,286 @@
+//! Bundles: many files of a silo streamed as one body, so a client can fetch a batch of
+//! assets in a single request. See [`Silo::serve_bundle`] for HTTP and [`write()`] otherwise.
+//!
+//! Two encodings are supported:
+//!
+//! - [`BundleFormat::Multipart`]: a `multipart/mixed` body with one part per file, carrying
+//!   `Content-Type`, `Content-Length` and `Content-Location` (the requested path,
+//!   percent-encoded). A final `text/plain` part with `Content-Location: missing` lists the
+//!   paths that could not be served, percent-encoded, one per line.
+//! - [`BundleFormat::LengthPrefixed`]: for each file, the path length (`u32`), the path (UTF-8),
+//!   the contents length (`u64`) and the contents. An empty path ends the files and is followed
+//!   by the number of missing paths (`u32`) and each of them, length (`u32`) first. All
+//!   integers are little-endian, as in [pack files](crate::pack).
+//!
+//! Contents are streamed from each file's reader as the body is read; only the framing is
+//! buffered. Lengths are taken from [`File::meta`] before a file is read, so a dynamic file
+//! that shrinks meanwhile fails the body with [`std::io::ErrorKind::UnexpectedEof`], and one
+//! that grows is cut at its announced length.
+
+use crate::{Error, File, FileReader, HttpFileResponse, ResponseBody, Silo};
+use std::io::{Cursor, Read, Take, Write};
+
+/// Most paths a single [`Silo::serve_bundle`] request may ask for.
+pub const MAX_PATHS: usize = 1024;
+
+/// Encoding of a bundle, see the [module documentation](self).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BundleFormat {
+    Multipart,
+    LengthPrefixed,
+}
+
+/// Outcome of [`write()`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BundleReport {
+    /// Number of files written.
+    pub files: usize,
+    /// Requested paths that were not found or could not be opened, in request order.
+    pub missing: Vec<String>,
+    /// Media type of the bundle, including the multipart boundary.
+    pub content_type: String,
+}
+
+/// Writes the files of `silo` at `paths` to `writer` as a bundle.
+///
+/// Paths that do not resolve to a readable file are listed at the end of the bundle and in
+/// the report rather than failing the whole write; errors are only returned for failures
+/// while streaming.
+pub fn write<W: Write>(
+    mut writer: W,
+    silo: &Silo,
+    paths: impl IntoIterator<Item = impl Into<String>>,
+    format: BundleFormat,
+) -> Result<BundleReport, Error> {
+    let paths = paths.into_iter().map(|path| {
+        let path = path.into();
+        (path.clone(), Some(path))
+    });
+    let mut reader = BundleReader::new(silo, paths.collect(), format);
+    std::io::copy(&mut reader, &mut writer)?;
+    Ok(BundleReport {
+        files: reader.files,
+        missing: reader.missing,
+        content_type: reader.content_type,
+    })
+}
+
+/// Streaming body of a bundle, see [`Silo::serve_bundle`].
+pub struct BundleReader {
+    silo: Silo,
+    /// Requested paths as reported when missing, with the path to look up (`None` for one
+    /// that was rejected before lookup).
+    paths: std::vec::IntoIter<(String, Option<String>)>,
+    format: BundleFormat,
+    boundary: String,
+    content_type: String,
+    /// Framing not yet read.
+    framing: Cursor<Vec<u8>>,
+    /// Contents of the current file, limited to its announced length.
+    body: Option<Take<FileReader>>,
+    files: usize,
+    missing: Vec<String>,
+    finished: bool,
+}
+
+impl BundleReader {
+    pub(crate) fn new(
+        silo: &Silo,
+        paths: Vec<(String, Option<String>)>,
+        format: BundleFormat,
+    ) -> Self {
+        let boundary = format!("silo-bundle-{:032x}", random());
+        let content_type = match format {
+            BundleFormat::Multipart => format!("multipart/mixed; boundary={boundary}"),
+            BundleFormat::LengthPrefixed => "application/octet-stream".to_owned(),
+        };
+        Self {
+            silo: silo.clone(),
+            paths: paths.into_iter(),
+            format,
+            boundary,
+            content_type,
+            framing: Cursor::new(Vec::new()),
+            body: None,
+            files: 0,
+            missing: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Media type of the bundle, including the multipart boundary.
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Opens the next requested file and queues its framing, or queues the trailer once all
+    /// paths are done. Returns `false` when nothing is left.
+    fn advance(&mut self) -> bool {
+        for (requested, path) in self.paths.by_ref() {
+            let opened = path.as_deref().and_then(|path| open(&self.silo, path));
+            let (Some(path), Some((file, length, reader))) = (path, opened) else {
+                self.missing.push(requested);
+                continue;
+            };
+            let mut framing = Vec::new();
+            match self.format {
+                BundleFormat::Multipart => framing.extend_from_slice(
+                    format!(
+                        "--{}\r\nContent-Type: {}\r\nContent-Length: {length}\r\nContent-Location: {}\r\n\r\n",
+                        self.boundary,
+                        crate::serve::content_type(&file),
+                        crate::percent_encode_path(&path)
+                    )
+                    .as_bytes(),
+                ),
+                BundleFormat::LengthPrefixed => {
+                    framing.extend_from_slice(&(path.len() as u32).to_le_bytes());
+                    framing.extend_from_slice(path.as_bytes());
+                    framing.extend_from_slice(&(length as u64).to_le_bytes());
+                }
+            }
+            self.framing = Cursor::new(framing);
+            self.body = Some(reader.take(length as u64));
+            self.files += 1;
+            return true;
+        }
+        if self.finished {
+            return false;
+        }
+        self.finished = true;
+        let mut trailer = Vec::new();
+        match self.format {
+            BundleFormat::Multipart => {
+                let listed: String = self
+                    .missing
+                    .iter()
+                    .map(|path| crate::percent_encode_path(path) + "\n")
+                    .collect();
+                trailer.extend_from_slice(
+                    format!(
+                        "--{0}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {1}\r\nContent-Location: missing\r\n\r\n{listed}\r\n--{0}--\r\n",
+                        self.boundary,
+                        listed.len()
+                    )
+                    .as_bytes(),
+                );
+            }
+            BundleFormat::LengthPrefixed => {
+                trailer.extend_from_slice(&0u32.to_le_bytes());
+                trailer.extend_from_slice(&(self.missing.len() as u32).to_le_bytes());
+                for path in &self.missing {
+                    trailer.extend_from_slice(&(path.len() as u32).to_le_bytes());
+                    trailer.extend_from_slice(path.as_bytes());
+                }
+            }
+        }
+        self.framing = Cursor::new(trailer);
+        true
+    }
+}
+
+impl Read for BundleReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let read = self.framing.read(buf)?;
+            if read > 0 {
+                return Ok(read);
+            }
+            if let Some(body) = &mut self.body {
+                let read = body.read(buf)?;
+                if read > 0 {
+                    return Ok(read);
+                }
+                if body.limit() > 0 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "file shrank while it was bundled",
+                    ));
+                }
+                self.body = None;
+                if self.format == BundleFormat::Multipart {
+                    self.framing = Cursor::new(b"\r\n".to_vec());
+                }
+                continue;
+            }
+            if !self.advance() {
+                return Ok(0);
+            }
+        }
+    }
+}
+
+impl Silo {
+    /// Serves a bundle of the files requested by `request_target` and `body`, for an endpoint
+    /// such as `GET /bundle?path=a.png&path=b.png` or a `POST /bundle` listing one path per
+    /// line.
+    ///
+    /// Paths are taken from every `path` query parameter, then from the non-empty lines of
+    /// `body`, and each is percent-decoded and validated like [`Silo::get_file_url`] input.
+    /// Paths that are rejected, not found or unreadable do not fail the response: they are
+    /// listed at the end of the bundle as requested. The response is 200 with the bundle's
+    /// `Content-Type` and no `Content-Length`, the body streaming each file as it is read;
+    /// a request with no paths or more than [`MAX_PATHS`] is 400.
+    pub fn serve_bundle(
+        &self,
+        request_target: &str,
+        body: Option<&str>,
+        format: BundleFormat,
+    ) -> HttpFileResponse {
+        let without_fragment = request_target.split('#').next().unwrap_or_default();
+        let query = without_fragment
+            .split_once('?')
+            .map_or("", |(_, query)| query);
+        let from_query = query
+            .split('&')
+            .filter_map(|pair| pair.strip_prefix("path="));
+        let from_body = body
+            .into_iter()
+            .flat_map(str::lines)
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let paths: Vec<(String, Option<String>)> = from_query
+            .chain(from_body)
+            .take(MAX_PATHS + 1)
+            .map(|raw| {
+                (
+                    raw.to_owned(),
+                    crate::serve::decode_target(raw).filter(|path| !path.is_empty()),
+                )
+            })
+            .collect();
+        if paths.is_empty() || paths.len() > MAX_PATHS {
+            return HttpFileResponse::empty(400);
+        }
+        let reader = BundleReader::new(self, paths, format);
+        HttpFileResponse {
+            status: 200,
+            headers: vec![("Content-Type", reader.content_type().to_owned())],
+            body: Some(ResponseBody::Bundle(Box::new(reader))),
+        }
+    }
+}
+
+/// Looks up and opens `path`, with its length.
+fn open(silo: &Silo, path: &str) -> Option<(File, usize, FileReader)> {
+    let file = silo.get_file(path)?;
+    let length = file.meta().ok()?.size;
+    let reader = file.reader().ok()?;
+    Some((file, length, reader))
+}
+
+/// A value unlikely to occur in any bundled file, for the multipart boundary.
+fn random() -> u128 {
+    use std::hash::{BuildHasher, Hasher};
+    let state = std::collections::hash_map::RandomState::new();
+    let mut high = state.build_hasher();
+    high.write_u8(0);
+    let mut low = state.build_hasher();
+    low.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (u128::from(high.finish()) << 64) | u128::from(low.finish())
+}
@@ -0,0 +1,168 @@
+//! Configuration cascades: a user-supplied directory, the platform's per-user config
+//! directory and embedded defaults, searched in that order. See [`ConfigCascade`].
+
+use crate::{File, Silo, SiloSet};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Builds a [`SiloSet`] of configuration layers, added from highest to lowest precedence:
+///
+/// ```no_run
+/// # use rust_silos::{Silo, cascade::ConfigCascade};
+/// # let defaults = Silo::new("defaults");
+/// # let user_dir: Option<std::path::PathBuf> = None;
+/// let cascade = ConfigCascade::new().dir_opt(user_dir).xdg("myapp").embedded(defaults);
+/// let config = cascade.first("config.toml");
+/// ```
+///
+/// Directories that do not exist when they are added are skipped, so a missing user or
+/// per-user directory simply leaves that layer out; so are paths that are not UTF-8.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigCascade {
+    /// Layers, highest precedence first.
+    layers: Vec<Silo>,
+}
+
+impl ConfigCascade {
+    /// An empty cascade.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the directory at `path` below the layers added so far, if it exists.
+    pub fn dir(mut self, path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if path.is_dir()
+            && let Some(path) = path.to_str()
+        {
+            self.layers.push(Silo::new(path));
+        }
+        self
+    }
+
+    /// [`ConfigCascade::dir`] for an optional directory, e.g. one given on the command line.
+    pub fn dir_opt(self, path: Option<impl AsRef<Path>>) -> Self {
+        match path {
+            Some(path) => self.dir(path),
+            None => self,
+        }
+    }
+
+    /// Adds `app`'s directory under the current user's config directory, see
+    /// [`Platform::config_dir`].
+    pub fn xdg(self, app: &str) -> Self {
+        self.dir_opt(
+            Platform::current()
+                .config_dir(|name| std::env::var_os(name))
+                .map(|dir| dir.join(app)),
+        )
+    }
+
+    /// Adds `silo`, typically embedded defaults, below the layers added so far.
+    pub fn embedded(mut self, silo: Silo) -> Self {
+        self.layers.push(silo);
+        self
+    }
+
+    /// The layers as a [`SiloSet`], whose lookups prefer the layers added first.
+    pub fn build(&self) -> SiloSet {
+        SiloSet::new(self.layers.iter().rev().cloned().collect())
+    }
+
+    /// `path` from the first layer that has it.
+    pub fn first(&self, path: &str) -> Option<File> {
+        self.layers.iter().find_map(|silo| silo.get_file(path))
+    }
+
+    /// Parses `path` from every layer that has it as TOML and deep-merges the tables from
+    /// the lowest layer to the highest: tables merge key by key, while any other value
+    /// (scalars, arrays, or a table meeting a non-table) is replaced by the higher layer's.
+    /// Returns `None` if no layer has `path`, and an error if any copy fails to read or parse
+    /// or the merged table does not deserialize into `T`.
+    #[cfg(feature = "toml")]
+    pub fn merged_toml<T: ::serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<T>, crate::Error> {
+        let mut merged: Option<toml::Table> = None;
+        for file in self
+            .layers
+            .iter()
+            .rev()
+            .filter_map(|silo| silo.get_file(path))
+        {
+            let table: toml::Table = file.load_toml()?;
+            merged = Some(match merged {
+                Some(mut lower) => {
+                    merge_tables(&mut lower, table);
+                    lower
+                }
+                None => table,
+            });
+        }
+        merged
+            .map(|table| Ok(toml::Value::Table(table).try_into()?))
+            .transpose()
+    }
+}
+
+/// Merges `higher` into `lower`, see [`ConfigCascade::merged_toml`].
+#[cfg(feature = "toml")]
+fn merge_tables(lower: &mut toml::Table, higher: toml::Table) {
+    for (key, value) in higher {
+        match (lower.get_mut(&key), value) {
+            (Some(toml::Value::Table(lower)), toml::Value::Table(higher)) => {
+                merge_tables(lower, higher)
+            }
+            (_, value) => {
+                lower.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A platform's convention for per-user configuration, see [`Platform::config_dir`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Platform {
+    /// Linux and other Unix systems, following the XDG Base Directory specification.
+    Unix,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    /// The platform this program was built for.
+    pub const fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Unix
+        }
+    }
+
+    /// The per-user config directory, with environment variables read through `var`:
+    ///
+    /// - Unix: `$XDG_CONFIG_HOME` if it is set to an absolute path, else `$HOME/.config`.
+    /// - macOS: `$HOME/Library/Application Support`.
+    /// - Windows: `%APPDATA%`, else `%USERPROFILE%\AppData\Roaming`.
+    ///
+    /// Returns `None` if the variables it needs are unset or empty.
+    pub fn config_dir(self, var: impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+        let var = |name: &str| {
+            var(name)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        match self {
+            Platform::Unix => var("XDG_CONFIG_HOME")
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| Some(var("HOME")?.join(".config"))),
+            Platform::MacOs => Some(var("HOME")?.join("Library").join("Application Support")),
+            Platform::Windows => {
+                var("APPDATA").or_else(|| Some(var("USERPROFILE")?.join("AppData").join("Roaming")))
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Read coalescing for dynamic files: concurrent reads of the same path share one
+//! in-flight read instead of each opening the file.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Function used to load a file's bytes from disk.
+pub(crate) type ReadFn = dyn Fn(&Path) -> io::Result<Vec<u8>> + Send + Sync;
+
+/// Outcome of a shared read. `io::Error` is not `Clone`, so failures keep kind and message.
+type SharedResult = Result<Arc<[u8]>, (io::ErrorKind, String)>;
+
+/// One in-flight read; every waiter blocks on the same `OnceLock`.
+#[derive(Default)]
+struct Flight {
+    result: OnceLock<SharedResult>,
+}
+
+/// Table of in-flight reads keyed by absolute path.
+///
+/// Entries only live while a read is in progress: the first caller to observe the finished
+/// result removes it, so the table never holds more than the reads currently running.
+pub(crate) struct Coalescer {
+    inflight: Mutex<HashMap<PathBuf, Arc<Flight>>>,
+    read: Box<ReadFn>,
+}
+
+impl std::fmt::Debug for Coalescer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inflight = self
+            .inflight
+            .lock()
+            .map(|map| map.len())
+            .unwrap_or_default();
+        f.debug_struct("Coalescer")
+            .field("inflight", &inflight)
+            .finish()
+    }
+}
+
+impl Coalescer {
+    pub fn new(read: Box<ReadFn>) -> Self {
+        Self {
+            inflight: Mutex::new(HashMap::new()),
+            read,
+        }
+    }
+
+    /// Reads `path`, joining an in-flight read of the same path if there is one.
+    pub fn read(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        let flight = {
+            let mut inflight = self.inflight.lock().unwrap_or_else(|e| e.into_inner());
+            inflight.entry(path.to_path_buf()).or_default().clone()
+        };
+        let result = flight
+            .result
+            .get_or_init(|| {
+                (self.read)(path)
+                    .map(Arc::from)
+                    .map_err(|e| (e.kind(), e.to_string()))
+            })
+            .clone();
+        {
+            let mut inflight = self.inflight.lock().unwrap_or_else(|e| e.into_inner());
+            if inflight
+                .get(path)
+                .is_some_and(|current| Arc::ptr_eq(current, &flight))
+            {
+                inflight.remove(path);
+            }
+        }
+        result.map_err(|(kind, msg)| io::Error::new(kind, msg))
+    }
+}
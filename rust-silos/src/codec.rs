@@ -0,0 +1,202 @@
+//! Compression codecs: the [`Codec`] trait, the built-in codecs, [`Silo::compressed`] and
+//! [`Silo::decompressed`].
+//!
+//! Codecs are plain runtime values, so applications can bring their own (shared as
+//! `Arc<dyn Codec>`) to [`Silo::compressed`], [`Silo::decompressed`], pack files
+//! ([`PackOptions::codec`](crate::pack::PackOptions::codec)) and, with the `compression`
+//! feature, to `CompressionConfig::codecs` for [`Silo::serve`]. `embed_silo!` runs at
+//! compile time and cannot call user code, so it only ever uses built-in codings.
+
+use crate::{DynOptions, Error, InnerSilo, Silo, View, memory};
+use std::io::Read;
+use std::sync::Arc;
+
+/// A compression codec.
+pub trait Codec: Send + Sync {
+    /// The codec's name, used as the HTTP content coding (e.g. `gzip`) when serving.
+    fn name(&self) -> &'static str;
+
+    /// Compresses `data` in one go.
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+
+    /// Wraps `reader`, which yields compressed bytes, in a reader of the decompressed bytes.
+    /// Malformed input surfaces as an I/O error from the returned reader.
+    fn decompress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send>;
+
+    /// Wraps `reader`, which yields uncompressed bytes, in a reader of the compressed bytes.
+    /// The default reads `reader` to the end and calls [`Codec::compress`]; codecs that can
+    /// stream should override it.
+    fn compress_stream(&self, mut reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        let mut data = Vec::new();
+        match reader.read_to_end(&mut data) {
+            Ok(_) => Box::new(std::io::Cursor::new(self.compress(&data))),
+            Err(err) => Box::new(FailedRead(Some(err))),
+        }
+    }
+
+    /// Decompresses `data` in one go, see [`Codec::decompress_stream`].
+    fn decompress(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.decompress_stream(Box::new(std::io::Cursor::new(data.to_vec())))
+            .read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+impl std::fmt::Debug for dyn Codec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Codec").field(&self.name()).finish()
+    }
+}
+
+/// A reader that fails once with the error that kept it from being built.
+struct FailedRead(Option<std::io::Error>);
+
+impl Read for FailedRead {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        self.0.take().map_or(Ok(0), Err)
+    }
+}
+
+/// `gzip`, with a compression level from 0 (none) to 9 (best).
+#[cfg(feature = "compression")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Gzip(pub u32);
+
+#[cfg(feature = "compression")]
+impl Codec for Gzip {
+    fn name(&self) -> &'static str {
+        "gzip"
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let _ = flate2::read::GzEncoder::new(data, flate2::Compression::new(self.0.min(9)))
+            .read_to_end(&mut out);
+        out
+    }
+
+    fn decompress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::GzDecoder::new(reader))
+    }
+
+    fn compress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::GzEncoder::new(
+            reader,
+            flate2::Compression::new(self.0.min(9)),
+        ))
+    }
+}
+
+/// HTTP `deflate`, which is zlib-wrapped deflate, with a compression level from 0 to 9.
+#[cfg(feature = "compression")]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Deflate(pub u32);
+
+#[cfg(feature = "compression")]
+impl Codec for Deflate {
+    fn name(&self) -> &'static str {
+        "deflate"
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let _ = flate2::read::ZlibEncoder::new(data, flate2::Compression::new(self.0.min(9)))
+            .read_to_end(&mut out);
+        out
+    }
+
+    fn decompress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::ZlibDecoder::new(reader))
+    }
+
+    fn compress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::ZlibEncoder::new(
+            reader,
+            flate2::Compression::new(self.0.min(9)),
+        ))
+    }
+}
+
+/// Raw deflate without a wrapper, as stored in [pack files](crate::pack), at level 6.
+#[cfg(feature = "deflate")]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RawDeflate;
+
+#[cfg(feature = "deflate")]
+impl Codec for RawDeflate {
+    fn name(&self) -> &'static str {
+        "raw-deflate"
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let _ = flate2::read::DeflateEncoder::new(data, flate2::Compression::new(6))
+            .read_to_end(&mut out);
+        out
+    }
+
+    fn decompress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::DeflateDecoder::new(reader))
+    }
+
+    fn compress_stream(&self, reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        Box::new(flate2::read::DeflateEncoder::new(
+            reader,
+            flate2::Compression::new(6),
+        ))
+    }
+}
+
+/// The built-in codec called `name`, if its feature is enabled.
+pub(crate) fn builtin(name: &str) -> Option<Arc<dyn Codec>> {
+    match name {
+        #[cfg(feature = "deflate")]
+        "raw-deflate" => Some(Arc::new(RawDeflate)),
+        #[cfg(feature = "compression")]
+        "gzip" => Some(Arc::new(Gzip(6))),
+        #[cfg(feature = "compression")]
+        "deflate" => Some(Arc::new(Deflate(6))),
+        _ => None,
+    }
+}
+
+impl Silo {
+    /// Builds an in-memory silo holding every file compressed with `codec`, e.g. to check
+    /// what a pack or a precompressed asset directory would store. Files keep their path and
+    /// modification time, as with [`Silo::transform`]. Fails on the first file that cannot
+    /// be read.
+    pub fn compressed(&self, codec: Arc<dyn Codec>) -> Result<Silo, Error> {
+        self.recoded(|data| Ok(codec.compress(data)))
+    }
+
+    /// Builds an in-memory silo holding every file decompressed with `codec`, for assets
+    /// stored compressed. Files keep their path and modification time, as with
+    /// [`Silo::transform`]. Fails on the first file that cannot be read or decompressed.
+    pub fn decompressed(&self, codec: Arc<dyn Codec>) -> Result<Silo, Error> {
+        self.recoded(|data| Ok(codec.decompress(data)?))
+    }
+
+    fn recoded(&self, recode: impl Fn(&[u8]) -> Result<Vec<u8>, Error>) -> Result<Silo, Error> {
+        let mut files = Vec::new();
+        for file in self.iter() {
+            let contents = recode(&file.contents()?)?;
+            files.push((
+                file.path().to_string_lossy().into_owned(),
+                contents,
+                file.meta()?.modified,
+            ));
+        }
+        Ok(Self {
+            inner: InnerSilo::Memory(Arc::new(memory::MemStore::build(files))),
+            index: self.index.clone(),
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View {
+                keys: self.view.keys,
+                headers: self.view.headers.clone(),
+                ..View::NONE
+            },
+        })
+    }
+}
@@ -0,0 +1,138 @@
+//! Compacted overlays: the override view of a [`SiloSet`] resolved once into a single silo,
+//! see [`SiloSet::compact`].
+
+use crate::{
+    DynOptions, Error, File, FileKind, InnerSilo, Silo, SiloSet, VersionToken, View, memory,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Options for [`SiloSet::compact_with`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct CompactOptions {
+    /// Read every file that comes from disk into memory while compacting, instead of
+    /// reading it from its recorded path whenever it is asked for.
+    pub preload: bool,
+}
+
+/// Backend of a compacted silo: the winning file of every path.
+pub(crate) struct CompactIndex {
+    /// Winners sorted by path.
+    files: Vec<(Box<str>, File)>,
+    /// Position of each path in `files`.
+    positions: HashMap<Box<str>, usize>,
+    /// [`SiloSet::version_token`] of the set when it was compacted.
+    token: VersionToken,
+}
+
+impl std::fmt::Debug for CompactIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompactIndex")
+            .field("files", &self.files.len())
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+impl CompactIndex {
+    pub(crate) fn get(&self, key: &str) -> Option<File> {
+        self.positions.get(key).map(|&at| self.files[at].1.clone())
+    }
+
+    /// Every file with its key, in path order.
+    pub(crate) fn files(&self) -> impl Iterator<Item = (&str, File)> + '_ {
+        self.files.iter().map(|(key, file)| (&**key, file.clone()))
+    }
+}
+
+impl SiloSet {
+    /// [`SiloSet::compact_with`] with default options: files on disk are read lazily.
+    pub fn compact(&self) -> Result<Silo, Error> {
+        self.compact_with(CompactOptions::default())
+    }
+
+    /// Resolves the override view once into a single silo that answers
+    /// [`Silo::get_file`] with one hash lookup instead of a scan of every layer.
+    ///
+    /// Embedded and in-memory winners are kept as they are, so their contents stay
+    /// zero-copy. Winners on disk keep their absolute path and read options and are read
+    /// when asked for, or up front with [`CompactOptions::preload`]. Lookups match exact
+    /// paths: the layers' index files and aliases are not carried over.
+    ///
+    /// The result is a snapshot of the file list: files later added to or removed from any
+    /// layer are not reflected, and with `preload` neither are changed contents. Compare
+    /// [`Silo::compacted_from`] with the set's current [`SiloSet::version_token`] to detect
+    /// that the compacted silo is stale. Fails if a layer cannot be walked or, with
+    /// `preload`, a file cannot be read.
+    pub fn compact_with(&self, options: CompactOptions) -> Result<Silo, Error> {
+        let token = self.version_token();
+        let mut seen = HashSet::new();
+        let mut winners = Vec::new();
+        for silo in self.silos.iter().rev() {
+            for file in silo.try_iter() {
+                let file = file?;
+                let key: Box<str> = file.path().to_string_lossy().into();
+                if seen.insert(key.clone()) {
+                    winners.push((key, file));
+                }
+            }
+        }
+        if options.preload {
+            let mut preloaded = Vec::new();
+            for (key, file) in &winners {
+                if file.absolute_path().is_some() {
+                    preloaded.push((
+                        key.to_string(),
+                        file.contents()?.into_owned(),
+                        file.meta()?.modified,
+                    ));
+                }
+            }
+            let store = Arc::new(memory::MemStore::build(preloaded));
+            for (key, file) in &mut winners {
+                if let Some(index) = store
+                    .index_of(key)
+                    .filter(|_| file.absolute_path().is_some())
+                {
+                    let requested = file.requested.take();
+                    *file = File {
+                        requested,
+                        inner: FileKind::Memory(memory::MemFile {
+                            store: store.clone(),
+                            index,
+                            strip: 0,
+                        }),
+                    };
+                }
+            }
+        }
+        winners.sort_by(|a, b| a.0.cmp(&b.0));
+        let positions = winners
+            .iter()
+            .enumerate()
+            .map(|(at, (key, _))| (key.clone(), at))
+            .collect();
+        Ok(Silo {
+            inner: InnerSilo::Compact(Arc::new(CompactIndex {
+                files: winners,
+                positions,
+                token,
+            })),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
+        })
+    }
+}
+
+impl Silo {
+    /// The [`SiloSet::version_token`] of the set this silo was compacted from, see
+    /// [`SiloSet::compact`]; `None` for any other silo.
+    pub fn compacted_from(&self) -> Option<VersionToken> {
+        match &self.inner {
+            InnerSilo::Compact(index) => Some(index.token),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! On-the-fly response compression for dynamic files served by [`Silo::serve`].
+
+use crate::{Codec, Deflate, Gzip, Silo};
+use std::sync::Arc;
+
+/// When [`Silo::serve`] compresses dynamic files, see [`Silo::with_dynamic_compression`].
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// Files smaller than this many bytes are sent as-is.
+    pub min_size: usize,
+    /// Compression level of the built-in `gzip` and `deflate` codings, 0 (none) to 9 (best).
+    pub level: u32,
+    /// Codecs to offer before the built-in ones, in order of preference. Each is used when the
+    /// client lists its [`Codec::name`] in `Accept-Encoding`.
+    pub codecs: Vec<Arc<dyn Codec>>,
+    /// Also compress in-memory files of the silo (e.g. from [`Silo::transform`] or
+    /// [`Silo::decompressed`]), which are otherwise sent as they are.
+    pub in_memory: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 1024,
+            level: 6,
+            codecs: Vec::new(),
+            in_memory: false,
+        }
+    }
+}
+
+/// Codecs compare by name.
+impl PartialEq for CompressionConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.min_size == other.min_size
+            && self.level == other.level
+            && self.in_memory == other.in_memory
+            && self
+                .codecs
+                .iter()
+                .map(|codec| codec.name())
+                .eq(other.codecs.iter().map(|codec| codec.name()))
+    }
+}
+
+impl Eq for CompressionConfig {}
+
+impl Silo {
+    /// Makes [`Silo::serve`] compress dynamic files on the fly, for development and staging
+    /// servers that serve straight from disk.
+    ///
+    /// Only text-like content types (`text/*`, JSON, JavaScript, XML, SVG, WebAssembly) of at
+    /// least `min_size` bytes are compressed, never files that are already compressed (images,
+    /// archives, `.gz`/`.br`/`.zst` variants). The client must accept one of the configured
+    /// [`CompressionConfig::codecs`], `gzip` or `deflate`, and `Cache-Control: no-transform`
+    /// on the request opts out. Compressed responses stream the file through the encoder
+    /// without reading it into memory first, and carry `Content-Encoding`, no
+    /// `Content-Length`, and an ETag specific to the encoding; every response that could have
+    /// been compressed carries `Vary: Accept-Encoding`. Embedded files are unaffected, and
+    /// in-memory files too unless [`CompressionConfig::in_memory`] is set.
+    pub fn with_dynamic_compression(mut self, config: CompressionConfig) -> Self {
+        self.dyn_options.compression = Some(config);
+        self
+    }
+}
+
+/// Whether responses of `content_type` for a file at `path` are worth compressing.
+pub(crate) fn is_compressible(content_type: &str, path: &str) -> bool {
+    let precompressed = [".gz", ".br", ".zst", ".zip", ".bz2", ".xz"];
+    if precompressed
+        .iter()
+        .any(|ext| path.to_ascii_lowercase().ends_with(ext))
+    {
+        return false;
+    }
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || matches!(
+            essence.as_str(),
+            "application/json"
+                | "application/javascript"
+                | "application/xml"
+                | "application/wasm"
+                | "image/svg+xml"
+                | "application/manifest+json"
+        )
+}
+
+/// Picks the codec to use from an `Accept-Encoding` value: `config.codecs` in order, then
+/// `gzip` over `deflate`, skipping codings with `q=0`. `*` stands for any built-in coding not
+/// listed; custom codecs must be named.
+pub(crate) fn negotiate(
+    accept_encoding: &str,
+    config: &CompressionConfig,
+) -> Option<Arc<dyn Codec>> {
+    let mut accepted = Vec::new();
+    let mut wildcard = None;
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';');
+        let name = params
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let q = params
+            .filter_map(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .or_else(|| param.trim().strip_prefix("Q="))
+            })
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        match name.as_str() {
+            "*" => wildcard = Some(q > 0.0),
+            "" => {}
+            _ => accepted.push((name, q > 0.0)),
+        }
+    }
+    let listed = |name: &str| {
+        accepted
+            .iter()
+            .find(|(accepted, _)| accepted.eq_ignore_ascii_case(name))
+            .map(|(_, allowed)| *allowed)
+    };
+    if let Some(codec) = config
+        .codecs
+        .iter()
+        .find(|codec| listed(codec.name()) == Some(true))
+    {
+        return Some(codec.clone());
+    }
+    let gzip = listed("gzip")
+        .or_else(|| listed("x-gzip"))
+        .or(wildcard)
+        .unwrap_or(false);
+    let deflate = listed("deflate").or(wildcard).unwrap_or(false);
+    if gzip {
+        Some(Arc::new(Gzip(config.level)))
+    } else if deflate {
+        Some(Arc::new(Deflate(config.level)))
+    } else {
+        None
+    }
+}
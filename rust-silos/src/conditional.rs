@@ -0,0 +1,216 @@
+//! Framework-neutral evaluation of HTTP conditional requests (RFC 7232, plus `If-Range` from
+//! RFC 7233) for `GET` and `HEAD`.
+//!
+//! Both inputs are plain data: the raw header values of the request and the validators of the
+//! file, so any HTTP integration can call [`evaluate`] and map the [`ConditionalResult`] onto
+//! its own response type. [`Silo::serve`](crate::Silo::serve) is built on it.
+
+/// Conditional headers of a request, as raw header values.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RequestConditionals {
+    pub if_match: Option<String>,
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+    pub if_unmodified_since: Option<String>,
+    pub if_range: Option<String>,
+}
+
+/// Validators of the selected file.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FileConditionalMeta {
+    /// Entity tag as sent in the `ETag` header, e.g. `"\"abc\""` or `"W/\"abc\""`.
+    pub etag: Option<String>,
+    /// Last modification time, in seconds since the UNIX epoch.
+    pub last_modified: Option<u64>,
+}
+
+/// Outcome of [`evaluate`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ConditionalResult {
+    /// Send the file. `honor_range` is `false` when `If-Range` failed, in which case a `Range`
+    /// header must be ignored and the full file sent.
+    Serve { honor_range: bool },
+    /// `304 Not Modified`.
+    NotModified,
+    /// `412 Precondition Failed`.
+    PreconditionFailed,
+}
+
+/// An entity tag: an opaque string, optionally marked weak (`W/"..."`).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EntityTag {
+    pub weak: bool,
+    /// The opaque tag without quotes.
+    pub tag: String,
+}
+
+impl EntityTag {
+    /// Parses a single entity tag such as `"abc"` or `W/"abc"`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (tag, rest) = Self::parse_prefix(value.trim())?;
+        rest.trim().is_empty().then_some(tag)
+    }
+
+    /// Parses a comma-separated list of entity tags, as in `If-Match` and `If-None-Match`.
+    /// Commas inside the quotes belong to the tag. Returns `None` if the list is malformed.
+    pub fn parse_list(value: &str) -> Option<Vec<Self>> {
+        let mut tags = Vec::new();
+        let mut rest = value;
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ',']);
+            if rest.is_empty() {
+                return Some(tags);
+            }
+            let (tag, after) = Self::parse_prefix(rest)?;
+            tags.push(tag);
+            rest = after.trim_start_matches([' ', '\t']);
+            if !rest.is_empty() && !rest.starts_with(',') {
+                return None;
+            }
+        }
+    }
+
+    fn parse_prefix(value: &str) -> Option<(Self, &str)> {
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let body = quoted.strip_prefix('"')?;
+        let end = body.find('"')?;
+        let tag = &body[..end];
+        if tag.bytes().any(|b| b < 0x21 || b == 0x7f) {
+            return None;
+        }
+        Some((
+            Self {
+                weak,
+                tag: tag.to_owned(),
+            },
+            &body[end + 1..],
+        ))
+    }
+
+    /// Strong comparison: both tags are strong and their opaque tags are equal.
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison: the opaque tags are equal, weak or not.
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl std::fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+/// Evaluates the preconditions of a `GET` or `HEAD` request in the order of RFC 7232 section 6.
+///
+/// `If-Match` takes precedence over `If-Unmodified-Since`, and `If-None-Match` over
+/// `If-Modified-Since`; a date condition is ignored when its tag counterpart is present.
+/// Unparseable dates are ignored, a malformed tag list matches nothing. `If-Range` only
+/// affects `honor_range` of [`ConditionalResult::Serve`]: a tag must match strongly, a date must
+/// equal the modification time exactly.
+pub fn evaluate(headers: &RequestConditionals, file: &FileConditionalMeta) -> ConditionalResult {
+    let etag = file.etag.as_deref().and_then(EntityTag::parse);
+    let matches_list = |list: &str, strong: bool| {
+        if list.trim() == "*" {
+            return true;
+        }
+        let Some(etag) = &etag else { return false };
+        EntityTag::parse_list(list)
+            .unwrap_or_default()
+            .iter()
+            .any(|tag| {
+                if strong {
+                    tag.strong_eq(etag)
+                } else {
+                    tag.weak_eq(etag)
+                }
+            })
+    };
+
+    if let Some(list) = &headers.if_match {
+        if !matches_list(list, true) {
+            return ConditionalResult::PreconditionFailed;
+        }
+    } else if let (Some(since), Some(modified)) = (
+        headers
+            .if_unmodified_since
+            .as_deref()
+            .and_then(parse_http_date),
+        file.last_modified,
+    ) && modified > since
+    {
+        return ConditionalResult::PreconditionFailed;
+    }
+
+    if let Some(list) = &headers.if_none_match {
+        if matches_list(list, false) {
+            return ConditionalResult::NotModified;
+        }
+    } else if let (Some(since), Some(modified)) = (
+        headers
+            .if_modified_since
+            .as_deref()
+            .and_then(parse_http_date),
+        file.last_modified,
+    ) && modified <= since
+    {
+        return ConditionalResult::NotModified;
+    }
+
+    let honor_range = match headers.if_range.as_deref().map(str::trim) {
+        None => true,
+        Some(value) if value.starts_with('"') || value.starts_with("W/") => {
+            matches!((EntityTag::parse(value), &etag), (Some(tag), Some(etag)) if tag.strong_eq(etag))
+        }
+        Some(value) => parse_http_date(value).is_some_and(|date| file.last_modified == Some(date)),
+    };
+    ConditionalResult::Serve { honor_range }
+}
+
+pub(crate) const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+pub(crate) const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parses an IMF-fixdate; the obsolete RFC 850 and asctime formats are not supported.
+pub(crate) fn parse_http_date(value: &str) -> Option<u64> {
+    let mut parts = value.split_ascii_whitespace();
+    let _weekday = parts.next()?.strip_suffix(',')?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts
+        .next()?
+        .split(':')
+        .map(|part| part.parse::<u64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" || parts.next().is_some() || time.next().is_some() {
+        return None;
+    }
+    if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
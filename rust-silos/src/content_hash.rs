@@ -0,0 +1,53 @@
+//! Stable content hash shared by embedded and dynamic files.
+//!
+//! The algorithm is 64-bit FNV-1a over the file contents (after transcoding, if enabled).
+//! `rust-silos-macros/src/content_hash.rs` computes the same hash at embed time; keep the two
+//! in sync so an embedded file and its on-disk original hash identically.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Incremental 64-bit FNV-1a hasher producing [`File::content_hash`](crate::File::content_hash)
+/// values. Feeding the contents in any chunking yields the same result.
+#[derive(Debug, Copy, Clone)]
+pub struct ContentHasher(u64);
+
+impl ContentHasher {
+    pub const fn new() -> Self {
+        Self(OFFSET_BASIS)
+    }
+
+    /// Hashes `bytes` in one go.
+    pub fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = Self::new();
+        hasher.update(bytes);
+        hasher.finish()
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::io::Write for ContentHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Bulk loading: iterate a silo's files together with their contents.
+
+use crate::{Error, File, Silo};
+use std::borrow::Cow;
+use std::io::Read;
+
+/// Capacity [`Silo::for_each_contents`] keeps in its read buffer between files; a larger
+/// file grows the buffer for that file only.
+const RETAINED_BUFFER: usize = 1 << 20;
+
+/// What [`Silo::for_each_contents_with`] does when a file cannot be read.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ReadErrors {
+    /// Stop at the first unreadable file.
+    #[default]
+    Stop,
+    /// Skip unreadable files and report them all once every file was visited.
+    Collect,
+}
+
+/// Failure of [`Silo::for_each_contents`].
+#[derive(Debug)]
+pub enum IterError<E> {
+    /// The callback failed for the file at `path`; no further files were visited.
+    Callback { path: String, error: E },
+    /// Files that could not be read, by relative path: the first one with
+    /// [`ReadErrors::Stop`], all of them with [`ReadErrors::Collect`].
+    Read(Vec<(String, Error)>),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for IterError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IterError::Callback { path, error } => write!(f, "Processing {path} failed: {error}"),
+            IterError::Read(failures) => {
+                write!(f, "{} file(s) could not be read", failures.len())?;
+                failures
+                    .iter()
+                    .try_for_each(|(path, error)| write!(f, "\n  {path}: {error}"))
+            }
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for IterError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IterError::Callback { error, .. } => Some(error),
+            IterError::Read(failures) => failures.first().map(|(_, error)| error as _),
+        }
+    }
+}
+
+impl Silo {
+    /// Iterates the files of [`Silo::iter`] together with their contents, reading each file
+    /// only when the iterator reaches it. An unreadable file yields an `Err` and iteration
+    /// can carry on past it.
+    pub fn iter_contents(
+        &self,
+    ) -> impl Iterator<Item = Result<(File, Cow<'static, [u8]>), Error>> + '_ {
+        self.iter().map(|file| {
+            let contents = file.contents()?;
+            Ok((file, contents))
+        })
+    }
+
+    /// Calls `f` with every file and its contents, stopping at the first unreadable file or
+    /// callback error. See [`Silo::for_each_contents_with`].
+    pub fn for_each_contents<E>(
+        &self,
+        f: impl FnMut(&File, &[u8]) -> Result<(), E>,
+    ) -> Result<(), IterError<E>> {
+        self.for_each_contents_with(ReadErrors::Stop, f)
+    }
+
+    /// Calls `f` with every file and its contents.
+    ///
+    /// A callback error stops iteration immediately and is returned as
+    /// [`IterError::Callback`], even if unreadable files were collected before it. Files that
+    /// cannot be read are handled as `read_errors` says.
+    ///
+    /// Embedded and in-memory contents are passed without copying. Dynamic files are read
+    /// into one buffer reused from file to file, so bulk loading does not allocate per file.
+    pub fn for_each_contents_with<E>(
+        &self,
+        read_errors: ReadErrors,
+        mut f: impl FnMut(&File, &[u8]) -> Result<(), E>,
+    ) -> Result<(), IterError<E>> {
+        let mut buffer = Vec::new();
+        let mut failures = Vec::new();
+        for file in self.iter() {
+            let result = match file.absolute_path() {
+                None => file.contents().map(|contents| f(&file, &contents)),
+                Some(_) => read_into(&file, &mut buffer).map(|()| f(&file, &buffer)),
+            };
+            if buffer.capacity() > RETAINED_BUFFER {
+                buffer = Vec::new();
+            }
+            let path = || file.path().to_string_lossy().into_owned();
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    return Err(IterError::Callback {
+                        path: path(),
+                        error,
+                    });
+                }
+                Err(error) => {
+                    failures.push((path(), error));
+                    if read_errors == ReadErrors::Stop {
+                        break;
+                    }
+                }
+            }
+        }
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(IterError::Read(failures))
+        }
+    }
+}
+
+/// Replaces the contents of `buffer` with those of `file`.
+fn read_into(file: &File, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    buffer.clear();
+    file.reader()?.read_to_end(buffer)?;
+    Ok(())
+}
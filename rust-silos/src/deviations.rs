@@ -0,0 +1,66 @@
+//! Files on disk that differ from the shipped embedded baseline, see [`SiloSet::deviations`].
+
+use crate::{Error, SiloSet};
+use std::collections::HashSet;
+
+/// A file of a dynamic layer that overrides or extends the baseline.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct Deviation {
+    pub path: String,
+    pub kind: DeviationKind,
+}
+
+/// How a [`Deviation`] relates to the baseline.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub enum DeviationKind {
+    /// The baseline has no file at this path.
+    AddedOnDisk,
+    /// The file hides the baseline's file at the same path. Both flags are `false` for an
+    /// identical copy.
+    ShadowsEmbedded {
+        size_differs: bool,
+        hash_differs: bool,
+    },
+}
+
+impl SiloSet {
+    /// Lists the files of the dynamic layers above `embedded_index` that are visible over
+    /// the baseline layer at that index (normally the embedded one), sorted by path.
+    ///
+    /// Only the highest-precedence dynamic file for each path is reported. Sizes are compared
+    /// first; a file of a different size differs without being read, otherwise its contents
+    /// are hashed and compared with the baseline's content hash. Returns
+    /// [`Error::NotFound`] if there is no layer at `embedded_index`.
+    pub fn deviations(&self, embedded_index: usize) -> Result<Vec<Deviation>, Error> {
+        let baseline = self.silos.get(embedded_index).ok_or(Error::NotFound)?;
+        let mut seen = HashSet::new();
+        let mut deviations = Vec::new();
+        let overlays = self.silos[embedded_index + 1..]
+            .iter()
+            .rev()
+            .filter(|silo| silo.is_dynamic());
+        for file in overlays.flat_map(|silo| silo.iter()) {
+            let path = file.path().to_string_lossy().into_owned();
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let kind = match baseline.get_file(&path) {
+                None => DeviationKind::AddedOnDisk,
+                Some(shipped) => {
+                    let size_differs = file.meta()?.size != shipped.meta()?.size;
+                    let hash_differs =
+                        size_differs || file.content_hash()? != shipped.content_hash()?;
+                    DeviationKind::ShadowsEmbedded {
+                        size_differs,
+                        hash_differs,
+                    }
+                }
+            };
+            deviations.push(Deviation { path, kind });
+        }
+        deviations.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(deviations)
+    }
+}
@@ -0,0 +1,133 @@
+//! Checksum-on-read: readers that hash the bytes they yield, so copying a file out and
+//! computing its digest take a single pass.
+
+use crate::{ContentHasher, Error, File, FileReader};
+use std::io::{Read, Write};
+use std::sync::{Arc, OnceLock};
+
+/// Hash algorithm for [`File::reader_with_digest`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HashAlg {
+    /// The 64-bit FNV-1a of [`File::content_hash`].
+    Fnv64,
+    /// SHA-256, as returned by [`File::hash`].
+    #[cfg(feature = "hash")]
+    Sha256,
+}
+
+/// A digest computed by a [`DigestReader`]. `Display` prints it as lowercase hex.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Digest {
+    Fnv64(u64),
+    #[cfg(feature = "hash")]
+    Sha256([u8; 32]),
+}
+
+impl std::fmt::Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Digest::Fnv64(hash) => write!(f, "{hash:016x}"),
+            #[cfg(feature = "hash")]
+            Digest::Sha256(bytes) => bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}")),
+        }
+    }
+}
+
+enum Hasher {
+    Fnv64(ContentHasher),
+    #[cfg(feature = "hash")]
+    Sha256(sha2::Sha256),
+}
+
+impl Hasher {
+    fn new(alg: HashAlg) -> Self {
+        match alg {
+            HashAlg::Fnv64 => Hasher::Fnv64(ContentHasher::new()),
+            #[cfg(feature = "hash")]
+            HashAlg::Sha256 => Hasher::Sha256(<sha2::Sha256 as sha2::Digest>::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Fnv64(hasher) => hasher.update(bytes),
+            #[cfg(feature = "hash")]
+            Hasher::Sha256(hasher) => sha2::Digest::update(hasher, bytes),
+        }
+    }
+
+    fn finish(self) -> Digest {
+        match self {
+            Hasher::Fnv64(hasher) => Digest::Fnv64(hasher.finish()),
+            #[cfg(feature = "hash")]
+            Hasher::Sha256(hasher) => Digest::Sha256(sha2::Digest::finalize(hasher).into()),
+        }
+    }
+}
+
+/// Reader returned by [`File::reader_with_digest`]: yields the file contents and hashes
+/// them on the way through.
+pub struct DigestReader {
+    inner: FileReader,
+    /// `None` once end of file was reached and the digest published.
+    hasher: Option<Hasher>,
+    digest: Arc<OnceLock<Digest>>,
+}
+
+impl Read for DigestReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            if let Some(hasher) = &mut self.hasher {
+                hasher.update(&buf[..read]);
+            }
+        } else if !buf.is_empty()
+            && let Some(hasher) = self.hasher.take()
+        {
+            let _ = self.digest.set(hasher.finish());
+        }
+        Ok(read)
+    }
+}
+
+/// Yields the digest of a [`DigestReader`] once it has been read to the end.
+#[derive(Debug, Clone)]
+pub struct DigestHandle {
+    digest: Arc<OnceLock<Digest>>,
+}
+
+impl DigestHandle {
+    /// Returns the digest, or [`Error::DigestIncomplete`] if the reader has not reached end
+    /// of file yet (a partial digest would silently describe other contents).
+    pub fn finalize(&self) -> Result<Digest, Error> {
+        self.digest.get().copied().ok_or(Error::DigestIncomplete)
+    }
+}
+
+impl File {
+    /// Opens the contents like [`File::reader`], hashing them with `alg` as they are read.
+    ///
+    /// The digest is available from the handle once the reader has returned end of file.
+    /// Embedded, in-memory and dynamic files with the same contents give the same digest.
+    pub fn reader_with_digest(&self, alg: HashAlg) -> Result<(DigestReader, DigestHandle), Error> {
+        let digest = Arc::new(OnceLock::new());
+        let reader = DigestReader {
+            inner: self.reader()?,
+            hasher: Some(Hasher::new(alg)),
+            digest: digest.clone(),
+        };
+        Ok((reader, DigestHandle { digest }))
+    }
+
+    /// Copies the contents to `out` in one pass, returning the number of bytes copied and
+    /// their digest.
+    pub fn copy_to(
+        &self,
+        out: &mut (impl Write + ?Sized),
+        alg: HashAlg,
+    ) -> Result<(u64, Digest), Error> {
+        let (mut reader, handle) = self.reader_with_digest(alg)?;
+        let copied = std::io::copy(&mut reader, out)?;
+        Ok((copied, handle.finalize()?))
+    }
+}
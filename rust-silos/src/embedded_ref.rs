@@ -0,0 +1,118 @@
+//! `'static` views of embedded silos for derived indexes, see [`Silo::as_embedded`] and
+//! [`Silo::to_lookup_map`].
+
+use crate::{EmbedEntry, EmbedFile, File, FileKind, InnerSilo, Silo};
+use std::collections::HashMap;
+
+/// A borrowed view of an embedded silo whose keys and entries are `'static`, so indexes
+/// built from it can outlive the silo, e.g. in a `OnceLock`. See [`Silo::as_embedded`].
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedSiloRef<'a> {
+    silo: &'a Silo,
+    map: &'static phf::Map<&'static str, EmbedEntry>,
+}
+
+impl<'a> EmbeddedSiloRef<'a> {
+    /// Every file of the silo as its path and entry, in no particular order. Paths are
+    /// relative to the silo's scope, and excluded or filtered-out files are skipped, as in
+    /// [`Silo::iter`]; aliases are not listed.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'static EmbedEntry)> + 'a {
+        self.files().map(|(path, entry, _)| (path, entry))
+    }
+
+    /// [`EmbeddedSiloRef::iter`] with each entry's file as the silo reports it.
+    fn files(&self) -> impl Iterator<Item = (&'static str, &'static EmbedEntry, File)> + 'a {
+        let silo = self.silo;
+        let prefix = silo.view.prefix.as_deref().unwrap_or_default();
+        self.map.entries().filter_map(move |(key, entry)| {
+            let relative = key.strip_prefix(prefix)?;
+            let file = File {
+                requested: None,
+                inner: FileKind::Embed(EmbedFile {
+                    inner: entry,
+                    strip: 0,
+                }),
+            };
+            Some((relative, entry, silo.view.apply(file, None)?))
+        })
+    }
+
+    /// The entry [`Silo::get_file`] finds for `path`, following index files and aliases.
+    pub fn get(&self, path: &str) -> Option<&'static EmbedEntry> {
+        match self.silo.get_file(path)?.inner {
+            FileKind::Embed(embed) => Some(embed.inner),
+            _ => None,
+        }
+    }
+
+    /// The silo this view borrows.
+    pub fn silo(&self) -> &'a Silo {
+        self.silo
+    }
+}
+
+/// Files of a silo by path, see [`Silo::to_lookup_map`].
+#[derive(Debug, Clone)]
+pub enum LookupMap {
+    /// Keys borrowed from an embedded silo.
+    Embedded(HashMap<&'static str, File>),
+    /// Keys of any other silo, owned.
+    Owned(HashMap<String, File>),
+}
+
+impl LookupMap {
+    /// The file at exactly `path`.
+    pub fn get(&self, path: &str) -> Option<&File> {
+        match self {
+            LookupMap::Embedded(map) => map.get(path),
+            LookupMap::Owned(map) => map.get(path),
+        }
+    }
+
+    /// Number of files.
+    pub fn len(&self) -> usize {
+        match self {
+            LookupMap::Embedded(map) => map.len(),
+            LookupMap::Owned(map) => map.len(),
+        }
+    }
+
+    /// Whether the map holds no files.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Silo {
+    /// A view of this silo whose paths are `&'static str` and entries `&'static EmbedEntry`,
+    /// or `None` unless the silo is embedded. Only embedded silos can offer that guarantee:
+    /// the keys of every other silo are built at runtime and live no longer than the silo.
+    pub fn as_embedded(&self) -> Option<EmbeddedSiloRef<'_>> {
+        match &self.inner {
+            InnerSilo::Embed(embed) => Some(EmbeddedSiloRef {
+                silo: self,
+                map: embed.map,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Collects the files of [`Silo::iter`] into a map by path: keyed by `&'static str` for
+    /// embedded silos (see [`Silo::as_embedded`]) and by owned `String` otherwise. Lookups
+    /// in the map match exact paths; index files and aliases are not resolved.
+    pub fn to_lookup_map(&self) -> LookupMap {
+        match self.as_embedded() {
+            Some(embedded) => LookupMap::Embedded(
+                embedded
+                    .files()
+                    .map(|(path, _, file)| (path, file))
+                    .collect(),
+            ),
+            None => LookupMap::Owned(
+                self.iter()
+                    .map(|file| (file.path().to_string_lossy().into_owned(), file))
+                    .collect(),
+            ),
+        }
+    }
+}
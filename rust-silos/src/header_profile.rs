@@ -0,0 +1,121 @@
+//! Extra response headers chosen by path, see [`Silo::with_header_profile`] and
+//! [`Silo::with_headers_for`].
+
+use crate::Silo;
+use std::sync::Arc;
+
+/// A named set of header rules for [`Silo::with_header_profile`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HeaderProfile {
+    /// What browsers expect for the web platform's pickier asset types: `application/wasm`
+    /// for streaming WebAssembly compilation, `text/javascript` for modules and workers,
+    /// fonts readable cross-origin, and service workers revalidated on every load and
+    /// allowed to control the whole origin.
+    WebDefaults,
+}
+
+/// `(glob, headers)` rules, applied in order.
+type Table = &'static [(&'static str, &'static [(&'static str, &'static str)])];
+
+const FONT_CORS: (&str, &str) = ("Access-Control-Allow-Origin", "*");
+
+const WEB_DEFAULTS: Table = &[
+    ("**/*.wasm", &[("Content-Type", "application/wasm")]),
+    ("**/*.mjs", &[("Content-Type", "text/javascript")]),
+    ("**/*.worker.js", &[("Content-Type", "text/javascript")]),
+    ("**/*.woff2", &[("Content-Type", "font/woff2"), FONT_CORS]),
+    ("**/*.woff", &[("Content-Type", "font/woff"), FONT_CORS]),
+    ("**/*.ttf", &[("Content-Type", "font/ttf"), FONT_CORS]),
+    ("**/*.otf", &[("Content-Type", "font/otf"), FONT_CORS]),
+    (
+        "**/sw.js",
+        &[
+            ("Content-Type", "text/javascript"),
+            ("Cache-Control", "no-cache"),
+            ("Service-Worker-Allowed", "/"),
+        ],
+    ),
+    (
+        "**/service-worker.js",
+        &[
+            ("Content-Type", "text/javascript"),
+            ("Cache-Control", "no-cache"),
+            ("Service-Worker-Allowed", "/"),
+        ],
+    ),
+];
+
+impl HeaderProfile {
+    fn table(self) -> Table {
+        match self {
+            HeaderProfile::WebDefaults => WEB_DEFAULTS,
+        }
+    }
+}
+
+/// Header names and values of one rule.
+type Headers = Vec<(&'static str, String)>;
+
+/// Header rules installed on a silo, in declaration order.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct HeaderRules {
+    rules: Vec<(Box<str>, Headers)>,
+}
+
+impl HeaderRules {
+    /// Headers for the file at `path`: every matching rule in order, a later value for the
+    /// same header (compared case-insensitively) replacing an earlier one in place.
+    pub(crate) fn resolve(&self, path: &str) -> Headers {
+        let mut headers = Headers::new();
+        for (glob, rule) in &self.rules {
+            if !crate::search::glob_match(glob, path) {
+                continue;
+            }
+            for (name, value) in rule {
+                match headers
+                    .iter_mut()
+                    .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+                {
+                    Some(header) => header.1.clone_from(value),
+                    None => headers.push((name, value.clone())),
+                }
+            }
+        }
+        headers
+    }
+}
+
+impl Silo {
+    /// Adds the rules of `profile` to the headers [`Silo::serve`] sends, after any rules
+    /// added before. See [`Silo::with_headers_for`] for how rules apply.
+    pub fn with_header_profile(self, profile: HeaderProfile) -> Self {
+        profile.table().iter().fold(self, |silo, (glob, headers)| {
+            silo.with_headers_for(glob, headers)
+        })
+    }
+
+    /// Makes [`Silo::serve`] add `headers` to responses for files whose path relative to the
+    /// silo matches `glob`, which takes `*`, `?` and `**` like
+    /// [`SearchOptions::path_glob`](crate::SearchOptions::path_glob).
+    ///
+    /// Rules apply in declaration order, so when several match a file, a later value for
+    /// the same header replaces an earlier one. A `Content-Type` rule replaces the resolved
+    /// type before compression is negotiated; other headers are sent on 200, 206 and 304
+    /// responses after the validators. Headers [`Silo::serve`] sets itself, such as `ETag` or
+    /// `Content-Length`, should not be given here. A [`SiloSet`](crate::SiloSet) serving a
+    /// file from this silo as a layer applies this silo's rules too.
+    pub fn with_headers_for(mut self, glob: &str, headers: &[(&'static str, &str)]) -> Self {
+        let rule = (
+            Box::from(glob),
+            headers
+                .iter()
+                .map(|&(name, value)| (name, value.to_owned()))
+                .collect(),
+        );
+        Arc::make_mut(self.view.headers.get_or_insert_default())
+            .rules
+            .push(rule);
+        self
+    }
+}
@@ -0,0 +1,129 @@
+//! Text splicing of `<!-- include: path -->` style directives, see [`Silo::render_includes`].
+
+use crate::{Error, Silo, SiloLike, SiloSet};
+
+/// What [`Silo::render_includes`] does with a directive whose file does not exist.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum MissingInclude {
+    /// Fail with [`Error::MissingInclude`].
+    #[default]
+    Error,
+    /// Leave the directive in the output as written.
+    Keep,
+    /// Replace the directive with nothing.
+    Empty,
+}
+
+/// Options for [`Silo::render_includes`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct IncludeOptions {
+    /// Text that opens a directive. Default `<!-- include:`.
+    pub open: String,
+    /// Text that closes a directive. Default `-->`.
+    pub close: String,
+    /// Maximum nesting of includes below the rendered file. Default 16.
+    pub max_depth: usize,
+    pub missing: MissingInclude,
+}
+
+impl Default for IncludeOptions {
+    fn default() -> Self {
+        Self {
+            open: "<!-- include:".to_owned(),
+            close: "-->".to_owned(),
+            max_depth: 16,
+            missing: MissingInclude::Error,
+        }
+    }
+}
+
+impl Silo {
+    /// Loads the text file at `path` and replaces every include directive, e.g.
+    /// `<!-- include: partials/nav.html -->`, with the rendered contents of the named file.
+    ///
+    /// Included paths are relative to the silo root, and included files are rendered the same
+    /// way. An include that leads back to a file being rendered fails with
+    /// [`Error::IncludeCycle`], nesting deeper than `opts.max_depth` with
+    /// [`Error::IncludeTooDeep`]. A directive without its closing text is left as is. Returns
+    /// [`Error::NotFound`] if `path` itself does not exist.
+    pub fn render_includes(&self, path: &str, opts: &IncludeOptions) -> Result<String, Error> {
+        render_includes(self, path, opts)
+    }
+}
+
+impl SiloSet {
+    /// Like [`Silo::render_includes`], resolving the file and every include through the
+    /// overlay, so a higher layer can override a partial.
+    pub fn render_includes(&self, path: &str, opts: &IncludeOptions) -> Result<String, Error> {
+        render_includes(self, path, opts)
+    }
+}
+
+fn render_includes(
+    source: &dyn SiloLike,
+    path: &str,
+    opts: &IncludeOptions,
+) -> Result<String, Error> {
+    let text = source
+        .get_file(path)
+        .ok_or(Error::NotFound)?
+        .text()?
+        .into_owned();
+    let mut stack = vec![path.to_owned()];
+    let mut out = String::with_capacity(text.len());
+    splice(source, &text, opts, &mut stack, &mut out)?;
+    Ok(out)
+}
+
+/// Appends `text` to `out` with its directives expanded. `stack` holds the files being
+/// rendered, outermost first.
+fn splice(
+    source: &dyn SiloLike,
+    text: &str,
+    opts: &IncludeOptions,
+    stack: &mut Vec<String>,
+    out: &mut String,
+) -> Result<(), Error> {
+    let mut rest = text;
+    while let Some(start) = rest.find(&opts.open) {
+        let after_open = &rest[start + opts.open.len()..];
+        let Some(end) = after_open.find(&opts.close) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let directive = &rest[start..start + opts.open.len() + end + opts.close.len()];
+        rest = &after_open[end + opts.close.len()..];
+        let target = after_open[..end].trim();
+
+        let Some(file) = source.get_file(target) else {
+            match opts.missing {
+                MissingInclude::Error => {
+                    return Err(Error::MissingInclude {
+                        path: target.to_owned(),
+                        included_from: stack.last().cloned().unwrap_or_default(),
+                    });
+                }
+                MissingInclude::Keep => out.push_str(directive),
+                MissingInclude::Empty => {}
+            }
+            continue;
+        };
+        if let Some(at) = stack.iter().position(|open| open == target) {
+            let mut chain = stack[at..].to_vec();
+            chain.push(target.to_owned());
+            return Err(Error::IncludeCycle { chain });
+        }
+        if stack.len() > opts.max_depth {
+            return Err(Error::IncludeTooDeep {
+                path: target.to_owned(),
+                max_depth: opts.max_depth,
+            });
+        }
+        let included = file.text()?;
+        stack.push(target.to_owned());
+        splice(source, &included, opts, stack, out)?;
+        stack.pop();
+    }
+    out.push_str(rest);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Key normalization, see [`Silo::with_key_normalization`].
+
+use crate::{Error, Silo};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How a silo normalizes file keys and lookup paths, so that spellings that differ only in
+/// Unicode normal form or case find the same file.
+///
+/// macOS writes file names in decomposed form (NFD), so an asset tree authored there can hold
+/// `cafe\u{301}.txt` where code asks for `caf\u{e9}.txt`; [`KeyNormalization::Nfc`] makes both
+/// resolve. The NFC variants need the `unicode` feature; the enum is non-exhaustive so that
+/// enabling the feature anywhere in the build does not break matches on it.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum KeyNormalization {
+    /// Keys are compared byte for byte.
+    #[default]
+    None,
+    /// Keys are compared lowercased (Unicode lowercase mapping).
+    Lowercase,
+    /// Keys are compared in Unicode Normalization Form C.
+    #[cfg(feature = "unicode")]
+    Nfc,
+    /// Keys are compared in Normalization Form C, then lowercased.
+    #[cfg(feature = "unicode")]
+    NfcLowercase,
+}
+
+impl KeyNormalization {
+    /// Normalizes `key`, borrowing it if it is already normal.
+    pub fn apply(self, key: &str) -> Cow<'_, str> {
+        match self {
+            KeyNormalization::None => Cow::Borrowed(key),
+            KeyNormalization::Lowercase => lowercase(Cow::Borrowed(key)),
+            #[cfg(feature = "unicode")]
+            KeyNormalization::Nfc => nfc(key),
+            #[cfg(feature = "unicode")]
+            KeyNormalization::NfcLowercase => lowercase(nfc(key)),
+        }
+    }
+}
+
+fn lowercase(key: Cow<'_, str>) -> Cow<'_, str> {
+    if key.chars().any(|c| c.to_lowercase().ne(std::iter::once(c))) {
+        Cow::Owned(key.to_lowercase())
+    } else {
+        key
+    }
+}
+
+#[cfg(feature = "unicode")]
+fn nfc(key: &str) -> Cow<'_, str> {
+    use unicode_normalization::UnicodeNormalization;
+    if unicode_normalization::is_nfc(key) {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(key.nfc().collect())
+    }
+}
+
+impl Silo {
+    /// Normalizes keys with `keys`: lookup paths are normalized before they are matched, and
+    /// dynamic files report their normalized path, so a silo only ever shows one spelling of
+    /// each key. A lookup first tries the path as given, then its normalized form.
+    ///
+    /// Embedded silos store the keys they were built with; use the `key_normalization` option
+    /// of `embed_silo!`, which normalizes them at compile time and rejects collisions. On
+    /// disk, a normalized lookup that matches several entries of a directory finds nothing;
+    /// use [`Silo::try_with_key_normalization`] to rule that out up front.
+    pub const fn with_key_normalization(mut self, keys: KeyNormalization) -> Self {
+        self.view.keys = keys;
+        self
+    }
+
+    /// Like [`Silo::with_key_normalization`], but first walks the silo and fails with
+    /// [`Error::KeyCollision`] if two files normalize to the same key.
+    pub fn try_with_key_normalization(self, keys: KeyNormalization) -> Result<Self, Error> {
+        let mut by_key: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let unnormalized = self.clone().with_key_normalization(KeyNormalization::None);
+        for file in unnormalized.try_iter() {
+            let path = file?.path().to_string_lossy().into_owned();
+            by_key
+                .entry(keys.apply(&path).into_owned())
+                .or_default()
+                .push(path);
+        }
+        if let Some((key, mut paths)) = by_key.into_iter().find(|(_, paths)| paths.len() > 1) {
+            paths.sort();
+            return Err(Error::KeyCollision { key, paths });
+        }
+        Ok(self.with_key_normalization(keys))
+    }
+
+    /// How this silo normalizes keys, see [`Silo::with_key_normalization`].
+    pub fn key_normalization(&self) -> KeyNormalization {
+        self.view.keys
+    }
+}
+
+/// Finds the file under `root` whose path normalizes to `key`, one directory at a time.
+/// Returns its relative path as named on disk, or `None` if a component matches no entry or
+/// more than one.
+#[deny(clippy::indexing_slicing)]
+pub(crate) fn resolve_on_disk(root: &str, key: &str, keys: KeyNormalization) -> Option<String> {
+    let mut dir = Path::new(root).to_path_buf();
+    let mut resolved = Vec::new();
+    for segment in key.split('/').filter(|segment| !segment.is_empty()) {
+        if matches!(segment, "." | "..") {
+            return None;
+        }
+        let mut matches = std::fs::read_dir(&dir)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| keys.apply(name) == segment);
+        let name = matches.next()?;
+        if matches.next().is_some() {
+            return None;
+        }
+        dir.push(&name);
+        resolved.push(name);
+    }
+    Some(resolved.join("/"))
+}
@@ -1,14 +1,94 @@
 // Re-export phf_map macro for consumers of rust-silos
 pub use phf::phf_map;
 pub use phf;
+use std::borrow::Cow;
 use std::hash::Hash;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
 
-pub use rust_silos_macros::embed_silo;
-
+pub use rust_silos_macros::{embed_silo, embed_silo_enum, static_embed_silo};
+
+mod base64;
+mod coalesce;
+mod content_hash;
+mod negative_cache;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "tera")]
+pub mod tera;
+#[cfg(feature = "vfs")]
+pub mod vfs;
+pub use content_hash::ContentHasher;
+mod transcode;
+pub use transcode::{TranscodeFailure, Transcoding};
+mod memory;
+pub use memory::MemoryBytes;
+pub mod pack;
+mod search;
+#[cfg(feature = "semver")]
+mod versioned;
+pub use search::{Match, SearchOptions};
+pub mod conditional;
+mod silo_like;
+pub use silo_like::{SiloLike, SiloStats};
+mod compact;
+pub use compact::CompactOptions;
+mod snapshot;
+pub use snapshot::SiloSnapshot;
+mod open;
+pub use open::OpenFile;
+mod queries;
+mod select;
+mod version_token;
+pub use version_token::VersionToken;
+mod include;
+pub use include::{IncludeOptions, MissingInclude};
+mod deviations;
+pub use deviations::{Deviation, DeviationKind};
+mod build_id;
+pub use build_id::BuildId;
+mod contents_iter;
+pub use contents_iter::{IterError, ReadErrors};
+mod digest;
+pub use digest::{Digest, DigestHandle, DigestReader, HashAlg};
+mod key_normalization;
+pub use key_normalization::KeyNormalization;
+mod embedded_ref;
+pub use embedded_ref::{EmbeddedSiloRef, LookupMap};
+mod header_profile;
+pub use header_profile::HeaderProfile;
+mod symlink;
+mod unix_mode;
+pub use symlink::SymlinkPolicy;
+pub use unix_mode::ExtractReport;
+mod walk_limits;
+use walk_limits::Breach;
+pub use walk_limits::{LimitKind, WalkLimits};
+pub mod bundle;
+pub mod cascade;
+mod codec;
+pub use codec::Codec;
+#[cfg(feature = "deflate")]
+pub use codec::RawDeflate;
+#[cfg(feature = "compression")]
+pub use codec::{Deflate, Gzip};
+mod serve;
+pub use serve::{HttpFileResponse, RequestHeaders, ResponseBody};
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "compression")]
+pub use compression::CompressionConfig;
+
+/// Layout version of [`EmbedEntry`] as emitted by generated code.
+///
+/// `embed_silo!` (and any pre-generated embed code) asserts this value at compile time, so
+/// code generated against a different layout fails fast with a message to regenerate it.
+/// Bumped whenever the fields of `EmbedEntry` change.
+pub const SILO_ABI: u32 = 5;
 
 /// Error type for file and silo operations.
 #[derive(Debug, Error)]
@@ -25,6 +105,69 @@ pub enum Error {
         #[from]
         source: std::io::Error,
     },
+    #[error("Failed to transcode {path} to UTF-8: encoding not recognized")]
+    TranscodeError { path: String },
+    #[error("{path} kept changing during {attempts} read attempts")]
+    Unstable { path: String, attempts: usize },
+    #[error("{path} is a directory")]
+    IsADirectory { path: String },
+    #[error("Permission denied reading {path}")]
+    PermissionDenied { path: String },
+    #[error("Walk stopped: {which} limit of {limit} exceeded")]
+    LimitExceeded { which: LimitKind, limit: u64 },
+    #[error("{path} was deleted after the snapshot was taken")]
+    Stale { path: String },
+    #[error("Included file {path} not found (included from {included_from})")]
+    MissingInclude { path: String, included_from: String },
+    #[error("Include cycle: {}", chain.join(" -> "))]
+    IncludeCycle {
+        /// The files in the cycle, starting and ending with the same one.
+        chain: Vec<String>,
+    },
+    #[error("Including {path} exceeds the maximum include depth of {max_depth}")]
+    IncludeTooDeep { path: String, max_depth: usize },
+    #[error("Digest finalized before the reader reached end of file")]
+    DigestIncomplete,
+    #[error("File is read-only (embedded or in-memory)")]
+    ReadOnly,
+    #[error("Embedded silo has no source directory (built with redact_root)")]
+    RootRedacted,
+    #[error(
+        "Alias {alias} points at {target}, which is itself an alias (chains and cycles are not allowed)"
+    )]
+    InvalidAlias { alias: String, target: String },
+    #[error("Files {} all normalize to the key {key}", paths.join(", "))]
+    KeyCollision { key: String, paths: Vec<String> },
+    #[error("Invalid pack: {reason}")]
+    PackError { reason: String },
+    #[cfg(feature = "json")]
+    #[error("JSON error: {source}")]
+    JsonError {
+        #[from]
+        source: ::serde_json::Error,
+    },
+    #[cfg(feature = "toml")]
+    #[error("TOML error: {source}")]
+    TomlError {
+        #[from]
+        source: ::toml::de::Error,
+    },
+    #[cfg(feature = "semver")]
+    #[error(
+        "No pack version matches {constraint} (found: {})",
+        if candidates.is_empty() { "none".to_owned() } else { candidates.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ") }
+    )]
+    NoMatchingVersion {
+        constraint: ::semver::VersionReq,
+        /// Every version found, in ascending order.
+        candidates: Vec<::semver::Version>,
+    },
+    #[cfg(feature = "tera")]
+    #[error("Template error: {source}")]
+    TemplateError {
+        #[from]
+        source: ::tera::Error,
+    },
 }
 
 
@@ -32,9 +175,60 @@ pub enum Error {
 #[derive(Debug)]
 pub struct EmbedEntry {
     pub path: &'static str,
+    /// Raw contents; empty when the entry is stored as [`EmbedEntry::base64`].
     pub contents: &'static [u8],
     pub size: usize,
     pub modified: u64,
+    /// Encoding the contents were transcoded from at embed time, if any.
+    pub original_encoding: Option<&'static str>,
+    /// [`ContentHasher`] hash of the (decoded) contents, computed at embed time.
+    pub content_hash: u64,
+    /// Contents as base64 text, for silos embedded with `base64 = true`.
+    pub base64: Option<&'static EmbedBase64>,
+    /// Unix permission bits (`st_mode & 0o7777`) at embed time; 0 if embedded on a platform
+    /// without them. See [`File::unix_mode`].
+    ///
+    /// 0 also means "not recorded": generated code that minimizes metadata drops the mode by
+    /// emitting 0, so [`File::unix_mode`] returns `None` and [`File::extract_to`] leaves the
+    /// permissions to the umask. `embed_silo!` itself always records it, whatever the other
+    /// options (`redact_root` and `deterministic` included).
+    pub mode: u32,
+}
+
+impl EmbedEntry {
+    /// Returns the contents, decoding [`EmbedEntry::base64`] on first access. The decoded
+    /// bytes are cached, so later reads borrow them like `include_bytes!` data.
+    ///
+    /// # Panics
+    /// Panics if the base64 text is malformed, which `embed_silo!` never generates.
+    pub fn bytes(&'static self) -> &'static [u8] {
+        match self.base64 {
+            None => self.contents,
+            Some(encoded) => encoded.decoded.get_or_init(|| {
+                base64::decode(encoded.text)
+                    .unwrap_or_else(|| panic!("embedded entry {} is not valid base64", self.path))
+                    .into_boxed_slice()
+            }),
+        }
+    }
+}
+
+/// Padded standard base64 contents of an embedded file, decoded once on first access.
+///
+/// Declared as its own `static` by `embed_silo!` (the PHF map cannot hold the cache).
+#[derive(Debug)]
+pub struct EmbedBase64 {
+    pub text: &'static str,
+    decoded: std::sync::OnceLock<Box<[u8]>>,
+}
+
+impl EmbedBase64 {
+    pub const fn new(text: &'static str) -> Self {
+        Self {
+            text,
+            decoded: std::sync::OnceLock::new(),
+        }
+    }
 }
 
 /// Metadata for a file.
@@ -45,16 +239,217 @@ pub struct FileMeta {
     pub modified: u64,
 }
 
+/// What was known about a file at some point, see [`File::snapshot`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct FileSnapshot {
+    pub size: Option<usize>,
+    /// Seconds since UNIX epoch.
+    pub modified: Option<u64>,
+    /// [`ContentHasher`] hash of the contents, if it was computed.
+    pub hash: Option<u64>,
+}
+
+/// Result of [`File::has_changed`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Changed {
+    No,
+    /// Size or modification time differ; the contents were not compared.
+    Metadata,
+    /// The content hash differs from the snapshot's.
+    Content,
+}
+
+/// Options for [`File::has_changed_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeOptions {
+    /// Hash the contents even when size and modification time match.
+    pub verify_content: bool,
+}
+
+/// A single entry of a route manifest, mapping a URL to a file in the silo.
+///
+/// Produced by [`Silo::routes`] and [`SiloSet::routes`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize))]
+pub struct RouteEntry {
+    /// Percent-encoded URL, including the mount prefix.
+    pub url: String,
+    /// Relative path of the file inside the silo.
+    pub path: String,
+    pub size: usize,
+    /// Content type guessed from the extension (requires the `mime` feature).
+    pub content_type: Option<&'static str>,
+    /// Hex-encoded SHA-256 of the contents (requires the `hash` feature).
+    pub hash: Option<String>,
+}
+
+/// Snapshot of a silo's files, for detecting changes between builds or releases.
+///
+/// Produced by [`Silo::manifest`]; entries are sorted by path.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+    /// [`Silo::build_id`] of the silo the manifest was taken from.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub build_id: Option<BuildId>,
+}
+
+/// A single file of a [`Manifest`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ManifestEntry {
+    /// Relative path of the file inside the silo.
+    pub path: String,
+    pub size: usize,
+    /// Seconds since UNIX epoch.
+    pub modified: u64,
+    /// [`File::content_hash`] of the contents; `None` for manifests recorded without hashes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hash: Option<u64>,
+}
+
+/// Differences between two manifests, see [`Manifest::diff`].
+///
+/// `Display` prints one line per change: `+ path` for added, `- path` for removed and
+/// `~ path` (with size and mtime deltas) for changed files.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ManifestDiff {
+    pub added: Vec<ManifestEntry>,
+    pub removed: Vec<ManifestEntry>,
+    pub changed: Vec<ManifestChange>,
+}
+
+/// A file present in both manifests whose contents differ.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+pub struct ManifestChange {
+    pub path: String,
+    pub old_size: usize,
+    pub new_size: usize,
+    pub old_modified: u64,
+    pub new_modified: u64,
+    /// `true` if the change was detected by content hash; `false` if one side had no hash and
+    /// size and mtime were compared instead (lower confidence: a touched file counts as changed).
+    pub by_hash: bool,
+}
+
+impl Manifest {
+    /// Compares this manifest against an `older` one.
+    ///
+    /// Files are matched by path. When both entries carry a hash, only the hash decides
+    /// whether a file changed; otherwise a differing size or mtime does.
+    pub fn diff(&self, older: &Manifest) -> ManifestDiff {
+        let old: std::collections::HashMap<&str, &ManifestEntry> =
+            older.entries.iter().map(|e| (e.path.as_str(), e)).collect();
+        let new: std::collections::HashMap<&str, &ManifestEntry> =
+            self.entries.iter().map(|e| (e.path.as_str(), e)).collect();
+        let mut diff = ManifestDiff::default();
+        for entry in &self.entries {
+            let Some(prev) = old.get(entry.path.as_str()) else {
+                diff.added.push(entry.clone());
+                continue;
+            };
+            let (changed, by_hash) = match (prev.hash, entry.hash) {
+                (Some(a), Some(b)) => (a != b, true),
+                _ => (
+                    prev.size != entry.size || prev.modified != entry.modified,
+                    false,
+                ),
+            };
+            if changed {
+                diff.changed.push(ManifestChange {
+                    path: entry.path.clone(),
+                    old_size: prev.size,
+                    new_size: entry.size,
+                    old_modified: prev.modified,
+                    new_modified: entry.modified,
+                    by_hash,
+                });
+            }
+        }
+        diff.removed = older
+            .entries
+            .iter()
+            .filter(|e| !new.contains_key(e.path.as_str()))
+            .cloned()
+            .collect();
+        diff.added.sort_by(|a, b| a.path.cmp(&b.path));
+        diff.removed.sort_by(|a, b| a.path.cmp(&b.path));
+        diff.changed.sort_by(|a, b| a.path.cmp(&b.path));
+        diff
+    }
+}
+
+impl ManifestDiff {
+    /// Returns `true` if nothing was added, removed or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl std::fmt::Display for ManifestDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.added {
+            writeln!(f, "+ {} ({} bytes)", entry.path, entry.size)?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {}", entry.path)?;
+        }
+        for change in &self.changed {
+            write!(
+                f,
+                "~ {}: size {} -> {}, modified {:+}s",
+                change.path,
+                change.old_size,
+                change.new_size,
+                i128::from(change.new_modified) - i128::from(change.old_modified)
+            )?;
+            if !change.by_hash {
+                write!(f, " (compared by size and mtime)")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// How [`Silo::merge_with`] resolves a path present in both silos.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MergeDecision {
+    /// Keep the file from the silo `merge_with` was called on.
+    TakeLeft,
+    /// Keep the file from the other silo.
+    TakeRight,
+    /// Replace both with these bytes, e.g. concatenated or merged contents.
+    TakeBytes(Vec<u8>),
+}
+
+/// An immediate child of a directory, as yielded by [`Silo::read_dir`].
+#[derive(Debug, Clone)]
+pub enum Entry {
+    File(File),
+    /// A subdirectory, given as its relative path within the silo (e.g. `"assets/img"`).
+    Dir(String),
+}
+
 /// Handle to an embedded file entry.
 #[derive(Copy, Clone, Debug)]
 struct EmbedFile {
     inner: &'static EmbedEntry,
+    /// Length of the scope prefix hidden from the reported path.
+    strip: usize,
 }
 
 impl EmbedFile {
     /// Returns the relative path of the embedded file.
-    pub fn path(&self) -> &Path {
-        Path::new(self.inner.path)
+    pub fn path(&self) -> &str {
+        &self.inner.path[self.strip..]
     }
 }
 
@@ -63,30 +458,312 @@ impl EmbedFile {
 enum FileKind {
     Embed(EmbedFile),
     Dynamic(DynFile),
+    Memory(memory::MemFile),
 }
 
 /// Represents a file, which may be embedded or dynamic.
 #[derive(Debug, Clone)]
 pub struct File {
+    /// Alias the file was looked up by, see [`File::requested_path`].
+    requested: Option<Arc<str>>,
     inner: FileKind,
 }
 
 impl File {
     /// Returns a reader for the file's contents. May return an error if the file cannot be opened.
+    ///
+    /// Each call returns a new, independent reader positioned at the start, so a file can be
+    /// read any number of times (e.g. two-pass parsing). Readers implement
+    /// [`std::io::Seek`] to rewind or jump within a single reader.
     pub fn reader(&self) -> Result<FileReader, Error> {
         match &self.inner {
-            FileKind::Embed(embed) => Ok(FileReader::Embed(Cursor::new(embed.inner.contents))),
+            FileKind::Embed(embed) => Ok(FileReader::Embed(Cursor::new(embed.inner.bytes()))),
+            FileKind::Dynamic(dyn_file) if dyn_file.transcoding().is_some() => {
+                Ok(FileReader::Transcoded(Cursor::new(dyn_file.read()?)))
+            }
             FileKind::Dynamic(dyn_file) => Ok(FileReader::Dynamic(std::fs::File::open(
                 dyn_file.absolute_path(),
             )?)),
+            FileKind::Memory(mem) => Ok(FileReader::Memory(Cursor::new(MemoryBytes(mem.clone())))),
+        }
+    }
+
+    /// Reads the bytes in `range` of the contents, the random-access primitive shared by all
+    /// backends. The range is clamped to the file length, so a range past the end yields
+    /// fewer (or no) bytes; an empty or reversed range yields none.
+    ///
+    /// Embedded and in-memory files copy the slice directly; dynamic files seek and read only
+    /// the requested bytes (transcoded files are decoded in full first).
+    pub fn read_range(&self, range: std::ops::Range<u64>) -> Result<Vec<u8>, Error> {
+        use std::io::{Read, Seek};
+        let slice = |bytes: &[u8]| {
+            let len = bytes.len() as u64;
+            let (start, end) = (range.start.min(len), range.end.min(len));
+            bytes[start as usize..end.max(start) as usize].to_vec()
+        };
+        match &self.inner {
+            FileKind::Embed(embed) => Ok(slice(embed.inner.bytes())),
+            FileKind::Memory(mem) => Ok(slice(mem.bytes())),
+            FileKind::Dynamic(_) => {
+                let mut reader = self.reader()?;
+                let mut bytes = Vec::new();
+                if range.end > range.start {
+                    reader.seek(std::io::SeekFrom::Start(range.start))?;
+                    reader
+                        .take(range.end - range.start)
+                        .read_to_end(&mut bytes)?;
+                }
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Returns a reader implementing [`std::io::BufRead`], for line-oriented or many small reads.
+    ///
+    /// Dynamic files are wrapped in a [`std::io::BufReader`] with the given capacity, turning
+    /// many small reads into few large `read` syscalls; a plain [`File::reader`] issues one
+    /// syscall per read call. Embedded and other in-memory contents are passed through
+    /// unbuffered, as they are already in memory, and `capacity` is ignored for them.
+    pub fn buffered_reader(&self, capacity: usize) -> Result<BufferedFileReader, Error> {
+        Ok(match self.reader()? {
+            FileReader::Embed(c) => BufferedFileReader::Embed(c),
+            FileReader::Dynamic(f) => {
+                BufferedFileReader::Dynamic(std::io::BufReader::with_capacity(capacity, f))
+            }
+            FileReader::Transcoded(c) | FileReader::Consistent(c) => {
+                BufferedFileReader::Transcoded(c)
+            }
+            FileReader::Memory(c) => BufferedFileReader::Memory(c),
+        })
+    }
+
+    /// Returns the file contents, borrowing the static bytes for embedded files
+    /// and reading them into memory for dynamic files.
+    pub fn contents(&self) -> Result<Cow<'static, [u8]>, Error> {
+        match &self.inner {
+            FileKind::Embed(embed) => Ok(Cow::Borrowed(embed.inner.bytes())),
+            FileKind::Dynamic(dyn_file) => Ok(Cow::Owned(dyn_file.read()?)),
+            FileKind::Memory(mem) => Ok(Cow::Owned(mem.bytes().to_vec())),
+        }
+    }
+
+    /// Like [`File::contents`], but guards against a dynamic file being rewritten while it is
+    /// read: the file is stat'ed before and after reading, and the read is retried (up to
+    /// [`CONSISTENT_READ_ATTEMPTS`] times) if its size or modification time changed in between.
+    /// Returns [`Error::Unstable`] if it never settles. Other files cannot change and are
+    /// returned as-is.
+    pub fn contents_consistent(&self) -> Result<Cow<'static, [u8]>, Error> {
+        let FileKind::Dynamic(dyn_file) = &self.inner else {
+            return self.contents();
+        };
+        for _ in 0..CONSISTENT_READ_ATTEMPTS {
+            if let Some(bytes) = dyn_file.read_consistent()? {
+                return Ok(Cow::Owned(bytes));
+            }
+        }
+        Err(Error::Unstable {
+            path: dyn_file.rel_path.to_string(),
+            attempts: CONSISTENT_READ_ATTEMPTS,
+        })
+    }
+
+    /// Returns the file contents as a shared buffer.
+    ///
+    /// For files from a silo configured with [`Silo::with_coalescing`], concurrent calls for
+    /// the same path share a single read and receive clones of the same `Arc`.
+    pub fn shared_contents(&self) -> Result<Arc<[u8]>, Error> {
+        match &self.inner {
+            FileKind::Dynamic(dyn_file) if dyn_file.transcoding().is_none() => {
+                match &dyn_file.options.coalescer {
+                    Some(coalescer) => Ok(coalescer.read(dyn_file.absolute_path())?),
+                    None => Ok(Arc::from(dyn_file.read()?)),
+                }
+            }
+            _ => Ok(Arc::from(self.contents()?)),
+        }
+    }
+
+    /// Returns the file contents as UTF-8 text, borrowed for embedded files.
+    pub fn text(&self) -> Result<Cow<'static, str>, Error> {
+        match self.contents()? {
+            Cow::Borrowed(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => Ok(Cow::Borrowed(text)),
+                Err(_) => Err(String::from_utf8(bytes.to_vec()).unwrap_err().into()),
+            },
+            Cow::Owned(bytes) => Ok(Cow::Owned(String::from_utf8(bytes)?)),
+        }
+    }
+
+    /// Writes the contents to a new named temporary file, for APIs that only accept paths.
+    /// The file keeps this file's extension and is deleted when the returned handle is dropped.
+    #[cfg(feature = "tempfile")]
+    pub fn into_temp_file(&self) -> Result<tempfile::NamedTempFile, Error> {
+        use std::io::Write;
+        let suffix = self
+            .path()
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let mut temp = tempfile::Builder::new()
+            .prefix("silo-")
+            .suffix(&suffix)
+            .tempfile()?;
+        std::io::copy(&mut self.reader()?, &mut temp)?;
+        temp.flush()?;
+        Ok(temp)
+    }
+
+    /// Calls `f` with the path of a temporary copy of this file (see [`File::into_temp_file`]),
+    /// removing the copy afterwards.
+    #[cfg(feature = "tempfile")]
+    pub fn with_temp_path<F, R>(&self, f: F) -> Result<R, Error>
+    where
+        F: FnOnce(&Path) -> R,
+    {
+        let temp = self.into_temp_file()?;
+        let result = f(temp.path());
+        temp.close()?;
+        Ok(result)
+    }
+
+    /// Replaces the file on disk with `contents`, for editing a dynamic silo in place.
+    ///
+    /// The bytes go to a temporary file next to the target, which is then renamed over it, so
+    /// readers see either the old or the new contents. The target's permissions are kept, and
+    /// a symlinked file is replaced at the link's destination. Contents are written as given:
+    /// a file read through [`Silo::with_transcoding`] is not converted back to its original
+    /// encoding. Embedded and in-memory files return [`Error::ReadOnly`].
+    pub fn write_back(&self, contents: &[u8]) -> Result<(), Error> {
+        let FileKind::Dynamic(dyn_file) = &self.inner else {
+            return Err(Error::ReadOnly);
+        };
+        let target = std::fs::canonicalize(dyn_file.absolute_path())
+            .unwrap_or_else(|_| dyn_file.absolute_path().to_path_buf());
+        write_atomic(&target, contents)?;
+        Ok(())
+    }
+
+    /// Deserializes the file contents as JSON.
+    #[cfg(feature = "json")]
+    pub fn load_json<T: ::serde::de::DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_json::from_slice(&self.contents()?)?)
+    }
+
+    /// Deserializes the file contents as TOML.
+    #[cfg(feature = "toml")]
+    pub fn load_toml<T: ::serde::de::DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(toml::from_str(&self.text()?)?)
+    }
+
+    /// Yields the file contents in chunks of up to `chunk_size` bytes.
+    ///
+    /// Embedded files are split in memory; dynamic files are read incrementally, so only one
+    /// chunk is held at a time. An I/O error is yielded once and ends the iteration.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn stream_chunks(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<Vec<u8>, Error>> + use<> {
+        use std::io::Read;
+        assert!(chunk_size > 0, "chunk_size must be non-zero");
+        let mut reader = Some(self.reader());
+        std::iter::from_fn(move || {
+            let mut current = match reader.take()? {
+                Ok(current) => current,
+                Err(err) => return Some(Err(err)),
+            };
+            let mut chunk = Vec::with_capacity(chunk_size);
+            match current
+                .by_ref()
+                .take(chunk_size as u64)
+                .read_to_end(&mut chunk)
+            {
+                Ok(0) => None,
+                Ok(_) => {
+                    reader = Some(Ok(current));
+                    Some(Ok(chunk))
+                }
+                Err(err) => Some(Err(err.into())),
+            }
+        })
+    }
+
+    /// Zero-copy variant of [`File::stream_chunks`] for embedded files.
+    /// Returns `None` for dynamic files.
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is 0.
+    pub fn stream_chunks_borrowed(
+        &self,
+        chunk_size: usize,
+    ) -> Option<impl Iterator<Item = &'static [u8]> + use<>> {
+        assert!(chunk_size > 0, "chunk_size must be non-zero");
+        match &self.inner {
+            FileKind::Embed(embed) => Some(embed.inner.bytes().chunks(chunk_size)),
+            _ => None,
         }
     }
 
     /// Returns the relative path of the file.
+    ///
+    /// Relative paths always use `/` as the separator, on every platform and for every
+    /// backend, so they can be compared, stored and used as URLs or map keys portably. Use
+    /// [`File::path_native`] for a path with the platform's separator.
     pub fn path(&self) -> &Path {
+        Path::new(self.path_str())
+    }
+
+    /// Returns the path this file was looked up by: the alias if it was found through one
+    /// (see [`Silo::with_aliases`]), otherwise the same as [`File::path`].
+    pub fn requested_path(&self) -> &Path {
+        match &self.requested {
+            Some(requested) => Path::new(&**requested),
+            None => self.path(),
+        }
+    }
+
+    /// Returns the relative path with `/` replaced by the platform separator (`\` on
+    /// Windows), for APIs that expect native paths. Identical to [`File::path`] elsewhere.
+    pub fn path_native(&self) -> PathBuf {
+        match std::path::MAIN_SEPARATOR {
+            '/' => PathBuf::from(self.path_str()),
+            sep => PathBuf::from(self.path_str().replace('/', &sep.to_string())),
+        }
+    }
+
+    /// Returns the relative path as a UTF-8 path. Silo keys are always UTF-8, so this is free.
+    #[cfg(feature = "camino")]
+    pub fn path_utf8(&self) -> &camino::Utf8Path {
+        camino::Utf8Path::new(self.path_str())
+    }
+
+    /// Returns the relative path as stored; silo keys are always UTF-8.
+    fn path_str(&self) -> &str {
         match &self.inner {
             FileKind::Embed(embed) => embed.path(),
-            FileKind::Dynamic(dyn_file) => dyn_file.path(),
+            FileKind::Dynamic(dyn_file) => &dyn_file.rel_path,
+            FileKind::Memory(mem) => mem.path(),
+        }
+    }
+
+    /// Identifies the file within its backend: the full key (ignoring any scope prefix) for
+    /// embedded and in-memory files, the path relative to the root for dynamic ones.
+    fn backend_key(&self) -> &str {
+        match &self.inner {
+            FileKind::Embed(embed) => embed.inner.path,
+            FileKind::Dynamic(dyn_file) => &dyn_file.rel_path,
+            FileKind::Memory(mem) => &mem.store.entry(mem.index).path,
+        }
+    }
+
+    /// Returns the absolute path as a UTF-8 path if the file is dynamic, or None if embedded.
+    #[cfg(feature = "camino")]
+    pub fn absolute_path_utf8(&self) -> Option<&camino::Utf8Path> {
+        match &self.inner {
+            FileKind::Dynamic(dyn_file) => Some(camino::Utf8Path::new(&*dyn_file.full_path)),
+            _ => None,
         }
     }
 
@@ -98,8 +775,20 @@ impl File {
     /// Returns the absolute path if the file is dynamic, or None if embedded.
     pub fn absolute_path(&self) -> Option<&Path> {
         match &self.inner {
-            FileKind::Embed(_) => None,
             FileKind::Dynamic(dyn_file) => Some(dyn_file.absolute_path()),
+            _ => None,
+        }
+    }
+
+    /// Returns the resolved target if this is a dynamic file that is itself a symlink.
+    /// Embedded files always return `None`; symlinks are resolved at embed time.
+    pub fn symlink_target(&self) -> Option<PathBuf> {
+        let path = self.absolute_path()?;
+        let meta = std::fs::symlink_metadata(path).ok()?;
+        if meta.file_type().is_symlink() {
+            path.canonicalize().ok()
+        } else {
+            None
         }
     }
 
@@ -108,22 +797,154 @@ impl File {
         self.path().extension().and_then(|s| s.to_str())
     }
 
+    /// Returns the content type guessed from the file extension, if known.
+    #[cfg(feature = "mime")]
+    pub fn mime_type(&self) -> Option<&'static str> {
+        mime_guess::from_path(self.path()).first_raw()
+    }
+
+    /// Like [`File::mime_type`], but falls back to [`File::sniff_mime`] when the extension
+    /// is missing or unknown.
+    #[cfg(feature = "mime")]
+    pub fn mime_type_or_sniff(&self) -> Option<&'static str> {
+        self.mime_type().or_else(|| self.sniff_mime())
+    }
+
+    /// Guesses the content type from the leading bytes of the file.
+    ///
+    /// Recognizes PNG, JPEG, GIF, PDF, gzip and zip signatures, and reports `text/plain`
+    /// for valid UTF-8 without control characters. Dynamic files only read a bounded prefix.
+    /// Returns `None` for empty or unrecognized content, or if the file cannot be read.
+    pub fn sniff_mime(&self) -> Option<&'static str> {
+        const SNIFF_LEN: usize = 512;
+        match &self.inner {
+            FileKind::Embed(embed) => {
+                let contents = embed.inner.bytes();
+                sniff_bytes(&contents[..contents.len().min(SNIFF_LEN)])
+            }
+            FileKind::Memory(mem) => {
+                let contents = mem.bytes();
+                sniff_bytes(&contents[..contents.len().min(SNIFF_LEN)])
+            }
+            FileKind::Dynamic(_) => {
+                use std::io::Read;
+                let mut prefix = Vec::with_capacity(SNIFF_LEN);
+                self.reader()
+                    .ok()?
+                    .take(SNIFF_LEN as u64)
+                    .read_to_end(&mut prefix)
+                    .ok()?;
+                sniff_bytes(&prefix)
+            }
+        }
+    }
+
+    /// Returns the stable 64-bit [`ContentHasher`] hash of the contents.
+    ///
+    /// Embedded files return the hash computed at embed time; other files are hashed while
+    /// streaming their contents, so an embedded file and its on-disk original (with the same
+    /// transcoding) always agree.
+    pub fn content_hash(&self) -> Result<u64, Error> {
+        match &self.inner {
+            FileKind::Embed(embed) => Ok(embed.inner.content_hash),
+            FileKind::Memory(mem) => Ok(mem
+                .store
+                .entry(mem.index)
+                .hash
+                .unwrap_or_else(|| ContentHasher::hash(mem.bytes()))),
+            FileKind::Dynamic(_) => {
+                let mut hasher = ContentHasher::new();
+                std::io::copy(&mut self.reader()?, &mut hasher)?;
+                Ok(hasher.finish())
+            }
+        }
+    }
+
+    /// Returns the SHA-256 digest of the file contents.
+    #[cfg(feature = "hash")]
+    pub fn hash(&self) -> Result<[u8; 32], Error> {
+        use sha2::{Digest, Sha256};
+        match &self.inner {
+            FileKind::Embed(embed) => Ok(Sha256::digest(embed.inner.bytes()).into()),
+            FileKind::Memory(mem) => Ok(Sha256::digest(mem.bytes()).into()),
+            FileKind::Dynamic(_) => {
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut self.reader()?, &mut hasher)?;
+                Ok(hasher.finalize().into())
+            }
+        }
+    }
+
+    /// Builds the route manifest entry for this file under the given mount prefix.
+    fn route(&self, prefix: &str) -> Result<RouteEntry, Error> {
+        let path = self.path().to_string_lossy().into_owned();
+        let url = format!(
+            "{}/{}",
+            prefix.trim_end_matches('/'),
+            percent_encode_path(&path)
+        );
+        #[cfg(feature = "mime")]
+        let content_type = self.mime_type();
+        #[cfg(not(feature = "mime"))]
+        let content_type = None;
+        #[cfg(feature = "hash")]
+        let hash = Some(to_hex(&self.hash()?));
+        #[cfg(not(feature = "hash"))]
+        let hash = None;
+        Ok(RouteEntry {
+            url,
+            path,
+            size: self.meta()?.size,
+            content_type,
+            hash,
+        })
+    }
+
+    /// Returns the encoding the contents were transcoded from, or `None` if they are used as-is.
+    ///
+    /// Embedded files report what was detected at embed time. Dynamic files from a silo with
+    /// [`Silo::with_transcoding`] are inspected on each call, so dev mode reports the same.
+    pub fn original_encoding(&self) -> Option<&'static str> {
+        match &self.inner {
+            FileKind::Embed(embed) => embed.inner.original_encoding,
+            FileKind::Dynamic(dyn_file) => {
+                dyn_file.transcoding()?;
+                match transcode::decode(&dyn_file.read_raw().ok()?) {
+                    transcode::Decoded::Transcoded(_, encoding) => Some(encoding),
+                    _ => None,
+                }
+            }
+            FileKind::Memory(_) => None,
+        }
+    }
+
     /// Returns file metadata (size and modified time).
     ///
     /// For embedded files, this is compile-time metadata.
-    /// For dynamic files, this reads filesystem metadata.
+    /// For dynamic files, this reads filesystem metadata; with transcoding enabled, the size
+    /// is that of the transcoded contents.
     pub fn meta(&self) -> Result<FileMeta, Error> {
         match &self.inner {
             FileKind::Embed(embed) => Ok(FileMeta {
                 size: embed.inner.size,
                 modified: embed.inner.modified,
             }),
+            FileKind::Memory(mem) => Ok(FileMeta {
+                size: mem.bytes().len(),
+                modified: mem.store.entry(mem.index).modified,
+            }),
             FileKind::Dynamic(dyn_file) => {
                 let metadata = std::fs::metadata(dyn_file.absolute_path())?;
-                let len = metadata.len();
-                let size = usize::try_from(len).map_err(|_| {
-                    std::io::Error::new(std::io::ErrorKind::InvalidData, "file size overflows usize")
-                })?;
+                let size = if dyn_file.transcoding().is_some() {
+                    dyn_file.read()?.len()
+                } else {
+                    usize::try_from(metadata.len()).map_err(|_| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidData,
+                            "file size overflows usize",
+                        )
+                    })?
+                };
 
                 let mtime = metadata.modified()?;
                 let dur = mtime.duration_since(std::time::UNIX_EPOCH).map_err(|_| {
@@ -140,6 +961,76 @@ impl File {
             }
         }
     }
+
+    /// Records this file's size and modification time, for a later [`File::has_changed`].
+    ///
+    /// Embedded and in-memory files also record their content hash, which costs nothing;
+    /// use [`File::snapshot_with_hash`] to hash a dynamic file too. Metadata that cannot be
+    /// read is left out rather than failing the snapshot.
+    pub fn snapshot(&self) -> FileSnapshot {
+        let meta = self.meta().ok();
+        FileSnapshot {
+            size: meta.map(|meta| meta.size),
+            modified: meta.map(|meta| meta.modified),
+            hash: match self.inner {
+                FileKind::Dynamic(_) => None,
+                _ => self.content_hash().ok(),
+            },
+        }
+    }
+
+    /// Like [`File::snapshot`], but always records the content hash.
+    pub fn snapshot_with_hash(&self) -> Result<FileSnapshot, Error> {
+        Ok(FileSnapshot {
+            hash: Some(self.content_hash()?),
+            ..self.snapshot()
+        })
+    }
+
+    /// Compares the file against an earlier [`File::snapshot`] using metadata only; see
+    /// [`File::has_changed_with`].
+    pub fn has_changed(&self, prev: &FileSnapshot) -> Result<Changed, Error> {
+        self.has_changed_with(prev, ChangeOptions::default())
+    }
+
+    /// Compares the file against an earlier [`File::snapshot`].
+    ///
+    /// A size or modification time that differs is reported as [`Changed::Metadata`] without
+    /// reading the contents. The contents are hashed only when the metadata matches and
+    /// `options.verify_content` is set, or when either side has no metadata to compare; a hash
+    /// that differs from the snapshot's is [`Changed::Content`]. Without metadata and without
+    /// a snapshot hash there is nothing to compare, so the file is assumed to have changed.
+    pub fn has_changed_with(
+        &self,
+        prev: &FileSnapshot,
+        options: ChangeOptions,
+    ) -> Result<Changed, Error> {
+        let meta = match self.meta() {
+            Ok(meta) => Some(meta),
+            Err(Error::IoError { source }) if source.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::NotFound);
+            }
+            Err(_) => None,
+        };
+        let metadata_known = match (meta, prev.size, prev.modified) {
+            (Some(meta), Some(size), Some(modified)) => {
+                if meta.size != size || meta.modified != modified {
+                    return Ok(Changed::Metadata);
+                }
+                true
+            }
+            _ => false,
+        };
+        if metadata_known && !options.verify_content {
+            return Ok(Changed::No);
+        }
+        match prev.hash {
+            Some(hash) if self.content_hash()? != hash => Ok(Changed::Content),
+            Some(_) => Ok(Changed::No),
+            None if metadata_known => Ok(Changed::No),
+            None => Ok(Changed::Content),
+        }
+    }
 }
 
 /// Files are equal if their relative paths are equal.
@@ -149,6 +1040,38 @@ impl PartialEq for File {
     }
 }
 
+impl File {
+    /// Attaches a silo's dynamic read options to a dynamic file.
+    fn with_options(mut self, options: &DynOptions) -> Self {
+        if let FileKind::Dynamic(dyn_file) = &mut self.inner {
+            dyn_file.options.clone_from(options);
+        }
+        self
+    }
+
+    /// Gives a dynamic file its normalized relative path; key-based files keep the key they
+    /// were stored under.
+    fn with_normalized_key(mut self, keys: KeyNormalization) -> Self {
+        if let FileKind::Dynamic(dyn_file) = &mut self.inner
+            && let Cow::Owned(key) = keys.apply(&dyn_file.rel_path)
+        {
+            dyn_file.rel_path = Arc::from(key);
+        }
+        self
+    }
+
+    /// Hides a scope prefix of `len` bytes from the path of a key-based (embedded or
+    /// in-memory) file.
+    fn strip_prefix_len(mut self, len: usize) -> Self {
+        match &mut self.inner {
+            FileKind::Embed(embed) => embed.strip = len,
+            FileKind::Memory(mem) => mem.strip = len,
+            FileKind::Dynamic(_) => {}
+        }
+        self
+    }
+}
+
 /// Hashes a file by its relative path.
 impl Hash for File {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -158,57 +1081,365 @@ impl Hash for File {
 
 impl Eq for File {}
 
+/// Percent-encodes a relative path for use in a URL, keeping `/` separators intact.
+fn percent_encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
 
-
-/// Represents a set of embedded files and their root.
-#[derive(Debug, Clone)]
-struct EmbedSilo {
-    map: &'static phf::Map<&'static str, EmbedEntry>,
-    root: &'static str,
+/// Lowercase hex encoding of a byte slice.
+#[cfg(feature = "hash")]
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
-impl EmbedSilo {
-    /// Create a new EmbedSilo from a PHF map and root path.
-    pub const fn new(map: &'static phf::Map<&'static str, EmbedEntry>, root: &'static str) -> Self {
-        Self { map, root }
+/// Writes `contents` to a temporary sibling of `target` and renames it into place, carrying
+/// over `target`'s permissions. The temporary file is removed if any step fails.
+fn write_atomic(target: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let name = target.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+    })?;
+    let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let temp = target.with_file_name(format!(
+        ".{}.silo-{}-{n}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| {
+        let mut file = std::fs::File::create_new(&temp)?;
+        file.write_all(contents)?;
+        if let Ok(meta) = std::fs::metadata(target) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&temp, target)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
     }
+    result
+}
 
-    /// Get an embedded file by its relative path.
-    /// Returns None if not found.
-    pub fn get_file(&self, path: &str) -> Option<EmbedFile> {
-        self.map.get(path).map(|entry| EmbedFile { inner: entry })
+/// Collects route entries for the given files, sorted by path for stable output.
+fn collect_routes(
+    files: impl Iterator<Item = File>,
+    prefix: &str,
+) -> Result<Vec<RouteEntry>, Error> {
+    let mut routes = files
+        .map(|file| file.route(prefix))
+        .collect::<Result<Vec<_>, _>>()?;
+    routes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(routes)
+}
+
+/// Matches a content prefix against known magic-byte signatures.
+fn sniff_bytes(prefix: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"PK\x03\x04", "application/zip"),
+    ];
+    if prefix.is_empty() {
+        return None;
     }
+    if let Some((_, mime)) = SIGNATURES
+        .iter()
+        .find(|(magic, _)| prefix.starts_with(magic))
+    {
+        return Some(mime);
+    }
+    // The prefix may cut a multi-byte character in half; only the tail may be incomplete.
+    let text = match std::str::from_utf8(prefix) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&prefix[..err.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+    let is_text = text
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c'));
+    is_text.then_some("text/plain")
+}
 
-    /// Iterate over all embedded files in this silo.
-    pub fn iter(&self) -> impl Iterator<Item = File> + '_ {
-        self.map.values().map(|entry| File {
-            inner: FileKind::Embed(EmbedFile { inner: entry }),
-        })
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
     }
+    prev[b.len()]
 }
 
-/// Represents a file from the filesystem (not embedded).
-#[derive(Debug, Clone)]
-struct DynFile {
-    rel_path: Arc<str>,
-    full_path: Arc<str>,
+/// Ranks the paths of `files` by edit distance to `path`, keeping the closest `max`.
+fn suggest_paths(files: impl Iterator<Item = File>, path: &str, max: usize) -> Vec<String> {
+    let limit = (path.chars().count() / 2).max(2);
+    let mut candidates: Vec<(usize, String)> = files
+        .map(|file| file.path().to_string_lossy().into_owned())
+        .map(|candidate| (edit_distance(path, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .collect();
+    candidates.sort();
+    candidates.dedup_by(|a, b| a.1 == b.1);
+    candidates
+        .into_iter()
+        .take(max)
+        .map(|(_, candidate)| candidate)
+        .collect()
 }
 
-fn normalize_rel_path(path: &str) -> Arc<str> {
-    // Keep paths stable across platforms (Windows uses `\`).
-    Arc::from(path.replace('\\', "/"))
+/// Grouping key of [`Silo::by_extension`]: the lowercase extension, `""` if there is none.
+/// `embed_silo!` applies the same rule when generating the extension index.
+fn extension_key(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
 }
 
-impl DynFile {
-    /// root is the base directory where the file is located, and path is the relative path to the file.
-    /// Create a new DynFile from absolute and relative paths.
-    /// Both must be valid UTF-8.
-    pub fn new<S: AsRef<str>>(full_path: S, rel_path: S) -> Self {
-        Self {
-            rel_path: Arc::from(rel_path.as_ref()),
-            full_path: Arc::from(full_path.as_ref()),
-        }
-    }
+/// Iterator with a precounted lower size bound, see [`Silo::iter_with_hint`].
+struct HintedIter<I> {
+    inner: I,
+    remaining: usize,
+}
+
+impl<I: Iterator> Iterator for HintedIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining = self.remaining.saturating_sub(1);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, None)
+    }
+}
+
+/// Owning iterator over the files of a [`Silo`], returned by its `IntoIterator` impl.
+///
+/// It holds only `'static` or reference-counted state, so it can be moved into another
+/// thread or task without borrowing the silo.
+pub struct IntoIter {
+    files: Box<dyn Iterator<Item = File> + Send>,
+}
+
+impl Iterator for IntoIter {
+    type Item = File;
+
+    fn next(&mut self) -> Option<File> {
+        self.files.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.files.size_hint()
+    }
+}
+
+impl std::fmt::Debug for IntoIter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IntoIter").finish_non_exhaustive()
+    }
+}
+
+/// Yields the same files as [`Silo::iter`], consuming the silo.
+impl IntoIterator for Silo {
+    type Item = File;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let limits = self.dyn_options.walk_limits.unwrap_or_default();
+        let files: Box<dyn Iterator<Item = File> + Send> = match &self.inner {
+            InnerSilo::Embed(embd) => Box::new(embd.iter()),
+            InnerSilo::Static(dynm) => {
+                Box::new(dynm.iter(self.dyn_options.symlink_policy, limits, None))
+            }
+            InnerSilo::Dynamic(dynm) => {
+                Box::new(dynm.iter(self.dyn_options.symlink_policy, limits, None))
+            }
+            InnerSilo::Memory(store) => {
+                let store = store.clone();
+                Box::new((0..store.len()).map(move |index| File {
+                    requested: None,
+                    inner: FileKind::Memory(memory::MemFile {
+                        store: store.clone(),
+                        index,
+                        strip: 0,
+                    }),
+                }))
+            }
+            InnerSilo::Compact(index) => {
+                let files: Vec<File> = index.files().map(|(_, file)| file).collect();
+                Box::new(files.into_iter())
+            }
+        };
+        let aliases = self.listed_aliases();
+        let dyn_options = self.file_options().cloned();
+        let Silo { view, .. } = self;
+        let files = match &view.prefix {
+            Some(prefix) => {
+                let prefix = prefix.clone();
+                Box::new(files.filter(move |file| file.path_str().starts_with(&*prefix)))
+            }
+            None => files,
+        };
+        IntoIter {
+            files: Box::new(
+                files
+                    .filter_map(move |file| view.apply(file, dyn_options.as_ref()))
+                    .chain(aliases),
+            ),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Silo {
+    type Item = File;
+    type IntoIter = Box<dyn Iterator<Item = File> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Represents a set of embedded files and their root.
+#[derive(Debug, Clone)]
+struct EmbedSilo {
+    map: &'static phf::Map<&'static str, EmbedEntry>,
+    root: &'static str,
+    /// Keys grouped by [`extension_key`], generated by `embed_silo!`.
+    extensions: Option<&'static phf::Map<&'static str, &'static [&'static str]>>,
+    /// Stamped by `embed_silo!(..., build_id = true)`, see [`Silo::build_id`].
+    build_id: Option<[u8; 16]>,
+    /// Every key in sorted order, generated by `embed_silo!`, see [`Silo::has_any`].
+    sorted_keys: Option<&'static [&'static str]>,
+}
+
+impl EmbedSilo {
+    /// Create a new EmbedSilo from a PHF map and root path.
+    pub const fn new(map: &'static phf::Map<&'static str, EmbedEntry>, root: &'static str) -> Self {
+        Self {
+            map,
+            root,
+            extensions: None,
+            build_id: None,
+            sorted_keys: None,
+        }
+    }
+
+    /// Get an embedded file by its relative path.
+    /// Returns None if not found.
+    pub fn get_file(&self, path: &str) -> Option<EmbedFile> {
+        self.map.get(path).map(|entry| EmbedFile {
+            inner: entry,
+            strip: 0,
+        })
+    }
+
+    /// Lists the immediate children of `dir`, derived from the key set.
+    pub fn read_dir(&self, dir: &str) -> Vec<Entry> {
+        let files = self.map.entries().map(|(key, entry)| {
+            let file = File {
+                requested: None,
+                inner: FileKind::Embed(EmbedFile {
+                    inner: entry,
+                    strip: 0,
+                }),
+            };
+            (*key, file)
+        });
+        read_dir_from_keys(dir, files)
+    }
+
+    /// Iterate over all embedded files in this silo.
+    pub fn iter(&self) -> impl Iterator<Item = File> + use<> {
+        self.map.values().map(|entry| File {
+            requested: None,
+            inner: FileKind::Embed(EmbedFile {
+                inner: entry,
+                strip: 0,
+            }),
+        })
+    }
+}
+
+/// Represents a file from the filesystem (not embedded).
+#[derive(Debug, Clone)]
+struct DynFile {
+    rel_path: Arc<str>,
+    full_path: Arc<str>,
+    /// Read options of the silo this file came from.
+    options: DynOptions,
+}
+
+/// How a silo reads its dynamic files.
+#[derive(Debug, Clone, Default)]
+struct DynOptions {
+    /// Shared read table, see [`Silo::with_coalescing`].
+    coalescer: Option<Arc<coalesce::Coalescer>>,
+    /// Text normalization, see [`Silo::with_transcoding`].
+    transcoding: Option<Transcoding>,
+    /// Recent lookup misses, see [`Silo::with_negative_cache`].
+    negative_cache: Option<Arc<negative_cache::NegativeCache>>,
+    /// On-the-fly response compression, see [`Silo::with_dynamic_compression`].
+    #[cfg(feature = "compression")]
+    compression: Option<CompressionConfig>,
+    /// Bounds on directory walks, see [`Silo::with_walk_limits`].
+    walk_limits: Option<WalkLimits>,
+    /// See [`Silo::with_symlink_policy`].
+    symlink_policy: SymlinkPolicy,
+}
+
+impl DynOptions {
+    const NONE: Self = Self {
+        coalescer: None,
+        transcoding: None,
+        negative_cache: None,
+        walk_limits: None,
+        symlink_policy: SymlinkPolicy::AllowWithinRoot,
+        #[cfg(feature = "compression")]
+        compression: None,
+    };
+}
+
+fn normalize_rel_path(path: &str) -> Arc<str> {
+    // Keep paths stable across platforms (Windows uses `\`).
+    Arc::from(path.replace('\\', "/"))
+}
+
+impl DynFile {
+    /// root is the base directory where the file is located, and path is the relative path to the file.
+    /// Create a new DynFile from absolute and relative paths.
+    /// Both must be valid UTF-8.
+    pub fn new<S: AsRef<str>>(full_path: S, rel_path: S) -> Self {
+        Self {
+            rel_path: Arc::from(rel_path.as_ref()),
+            full_path: Arc::from(full_path.as_ref()),
+            options: DynOptions::NONE,
+        }
+    }
 
     /// Returns the relative path of the file.
     pub fn path(&self) -> &Path {
@@ -219,43 +1450,328 @@ impl DynFile {
     pub fn absolute_path(&self) -> &Path {
         Path::new(&*self.full_path)
     }
+
+    /// Returns the transcoding settings if they apply to this file.
+    fn transcoding(&self) -> Option<&Transcoding> {
+        self.options
+            .transcoding
+            .as_ref()
+            .filter(|t| t.applies_to(self.path()))
+    }
+
+    /// Reads the bytes on disk, sharing an in-flight read if coalescing is enabled.
+    fn read_raw(&self) -> Result<Vec<u8>, Error> {
+        match &self.options.coalescer {
+            Some(coalescer) => Ok(coalescer.read(self.absolute_path())?.to_vec()),
+            None => Ok(std::fs::read(self.absolute_path())?),
+        }
+    }
+
+    /// Reads the contents, transcoded to UTF-8 if transcoding applies.
+    fn read(&self) -> Result<Vec<u8>, Error> {
+        self.decode(self.read_raw()?)
+    }
+
+    /// One attempt of [`File::contents_consistent`]: reads the file between two stats and
+    /// returns `None` if its size or mtime moved, or the bytes read disagree with the size.
+    /// Bypasses the coalescer, whose shared read may have started before the first stat.
+    fn read_consistent(&self) -> Result<Option<Vec<u8>>, Error> {
+        let stamp = |meta: std::fs::Metadata| (meta.len(), meta.modified().ok());
+        let before = stamp(std::fs::metadata(self.absolute_path())?);
+        let raw = std::fs::read(self.absolute_path())?;
+        let after = stamp(std::fs::metadata(self.absolute_path())?);
+        if before != after || raw.len() as u64 != before.0 {
+            return Ok(None);
+        }
+        self.decode(raw).map(Some)
+    }
+
+    /// Applies transcoding, if configured, to bytes read from disk.
+    fn decode(&self, raw: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let Some(transcoding) = self.transcoding() else {
+            return Ok(raw);
+        };
+        match transcode::decode(&raw) {
+            transcode::Decoded::Utf8 => Ok(raw),
+            transcode::Decoded::Transcoded(bytes, _) => Ok(bytes),
+            transcode::Decoded::Unknown => match transcoding.on_failure {
+                TranscodeFailure::Skip => Ok(raw),
+                TranscodeFailure::Error => Err(Error::TranscodeError {
+                    path: self.rel_path.to_string(),
+                }),
+            },
+        }
+    }
 }
 
 /// Get a dynamic file by its relative path. Returns None if not found or not a file.
-fn get_file_for_root(root: &str, path: &str) -> Option<DynFile> {
-    // Security note: this performs a canonicalized path-prefix check to prevent `..` traversal
-    // and symlink escapes when looking up filesystem-backed files.
+#[deny(clippy::indexing_slicing)]
+fn get_file_for_root(root: &str, path: &str, symlinks: SymlinkPolicy) -> Option<DynFile> {
+    // Security note: `..` is resolved lexically and must stay under the canonical root, and
+    // the path actually opened is then checked against the symlink policy, which by default
+    // rejects symlink escapes.
     let root_canon = Path::new(root).canonicalize().ok()?;
     let normalized_rel = normalize_rel_path(path);
-    let joined = root_canon.join(normalized_rel.as_ref());
+    let lexical = lexical_normalize(&root_canon.join(normalized_rel.as_ref()));
+    if !lexical.starts_with(&root_canon) {
+        return None;
+    }
 
-    let candidate = joined.canonicalize().ok()?;
-    if !candidate.starts_with(&root_canon) {
+    let candidate = lexical.canonicalize().ok()?;
+    if !symlinks.permits(&root_canon, &lexical, &candidate) {
         return None;
     }
     if !candidate.is_file() {
         return None;
     }
 
-    let rel_path = candidate
+    // Report the path as requested (with `.`/`..` resolved lexically) rather than the
+    // canonical target, so a symlinked file keeps its own name and identity.
+    let rel_path = lexical
         .strip_prefix(&root_canon)
         .ok()?
         .to_str()?
         .replace('\\', "/");
-    let full_path = candidate.to_str()?;
+    let full_path = lexical.to_str()?;
     Some(DynFile::new(Arc::from(full_path), Arc::from(rel_path)))
 }
 
+/// Windows device names, reserved in every directory and with any extension.
+const RESERVED_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Returns `true` for a lookup path that must never resolve. Paths with a UNC or device
+/// prefix (`\\server\share`, `//?/C:`) are rejected on every platform. On Windows, drive
+/// paths (`C:\x`, `c:x`) and components naming a reserved device (`CON`, `nul.txt`) are
+/// rejected too, as they would reach another drive or the device rather than a file; on
+/// other platforms they are ordinary names (`a:b.txt`), listed and found like any other.
+#[deny(clippy::indexing_slicing)]
+pub(crate) fn is_rejected_lookup(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let drive = matches!(bytes, [letter, b':', ..] if letter.is_ascii_alphabetic());
+    let unc = matches!(bytes, [b'/' | b'\\', b'/' | b'\\', ..]);
+    unc || (cfg!(windows) && (drive || path.split(['/', '\\']).any(is_reserved_device_name)))
+}
+
+/// Windows ignores the extension and trailing spaces when matching device names.
+fn is_reserved_device_name(component: &str) -> bool {
+    let stem = component
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches(' ');
+    RESERVED_DEVICE_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(stem))
+}
+
+/// Resolves `.` and `..` components without touching the filesystem.
+#[deny(clippy::indexing_slicing)]
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Returns `true` if a walked symlink resolves to a regular file inside the root.
+pub(crate) fn is_contained_symlink_file(path: &Path, root: &Path) -> bool {
+    let Ok(root_canon) = root.canonicalize() else {
+        return false;
+    };
+    path.canonicalize()
+        .is_ok_and(|target| target.starts_with(&root_canon) && target.is_file())
+}
+
+/// Lists the immediate children of `dir` inside a filesystem root, sorted by path.
+/// Returns an empty list if `dir` does not exist or escapes the root.
+fn read_dir_for_root(root: &str, dir: &str, symlinks: SymlinkPolicy) -> Vec<Entry> {
+    let Ok(root_canon) = Path::new(root).canonicalize() else {
+        return Vec::new();
+    };
+    let prefix = normalize_rel_path(dir.trim_matches('/'));
+    let dir_path = lexical_normalize(&root_canon.join(prefix.as_ref()));
+    let contained = dir_path
+        .canonicalize()
+        .is_ok_and(|canon| symlinks.permits(&root_canon, &dir_path, &canon) && canon.is_dir());
+    if !contained || !dir_path.starts_with(&root_canon) {
+        return Vec::new();
+    }
+    let Ok(read_dir) = std::fs::read_dir(&dir_path) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<Entry> = read_dir
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            let rel = normalize_rel_path(path.strip_prefix(&root_canon).ok()?.to_str()?);
+            let file_type = entry.file_type().ok()?;
+            if file_type.is_symlink() && !symlinks.permits_symlink_file(&path, &root_canon) {
+                return None;
+            }
+            if file_type.is_dir() {
+                Some(Entry::Dir(rel.to_string()))
+            } else if path.is_file() {
+                Some(Entry::File(File {
+                    requested: None,
+                    inner: FileKind::Dynamic(DynFile::new(Arc::from(path.to_str()?), rel)),
+                }))
+            } else {
+                None
+            }
+        })
+        .collect();
+    sort_entries(&mut entries);
+    entries
+}
+
+/// Lists the immediate children of `dir` from `(key, file)` pairs of a flat key set.
+fn read_dir_from_keys<'a>(dir: &str, files: impl Iterator<Item = (&'a str, File)>) -> Vec<Entry> {
+    let dir = dir.trim_matches('/');
+    let mut dirs = std::collections::BTreeSet::new();
+    let mut entries = Vec::new();
+    for (key, file) in files {
+        let rest = if dir.is_empty() {
+            key
+        } else {
+            match key.strip_prefix(dir).and_then(|r| r.strip_prefix('/')) {
+                Some(rest) => rest,
+                None => continue,
+            }
+        };
+        match rest.split_once('/') {
+            Some((child, _)) if dir.is_empty() => {
+                dirs.insert(child.to_owned());
+            }
+            Some((child, _)) => {
+                dirs.insert(format!("{dir}/{child}"));
+            }
+            None => entries.push(Entry::File(file)),
+        }
+    }
+    entries.extend(dirs.into_iter().map(Entry::Dir));
+    sort_entries(&mut entries);
+    entries
+}
+
+/// Yields `(key, file)` pairs for every file in an in-memory store.
+fn memory_files(store: &Arc<memory::MemStore>) -> impl Iterator<Item = (&str, File)> + '_ {
+    memory_files_from(store, 0)
+}
+
+/// Yields `(key, file)` pairs for the files of an in-memory store from index `start` on.
+fn memory_files_from(
+    store: &Arc<memory::MemStore>,
+    start: usize,
+) -> impl Iterator<Item = (&str, File)> + '_ {
+    (start..store.len()).map(move |index| {
+        let file = File {
+            requested: None,
+            inner: FileKind::Memory(memory::MemFile {
+                store: store.clone(),
+                index,
+                strip: 0,
+            }),
+        };
+        (&*store.entry(index).path, file)
+    })
+}
+
+/// Opens a dynamic file and reads its first page to pull it into the OS cache.
+fn prewarm_file(file: &File) -> Result<(), Error> {
+    use std::io::Read;
+    const HEADER_LEN: u64 = 4096;
+    let mut header = Vec::with_capacity(HEADER_LEN as usize);
+    file.reader()?.take(HEADER_LEN).read_to_end(&mut header)?;
+    Ok(())
+}
+
+/// Sorts directory entries by relative path.
+fn sort_entries(entries: &mut [Entry]) {
+    fn key(entry: &Entry) -> std::borrow::Cow<'_, str> {
+        match entry {
+            Entry::File(file) => file.path().to_string_lossy(),
+            Entry::Dir(dir) => std::borrow::Cow::Borrowed(dir.as_str()),
+        }
+    }
+    entries.sort_by(|a, b| key(a).cmp(&key(b)));
+}
+
 /// Iterate over all files in the dynamic silo.
-fn iter_root(root: &str) -> impl Iterator<Item = File> {
+fn iter_root(
+    root: &str,
+    symlinks: SymlinkPolicy,
+    limits: WalkLimits,
+    breach: Option<Breach>,
+) -> impl Iterator<Item = File> + use<> {
     let root_path = PathBuf::from(root);
-    walkdir::WalkDir::new(&root_path)
+    // `min_depth(1)`: a root that is itself a file is not a file *in* the silo.
+    let mut walker = walkdir::WalkDir::new(&root_path)
+        .min_depth(1)
+        .follow_links(symlinks == SymlinkPolicy::AllowAll);
+    if let Some(depth) = limits.max_depth {
+        // One level more than allowed, so that going over the limit can be noticed.
+        walker = walker.max_depth(depth.saturating_add(1));
+    }
+    let started = std::time::Instant::now();
+    let mut walked = 0usize;
+    let unlimited = limits.is_unlimited();
+    let exceeded = move |which: LimitKind, limit: u64| {
+        if let Some(breach) = &breach {
+            let _ = breach.set((which, limit));
+        }
+        None
+    };
+    walker
         .into_iter()
-        .filter_map(move |entry| {
-            let entry = entry.ok()?;
+        .map_while(move |entry| {
+            if unlimited {
+                return Some(entry.ok());
+            }
+            if let Some(max) = limits.max_duration
+                && started.elapsed() > max
+            {
+                return exceeded(
+                    LimitKind::Duration,
+                    u64::try_from(max.as_millis()).unwrap_or(u64::MAX),
+                );
+            }
+            let Ok(entry) = entry else {
+                return Some(None);
+            };
+            if let Some(max) = limits.max_depth
+                && entry.depth() > max
+            {
+                return exceeded(LimitKind::Depth, max as u64);
+            }
             if entry.file_type().is_file() {
+                walked += 1;
+                if let Some(max) = limits.max_files
+                    && walked > max
+                {
+                    return exceeded(LimitKind::Files, max as u64);
+                }
+            }
+            Some(Some(entry))
+        })
+        .flatten()
+        .filter_map(move |entry| {
+            let is_file = entry.file_type().is_file()
+                || (entry.path_is_symlink()
+                    && symlinks.permits_symlink_file(entry.path(), &root_path));
+            if is_file {
                 let relative_path = entry.path().strip_prefix(&root_path).ok()?;
                 Some(File {
+                    requested: None,
                     inner: FileKind::Dynamic(DynFile::new(
                         Arc::from(entry.path().to_str()?),
                         normalize_rel_path(relative_path.to_str()?),
@@ -280,16 +1796,15 @@ impl DynamicSilo {
         Self { root: Arc::from(root) }
     }
 
-    /// Gets a dynamic file by its relative path.
-    /// Returns `None` if the file is not found or is not a valid file.
-    pub fn get_file(&self, path: &str) -> Option<DynFile> {
-        get_file_for_root(self.root.as_ref(), path)
-    }
-
     /// Iterates over all files in the dynamic silo.
     /// Returns an iterator of `File` objects representing the files.
-    pub fn iter(&self) -> impl Iterator<Item = File> {
-        iter_root(self.root.as_ref())
+    pub fn iter(
+        &self,
+        symlinks: SymlinkPolicy,
+        limits: WalkLimits,
+        breach: Option<Breach>,
+    ) -> impl Iterator<Item = File> + use<> {
+        iter_root(self.root.as_ref(), symlinks, limits, breach)
     }
 }
 
@@ -306,16 +1821,15 @@ impl StaticSilo {
         Self { root }
     }
 
-    /// Gets a static file by its relative path.
-    /// Returns `None` if the file is not found or is not a valid file.
-    pub fn get_file(&self, path: &str) -> Option<DynFile> {
-        get_file_for_root(self.root, path)
-    }
-
     /// Iterates over all files in the static silo.
     /// Returns an iterator of `File` objects representing the files.
-    pub fn iter(&self) -> impl Iterator<Item = File> {
-        iter_root(self.root)
+    pub fn iter(
+        &self,
+        symlinks: SymlinkPolicy,
+        limits: WalkLimits,
+        breach: Option<Breach>,
+    ) -> impl Iterator<Item = File> + use<> {
+        iter_root(self.root, symlinks, limits, breach)
     }
 }
 
@@ -325,14 +1839,183 @@ enum InnerSilo {
     Embed(EmbedSilo),
     Static(StaticSilo),
     Dynamic(DynamicSilo),
+    Memory(Arc<memory::MemStore>),
+    /// The resolved override view of a [`SiloSet`], see [`SiloSet::compact`].
+    Compact(Arc<compact::CompactIndex>),
 }
 
 /// Represents a root directory, which may be embedded or dynamic.
 #[derive(Debug, Clone)]
 pub struct Silo {
     inner: InnerSilo,
+    /// Index filename used to resolve directory-style lookups, if configured.
+    index: Option<Arc<str>>,
+    /// Diagnostic name of this silo when used as a layer in a [`SiloSet`].
+    name: Option<Arc<str>>,
+    /// How dynamic files are read (coalescing, transcoding).
+    dyn_options: DynOptions,
+    /// Scope and filter applied on top of the backend.
+    view: View,
+}
+
+/// Predicate deciding which files a filtered silo exposes, see [`Silo::filtered`].
+type FileFilter = dyn Fn(&File) -> bool + Send + Sync;
+
+/// Scope and filter applied on top of a silo's backend, see [`Silo::scope`] and [`Silo::filtered`].
+#[derive(Clone)]
+struct View {
+    /// Key prefix (ending in `/`) for embedded and in-memory backends.
+    /// Filesystem backends are re-rooted at the subdirectory instead.
+    prefix: Option<Arc<str>>,
+    filter: Option<Arc<FileFilter>>,
+    /// Backend keys of files removed by [`Silo::retain_files`].
+    excluded: Option<Arc<std::collections::HashSet<Box<str>>>>,
+    aliases: Option<Arc<Aliases>>,
+    /// See [`Silo::with_key_normalization`].
+    keys: KeyNormalization,
+    /// See [`Silo::with_headers_for`].
+    headers: Option<Arc<header_profile::HeaderRules>>,
+    /// See [`Silo::with_not_found_page`] and [`Silo::with_error_page`].
+    pages: Option<Arc<serve::ErrorPages>>,
+}
+
+/// Alias table installed by [`Silo::with_aliases`], keyed by alias path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Aliases {
+    map: std::collections::HashMap<Box<str>, Box<str>>,
+    options: AliasOptions,
+}
+
+/// How a silo treats its aliases, see [`Silo::with_alias_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AliasOptions {
+    /// Also yield each alias from [`Silo::iter`], as its target file with
+    /// [`File::requested_path`] set to the alias.
+    pub include_in_iter: bool,
+    /// Make [`Silo::serve`] answer requests for an alias with `301 Moved Permanently`
+    /// pointing at the target instead of serving the file.
+    pub redirect: bool,
+}
+
+impl View {
+    const NONE: Self = Self {
+        prefix: None,
+        filter: None,
+        excluded: None,
+        aliases: None,
+        keys: KeyNormalization::None,
+        headers: None,
+        pages: None,
+    };
+
+    /// Aliases of a view narrowed to `prefix` (ending in `/`): only those whose alias and
+    /// target both lie under it survive, relative to it.
+    fn rebased_aliases(&self, prefix: &str) -> Option<Arc<Aliases>> {
+        let aliases = self.aliases.as_ref()?;
+        let map = aliases
+            .map
+            .iter()
+            .filter_map(|(alias, target)| {
+                Some((
+                    Box::from(alias.strip_prefix(prefix)?),
+                    Box::from(target.strip_prefix(prefix)?),
+                ))
+            })
+            .collect();
+        Some(Arc::new(Aliases {
+            map,
+            options: aliases.options.clone(),
+        }))
+    }
+
+    /// Exclusions for a filesystem backend re-rooted at `prefix` (ending in `/`, or empty).
+    fn rebased_exclusions(&self, prefix: &str) -> Option<Arc<std::collections::HashSet<Box<str>>>> {
+        let excluded = self.excluded.as_ref()?;
+        Some(Arc::new(
+            excluded
+                .iter()
+                .filter_map(|key| key.strip_prefix(prefix))
+                .map(Box::from)
+                .collect(),
+        ))
+    }
+
+    /// Applies read options (unless the backend's files carry their own), prefix stripping,
+    /// exclusions and the filter to a file from the backend.
+    fn apply(&self, file: File, options: Option<&DynOptions>) -> Option<File> {
+        let file = file.with_normalized_key(self.keys);
+        if self
+            .excluded
+            .as_ref()
+            .is_some_and(|excluded| excluded.contains(file.backend_key()))
+        {
+            return None;
+        }
+        let mut file = match options {
+            Some(options) => file.with_options(options),
+            None => file,
+        };
+        if let Some(prefix) = &self.prefix {
+            file = file.strip_prefix_len(prefix.len());
+        }
+        match &self.filter {
+            Some(filter) if !filter(&file) => None,
+            _ => Some(file),
+        }
+    }
+}
+
+impl std::fmt::Debug for View {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("View")
+            .field("prefix", &self.prefix)
+            .field("filtered", &self.filter.is_some())
+            .field(
+                "excluded",
+                &self.excluded.as_ref().map_or(0, |excluded| excluded.len()),
+            )
+            .field(
+                "aliases",
+                &self.aliases.as_ref().map_or(0, |aliases| aliases.map.len()),
+            )
+            .field("keys", &self.keys)
+            .finish()
+    }
 }
 
+/// Silos are equal if they read the same files the same way: the same embedded map (by
+/// identity), the same filesystem root, or the same in-memory store, with the same scope,
+/// filter (by identity), key normalization, header rules, error pages, index, transcoding,
+/// symlink policy and walk limits. Layer names and coalescing are ignored.
+impl PartialEq for Silo {
+    fn eq(&self, other: &Self) -> bool {
+        let same_backend = match (&self.inner, &other.inner) {
+            (InnerSilo::Embed(a), InnerSilo::Embed(b)) => std::ptr::eq(a.map, b.map),
+            (InnerSilo::Memory(a), InnerSilo::Memory(b)) => Arc::ptr_eq(a, b),
+            (InnerSilo::Compact(a), InnerSilo::Compact(b)) => Arc::ptr_eq(a, b),
+            _ => self.dynamic_root().is_some() && self.dynamic_root() == other.dynamic_root(),
+        };
+        let same_filter = match (&self.view.filter, &other.view.filter) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        same_backend
+            && same_filter
+            && self.view.prefix == other.view.prefix
+            && self.view.excluded == other.view.excluded
+            && self.view.aliases == other.view.aliases
+            && self.view.keys == other.view.keys
+            && self.view.headers == other.view.headers
+            && self.view.pages == other.view.pages
+            && self.index == other.index
+            && self.dyn_options.transcoding == other.dyn_options.transcoding
+            && self.dyn_options.symlink_policy == other.dyn_options.symlink_policy
+            && self.dyn_options.walk_limits == other.dyn_options.walk_limits
+    }
+}
+
+impl Eq for Silo {}
+
 impl Silo {
 
     #[doc(hidden)]
@@ -340,7 +2023,44 @@ impl Silo {
     pub const fn from_embedded(phf_map: &'static phf::Map<&'static str, EmbedEntry>, root: &'static str) -> Self {
         Self {
             inner: InnerSilo::Embed(EmbedSilo::new(phf_map, root)),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Creates a Silo from an embedded PHF map with a precomputed extension index (keys
+    /// grouped by lowercase extension, `""` for none), see [`Silo::by_extension`].
+    pub const fn from_embedded_with_extensions(
+        phf_map: &'static phf::Map<&'static str, EmbedEntry>,
+        extensions: &'static phf::Map<&'static str, &'static [&'static str]>,
+        root: &'static str,
+    ) -> Self {
+        Self {
+            inner: InnerSilo::Embed(EmbedSilo {
+                map: phf_map,
+                root,
+                extensions: Some(extensions),
+                build_id: None,
+                sorted_keys: None,
+            }),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
+        }
+    }
+
+    #[doc(hidden)]
+    /// Attaches every key of an embedded silo in sorted order, so [`Silo::has_any`] probes
+    /// instead of scanning; used by `embed_silo!`.
+    pub const fn with_sorted_keys(mut self, keys: &'static [&'static str]) -> Self {
+        if let InnerSilo::Embed(embed) = &mut self.inner {
+            embed.sorted_keys = Some(keys);
         }
+        self
     }
 
     #[doc(hidden)]
@@ -348,6 +2068,10 @@ impl Silo {
     pub const fn from_static(path: &'static str) -> Self {
         Self {
             inner: InnerSilo::Static(StaticSilo::new(path)),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
         }
     }
 
@@ -355,27 +2079,60 @@ impl Silo {
     pub fn new(path: &str) -> Self {
         Self {
             inner: InnerSilo::Dynamic(DynamicSilo::new(path)),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
         }
     }
 
+    /// Creates a dynamic Silo from a UTF-8 path.
+    #[cfg(feature = "camino")]
+    pub fn from_utf8_path(path: &camino::Utf8Path) -> Self {
+        Self::new(path.as_str())
+    }
+
     /// Converts the Silo to a dynamic Silo if it is currently embedded.
-    /// Returns `self` unchanged if the Silo is already dynamic or static.
-    pub fn into_dynamic(self) -> Self {
-        match self.inner {
-            InnerSilo::Embed(emb_silo) => Self::from_static(emb_silo.root),
-            InnerSilo::Static(_) => self,
-            InnerSilo::Dynamic(_) => self,
+    /// Returns `self` unchanged if the Silo is already dynamic or static. Fails with
+    /// [`Error::RootRedacted`] if it was embedded with `redact_root = true` and has no source
+    /// directory to read from.
+    pub fn into_dynamic(self) -> Result<Self, Error> {
+        match &self.inner {
+            InnerSilo::Embed(emb_silo) if emb_silo.root.is_empty() => Err(Error::RootRedacted),
+            InnerSilo::Embed(emb_silo) => {
+                let inner = match &self.view.prefix {
+                    None => InnerSilo::Static(StaticSilo::new(emb_silo.root)),
+                    Some(prefix) => InnerSilo::Dynamic(DynamicSilo::new(&format!(
+                        "{}/{}",
+                        emb_silo.root, prefix
+                    ))),
+                };
+                let view = View {
+                    prefix: None,
+                    excluded: self
+                        .view
+                        .rebased_exclusions(self.view.prefix.as_deref().unwrap_or_default()),
+                    ..self.view.clone()
+                };
+                Ok(Self {
+                    inner,
+                    view,
+                    ..self
+                })
+            }
+            _ => Ok(self),
         }
     }
 
-    /// Automatically converts the Silo to a dynamic directory in debug mode (`cfg!(debug_assertions)`).
+    /// Automatically converts the Silo to a dynamic directory in debug mode (`cfg!(debug_assertions)`)
+    /// with [`Silo::into_dynamic`], including its [`Error::RootRedacted`] failure.
     /// In release mode, returns `self` unchanged.
     /// This is a no-op if the Silo is not embedded.
-    pub fn auto_dynamic(self) -> Self {
+    pub fn auto_dynamic(self) -> Result<Self, Error> {
         if cfg!(debug_assertions) {
             self.into_dynamic()
         } else {
-            self
+            Ok(self)
         }
     }
 
@@ -389,85 +2146,1337 @@ impl Silo {
         matches!(self.inner, InnerSilo::Embed(_))
     }
 
-    /// Gets a file by its relative path from this Silo.
-    /// Returns `None` if the file is not found.
-    pub fn get_file(&self, path: &str) -> Option<File> {
+    /// Returns the underlying PHF map of an embedded silo, keyed by relative path, or
+    /// `None` for other silos. This is an escape hatch for batch operations and custom
+    /// indexes over the embedded entries; prefer [`Silo::get_file`] and [`Silo::iter`].
+    pub fn embedded_map(&self) -> Option<&'static phf::Map<&'static str, EmbedEntry>> {
         match &self.inner {
-            InnerSilo::Embed(embed) => embed.get_file(path).map(|f| File {
-                inner: FileKind::Embed(f),
-            }),
-            InnerSilo::Static(dyn_silo) => dyn_silo.get_file(path).map(|f| File {
-                inner: FileKind::Dynamic(f),
-            }),
-            InnerSilo::Dynamic(dyn_silo) => dyn_silo.get_file(path).map(|f| File {
-                inner: FileKind::Dynamic(f),
-            }),
+            InnerSilo::Embed(embed) => Some(embed.map),
+            _ => None,
         }
     }
 
-    /// Iterates over all files in this Silo.
-    /// Returns a boxed iterator of `File` objects representing the files.
-    pub fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
-        match &self.inner {
-            InnerSilo::Embed(embd) => Box::new(embd.iter()),
-            InnerSilo::Static(dynm) => Box::new(dynm.iter()),
-            InnerSilo::Dynamic(dynm) => Box::new(dynm.iter()),
-        }
+    /// Escape hatch returning the embedded PHF map, or `None` for non-embedded silos.
+    /// Same as [`Silo::embedded_map`], with the lifetime tied to the silo.
+    pub fn as_embed_ref(&self) -> Option<&phf::Map<&'static str, EmbedEntry>> {
+        self.embedded_map()
     }
-}
-
 
+    /// Configures an index filename so directory-style lookups resolve transparently,
+    /// like a web root: `get_file("docs/")` or `get_file("docs")` returns `docs/index.html`.
+    ///
+    /// The index is only consulted when an exact file match fails and the path denotes a
+    /// directory, i.e. it is empty, ends with `/`, or contains the index file.
+    pub fn with_index(self, name: &str) -> Self {
+        Self {
+            index: Some(Arc::from(name)),
+            ..self
+        }
+    }
 
-/// Represents a set of root directories, supporting overlay and override semantics.
-/// Later directories in the set can override files from earlier ones with the same relative path.
-#[derive(Debug, Clone)]
-pub struct SiloSet {
-    /// The list of root directories, in order of increasing precedence.
-    pub silos: Vec<Silo>,
-}
+    /// Shorthand for [`Silo::with_index`] with `index.html`.
+    pub fn with_default_index(self) -> Self {
+        self.with_index("index.html")
+    }
 
-impl SiloSet {
-    /// Creates a new SiloSet from the given list of directories.
-    /// The order of directories determines override precedence.
-    /// Create a new SiloSet from a list of Silos. Order determines override precedence.
-    pub fn new(dirs: Vec<Silo>) -> Self {
-        Self { silos: dirs }
+    /// Names this silo, so a [`SiloSet`] can report it as the layer that provided a file.
+    pub fn with_name(self, name: &str) -> Self {
+        Self {
+            name: Some(Arc::from(name)),
+            ..self
+        }
     }
 
+    /// Enables read coalescing for dynamic files: concurrent [`File::contents`] or
+    /// [`File::shared_contents`] calls for the same path share one in-flight read, so a burst
+    /// of requests for a cold file opens it once. Completed reads are evicted immediately;
+    /// this is not a cache. Embedded silos are unaffected.
+    pub fn with_coalescing(self) -> Self {
+        self.with_coalescing_reader(|path| std::fs::read(path))
+    }
 
-    /// Returns the file with the given name, searching roots in reverse order.
-    /// Files in later roots override those in earlier roots if the relative path matches.
-    /// Get a file by name, searching Silos in reverse order (highest precedence first).
-    pub fn get_file(&self, name: &str) -> Option<File> {
-        for silo in self.silos.iter().rev() {
-            if let Some(file) = silo.get_file(name) {
-                return Some(file);
-            }
+    /// Like [`Silo::with_coalescing`], but loads files with `read` instead of [`std::fs::read`],
+    /// e.g. to instrument or throttle disk access.
+    pub fn with_coalescing_reader(
+        self,
+        read: impl Fn(&Path) -> std::io::Result<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        let coalescer = Some(Arc::new(coalesce::Coalescer::new(Box::new(read))));
+        Self {
+            dyn_options: DynOptions {
+                coalescer,
+                ..self.dyn_options.clone()
+            },
+            ..self
         }
-        None
     }
 
-    /// Recursively walks all files in all root directories.
-    /// Files with the same relative path from different roots are all included.
-    /// Iterate all files in all Silos, including duplicates.
-    pub fn iter(&self) -> impl Iterator<Item = File> + '_ {
-        self.silos.iter().rev().flat_map(|silo| silo.iter())
+    /// Remembers up to `capacity` lookups of missing files for `ttl`, so repeated probes for
+    /// nonexistent paths (e.g. scanners hitting a public server) are answered without a
+    /// filesystem call. Once the oldest miss is evicted or expires, the path is checked on disk
+    /// again, so files created later become visible within `ttl`. Clones share the cache.
+    /// Embedded and in-memory silos are unaffected.
+    pub fn with_negative_cache(self, capacity: usize, ttl: std::time::Duration) -> Self {
+        let negative_cache = Some(Arc::new(negative_cache::NegativeCache::new(capacity, ttl)));
+        Self {
+            dyn_options: DynOptions {
+                negative_cache,
+                ..self.dyn_options.clone()
+            },
+            ..self
+        }
     }
 
-    /// Recursively walks all files, yielding only the highest-precedence file for each relative path.
-    /// This implements the override behaviour: later roots take precedence over earlier ones.
-    /// Iterate all files, yielding only the highest-precedence file for each path.
-    pub fn iter_override(&self) -> impl Iterator<Item = File> + '_ {
-        let mut history = std::collections::HashSet::new();
-        self.iter().filter(move |file| history.insert(file.clone()))
+    /// Forgets all misses recorded by [`Silo::with_negative_cache`], e.g. after writing files.
+    pub fn clear_negative_cache(&self) {
+        if let Some(cache) = &self.dyn_options.negative_cache {
+            cache.clear();
+        }
     }
-}
-
+
+    /// Transcodes dynamic text files to UTF-8 when they are read, matching what
+    /// `embed_silo!(..., transcode = "utf8")` does at embed time. Files whose extension is
+    /// not listed pass through untouched. Embedded silos are unaffected.
+    pub fn with_transcoding(self, transcoding: Transcoding) -> Self {
+        Self {
+            dyn_options: DynOptions {
+                transcoding: Some(transcoding),
+                ..self.dyn_options.clone()
+            },
+            ..self
+        }
+    }
+
+    #[doc(hidden)]
+    /// Creates a static-path Silo that transcodes text files; used by `embed_silo!`.
+    pub const fn from_static_transcoded(path: &'static str, transcoding: Transcoding) -> Self {
+        Self {
+            inner: InnerSilo::Static(StaticSilo::new(path)),
+            index: None,
+            name: None,
+            dyn_options: DynOptions {
+                coalescer: None,
+                transcoding: Some(transcoding),
+                negative_cache: None,
+                walk_limits: None,
+                symlink_policy: SymlinkPolicy::AllowWithinRoot,
+                #[cfg(feature = "compression")]
+                compression: None,
+            },
+            view: View::NONE,
+        }
+    }
+
+    /// Returns the name given with [`Silo::with_name`], if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Gets a file by its relative path from this Silo.
+    /// Returns `None` if the file is not found.
+    ///
+    /// If an index is configured (see [`Silo::with_index`]) and the path denotes a directory,
+    /// the directory's index file is returned instead. Aliases (see [`Silo::with_aliases`])
+    /// are resolved first.
+    #[deny(clippy::indexing_slicing)]
+    pub fn get_file(&self, path: &str) -> Option<File> {
+        if let Some(target) = self.alias_target(path) {
+            let file = self.get_file_unaliased(target)?;
+            return Some(File {
+                requested: Some(Arc::from(path)),
+                ..file
+            });
+        }
+        self.get_file_unaliased(path)
+    }
+
+    /// Returns the target of `path` if it is one of this silo's aliases. Like lookups, `\`
+    /// is accepted as a separator.
+    #[deny(clippy::indexing_slicing)]
+    pub fn alias_target(&self, path: &str) -> Option<&str> {
+        let aliases = self.view.aliases.as_ref()?;
+        let path = if path.contains('\\') {
+            Cow::Owned(path.replace('\\', "/"))
+        } else {
+            Cow::Borrowed(path)
+        };
+        aliases.map.get(&*path).map(|target| &**target)
+    }
+
+    /// [`Silo::get_file`] without consulting the alias table.
+    #[deny(clippy::indexing_slicing)]
+    fn get_file_unaliased(&self, path: &str) -> Option<File> {
+        self.get_file_exact(path).or_else(|| {
+            let index = self.index.as_deref()?;
+            let dir = path.trim_end_matches('/');
+            if dir.is_empty() {
+                self.get_file_exact(index)
+            } else {
+                self.get_file_exact(&format!("{dir}/{index}"))
+            }
+        })
+    }
+
+    /// Read options to attach to files from the backend; `None` for a compacted silo, whose
+    /// files keep the options of the layer they came from.
+    fn file_options(&self) -> Option<&DynOptions> {
+        match &self.inner {
+            InnerSilo::Compact(_) => None,
+            _ => Some(&self.dyn_options),
+        }
+    }
+
+    /// Returns the root directory if this Silo is filesystem-backed.
+    pub(crate) fn dynamic_root(&self) -> Option<&Path> {
+        match &self.inner {
+            InnerSilo::Static(s) => Some(Path::new(s.root)),
+            InnerSilo::Dynamic(d) => Some(Path::new(d.root.as_ref())),
+            _ => None,
+        }
+    }
+
+    /// Resolves `stem` against each extension in order (`"html"` or `".html"`), returning the
+    /// first file that exists, e.g. `get_file_with_exts("page", &["html", "htm", "tmpl"])`.
+    pub fn get_file_with_exts(&self, stem: &str, exts: &[&str]) -> Option<File> {
+        exts.iter().find_map(|ext| {
+            let ext = ext.trim_start_matches('.');
+            if ext.is_empty() {
+                self.get_file(stem)
+            } else {
+                self.get_file(&format!("{stem}.{ext}"))
+            }
+        })
+    }
+
+    /// Returns the UTF-8 contents of an embedded file as a `&'static str`, borrowed straight
+    /// from the binary. Returns `None` if the file does not exist or is not embedded (dynamic
+    /// and in-memory files have no `'static` contents).
+    ///
+    /// Use this when the text must outlive the silo or be stored in `'static` places; for
+    /// reading text from any backend, [`File::text`] (also zero-copy for embedded files) is
+    /// the general API.
+    pub fn read_file_as_str_static(
+        &self,
+        path: &str,
+    ) -> Option<Result<&'static str, std::str::Utf8Error>> {
+        match self.get_file(path)?.inner {
+            FileKind::Embed(embed) => Some(std::str::from_utf8(embed.inner.bytes())),
+            _ => None,
+        }
+    }
+
+    /// Candidate manifest files searched by [`Silo::auto_manifest`], in order.
+    #[cfg(feature = "json")]
+    pub const MANIFEST_CANDIDATES: &'static [&'static str] =
+        &["manifest.json", "manifest.toml", "assets.json"];
+
+    /// Deserializes the first of [`Silo::MANIFEST_CANDIDATES`] found at the silo root.
+    /// Returns `Ok(None)` if none exist.
+    #[cfg(feature = "json")]
+    pub fn auto_manifest<T: ::serde::de::DeserializeOwned>(&self) -> Result<Option<T>, Error> {
+        self.auto_manifest_with_candidates(Self::MANIFEST_CANDIDATES)
+    }
+
+    /// Like [`Silo::auto_manifest`] with a custom search list. Files ending in `.toml` are
+    /// parsed as TOML (skipped unless the `toml` feature is enabled), anything else as JSON.
+    #[cfg(feature = "json")]
+    pub fn auto_manifest_with_candidates<T: ::serde::de::DeserializeOwned>(
+        &self,
+        candidates: &[&str],
+    ) -> Result<Option<T>, Error> {
+        for candidate in candidates {
+            let is_toml = candidate.ends_with(".toml");
+            if is_toml && cfg!(not(feature = "toml")) {
+                continue;
+            }
+            let Some(file) = self.get_file(candidate) else {
+                continue;
+            };
+            #[cfg(feature = "toml")]
+            if is_toml {
+                return file.load_toml().map(Some);
+            }
+            return file.load_json().map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Returns the contents of the file at `path` without copying embedded bytes.
+    ///
+    /// Embedded files yield `Cow::Borrowed` over their `'static` bytes; dynamic files are read
+    /// into `Cow::Owned`. Returns [`Error::NotFound`] if the file does not exist.
+    pub fn get_file_content(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        self.get_file(path).ok_or(Error::NotFound)?.contents()
+    }
+
+    /// Like [`Silo::get_file_content`], but decodes the contents as UTF-8 text.
+    pub fn get_text_content(&self, path: &str) -> Result<Cow<'static, str>, Error> {
+        self.get_file(path).ok_or(Error::NotFound)?.text()
+    }
+
+    /// Returns up to `max` existing relative paths closest to `path` by edit distance,
+    /// nearest first, for "did you mean" hints after a failed lookup.
+    ///
+    /// Paths further than half the length of `path` (but at least 2 edits) are not suggested.
+    pub fn suggest(&self, path: &str, max: usize) -> Vec<String> {
+        suggest_paths(self.iter(), path, max)
+    }
+
+    /// Lists the immediate children of `dir` (`""` for the root) without recursing,
+    /// for level-by-level navigation. Entries are sorted by path.
+    ///
+    /// Embedded silos derive children from the key set; dynamic silos read the directory
+    /// on disk. A missing directory yields no entries.
+    pub fn read_dir(&self, dir: &str) -> impl Iterator<Item = Entry> + use<> {
+        let key = self.scoped_key(dir);
+        let entries = match &self.inner {
+            InnerSilo::Embed(embed) => embed.read_dir(&key),
+            InnerSilo::Static(s) => read_dir_for_root(s.root, dir, self.dyn_options.symlink_policy),
+            InnerSilo::Dynamic(d) => {
+                read_dir_for_root(&d.root, dir, self.dyn_options.symlink_policy)
+            }
+            InnerSilo::Memory(store) => read_dir_from_keys(&key, memory_files(store)),
+            InnerSilo::Compact(index) => read_dir_from_keys(&key, index.files()),
+        };
+        let options = self.file_options().cloned();
+        let view = self.view.clone();
+        entries.into_iter().filter_map(move |entry| match entry {
+            Entry::File(file) => view.apply(file, options.as_ref()).map(Entry::File),
+            Entry::Dir(dir) => match &view.prefix {
+                Some(prefix) => Some(Entry::Dir(dir[prefix.len()..].to_owned())),
+                None => Some(Entry::Dir(dir)),
+            },
+        })
+    }
+
+    /// Narrows this silo to the subtree under `prefix`: paths are looked up, iterated and
+    /// reported relative to it, so `silo.scope("templates").get_file("base.html")` finds
+    /// `templates/base.html`. Scopes nest; `.` and `..` components are resolved lexically and
+    /// cannot climb above the current scope.
+    ///
+    /// Filesystem silos are re-rooted at the subdirectory; embedded and in-memory silos keep
+    /// sharing their map and filter keys by prefix.
+    pub fn scope(self, prefix: &str) -> Self {
+        let mut parts: Vec<&str> = Vec::new();
+        for part in prefix.split(['/', '\\']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        if parts.is_empty() {
+            return self;
+        }
+        let sub = parts.join("/");
+        match self.dynamic_root() {
+            Some(root) => {
+                let inner =
+                    InnerSilo::Dynamic(DynamicSilo::new(&format!("{}/{sub}", root.display())));
+                let view = View {
+                    excluded: self.view.rebased_exclusions(&format!("{sub}/")),
+                    aliases: self.view.rebased_aliases(&format!("{sub}/")),
+                    ..self.view.clone()
+                };
+                Self {
+                    inner,
+                    view,
+                    ..self
+                }
+            }
+            None => {
+                let prefix = format!("{}{sub}/", self.view.prefix.as_deref().unwrap_or_default());
+                let view = View {
+                    prefix: Some(Arc::from(prefix)),
+                    aliases: self.view.rebased_aliases(&format!("{sub}/")),
+                    ..self.view.clone()
+                };
+                Self { view, ..self }
+            }
+        }
+    }
+
+    /// Builds the overlay for one tenant: files under `{tenant_prefix}/{tenant}` take
+    /// precedence over those under `default_prefix`, e.g.
+    /// `silo.tenant_view("acme", "tenants", "default")`. Only views are created, so this is
+    /// cheap enough to call per request.
+    ///
+    /// `tenant` must be a single path component; anything else (empty, `.`, `..`, or
+    /// containing a separator) yields a set with only the default layer, so a malicious
+    /// tenant name cannot reach another tenant's files. Layers are named after the tenant and
+    /// `"default"`.
+    pub fn tenant_view(&self, tenant: &str, tenant_prefix: &str, default_prefix: &str) -> SiloSet {
+        let mut set = SiloSet::new(vec![
+            self.clone().scope(default_prefix).with_name("default"),
+        ]);
+        let valid = !matches!(tenant, "" | "." | "..") && !tenant.contains(['/', '\\']);
+        if valid {
+            set.push(
+                self.clone()
+                    .scope(tenant_prefix)
+                    .scope(tenant)
+                    .with_name(tenant),
+            );
+        }
+        set
+    }
+
+    /// Iterates over the files whose extension matches `ext`, compared ASCII
+    /// case-insensitively and with or without the leading dot (`"wasm"`, `".WASM"`). Files
+    /// without an extension, including dotfiles like `.gitignore`, match `""`. Only the last
+    /// extension counts: `app.tar.gz` matches `"gz"`.
+    ///
+    /// Embedded silos look the group up in an index built by `embed_silo!`; other silos
+    /// filter a full scan.
+    pub fn by_extension(&self, ext: &str) -> Box<dyn Iterator<Item = File> + '_> {
+        let ext = ext.strip_prefix('.').unwrap_or(ext).to_ascii_lowercase();
+        match &self.inner {
+            InnerSilo::Embed(EmbedSilo {
+                map,
+                extensions: Some(extensions),
+                ..
+            }) => {
+                let keys = extensions.get(ext.as_str()).copied().unwrap_or_default();
+                let prefix = self.view.prefix.clone();
+                Box::new(
+                    keys.iter()
+                        .filter(move |key| {
+                            prefix
+                                .as_deref()
+                                .is_none_or(|prefix| key.starts_with(prefix))
+                        })
+                        .filter_map(|key| map.get(key))
+                        .filter_map(|entry| {
+                            let file = File {
+                                requested: None,
+                                inner: FileKind::Embed(EmbedFile {
+                                    inner: entry,
+                                    strip: 0,
+                                }),
+                            };
+                            self.view.apply(file, self.file_options())
+                        }),
+                )
+            }
+            _ => Box::new(
+                self.iter()
+                    .filter(move |file| extension_key(&file.path().to_string_lossy()) == ext),
+            ),
+        }
+    }
+
+    /// Returns `true` if at least one file exists under the directory `prefix` (resolved as
+    /// by [`Silo::scope`]; `""` means the whole silo). Embedded and in-memory silos binary
+    /// search their sorted keys for the prefix and only look at the keys under it; a dynamic
+    /// silo only walks the subdirectory until its first file.
+    pub fn has_any(&self, prefix: &str) -> bool {
+        let scoped = self.clone().scope(prefix);
+        let prefix = scoped.view.prefix.as_deref().unwrap_or_default();
+        let visible = |file: File| scoped.view.apply(file, scoped.file_options()).is_some();
+        match &scoped.inner {
+            InnerSilo::Embed(EmbedSilo {
+                map,
+                sorted_keys: Some(keys),
+                ..
+            }) => {
+                let start = keys.partition_point(|key| *key < prefix);
+                keys[start..]
+                    .iter()
+                    .take_while(|key| key.starts_with(prefix))
+                    .filter_map(|key| map.get(key))
+                    .any(|entry| {
+                        visible(File {
+                            requested: None,
+                            inner: FileKind::Embed(EmbedFile {
+                                inner: entry,
+                                strip: 0,
+                            }),
+                        })
+                    })
+            }
+            InnerSilo::Memory(store) => memory_files_from(store, store.partition_point(prefix))
+                .take_while(|(path, _)| path.starts_with(prefix))
+                .any(|(_, file)| visible(file)),
+            _ => scoped.iter().next().is_some(),
+        }
+    }
+
+    /// Hides every file for which `pred` returns `false` and returns how many were hidden.
+    ///
+    /// Nothing is deleted: the silo keeps an in-memory exclusion list that lookups, iteration
+    /// and directory listings consult, until [`Silo::clear_exclusions`]. Unlike
+    /// [`Silo::filtered`], the predicate runs once, so files created later are not affected.
+    /// Clones made before the call keep their own exclusions.
+    pub fn retain_files(&mut self, pred: impl Fn(&File) -> bool) -> usize {
+        let removed: Vec<Box<str>> = self
+            .iter()
+            .filter(|file| !pred(file))
+            .map(|file| file.backend_key().into())
+            .collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        let mut excluded = self.view.excluded.as_deref().cloned().unwrap_or_default();
+        let count = removed.len();
+        excluded.extend(removed);
+        self.view.excluded = Some(Arc::new(excluded));
+        count
+    }
+
+    /// Restores every file hidden by [`Silo::retain_files`].
+    pub fn clear_exclusions(&mut self) {
+        self.view.excluded = None;
+    }
+
+    /// Makes old paths keep resolving after a rename: [`Silo::get_file`] looks `alias` up in
+    /// `(alias, target)` pairs first and returns the target file, whose [`File::path`] is the
+    /// target and [`File::requested_path`] the alias. Aliases resolve one level only; a
+    /// target that is itself an alias (a chain, or a cycle) is rejected. Replaces any aliases
+    /// set before; aliases are not listed by [`Silo::iter`] unless
+    /// [`AliasOptions::include_in_iter`] is set.
+    ///
+    /// # Panics
+    /// Panics on a chain or cycle; see [`Silo::try_with_aliases`].
+    pub fn with_aliases(self, aliases: impl IntoIterator<Item = (String, String)>) -> Silo {
+        self.try_with_aliases(aliases)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Like [`Silo::with_aliases`], but reports a chain or cycle as [`Error::InvalidAlias`].
+    pub fn try_with_aliases(
+        mut self,
+        aliases: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Silo, Error> {
+        let map: std::collections::HashMap<Box<str>, Box<str>> = aliases
+            .into_iter()
+            .map(|(alias, target)| {
+                (
+                    alias.replace('\\', "/").into(),
+                    target.replace('\\', "/").into(),
+                )
+            })
+            .collect();
+        if let Some((alias, target)) = map.iter().find(|(_, target)| map.contains_key(&**target)) {
+            return Err(Error::InvalidAlias {
+                alias: alias.to_string(),
+                target: target.to_string(),
+            });
+        }
+        let options = self
+            .view
+            .aliases
+            .as_ref()
+            .map(|aliases| aliases.options.clone())
+            .unwrap_or_default();
+        self.view.aliases = (!map.is_empty()).then(|| Arc::new(Aliases { map, options }));
+        Ok(self)
+    }
+
+    /// Sets how aliases are listed and served. Call it after [`Silo::with_aliases`]; it has no
+    /// effect on a silo without aliases.
+    pub fn with_alias_options(mut self, options: AliasOptions) -> Silo {
+        if let Some(aliases) = &mut self.view.aliases {
+            Arc::make_mut(aliases).options = options;
+        }
+        self
+    }
+
+    /// The alias options in effect, or `None` without aliases.
+    pub(crate) fn alias_options(&self) -> Option<&AliasOptions> {
+        self.view.aliases.as_ref().map(|aliases| &aliases.options)
+    }
+
+    /// Files for the aliases to list from [`Silo::iter`], sorted by alias.
+    fn listed_aliases(&self) -> Vec<File> {
+        let Some(aliases) = self
+            .view
+            .aliases
+            .as_ref()
+            .filter(|aliases| aliases.options.include_in_iter)
+        else {
+            return Vec::new();
+        };
+        let mut listed: Vec<&str> = aliases.map.keys().map(|alias| &**alias).collect();
+        listed.sort_unstable();
+        listed
+            .into_iter()
+            .filter_map(|alias| self.get_file(alias))
+            .collect()
+    }
+
+    /// Restricts this silo to files for which `pred` returns `true`, e.g. by extension.
+    /// The predicate sees paths relative to the current scope. Filters compose: a filtered
+    /// silo filtered again exposes files accepted by both. Directories are still listed by
+    /// [`Silo::read_dir`] even if every file in them is filtered out.
+    pub fn filtered(self, pred: impl Fn(&File) -> bool + Send + Sync + 'static) -> Self {
+        self.with_filter(Arc::new(pred))
+    }
+
+    fn with_filter(self, pred: Arc<FileFilter>) -> Self {
+        let filter: Arc<FileFilter> = match self.view.filter.clone() {
+            Some(existing) => Arc::new(move |file: &File| existing(file) && pred(file)),
+            None => pred,
+        };
+        let view = View {
+            filter: Some(filter),
+            ..self.view.clone()
+        };
+        Self { view, ..self }
+    }
+
+    /// Prepends the scope prefix of a key-based backend to a relative path.
+    fn scoped_key<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        match &self.view.prefix {
+            Some(prefix) => Cow::Owned(format!("{prefix}{path}")),
+            None => Cow::Borrowed(path),
+        }
+    }
+
+    /// Pairs the files of this silo and `other` that share a relative path, sorted by path.
+    /// Paths present in only one silo are skipped.
+    pub fn zip_with<'a>(&'a self, other: &'a Silo) -> impl Iterator<Item = (File, File)> + 'a {
+        self.zip_with_full(other).filter_map(|pair| match pair {
+            (Some(left), Some(right)) => Some((left, right)),
+            _ => None,
+        })
+    }
+
+    /// Like [`Silo::zip_with`], but yields every path of either silo, with `None` for the
+    /// side it is missing from (a full outer join).
+    pub fn zip_with_full<'a>(
+        &'a self,
+        other: &'a Silo,
+    ) -> impl Iterator<Item = (Option<File>, Option<File>)> + 'a {
+        let by_path = |silo: &Silo| -> std::collections::BTreeMap<PathBuf, File> {
+            silo.iter()
+                .map(|file| (file.path().to_path_buf(), file))
+                .collect()
+        };
+        let mut left = by_path(self);
+        let mut right = by_path(other);
+        let paths: std::collections::BTreeSet<PathBuf> =
+            left.keys().chain(right.keys()).cloned().collect();
+        paths
+            .into_iter()
+            .map(move |path| (left.remove(&path), right.remove(&path)))
+    }
+
+    /// Builds a new in-memory silo by passing every file through `f`: its output replaces the
+    /// file's contents, and `None` drops the file. Files keep their relative path and
+    /// modification time, e.g. to minify and prune assets once at startup.
+    ///
+    /// `f` receives the original file and reads it as needed; only the outputs are held in
+    /// memory. Fails if a file's metadata cannot be read.
+    pub fn transform(&self, f: impl Fn(&File) -> Option<Vec<u8>>) -> Result<Silo, Error> {
+        let mut files = Vec::new();
+        for file in self.iter() {
+            if let Some(contents) = f(&file) {
+                files.push((
+                    file.path().to_string_lossy().into_owned(),
+                    contents,
+                    file.meta()?.modified,
+                ));
+            }
+        }
+        Ok(Self {
+            inner: InnerSilo::Memory(Arc::new(memory::MemStore::build(files))),
+            index: self.index.clone(),
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View {
+                keys: self.view.keys,
+                headers: self.view.headers.clone(),
+                pages: self.view.pages.clone(),
+                ..View::NONE
+            },
+        })
+    }
+
+    /// Merges this silo with `other` into a new in-memory silo.
+    ///
+    /// Files present in only one silo pass through unchanged; for paths present in both,
+    /// `resolver(left, right)` decides which to keep or supplies merged bytes. All contents
+    /// are read eagerly, so the result no longer depends on either input (or the disk).
+    /// Files created from [`MergeDecision::TakeBytes`] get the later modification time of
+    /// the two inputs.
+    pub fn merge_with<F>(&self, other: &Silo, resolver: F) -> Result<Silo, Error>
+    where
+        F: Fn(&File, &File) -> MergeDecision,
+    {
+        fn load(file: &File) -> Result<(String, Vec<u8>, u64), Error> {
+            let path = file.path().to_string_lossy().into_owned();
+            Ok((path, file.contents()?.into_owned(), file.meta()?.modified))
+        }
+
+        let mut right: std::collections::BTreeMap<String, File> = other
+            .iter()
+            .map(|file| (file.path().to_string_lossy().into_owned(), file))
+            .collect();
+        let mut files = Vec::new();
+        for left in self.iter() {
+            let Some(right) = right.remove(left.path().to_string_lossy().as_ref()) else {
+                files.push(load(&left)?);
+                continue;
+            };
+            files.push(match resolver(&left, &right) {
+                MergeDecision::TakeLeft => load(&left)?,
+                MergeDecision::TakeRight => load(&right)?,
+                MergeDecision::TakeBytes(bytes) => {
+                    let modified = left.meta()?.modified.max(right.meta()?.modified);
+                    (left.path().to_string_lossy().into_owned(), bytes, modified)
+                }
+            });
+        }
+        for file in right.values() {
+            files.push(load(file)?);
+        }
+        Ok(Self {
+            inner: InnerSilo::Memory(Arc::new(memory::MemStore::build(files))),
+            index: self.index.clone(),
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
+        })
+    }
+
+    /// Loads every file of this silo into memory and indexes them with a perfect hash table
+    /// built at runtime, giving an embedded-like silo from files discovered at runtime.
+    ///
+    /// Lookups on the result are a single hash probe instead of filesystem canonicalization
+    /// and stat calls. The tradeoff is memory: all contents are held in one heap arena for
+    /// the lifetime of the returned silo (and any `File` handles cloned from it), and later
+    /// changes on disk are not reflected. The walk is bounded as in [`Silo::prewarm`].
+    #[cfg(feature = "runtime-phf")]
+    pub fn build_phf_at_runtime(&self) -> Result<Silo, Error> {
+        let files = self
+            .try_iter_with(self.walk_limits_or(WalkLimits::GENEROUS))
+            .map(|file| {
+                let file = file?;
+                let path = file.path().to_string_lossy().into_owned();
+                Ok((path, file.contents()?.into_owned(), file.meta()?.modified))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self {
+            inner: InnerSilo::Memory(Arc::new(memory::MemStore::build_phf(files))),
+            index: self.index.clone(),
+            name: self.name.clone(),
+            dyn_options: DynOptions::NONE,
+            view: View {
+                keys: self.view.keys,
+                headers: self.view.headers.clone(),
+                pages: self.view.pages.clone(),
+                ..View::NONE
+            },
+        })
+    }
+
+    /// Looks `path` up on disk under `root`, through the negative cache if there is one.
+    fn get_dyn_file(&self, root: &str, path: &str) -> Option<DynFile> {
+        let Some(cache) = &self.dyn_options.negative_cache else {
+            return get_file_for_root(root, path, self.dyn_options.symlink_policy);
+        };
+        let key = format!("{root}\0{path}");
+        if cache.contains(&key) {
+            return None;
+        }
+        let file = get_file_for_root(root, path, self.dyn_options.symlink_policy);
+        if file.is_none() {
+            cache.insert(&key);
+        }
+        file
+    }
+
+    /// Looks up a file by its exact relative path, without index resolution.
+    #[deny(clippy::indexing_slicing)]
+    fn get_file_exact(&self, path: &str) -> Option<File> {
+        if is_rejected_lookup(path) {
+            return None;
+        }
+        // Key-based backends accept `\` like the filesystem ones do.
+        let path = if path.contains('\\') {
+            Cow::Owned(path.replace('\\', "/"))
+        } else {
+            Cow::Borrowed(path)
+        };
+        let file = self.get_backend_file(&self.scoped_key(&path)).or_else(|| {
+            if self.view.keys == KeyNormalization::None {
+                return None;
+            }
+            let key = self.scoped_key(&self.view.keys.apply(&path)).into_owned();
+            match self.dynamic_root() {
+                Some(root) => {
+                    let resolved =
+                        key_normalization::resolve_on_disk(root.to_str()?, &key, self.view.keys)?;
+                    self.get_backend_file(&resolved)
+                }
+                None => self.get_backend_file(&key),
+            }
+        });
+        file.and_then(|file| self.view.apply(file, self.file_options()))
+    }
+
+    /// Looks up the file stored under `path` in the backend, before the view is applied.
+    #[deny(clippy::indexing_slicing)]
+    fn get_backend_file(&self, path: &str) -> Option<File> {
+        match &self.inner {
+            InnerSilo::Embed(embed) => embed.get_file(path).map(|f| File {
+                requested: None,
+                inner: FileKind::Embed(f),
+            }),
+            InnerSilo::Static(dyn_silo) => self.get_dyn_file(dyn_silo.root, path).map(|f| File {
+                requested: None,
+                inner: FileKind::Dynamic(f),
+            }),
+            InnerSilo::Dynamic(dyn_silo) => self.get_dyn_file(&dyn_silo.root, path).map(|f| File {
+                requested: None,
+                inner: FileKind::Dynamic(f),
+            }),
+            InnerSilo::Memory(store) => store.index_of(path).map(|index| File {
+                requested: None,
+                inner: FileKind::Memory(memory::MemFile {
+                    store: store.clone(),
+                    index,
+                    strip: 0,
+                }),
+            }),
+            InnerSilo::Compact(index) => index.get(path),
+        }
+    }
+
+    /// Iterates over all files in this Silo.
+    /// Returns a boxed iterator of `File` objects representing the files.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+        self.iter_limited(self.dyn_options.walk_limits.unwrap_or_default(), None)
+    }
+
+    /// [`Silo::iter`] with explicit walk limits, recording a stopped walk in `breach`.
+    fn iter_limited(
+        &self,
+        limits: WalkLimits,
+        breach: Option<Breach>,
+    ) -> Box<dyn Iterator<Item = File> + '_> {
+        let files: Box<dyn Iterator<Item = File> + '_> = match &self.inner {
+            InnerSilo::Embed(embd) => Box::new(embd.iter()),
+            InnerSilo::Static(dynm) => {
+                Box::new(dynm.iter(self.dyn_options.symlink_policy, limits, breach))
+            }
+            InnerSilo::Dynamic(dynm) => {
+                Box::new(dynm.iter(self.dyn_options.symlink_policy, limits, breach))
+            }
+            InnerSilo::Memory(store) => Box::new(memory_files(store).map(|(_, file)| file)),
+            InnerSilo::Compact(index) => Box::new(index.files().map(|(_, file)| file)),
+        };
+        let files = match &self.view.prefix {
+            Some(prefix) => {
+                Box::new(files.filter(move |file| file.path_str().starts_with(&**prefix)))
+            }
+            None => files,
+        };
+        let files = match (
+            &self.dyn_options.coalescer,
+            &self.dyn_options.transcoding,
+            &self.view.filter,
+        ) {
+            (None, None, None)
+                if self.view.prefix.is_none()
+                    && self.view.excluded.is_none()
+                    && self.view.keys == KeyNormalization::None =>
+            {
+                files
+            }
+            _ => Box::new(files.filter_map(|file| self.view.apply(file, self.file_options()))),
+        };
+        match self.listed_aliases() {
+            aliases if aliases.is_empty() => files,
+            aliases => Box::new(files.chain(aliases)),
+        }
+    }
+
+    /// Like [`Silo::iter`], but with a `size_hint` so `collect()` can allocate once.
+    ///
+    /// Walking a directory cannot be sized up front, so for filesystem silos this first walks
+    /// the tree to count the files and then walks it again to yield them: roughly twice the
+    /// directory traversal in exchange for no reallocations, worthwhile for large trees that
+    /// are collected. Files added or removed between the passes only make the hint inexact.
+    /// Embedded and in-memory silos are counted without touching any file contents.
+    pub fn iter_with_hint(&self) -> impl Iterator<Item = File> + '_ {
+        HintedIter {
+            remaining: self.iter().count(),
+            inner: self.iter(),
+        }
+    }
+
+    /// Touches every file so a dynamic silo is warm before taking traffic: each file is opened
+    /// and its first few KiB read, which populates the OS page cache and dentry cache.
+    /// Returns the number of files prewarmed.
+    ///
+    /// Embedded and in-memory silos are a no-op that only counts files, as their bytes are
+    /// already resident. With the `parallel` feature, files are opened concurrently on the
+    /// rayon thread pool. Fails on the first file that cannot be read, and with
+    /// [`Error::LimitExceeded`] if the walk exceeds the configured [`WalkLimits`], or
+    /// [`WalkLimits::GENEROUS`] if none are.
+    pub fn prewarm(&self) -> Result<usize, Error> {
+        if self.dynamic_root().is_none() {
+            return Ok(self.iter().count());
+        }
+        let files: Vec<File> = self
+            .try_iter_with(self.walk_limits_or(WalkLimits::GENEROUS))
+            .collect::<Result<_, _>>()?;
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            files.par_iter().try_for_each(prewarm_file)?;
+        }
+        #[cfg(not(feature = "parallel"))]
+        files.iter().try_for_each(prewarm_file)?;
+        Ok(files.len())
+    }
+
+    /// Iterates all files sorted by relative path.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = File> + use<> {
+        let mut files: Vec<File> = self.iter().collect();
+        files.sort_by(|a, b| a.path().cmp(b.path()));
+        files.into_iter()
+    }
+
+    /// Iterates all files in the order given by `cmp`.
+    /// All files are collected up front, since arbitrary orders cannot be streamed.
+    pub fn iter_sorted_by<F>(&self, cmp: F) -> impl Iterator<Item = File> + use<F>
+    where
+        F: Fn(&File, &File) -> std::cmp::Ordering,
+    {
+        let mut files: Vec<File> = self.iter().collect();
+        files.sort_by(cmp);
+        files.into_iter()
+    }
+
+    /// Iterates all files from smallest to largest, ties broken by path.
+    /// Files whose metadata cannot be read sort first.
+    pub fn iter_sorted_by_size(&self) -> impl Iterator<Item = File> + use<> {
+        self.sorted_by_key(|file| file.meta().ok().map(|meta| meta.size))
+            .into_iter()
+    }
+
+    /// Iterates all files from least to most recently modified, ties broken by path.
+    /// Files whose metadata cannot be read sort first.
+    pub fn iter_sorted_by_modified(&self) -> impl Iterator<Item = File> + use<> {
+        self.sorted_by_key(|file| file.meta().ok().map(|meta| meta.modified))
+            .into_iter()
+    }
+
+    /// Returns the newest modification time of any file in the silo, or `None` if it is empty.
+    ///
+    /// Embedded and in-memory silos use stored timestamps; dynamic silos stat every file.
+    /// Files whose metadata cannot be read are skipped. Precision is whole seconds.
+    pub fn modified_latest(&self) -> Option<std::time::SystemTime> {
+        self.iter()
+            .filter_map(|file| file.meta().ok())
+            .map(|meta| meta.modified)
+            .max()
+            .map(|secs| std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+    }
+
+    /// Sorts by a key computed once per file, then by path.
+    fn sorted_by_key<K: Ord>(&self, key: impl Fn(&File) -> K) -> Vec<File> {
+        let mut files: Vec<(K, File)> = self.iter().map(|file| (key(&file), file)).collect();
+        files.sort_by(|(ka, a), (kb, b)| ka.cmp(kb).then_with(|| a.path().cmp(b.path())));
+        files.into_iter().map(|(_, file)| file).collect()
+    }
+
+    /// Builds a route manifest (URL → size, content type, hash) for web bundlers.
+    /// URLs are percent-encoded and mounted under `prefix` (e.g. `"/assets"`).
+    /// Entries are sorted by path.
+    pub fn routes(&self, prefix: &str) -> Result<Vec<RouteEntry>, Error> {
+        collect_routes(self.iter(), prefix)
+    }
+
+    /// Records the path, size, mtime and [`File::content_hash`] of every file.
+    /// Dynamic files are read in full to hash them.
+    pub fn manifest(&self) -> Result<Manifest, Error> {
+        let mut entries = self
+            .iter()
+            .map(|file| {
+                let meta = file.meta()?;
+                Ok(ManifestEntry {
+                    path: file.path().to_string_lossy().into_owned(),
+                    size: meta.size,
+                    modified: meta.modified,
+                    hash: Some(file.content_hash()?),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Manifest {
+            entries,
+            build_id: self.build_id(),
+        })
+    }
+
+    /// Computes the SHA-256 digest of every file, keyed by relative path, e.g. to ship a
+    /// checksum manifest alongside the binary. Embedded and in-memory files are hashed in
+    /// place; dynamic files are streamed.
+    #[cfg(feature = "hash")]
+    pub fn checksum_files(&self) -> Result<std::collections::HashMap<String, [u8; 32]>, Error> {
+        self.iter()
+            .map(|file| Ok((file.path().to_string_lossy().into_owned(), file.hash()?)))
+            .collect()
+    }
+
+    /// Like [`Silo::checksum_files`], with the digests as lowercase hex strings.
+    #[cfg(feature = "hash")]
+    pub fn checksum_files_hex(&self) -> Result<std::collections::HashMap<String, String>, Error> {
+        Ok(self
+            .checksum_files()?
+            .into_iter()
+            .map(|(path, digest)| (path, to_hex(&digest)))
+            .collect())
+    }
+
+    /// Shorthand for `self.manifest()?.diff(older)`.
+    pub fn diff_manifest(&self, older: &Manifest) -> Result<ManifestDiff, Error> {
+        Ok(self.manifest()?.diff(older))
+    }
+
+    /// Returns a size distribution as `(lower_bound, count)` pairs, one per bucket boundary.
+    ///
+    /// Bucket `i` counts files whose size falls in `[buckets[i], buckets[i + 1])`; the last
+    /// bucket catches everything at or above the final boundary. Boundaries must be ascending.
+    /// Files smaller than the first boundary, or whose metadata cannot be read, are not counted.
+    pub fn size_histogram(&self, buckets: &[usize]) -> Vec<(usize, usize)> {
+        let mut counts: Vec<(usize, usize)> = buckets.iter().map(|&b| (b, 0)).collect();
+        for meta in self.iter().filter_map(|f| f.meta().ok()) {
+            let idx = buckets.partition_point(|&b| b <= meta.size);
+            if idx > 0 {
+                counts[idx - 1].1 += 1;
+            }
+        }
+        counts
+    }
+
+    /// Returns [`Silo::size_histogram`] with a lowest bucket of `[0, 1 KiB)` followed by
+    /// power-of-two boundaries from 1 KiB up to 1 MiB.
+    pub fn size_histogram_default(&self) -> Vec<(usize, usize)> {
+        let mut buckets = vec![0];
+        buckets.extend((10..=20).map(|exp| 1usize << exp));
+        self.size_histogram(&buckets)
+    }
+}
+
+/// Hooks for metrics on [`SiloSet`] lookups and reads, see [`SiloSet::with_observer`].
+/// Every method defaults to a no-op, so implementors only override what they count.
+pub trait SiloObserver: Send + Sync {
+    /// A lookup for `path` was served by the silo at `silo_index` in [`SiloSet::silos`].
+    fn on_hit(&self, _path: &str, _silo_index: usize, _embedded: bool) {}
+
+    /// A lookup for `path` matched no silo.
+    fn on_miss(&self, _path: &str) {}
+
+    /// `bytes` bytes of `path` were read through [`SiloSet::get_file_content`] or
+    /// [`SiloSet::get_text_content`], or are about to be sent by [`SiloLike::serve`]: the
+    /// range for a partial response, the whole file otherwise (even if compressed on the way).
+    /// Served bytes are reported when the response is built, not as the body streams.
+    fn on_read(&self, _path: &str, _bytes: usize) {}
+}
+
+/// Result of [`SiloSet::discover`]: one named layer per pack directory, plus the entries
+/// that were not loaded as packs.
+#[derive(Debug, Clone)]
+pub struct PackDiscovery {
+    pub set: SiloSet,
+    /// Entries of the packs root that are not pack directories (stray files, hidden entries,
+    /// non-UTF-8 names).
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Represents a set of root directories, supporting overlay and override semantics.
+/// Later directories in the set can override files from earlier ones with the same relative path.
+///
+/// Build it with [`SiloSet::new`]: the set holds a private observer slot (see
+/// [`SiloSet::with_observer`]), so `SiloSet { silos }` literals no longer compile.
+#[derive(Clone)]
+pub struct SiloSet {
+    /// The list of root directories, in order of increasing precedence.
+    pub silos: Vec<Silo>,
+    observer: Option<Arc<dyn SiloObserver>>,
+}
+
+impl std::fmt::Debug for SiloSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SiloSet")
+            .field("silos", &self.silos)
+            .field("observed", &self.observer.is_some())
+            .finish()
+    }
+}
+
+/// Sets are equal if their silos are equal, in order; observers are ignored.
+impl PartialEq for SiloSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.silos == other.silos
+    }
+}
+
+impl Eq for SiloSet {}
+
+impl SiloSet {
+    /// Creates a new SiloSet from the given list of directories.
+    /// The order of directories determines override precedence.
+    /// Create a new SiloSet from a list of Silos. Order determines override precedence.
+    pub fn new(dirs: Vec<Silo>) -> Self {
+        Self {
+            silos: dirs,
+            observer: None,
+        }
+    }
+
+    /// Reports lookups through [`SiloSet::get_file`] (and the methods built on it), reads
+    /// through the content helpers and response bodies from [`SiloLike::serve`] to
+    /// `observer`, e.g. to feed hit/miss counters.
+    /// Without an observer the only cost is an `Option` check.
+    pub fn with_observer(self, observer: Arc<dyn SiloObserver>) -> Self {
+        Self {
+            observer: Some(observer),
+            ..self
+        }
+    }
+
+    /// Creates a SiloSet of dynamic silos from UTF-8 paths, in order of increasing precedence.
+    #[cfg(feature = "camino")]
+    pub fn from_utf8_paths<'a>(paths: impl IntoIterator<Item = &'a camino::Utf8Path>) -> Self {
+        Self::new(paths.into_iter().map(Silo::from_utf8_path).collect())
+    }
+
+    /// Loads every immediate subdirectory of `root` as a dynamic silo named after it, e.g.
+    /// `packs/<name>/`. Empty packs are kept.
+    ///
+    /// Packs are ordered by increasing precedence: names listed in `root/order.txt` (one per
+    /// line, blank lines and `#` comments ignored) come first, in that order; the remaining
+    /// packs with a numeric prefix (`10-theme`) follow in numeric order, then the rest
+    /// alphabetically.
+    pub fn discover(root: &Path) -> Result<PackDiscovery, Error> {
+        let mut packs = Vec::new();
+        let mut skipped = Vec::new();
+        for entry in std::fs::read_dir(root)? {
+            let entry = entry?;
+            let path = entry.path();
+            match entry.file_name().to_str() {
+                Some("order.txt") if !path.is_dir() => {}
+                Some(name)
+                    if !name.starts_with('.') && path.is_dir() && path.to_str().is_some() =>
+                {
+                    packs.push(name.to_owned());
+                }
+                _ => skipped.push(path),
+            }
+        }
+        let order = match std::fs::read_to_string(root.join("order.txt")) {
+            Ok(text) => text
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_owned)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        packs.sort_by_cached_key(|name| {
+            let listed = order.iter().position(|o| o == name).unwrap_or(usize::MAX);
+            let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+            let number = name[..digits].parse::<u64>().ok();
+            (listed, number.is_none(), number, name.clone())
+        });
+        skipped.sort();
+        let silos = packs
+            .iter()
+            .map(|name| Silo::new(root.join(name).to_str().unwrap_or_default()).with_name(name))
+            .collect();
+        Ok(PackDiscovery {
+            set: Self::new(silos),
+            skipped,
+        })
+    }
+
+    /// Like [`SiloSet::discover`], with `base` (e.g. an embedded silo of defaults) as the
+    /// lowest-precedence layer below the packs.
+    pub fn discover_with_base(base: Silo, root: &Path) -> Result<PackDiscovery, Error> {
+        let mut discovery = Self::discover(root)?;
+        discovery.set.silos.insert(0, base);
+        Ok(discovery)
+    }
+
+    /// Appends a silo as the new highest-precedence layer.
+    pub fn push(&mut self, silo: Silo) {
+        self.silos.push(silo);
+    }
+
+    /// Appends a silo under `name` as the new highest-precedence layer.
+    pub fn push_named(&mut self, name: &str, silo: Silo) {
+        self.push(silo.with_name(name));
+    }
+
+    /// Returns the number of silos (layers) in the set.
+    pub fn len(&self) -> usize {
+        self.silos.len()
+    }
+
+    /// Returns `true` if the set has no silos.
+    pub fn is_empty(&self) -> bool {
+        self.silos.is_empty()
+    }
+
+    /// Returns a set of every member narrowed with [`Silo::scope`], preserving precedence.
+    /// Members with nothing under `prefix` are kept (and simply never match), so the result
+    /// has the same [`SiloSet::len`] and layer names as `self`.
+    pub fn scoped(&self, prefix: &str) -> SiloSet {
+        Self {
+            silos: self
+                .silos
+                .iter()
+                .map(|silo| silo.clone().scope(prefix))
+                .collect(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    /// Returns a set of every member restricted with [`Silo::filtered`], preserving precedence.
+    pub fn filtered(&self, pred: impl Fn(&File) -> bool + Send + Sync + 'static) -> SiloSet {
+        let pred: Arc<FileFilter> = Arc::new(pred);
+        Self {
+            silos: self
+                .silos
+                .iter()
+                .map(|silo| silo.clone().with_filter(pred.clone()))
+                .collect(),
+            observer: self.observer.clone(),
+        }
+    }
+
+    /// Removes silos that are equal to a higher-precedence member, keeping the last occurrence
+    /// of each so the effective lookup order is unchanged.
+    pub fn dedup(&mut self) {
+        let mut kept: Vec<Silo> = Vec::with_capacity(self.silos.len());
+        for silo in self.silos.drain(..).rev() {
+            if !kept.contains(&silo) {
+                kept.push(silo);
+            }
+        }
+        kept.reverse();
+        self.silos = kept;
+    }
+
+    /// Returns the name of every layer in order of increasing precedence.
+    /// Unnamed layers are reported by position, as `layer-<index>`.
+    pub fn layer_names(&self) -> Vec<Cow<'_, str>> {
+        (0..self.silos.len()).map(|i| self.layer_name(i)).collect()
+    }
+
+    fn layer_name(&self, index: usize) -> Cow<'_, str> {
+        match self.silos[index].name() {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(format!("layer-{index}")),
+        }
+    }
+
+    /// Looks up a file like [`SiloSet::get_file`] and also returns the name of the layer
+    /// that provided it (see [`SiloSet::layer_names`]).
+    pub fn resolve_with_source(&self, path: &str) -> Option<(File, Cow<'_, str>)> {
+        self.lookup(path)
+            .map(|(i, file)| (file, self.layer_name(i)))
+    }
+
+    /// Finds the highest-precedence file for `path` with its silo index, notifying the observer.
+    fn lookup(&self, path: &str) -> Option<(usize, File)> {
+        let found = self
+            .silos
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, silo)| silo.get_file(path).map(|file| (i, file)));
+        if let Some(observer) = &self.observer {
+            match &found {
+                Some((i, file)) => observer.on_hit(path, *i, file.is_embedded()),
+                None => observer.on_miss(path),
+            }
+        }
+        found
+    }
+
+    /// Returns the file with the given name, searching roots in reverse order.
+    /// Files in later roots override those in earlier roots if the relative path matches.
+    /// Get a file by name, searching Silos in reverse order (highest precedence first).
+    pub fn get_file(&self, name: &str) -> Option<File> {
+        self.lookup(name).map(|(_, file)| file)
+    }
+
+    /// Returns the contents of the highest-precedence file at `path`, see [`Silo::get_file_content`].
+    pub fn get_file_content(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        let contents = self.get_file(path).ok_or(Error::NotFound)?.contents()?;
+        if let Some(observer) = &self.observer {
+            observer.on_read(path, contents.len());
+        }
+        Ok(contents)
+    }
+
+    /// Like [`SiloSet::get_file_content`], but decodes the contents as UTF-8 text.
+    pub fn get_text_content(&self, path: &str) -> Result<Cow<'static, str>, Error> {
+        let text = self.get_file(path).ok_or(Error::NotFound)?.text()?;
+        if let Some(observer) = &self.observer {
+            observer.on_read(path, text.len());
+        }
+        Ok(text)
+    }
+
+    /// Recursively walks all files in all root directories.
+    /// Files with the same relative path from different roots are all included.
+    /// Iterate all files in all Silos, including duplicates.
+    pub fn iter(&self) -> impl Iterator<Item = File> + '_ {
+        self.silos.iter().rev().flat_map(|silo| silo.iter())
+    }
+
+    /// Recursively walks all files, yielding only the highest-precedence file for each relative path.
+    /// This implements the override behaviour: later roots take precedence over earlier ones.
+    /// Iterate all files, yielding only the highest-precedence file for each path.
+    /// See [`SiloSet::iter_overlay`] for the same view described as layers.
+    pub fn iter_override(&self) -> impl Iterator<Item = File> + '_ {
+        let mut history = std::collections::HashSet::new();
+        self.iter()
+            .filter(move |file| history.insert(file.path().to_path_buf()))
+    }
+
+    /// Iterates the composited view of the set: each silo is a layer, and higher-priority
+    /// (later) silos paint over lower-priority ones, so only the topmost file for each
+    /// relative path is visible. Alias for [`SiloSet::iter_override`].
+    pub fn iter_overlay(&self) -> impl Iterator<Item = File> + '_ {
+        self.iter_override()
+    }
+
+    /// Iterates all files with duplicate relative paths removed, keeping the highest-precedence
+    /// one. Alias for [`SiloSet::iter_override`] / [`SiloSet::iter_overlay`].
+    pub fn iter_all_deduplicated(&self) -> impl Iterator<Item = File> + '_ {
+        self.iter_override()
+    }
+
+    /// Builds a route manifest over the override view (see [`Silo::routes`]).
+    pub fn routes(&self, prefix: &str) -> Result<Vec<RouteEntry>, Error> {
+        collect_routes(self.iter_override(), prefix)
+    }
+}
+
+/// Number of reads [`File::contents_consistent`] attempts before giving up.
+pub const CONSISTENT_READ_ATTEMPTS: usize = 5;
 
 /// Reader for file contents, either embedded or dynamic.
 pub enum FileReader {
     Embed(std::io::Cursor<&'static [u8]>),
     Dynamic(std::fs::File),
+    /// Dynamic file contents decoded to UTF-8 in memory, see [`Silo::with_transcoding`].
+    Transcoded(std::io::Cursor<Vec<u8>>),
+    /// Contents of a silo loaded into memory at runtime.
+    Memory(std::io::Cursor<MemoryBytes>),
+    /// Dynamic file contents read in full by [`File::contents_consistent`].
+    Consistent(std::io::Cursor<Vec<u8>>),
 }
 
 /// Implements std::io::Read for FileReader.
@@ -476,6 +3485,108 @@ impl std::io::Read for FileReader {
         match self {
             FileReader::Embed(c) => c.read(buf),
             FileReader::Dynamic(f) => f.read(buf),
+            FileReader::Transcoded(c) => c.read(buf),
+            FileReader::Memory(c) => c.read(buf),
+            FileReader::Consistent(c) => c.read(buf),
+        }
+    }
+
+    // Forwarded so in-memory contents are appended in one copy (and files sized up front)
+    // instead of going through the generic chunked `read` loop.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        match self {
+            FileReader::Embed(c) => c.read_to_end(buf),
+            FileReader::Dynamic(f) => f.read_to_end(buf),
+            FileReader::Transcoded(c) => c.read_to_end(buf),
+            FileReader::Memory(c) => c.read_to_end(buf),
+            FileReader::Consistent(c) => c.read_to_end(buf),
+        }
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> std::io::Result<usize> {
+        match self {
+            FileReader::Embed(c) => c.read_to_string(buf),
+            FileReader::Dynamic(f) => f.read_to_string(buf),
+            FileReader::Transcoded(c) => c.read_to_string(buf),
+            FileReader::Memory(c) => c.read_to_string(buf),
+            FileReader::Consistent(c) => c.read_to_string(buf),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        match self {
+            FileReader::Embed(c) => c.read_exact(buf),
+            FileReader::Dynamic(f) => f.read_exact(buf),
+            FileReader::Transcoded(c) => c.read_exact(buf),
+            FileReader::Memory(c) => c.read_exact(buf),
+            FileReader::Consistent(c) => c.read_exact(buf),
+        }
+    }
+}
+
+impl std::io::Seek for FileReader {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self {
+            FileReader::Embed(c) => c.seek(pos),
+            FileReader::Dynamic(f) => f.seek(pos),
+            FileReader::Transcoded(c) => c.seek(pos),
+            FileReader::Memory(c) => c.seek(pos),
+            FileReader::Consistent(c) => c.seek(pos),
+        }
+    }
+}
+
+/// Buffered reader for file contents, returned by [`File::buffered_reader`].
+///
+/// Only dynamic files are wrapped in a [`std::io::BufReader`]; in-memory contents are
+/// already buffered and are read directly.
+pub enum BufferedFileReader {
+    Embed(std::io::Cursor<&'static [u8]>),
+    Dynamic(std::io::BufReader<std::fs::File>),
+    /// Dynamic file contents decoded to UTF-8 in memory, see [`Silo::with_transcoding`].
+    Transcoded(std::io::Cursor<Vec<u8>>),
+    /// Contents of a silo loaded into memory at runtime.
+    Memory(std::io::Cursor<MemoryBytes>),
+}
+
+impl std::io::Read for BufferedFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            BufferedFileReader::Embed(c) => c.read(buf),
+            BufferedFileReader::Dynamic(f) => f.read(buf),
+            BufferedFileReader::Transcoded(c) => c.read(buf),
+            BufferedFileReader::Memory(c) => c.read(buf),
+        }
+    }
+}
+
+impl std::io::BufRead for BufferedFileReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        match self {
+            BufferedFileReader::Embed(c) => c.fill_buf(),
+            BufferedFileReader::Dynamic(f) => f.fill_buf(),
+            BufferedFileReader::Transcoded(c) => c.fill_buf(),
+            BufferedFileReader::Memory(c) => c.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            BufferedFileReader::Embed(c) => c.consume(amt),
+            BufferedFileReader::Dynamic(f) => f.consume(amt),
+            BufferedFileReader::Transcoded(c) => c.consume(amt),
+            BufferedFileReader::Memory(c) => c.consume(amt),
+        }
+    }
+}
+
+impl std::io::Seek for BufferedFileReader {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        match self {
+            BufferedFileReader::Embed(c) => c.seek(pos),
+            BufferedFileReader::Dynamic(f) => f.seek(pos),
+            BufferedFileReader::Transcoded(c) => c.seek(pos),
+            BufferedFileReader::Memory(c) => c.seek(pos),
         }
     }
 }
@@ -0,0 +1,155 @@
+//! In-memory silo backend: file contents live in a single heap arena. Lookups binary-search
+//! the sorted paths, or with the `runtime-phf` feature go through a perfect hash table built
+//! at runtime (the same scheme `phf` uses at compile time).
+
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A file stored in a [`MemStore`].
+#[derive(Debug)]
+pub(crate) struct MemEntry {
+    pub path: Box<str>,
+    pub range: Range<usize>,
+    pub modified: u64,
+    /// Precomputed [`ContentHasher`](crate::ContentHasher) digest, e.g. from a pack file.
+    pub hash: Option<u64>,
+}
+
+/// Immutable set of files loaded into memory, sorted by path.
+pub(crate) struct MemStore {
+    #[cfg(feature = "runtime-phf")]
+    phf: Option<PhfIndex>,
+    entries: Vec<MemEntry>,
+    /// Heap-allocated, or borrowed from a `'static` pack file.
+    arena: Cow<'static, [u8]>,
+}
+
+/// Perfect hash index over `MemStore::entries`.
+#[cfg(feature = "runtime-phf")]
+struct PhfIndex {
+    key: phf_shared::HashKey,
+    disps: Vec<(u32, u32)>,
+    /// Maps a PHF slot to an index into `entries`.
+    slots: Vec<usize>,
+}
+
+impl std::fmt::Debug for MemStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemStore")
+            .field("entries", &self.entries)
+            .field("arena_len", &self.arena.len())
+            .finish()
+    }
+}
+
+impl MemStore {
+    /// Builds a store from `(relative path, contents, modified)` triples.
+    /// Entries are kept sorted by path so iteration order is stable; the first of several
+    /// files with the same path wins.
+    pub fn build(mut files: Vec<(String, Vec<u8>, u64)>) -> Self {
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        files.dedup_by(|a, b| a.0 == b.0);
+        let mut arena = Vec::with_capacity(files.iter().map(|(_, data, _)| data.len()).sum());
+        let mut entries = Vec::with_capacity(files.len());
+        for (path, data, modified) in files {
+            let start = arena.len();
+            arena.extend_from_slice(&data);
+            entries.push(MemEntry {
+                path: path.into_boxed_str(),
+                range: start..arena.len(),
+                modified,
+                hash: None,
+            });
+        }
+        Self::from_parts(entries, Cow::Owned(arena))
+    }
+
+    /// Wraps entries that are already sorted by path, without duplicates, and whose ranges
+    /// lie within `arena`.
+    pub fn from_parts(entries: Vec<MemEntry>, arena: Cow<'static, [u8]>) -> Self {
+        Self {
+            #[cfg(feature = "runtime-phf")]
+            phf: None,
+            entries,
+            arena,
+        }
+    }
+
+    /// Like [`MemStore::build`], and also indexes the paths with a perfect hash table.
+    #[cfg(feature = "runtime-phf")]
+    pub fn build_phf(files: Vec<(String, Vec<u8>, u64)>) -> Self {
+        let mut store = Self::build(files);
+        let keys: Vec<&str> = store.entries.iter().map(|e| &*e.path).collect();
+        let state = phf_generator::generate_hash(&keys);
+        store.phf = Some(PhfIndex {
+            key: state.key,
+            disps: state.disps,
+            slots: state.map,
+        });
+        store
+    }
+
+    /// Returns the entry index for `path`, if present: O(1) with a PHF index,
+    /// otherwise a binary search.
+    pub fn index_of(&self, path: &str) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        #[cfg(feature = "runtime-phf")]
+        if let Some(phf) = &self.phf {
+            let hashes = phf_shared::hash(path, &phf.key);
+            let slot = phf_shared::get_index(&hashes, &phf.disps, phf.slots.len()) as usize;
+            let index = *phf.slots.get(slot)?;
+            return (&*self.entries[index].path == path).then_some(index);
+        }
+        self.entries.binary_search_by(|e| (*e.path).cmp(path)).ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Index of the first entry whose path is not less than `path`.
+    pub fn partition_point(&self, path: &str) -> usize {
+        self.entries.partition_point(|e| &*e.path < path)
+    }
+
+    pub fn entry(&self, index: usize) -> &MemEntry {
+        &self.entries[index]
+    }
+
+    pub fn bytes(&self, index: usize) -> &[u8] {
+        &self.arena[self.entries[index].range.clone()]
+    }
+}
+
+/// Handle to a file inside a shared [`MemStore`].
+#[derive(Debug, Clone)]
+pub(crate) struct MemFile {
+    pub store: Arc<MemStore>,
+    pub index: usize,
+    /// Length of the scope prefix hidden from the reported path.
+    pub strip: usize,
+}
+
+impl MemFile {
+    pub fn path(&self) -> &str {
+        &self.store.entry(self.index).path[self.strip..]
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.store.bytes(self.index)
+    }
+}
+
+/// Shared, reference-counted view of an in-memory file's bytes, used by
+/// [`FileReader::Memory`](crate::FileReader::Memory).
+#[derive(Debug, Clone)]
+pub struct MemoryBytes(pub(crate) MemFile);
+
+impl AsRef<[u8]> for MemoryBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.0.bytes()
+    }
+}
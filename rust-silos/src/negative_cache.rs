@@ -0,0 +1,85 @@
+//! Bounded cache of recent lookup misses for filesystem-backed silos, so repeated probes for
+//! missing files answer from memory instead of touching the disk.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Misses keyed by root and relative path, each expiring `ttl` after it was recorded.
+///
+/// At most `capacity` misses are remembered; recording one more evicts the oldest.
+pub(crate) struct NegativeCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<Misses>,
+}
+
+#[derive(Default)]
+struct Misses {
+    expiry: HashMap<Box<str>, Instant>,
+    /// Recording order. A key recorded again leaves a stale record behind, recognized by its
+    /// expiry no longer matching `expiry`.
+    order: VecDeque<(Box<str>, Instant)>,
+}
+
+impl std::fmt::Debug for NegativeCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let len = self
+            .inner
+            .lock()
+            .map(|misses| misses.expiry.len())
+            .unwrap_or_default();
+        f.debug_struct("NegativeCache")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("len", &len)
+            .finish()
+    }
+}
+
+impl NegativeCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::default(),
+        }
+    }
+
+    /// Returns `true` if `key` missed within the last `ttl`.
+    pub fn contains(&self, key: &str) -> bool {
+        let mut misses = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match misses.expiry.get(key) {
+            Some(&expires) if expires > Instant::now() => true,
+            Some(_) => {
+                misses.expiry.remove(key);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Records a miss for `key`.
+    pub fn insert(&self, key: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        let expires = Instant::now() + self.ttl;
+        let mut misses = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        misses.expiry.insert(key.into(), expires);
+        misses.order.push_back((key.into(), expires));
+        while misses.expiry.len() > self.capacity || misses.order.len() > 2 * self.capacity {
+            let Some((oldest, recorded)) = misses.order.pop_front() else {
+                break;
+            };
+            if misses.expiry.get(&oldest) == Some(&recorded) {
+                misses.expiry.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut misses = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        *misses = Misses::default();
+    }
+}
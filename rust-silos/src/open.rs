@@ -0,0 +1,84 @@
+//! `open`-style access: one call that resolves a path, opens a seekable reader and stats the
+//! file, with distinct errors for the cases C-style callers tell apart.
+
+use crate::{Error, File, FileMeta, FileReader, Silo, SiloSet};
+use std::io::{Read, Seek, SeekFrom};
+
+/// A file opened with [`Silo::open`] or [`SiloSet::open`].
+///
+/// Reads and seeks go to `reader`; `meta` was taken when the file was opened.
+pub struct OpenFile {
+    pub reader: FileReader,
+    pub meta: FileMeta,
+    /// Relative path of the file that was opened, after resolving aliases and index files.
+    pub path: String,
+}
+
+impl Read for OpenFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Seek for OpenFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.reader.seek(pos)
+    }
+}
+
+impl std::fmt::Debug for OpenFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenFile")
+            .field("path", &self.path)
+            .field("meta", &self.meta)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Silo {
+    /// Opens the file at `path` for reading and returns it with its metadata.
+    ///
+    /// Fails with [`Error::IsADirectory`] if `path` is empty or names a directory,
+    /// [`Error::NotFound`] if nothing is there, and [`Error::PermissionDenied`] if a dynamic
+    /// file exists but cannot be read. Directories are known by the files under them, as in
+    /// embedded silos, so an empty directory on disk is [`Error::NotFound`].
+    pub fn open(&self, path: &str) -> Result<OpenFile, Error> {
+        open(self.get_file(path), path, || self.has_any(path))
+    }
+}
+
+impl SiloSet {
+    /// Opens the highest-precedence file at `path`, see [`Silo::open`].
+    pub fn open(&self, path: &str) -> Result<OpenFile, Error> {
+        open(self.get_file(path), path, || {
+            self.silos.iter().any(|silo| silo.has_any(path))
+        })
+    }
+}
+
+fn open(file: Option<File>, path: &str, is_dir: impl FnOnce() -> bool) -> Result<OpenFile, Error> {
+    let file = match file {
+        Some(file) => file,
+        None if path.trim_matches('/').is_empty() || is_dir() => {
+            return Err(Error::IsADirectory {
+                path: path.to_owned(),
+            });
+        }
+        None => return Err(Error::NotFound),
+    };
+    let denied = |err: Error| match err {
+        Error::IoError { source } if source.kind() == std::io::ErrorKind::PermissionDenied => {
+            Error::PermissionDenied {
+                path: file.path().to_string_lossy().into_owned(),
+            }
+        }
+        err => err,
+    };
+    let reader = file.reader().map_err(denied)?;
+    let meta = file.meta().map_err(denied)?;
+    Ok(OpenFile {
+        reader,
+        meta,
+        path: file.path().to_string_lossy().into_owned(),
+    })
+}
@@ -0,0 +1,364 @@
+//! Pack files: a directory serialized into one binary blob, typically from `build.rs`, and
+//! indexed at runtime by [`Silo::from_pack_bytes`], so embedding needs only `include_bytes!`.
+//!
+//! ```ignore
+//! // build.rs
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR")?).join("assets.pack");
+//! rust_silos::pack::pack_dir("assets".as_ref(), &out, &PackOptions::default())?;
+//! println!("cargo:rerun-if-changed=assets");
+//!
+//! // src/main.rs
+//! static PACK: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/assets.pack"));
+//! let silo = Silo::from_pack_bytes(PACK)?;
+//! ```
+//!
+//! Layout, all integers little-endian:
+//!
+//! - Header (32 bytes): magic `SILOPACK`, format version (`u16`, [`PACK_VERSION`]), reserved
+//!   (`u16`, zero), entry count (`u32`), offset of the data section (`u64`), and the FNV-1a
+//!   checksum ([`ContentHasher`]) of every byte after the header (`u64`).
+//! - Index: one record per file, sorted by path: path length (`u32`), path (UTF-8, `/`
+//!   separated), flags (`u8`), for compressed entries the length (`u8`) and name of the
+//!   [`Codec`] they are stored with, then offset into the data section, stored length,
+//!   original length, modified time in seconds and content hash (`u64` each; the hash is 0
+//!   unless flagged).
+//! - Data: the stored bytes of each entry, back to back.
+
+use crate::Codec;
+use crate::memory::{MemEntry, MemStore};
+use crate::{ContentHasher, DynOptions, Error, InnerSilo, Silo, View};
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Format version written by [`pack_dir`]; packs with any other version are rejected.
+pub const PACK_VERSION: u16 = 2;
+
+const MAGIC: &[u8; 8] = b"SILOPACK";
+const HEADER_LEN: usize = 32;
+/// Record length excluding the path.
+const RECORD_LEN: usize = 4 + 1 + 5 * 8;
+const FLAG_HASH: u8 = 1;
+const FLAG_CODEC: u8 = 2;
+
+/// Options for [`pack_dir`] and [`pack_silo`].
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// Store each file's [`File::content_hash`](crate::File::content_hash) so it is answered
+    /// without reading the contents.
+    pub hashes: bool,
+    /// Compress files that get smaller by doing so, with [`PackOptions::codec`].
+    pub compress: bool,
+    /// The codec to compress with, recorded by name for each compressed entry. Defaults to
+    /// [`RawDeflate`](crate::RawDeflate), which requires the `deflate` feature; a pack using
+    /// another codec must be loaded with [`Silo::from_pack_bytes_with_codecs`].
+    pub codec: Option<Arc<dyn Codec>>,
+}
+
+/// Codecs compare by name.
+impl PartialEq for PackOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.hashes == other.hashes
+            && self.compress == other.compress
+            && self.codec.as_ref().map(|codec| codec.name())
+                == other.codec.as_ref().map(|codec| codec.name())
+    }
+}
+
+impl Eq for PackOptions {}
+
+/// What [`pack_dir`] wrote.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PackSummary {
+    pub files: usize,
+    /// Total size of the files as read.
+    pub original_bytes: u64,
+    /// Total size of the data section, after compression.
+    pub stored_bytes: u64,
+    /// Size of the whole pack, including header and index.
+    pub pack_bytes: u64,
+}
+
+/// Packs every file under `src` (as [`Silo::new`] would list them) into `out`.
+pub fn pack_dir(src: &Path, out: &Path, options: &PackOptions) -> Result<PackSummary, Error> {
+    if !src.is_dir() {
+        return Err(Error::NotFound);
+    }
+    let src = src.to_str().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "pack source path is not UTF-8",
+        )
+    })?;
+    let (bytes, summary) = write_pack(&Silo::new(src), options)?;
+    std::fs::write(out, bytes)?;
+    Ok(summary)
+}
+
+/// Serializes the files of `silo` into pack bytes.
+pub fn pack_silo(silo: &Silo, options: &PackOptions) -> Result<Vec<u8>, Error> {
+    Ok(write_pack(silo, options)?.0)
+}
+
+fn write_pack(silo: &Silo, options: &PackOptions) -> Result<(Vec<u8>, PackSummary), Error> {
+    let codec = match &options.codec {
+        _ if !options.compress => None,
+        Some(codec) => Some(codec.clone()),
+        None => Some(
+            crate::codec::builtin("raw-deflate")
+                .ok_or_else(|| invalid("compression requires the `deflate` feature"))?,
+        ),
+    };
+    if let Some(codec) = &codec
+        && codec.name().len() > u8::MAX as usize
+    {
+        return Err(invalid(format!("codec name {} is too long", codec.name())));
+    }
+    let mut files = silo.iter().collect::<Vec<_>>();
+    files.sort_by(|a, b| a.path_str().cmp(b.path_str()));
+    files.dedup_by(|a, b| a.path_str() == b.path_str());
+
+    let mut index = Vec::new();
+    let mut data = Vec::new();
+    let mut summary = PackSummary {
+        files: files.len(),
+        ..PackSummary::default()
+    };
+    for file in &files {
+        let contents = file.contents()?;
+        let mut flags = 0;
+        let mut hash = 0;
+        if options.hashes {
+            flags |= FLAG_HASH;
+            hash = ContentHasher::hash(&contents);
+        }
+        let compressed = codec
+            .as_ref()
+            .map(|codec| (codec, codec.compress(&contents)))
+            .filter(|(_, compressed)| compressed.len() < contents.len());
+        if compressed.is_some() {
+            flags |= FLAG_CODEC;
+        }
+        let path = file.path_str();
+        index.extend_from_slice(&(path.len() as u32).to_le_bytes());
+        index.extend_from_slice(path.as_bytes());
+        index.push(flags);
+        let stored = match compressed {
+            Some((codec, compressed)) => {
+                index.push(codec.name().len() as u8);
+                index.extend_from_slice(codec.name().as_bytes());
+                Cow::Owned(compressed)
+            }
+            None => contents.clone(),
+        };
+        for value in [
+            data.len() as u64,
+            stored.len() as u64,
+            contents.len() as u64,
+            file.meta()?.modified,
+            hash,
+        ] {
+            index.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&stored);
+        summary.original_bytes += contents.len() as u64;
+    }
+    summary.stored_bytes = data.len() as u64;
+
+    let mut body = index;
+    body.extend_from_slice(&data);
+    let mut pack = Vec::with_capacity(HEADER_LEN + body.len());
+    pack.extend_from_slice(MAGIC);
+    pack.extend_from_slice(&PACK_VERSION.to_le_bytes());
+    pack.extend_from_slice(&0u16.to_le_bytes());
+    pack.extend_from_slice(&(files.len() as u32).to_le_bytes());
+    pack.extend_from_slice(&((HEADER_LEN + body.len() - data.len()) as u64).to_le_bytes());
+    pack.extend_from_slice(&ContentHasher::hash(&body).to_le_bytes());
+    pack.extend_from_slice(&body);
+    summary.pack_bytes = pack.len() as u64;
+    Ok((pack, summary))
+}
+
+impl Silo {
+    /// Loads a pack written by [`pack_dir`], usually `include_bytes!`-ed.
+    ///
+    /// The header, checksum and every index record are validated up front, so a truncated,
+    /// corrupted or newer-format pack is an [`Error::PackError`] here rather than a panic later.
+    /// Lookups binary-search the sorted index. Uncompressed packs are served straight from
+    /// `bytes`; a pack with compressed entries is decompressed into a heap arena once, here,
+    /// which only knows the built-in codecs, see [`Silo::from_pack_bytes_with_codecs`].
+    pub fn from_pack_bytes(bytes: &'static [u8]) -> Result<Silo, Error> {
+        Self::from_pack_bytes_with_codecs(bytes, &[])
+    }
+
+    /// Like [`Silo::from_pack_bytes`], decompressing entries with the codec of the recorded
+    /// name from `codecs`, or else a built-in one. An entry stored with a codec found in
+    /// neither is an [`Error::PackError`].
+    pub fn from_pack_bytes_with_codecs(
+        bytes: &'static [u8],
+        codecs: &[Arc<dyn Codec>],
+    ) -> Result<Silo, Error> {
+        Ok(Self {
+            inner: InnerSilo::Memory(Arc::new(parse(bytes, codecs)?)),
+            index: None,
+            name: None,
+            dyn_options: DynOptions::NONE,
+            view: View::NONE,
+        })
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> Error {
+    Error::PackError {
+        reason: reason.into(),
+    }
+}
+
+/// Bounds-checked little-endian reader over the index.
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len());
+        let slice = &self.bytes[self.pos..end.ok_or_else(|| invalid("index truncated"))?];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+fn parse(bytes: &'static [u8], codecs: &[Arc<dyn Codec>]) -> Result<MemStore, Error> {
+    if bytes.len() < HEADER_LEN || &bytes[..8] != MAGIC {
+        return Err(invalid("not a silo pack (bad magic)"));
+    }
+    let mut header = Cursor { bytes, pos: 8 };
+    let version = u16::from_le_bytes(header.take(2)?.try_into().unwrap());
+    if version != PACK_VERSION {
+        return Err(invalid(format!(
+            "unsupported format version {version} (expected {PACK_VERSION})"
+        )));
+    }
+    header.take(2)?;
+    let count = header.u32()? as usize;
+    let data_offset =
+        usize::try_from(header.u64()?).map_err(|_| invalid("data offset out of range"))?;
+    let checksum = header.u64()?;
+    if ContentHasher::hash(&bytes[HEADER_LEN..]) != checksum {
+        return Err(invalid("checksum mismatch"));
+    }
+    if data_offset < HEADER_LEN || data_offset > bytes.len() {
+        return Err(invalid("data offset out of range"));
+    }
+    let data = &bytes[data_offset..];
+
+    let mut index = Cursor {
+        bytes: &bytes[..data_offset],
+        pos: HEADER_LEN,
+    };
+    // Every record is at least RECORD_LEN bytes, so a bogus count fails before allocating.
+    if count > (data_offset - HEADER_LEN) / RECORD_LEN {
+        return Err(invalid("entry count exceeds index size"));
+    }
+    let mut entries: Vec<MemEntry> = Vec::with_capacity(count);
+    // Codec and original length of each entry, for those stored compressed.
+    let mut compressed: Vec<Option<(Arc<dyn Codec>, u64)>> = Vec::with_capacity(count);
+    for _ in 0..count {
+        let path_len = index.u32()? as usize;
+        let path =
+            std::str::from_utf8(index.take(path_len)?).map_err(|_| invalid("path is not UTF-8"))?;
+        if entries.last().is_some_and(|last| *last.path >= *path) {
+            return Err(invalid("index is not sorted"));
+        }
+        let flags = index.u8()?;
+        if flags & !(FLAG_HASH | FLAG_CODEC) != 0 {
+            return Err(invalid(format!("unknown flags on {path}")));
+        }
+        let codec = if flags & FLAG_CODEC != 0 {
+            let name_len = index.u8()? as usize;
+            let name = std::str::from_utf8(index.take(name_len)?)
+                .map_err(|_| invalid(format!("codec name of {path} is not UTF-8")))?;
+            let codec = codecs
+                .iter()
+                .find(|codec| codec.name() == name)
+                .cloned()
+                .or_else(|| crate::codec::builtin(name))
+                .ok_or_else(|| invalid(format!("{path} is stored with unknown codec {name}")))?;
+            Some(codec)
+        } else {
+            None
+        };
+        let [offset, stored, original, modified, hash] = [
+            index.u64()?,
+            index.u64()?,
+            index.u64()?,
+            index.u64()?,
+            index.u64()?,
+        ];
+        let range = offset
+            .checked_add(stored)
+            .filter(|&end| end <= data.len() as u64)
+            .map(|end| offset as usize..end as usize)
+            .ok_or_else(|| invalid(format!("data of {path} out of range")))?;
+        if codec.is_none() && stored != original {
+            return Err(invalid(format!("length mismatch for {path}")));
+        }
+        compressed.push(codec.map(|codec| (codec, original)));
+        entries.push(MemEntry {
+            path: path.into(),
+            range,
+            modified,
+            hash: (flags & FLAG_HASH != 0).then_some(hash),
+        });
+    }
+    if index.pos != data_offset {
+        return Err(invalid("trailing bytes after index"));
+    }
+    if compressed.iter().all(Option::is_none) {
+        return Ok(MemStore::from_parts(entries, Cow::Borrowed(data)));
+    }
+    decompress_all(entries, &compressed, data)
+}
+
+/// Copies every entry into a heap arena, decompressing the compressed ones.
+fn decompress_all(
+    mut entries: Vec<MemEntry>,
+    compressed: &[Option<(Arc<dyn Codec>, u64)>],
+    data: &[u8],
+) -> Result<MemStore, Error> {
+    let mut arena = Vec::new();
+    for (entry, compressed) in entries.iter_mut().zip(compressed) {
+        let stored = &data[entry.range.clone()];
+        let start = arena.len();
+        match compressed {
+            Some((codec, original)) => {
+                let inflated = codec
+                    .decompress(stored)
+                    .map_err(|err| invalid(format!("cannot decompress {}: {err}", entry.path)))?;
+                if inflated.len() as u64 != *original {
+                    return Err(invalid(format!("length mismatch for {}", entry.path)));
+                }
+                arena.extend_from_slice(&inflated);
+            }
+            None => arena.extend_from_slice(stored),
+        }
+        entry.range = start..arena.len();
+    }
+    Ok(MemStore::from_parts(entries, Cow::Owned(arena)))
+}
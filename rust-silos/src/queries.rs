@@ -0,0 +1,109 @@
+//! Size and recency queries for dashboards and reports, see [`Silo::top_by_size`] and
+//! [`Silo::modified_since`].
+
+use crate::{File, Silo, SiloSet};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+impl Silo {
+    /// The `n` largest files, largest first; files of equal size are ordered by path. Only
+    /// `n` files are held at a time, so this runs in bounded memory over any silo. Files
+    /// whose metadata cannot be read are skipped.
+    pub fn top_by_size(&self, n: usize) -> Vec<File> {
+        top_by_size(self.iter(), n)
+    }
+
+    /// Files modified at or after `since`, compared in whole seconds, in iteration order.
+    ///
+    /// Embedded files use the mtime recorded at embed time. Files whose modification time is
+    /// unknown (recorded as 0, e.g. in-memory files built without one) or whose metadata
+    /// cannot be read are never returned.
+    pub fn modified_since(&self, since: SystemTime) -> impl Iterator<Item = File> + '_ {
+        modified_since(self.iter(), since)
+    }
+}
+
+impl SiloSet {
+    /// Like [`Silo::top_by_size`], over the override view.
+    pub fn top_by_size(&self, n: usize) -> Vec<File> {
+        top_by_size(self.iter_override(), n)
+    }
+
+    /// Like [`Silo::modified_since`], over the override view.
+    pub fn modified_since(&self, since: SystemTime) -> impl Iterator<Item = File> + '_ {
+        modified_since(self.iter_override(), since)
+    }
+}
+
+/// A file ranked by size, then by path with the smaller path ranking higher.
+struct Ranked {
+    size: usize,
+    path: Reverse<String>,
+    file: File,
+}
+
+impl Ranked {
+    fn key(&self) -> (usize, &Reverse<String>) {
+        (self.size, &self.path)
+    }
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+fn top_by_size(files: impl Iterator<Item = File>, n: usize) -> Vec<File> {
+    if n == 0 {
+        return Vec::new();
+    }
+    // Min-heap of the best `n` so far; its root is the one to evict.
+    let mut heap = BinaryHeap::new();
+    for file in files {
+        let Ok(meta) = file.meta() else { continue };
+        let ranked = Ranked {
+            size: meta.size,
+            path: Reverse(file.path().to_string_lossy().into_owned()),
+            file,
+        };
+        if heap.len() < n {
+            heap.push(Reverse(ranked));
+        } else if heap.peek().is_some_and(|Reverse(lowest)| ranked > *lowest) {
+            heap.pop();
+            heap.push(Reverse(ranked));
+        }
+    }
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| ranked.file)
+        .collect()
+}
+
+fn modified_since(
+    files: impl Iterator<Item = File>,
+    since: SystemTime,
+) -> impl Iterator<Item = File> {
+    let since = since
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    files.filter(move |file| {
+        file.meta()
+            .is_ok_and(|meta| meta.modified != 0 && meta.modified >= since)
+    })
+}
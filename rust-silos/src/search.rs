@@ -0,0 +1,224 @@
+//! Byte-string search across the files of a silo.
+//!
+//! In-memory contents are scanned as one slice; dynamic files are streamed in chunks, keeping
+//! `needle.len() - 1` bytes of overlap so matches spanning a chunk boundary are found while
+//! memory stays bounded by the chunk size.
+
+use crate::{Error, File, FileKind, Silo};
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Options for [`Silo::find_bytes`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SearchOptions {
+    /// Stop after this many matches in a single file.
+    pub max_matches_per_file: Option<usize>,
+    /// Compare ASCII letters case-insensitively; other bytes must match exactly.
+    pub ascii_case_insensitive: bool,
+    /// Only search files whose relative path matches this glob: `*` and `?` match within a
+    /// path segment, `**` matches across segments (e.g. `"logs/**/*.log"`).
+    pub path_glob: Option<String>,
+    /// Bytes read at a time from dynamic files (default 64 KiB).
+    pub chunk_size: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            max_matches_per_file: None,
+            ascii_case_insensitive: false,
+            path_glob: None,
+            chunk_size: 64 * 1024,
+        }
+    }
+}
+
+/// An occurrence of the needle, see [`Silo::find_bytes`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Match {
+    /// Relative path of the file.
+    pub path: PathBuf,
+    /// Byte offset of the match within the file contents.
+    pub offset: u64,
+}
+
+impl Silo {
+    /// Finds every occurrence of `needle` in the files of this silo, file by file.
+    ///
+    /// Offsets refer to the contents as returned by [`File::contents`] (after transcoding,
+    /// if enabled). Matches never overlap within a file. A file that cannot be read yields
+    /// one error and the search moves on. An empty needle matches nothing.
+    ///
+    /// # Panics
+    /// Panics if `opts.chunk_size` is 0.
+    pub fn find_bytes(
+        &self,
+        needle: &[u8],
+        opts: SearchOptions,
+    ) -> impl Iterator<Item = Result<Match, Error>> + '_ {
+        assert!(opts.chunk_size > 0, "chunk_size must be non-zero");
+        let needle = needle.to_vec();
+        let glob = opts.path_glob.clone();
+        let searchable = !needle.is_empty();
+        self.iter()
+            .filter(move |file| {
+                searchable
+                    && glob
+                        .as_deref()
+                        .is_none_or(|glob| glob_match(glob, &file.path().to_string_lossy()))
+            })
+            .map(move |file| {
+                let limit = opts.max_matches_per_file.unwrap_or(usize::MAX);
+                let offsets = search_file(
+                    &file,
+                    &needle,
+                    opts.ascii_case_insensitive,
+                    limit,
+                    opts.chunk_size,
+                );
+                let path = file.path().to_path_buf();
+                offsets.map(|offsets| {
+                    offsets.into_iter().map(move |offset| {
+                        Ok(Match {
+                            path: path.clone(),
+                            offset,
+                        })
+                    })
+                })
+            })
+            .flat_map(|result| -> Box<dyn Iterator<Item = Result<Match, Error>>> {
+                match result {
+                    Ok(matches) => Box::new(matches),
+                    Err(err) => Box::new(std::iter::once(Err(err))),
+                }
+            })
+    }
+}
+
+/// Returns the offsets of up to `limit` non-overlapping matches of `needle` in `file`.
+fn search_file(
+    file: &File,
+    needle: &[u8],
+    ci: bool,
+    limit: usize,
+    chunk_size: usize,
+) -> Result<Vec<u64>, Error> {
+    let mut offsets = Vec::new();
+    if let FileKind::Embed(_) | FileKind::Memory(_) = &file.inner {
+        let contents = file.contents()?;
+        find_in(&contents, needle, ci, 0, limit, &mut offsets);
+        return Ok(offsets);
+    }
+    let mut reader = file.reader()?;
+    // `buf` holds the carried-over tail of the previous chunk followed by the new one.
+    let mut buf: Vec<u8> = Vec::with_capacity(chunk_size + needle.len());
+    // Absolute offset of `buf[0]`, and the first position a new match may start at.
+    let mut base = 0u64;
+    let mut skip = 0usize;
+    let mut chunk = vec![0u8; chunk_size];
+    while offsets.len() < limit {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..read]);
+        find_in(
+            &buf[skip..],
+            needle,
+            ci,
+            base + skip as u64,
+            limit,
+            &mut offsets,
+        );
+        let keep = buf.len().min(needle.len() - 1);
+        let drop = buf.len() - keep;
+        // A match ending inside the carry-over must not be found again.
+        skip = match offsets.last() {
+            Some(&last) if last + needle.len() as u64 > base + drop as u64 => {
+                (last + needle.len() as u64 - base) as usize - drop
+            }
+            _ => 0,
+        };
+        buf.drain(..drop);
+        base += drop as u64;
+    }
+    Ok(offsets)
+}
+
+/// Appends the offsets (shifted by `base`) of non-overlapping matches in `hay` until
+/// `offsets` holds `limit` entries.
+fn find_in(hay: &[u8], needle: &[u8], ci: bool, base: u64, limit: usize, offsets: &mut Vec<u64>) {
+    let mut pos = 0;
+    while offsets.len() < limit && pos + needle.len() <= hay.len() {
+        let window = &hay[pos..pos + needle.len()];
+        let found = if ci {
+            window.eq_ignore_ascii_case(needle)
+        } else {
+            window == needle
+        };
+        if found {
+            offsets.push(base + pos as u64);
+            pos += needle.len();
+        } else {
+            pos += 1;
+        }
+    }
+}
+
+/// Matches a `/`-separated path against a glob with `*`, `?` and `**`.
+///
+/// `?` matches one character. Runs in time proportional to the product of the lengths at
+/// worst, so untrusted globs cannot make it backtrack exponentially.
+#[deny(clippy::indexing_slicing)]
+pub(crate) fn glob_match(glob: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = glob.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    wildcard(
+        &pattern,
+        &path,
+        |&segment| segment == "**",
+        |pattern, name| {
+            let pattern: Vec<char> = pattern.chars().collect();
+            let name: Vec<char> = name.chars().collect();
+            wildcard(&pattern, &name, |&c| c == '*', |&p, &c| p == '?' || p == c)
+        },
+    )
+}
+
+/// Matches `text` against `pattern`, where items satisfying `is_star` match any run of items
+/// and every other item matches one item for which `matches` holds. A later star makes an
+/// earlier one's extent irrelevant, so only the most recent star is ever retried.
+#[deny(clippy::indexing_slicing)]
+fn wildcard<P, T>(
+    pattern: &[P],
+    text: &[T],
+    is_star: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last star seen and the text position it currently extends to.
+    let mut retry: Option<(usize, usize)> = None;
+    while let Some(item) = text.get(t) {
+        match pattern.get(p) {
+            Some(star) if is_star(star) => {
+                retry = Some((p, t));
+                p += 1;
+            }
+            Some(single) if matches(single, item) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match retry {
+                Some((star, extent)) => {
+                    retry = Some((star, extent + 1));
+                    p = star + 1;
+                    t = extent + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern
+        .get(p..)
+        .is_some_and(|rest| rest.iter().all(is_star))
+}
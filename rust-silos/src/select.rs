@@ -0,0 +1,55 @@
+//! Deterministic weighted choice between asset variants, e.g. for A/B tests.
+
+use crate::{File, Silo, SiloSet};
+
+impl Silo {
+    /// Picks one of the `(path, weight)` candidates for `key` (e.g. a user id), with
+    /// probability proportional to its weight over many keys.
+    ///
+    /// The same key and candidates always give the same file. Candidates missing from the
+    /// silo are skipped and the remaining weights renormalized. Returns `None` only if no
+    /// candidate exists; if every existing candidate has weight 0, the first of them is
+    /// returned.
+    pub fn select(&self, candidates: &[(&str, u32)], key: u64) -> Option<File> {
+        select(candidates, key, |path| self.get_file(path))
+    }
+}
+
+impl SiloSet {
+    /// Like [`Silo::select`], resolving each candidate through the overlay.
+    pub fn select(&self, candidates: &[(&str, u32)], key: u64) -> Option<File> {
+        select(candidates, key, |path| self.get_file(path))
+    }
+}
+
+fn select(
+    candidates: &[(&str, u32)],
+    key: u64,
+    get_file: impl Fn(&str) -> Option<File>,
+) -> Option<File> {
+    let present: Vec<(File, u32)> = candidates
+        .iter()
+        .filter_map(|&(path, weight)| Some((get_file(path)?, weight)))
+        .collect();
+    let total: u64 = present.iter().map(|(_, weight)| u64::from(*weight)).sum();
+    if total == 0 {
+        return present.into_iter().next().map(|(file, _)| file);
+    }
+    // Scale the mixed key into [0, total) without modulo bias.
+    let mut point = ((u128::from(mix(key)) * u128::from(total)) >> 64) as u64;
+    for (file, weight) in present {
+        if point < u64::from(weight) {
+            return Some(file);
+        }
+        point -= u64::from(weight);
+    }
+    unreachable!("point is below the total weight")
+}
+
+/// SplitMix64 finalizer, so that sequential keys spread over the whole range.
+fn mix(key: u64) -> u64 {
+    let mut z = key.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,453 @@
+//! Resolve file references in deserialized configuration against a silo.
+//!
+//! A field typed as [`SiloRef<T>`] is written as a path string in the source document and
+//! holds the referenced file once deserialized:
+//!
+//! ```ignore
+//! #[derive(serde::Deserialize)]
+//! struct Config {
+//!     banner: SiloRef<String>,
+//!     logo: SiloRef<Vec<u8>>,
+//!     stylesheet: SiloRef<File>,
+//! }
+//!
+//! let config: Config = rust_silos::serde::with_silo(&silo, || serde_json::from_str(json))?;
+//! ```
+//!
+//! Derived `Deserialize` impls cannot pass state down to their fields, so the silo is installed
+//! for the current thread while the closure runs. Deserializing a `SiloRef` anywhere else is an
+//! error.
+//!
+//! Errors name the path that failed to resolve. Through [`SiloSeed`], which sees the
+//! deserializer, they also name the field it was read from, e.g. `servers[0].tls_cert`.
+
+use crate::{Error, File, Silo};
+use ::serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor,
+};
+use ::serde::{Serialize, Serializer};
+use std::cell::RefCell;
+use std::marker::PhantomData;
+
+thread_local! {
+    static CURRENT: RefCell<Vec<Silo>> = const { RefCell::new(Vec::new()) };
+    /// Keys and indices leading to the value being deserialized through [`SiloSeed`].
+    static FIELD: RefCell<Vec<Segment>> = const { RefCell::new(Vec::new()) };
+    /// The map key most recently read through [`SiloSeed`].
+    static LAST_KEY: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `silo` as the silo that [`SiloRef`] fields are resolved against.
+///
+/// Calls may nest; the innermost silo wins. The silo is only visible to deserialization
+/// running on the current thread.
+pub fn with_silo<R>(silo: &Silo, f: impl FnOnce() -> R) -> R {
+    struct Restore;
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+    CURRENT.with(|stack| stack.borrow_mut().push(silo.clone()));
+    let _restore = Restore;
+    f()
+}
+
+/// [`DeserializeSeed`] that deserializes a `T` with [`SiloRef`] fields resolved against a silo,
+/// for formats that expose a [`Deserializer`] directly.
+pub struct SiloSeed<'a, T> {
+    silo: &'a Silo,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> SiloSeed<'a, T> {
+    pub fn new(silo: &'a Silo) -> Self {
+        Self {
+            silo,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for SiloSeed<'_, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        with_silo(self.silo, || {
+            T::deserialize(Tracked {
+                inner: deserializer,
+                key: false,
+            })
+        })
+    }
+}
+
+/// Types a [`SiloRef`] can resolve a file into.
+pub trait FromSiloFile: Sized {
+    fn from_silo_file(file: File) -> Result<Self, Error>;
+}
+
+impl FromSiloFile for Vec<u8> {
+    fn from_silo_file(file: File) -> Result<Self, Error> {
+        Ok(file.contents()?.into_owned())
+    }
+}
+
+impl FromSiloFile for String {
+    fn from_silo_file(file: File) -> Result<Self, Error> {
+        Ok(file.text()?.into_owned())
+    }
+}
+
+/// Defers reading: the file handle is resolved, its contents are not.
+impl FromSiloFile for File {
+    fn from_silo_file(file: File) -> Result<Self, Error> {
+        Ok(file)
+    }
+}
+
+/// A path in a silo, resolved to `T` during deserialization. Serializes back to the path.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SiloRef<T> {
+    path: String,
+    value: T,
+}
+
+impl<T> SiloRef<T> {
+    /// The path as written in the source document.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for SiloRef<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Serialize for SiloRef<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.path)
+    }
+}
+
+impl<'de, T: FromSiloFile> Deserialize<'de> for SiloRef<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        let file = CURRENT.with(|stack| {
+            let stack = stack.borrow();
+            let silo = stack.last().ok_or_else(|| {
+                de::Error::custom(format!(
+                    "cannot resolve silo path `{path}` outside rust_silos::serde::with_silo"
+                ))
+            })?;
+            silo.get_file(&path).ok_or_else(|| {
+                de::Error::custom(format!("silo path `{path}` not found{}", field_context()))
+            })
+        })?;
+        let value = T::from_silo_file(file).map_err(|err| {
+            de::Error::custom(format!("silo path `{path}`{}: {err}", field_context()))
+        })?;
+        Ok(Self { path, value })
+    }
+}
+
+/// A step from a value to one nested in it.
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// ` (field `a.b[2]`)` for the field being deserialized through [`SiloSeed`], or nothing.
+fn field_context() -> String {
+    FIELD.with(|field| {
+        let mut path = String::new();
+        for segment in field.borrow().iter() {
+            match segment {
+                Segment::Key(key) if path.is_empty() => path.push_str(key),
+                Segment::Key(key) => {
+                    path.push('.');
+                    path.push_str(key);
+                }
+                Segment::Index(index) => path.push_str(&format!("[{index}]")),
+            }
+        }
+        if path.is_empty() {
+            path
+        } else {
+            format!(" (field `{path}`)")
+        }
+    })
+}
+
+/// Runs `f` with `segment` appended to the current field path.
+fn within<R>(segment: Segment, f: impl FnOnce() -> R) -> R {
+    FIELD.with(|field| field.borrow_mut().push(segment));
+    let result = f();
+    FIELD.with(|field| field.borrow_mut().pop());
+    result
+}
+
+/// Deserializer that records map keys and sequence indices on the way down, for
+/// [`field_context`]. With `key` set, it is reading a map key and records the string read.
+struct Tracked<D> {
+    inner: D,
+    key: bool,
+}
+
+/// Forwards `deserialize_*` methods to the inner deserializer with a tracking visitor.
+macro_rules! forward_tracked {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+                self.inner.$method($($arg,)* TrackedVisitor { inner: visitor, key: self.key })
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Tracked<D> {
+    type Error = D::Error;
+
+    forward_tracked! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor that hands tracking wrappers to the visitor it wraps.
+struct TrackedVisitor<V> {
+    inner: V,
+    key: bool,
+}
+
+/// Forwards `visit_*` methods that carry a plain value.
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for TrackedVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        self.record(v);
+        self.inner.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        self.record(v);
+        self.inner.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        self.record(&v);
+        self.inner.visit_string(v)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(Tracked {
+            inner: deserializer,
+            key: false,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(Tracked {
+            inner: deserializer,
+            key: false,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(TrackedSeq {
+            inner: seq,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(TrackedMap {
+            inner: map,
+            key: None,
+        })
+    }
+
+    // Enum variants are not descended into; values inside report the enclosing field.
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<V> TrackedVisitor<V> {
+    fn record(&self, key: &str) {
+        if self.key {
+            LAST_KEY.with(|last| *last.borrow_mut() = Some(key.to_owned()));
+        }
+    }
+}
+
+/// Seed that deserializes through [`Tracked`].
+struct TrackedSeed<S> {
+    inner: S,
+    key: bool,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for TrackedSeed<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.inner.deserialize(Tracked {
+            inner: deserializer,
+            key: self.key,
+        })
+    }
+}
+
+struct TrackedSeq<A> {
+    inner: A,
+    index: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for TrackedSeq<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, A::Error> {
+        let index = self.index;
+        self.index += 1;
+        within(Segment::Index(index), || {
+            self.inner.next_element_seed(TrackedSeed {
+                inner: seed,
+                key: false,
+            })
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct TrackedMap<A> {
+    inner: A,
+    /// The key of the value to be read next, if it was a string.
+    key: Option<String>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for TrackedMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        LAST_KEY.with(|last| last.borrow_mut().take());
+        let key = self.inner.next_key_seed(TrackedSeed {
+            inner: seed,
+            key: true,
+        });
+        self.key = LAST_KEY.with(|last| last.borrow_mut().take());
+        key
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let key = self.key.take().unwrap_or_else(|| "?".to_owned());
+        within(Segment::Key(key), || {
+            self.inner.next_value_seed(TrackedSeed {
+                inner: seed,
+                key: false,
+            })
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,563 @@
+//! Framework-neutral static file serving: resolves a request target against a silo and
+//! builds the status, headers and body, including conditional `304 Not Modified` handling.
+
+use crate::bundle::BundleReader;
+use crate::conditional::{
+    self, ConditionalResult, FileConditionalMeta, MONTHS, RequestConditionals, WEEKDAYS,
+};
+use crate::header_profile::HeaderRules;
+use crate::{ContentHasher, DynOptions, Error, File, FileReader, Silo};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Conditional request headers consulted by [`Silo::serve`].
+///
+/// Build it field by field, or collect it from `(name, value)` pairs (names are matched
+/// case-insensitively; unrelated headers are ignored).
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RequestHeaders {
+    pub if_match: Option<String>,
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+    pub if_unmodified_since: Option<String>,
+    /// Consulted with [`Silo::with_dynamic_compression`](crate::Silo::with_dynamic_compression).
+    pub accept_encoding: Option<String>,
+    /// `no-transform` opts out of on-the-fly compression.
+    pub cache_control: Option<String>,
+    /// A single `bytes=` range yields 206; a list of ranges is ignored and the whole file served.
+    pub range: Option<String>,
+    pub if_range: Option<String>,
+}
+
+impl RequestHeaders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for RequestHeaders {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Self {
+        let mut headers = Self::new();
+        for (name, value) in pairs {
+            let name = name.as_ref();
+            let slot = match name {
+                _ if name.eq_ignore_ascii_case("if-match") => &mut headers.if_match,
+                _ if name.eq_ignore_ascii_case("if-none-match") => &mut headers.if_none_match,
+                _ if name.eq_ignore_ascii_case("if-modified-since") => {
+                    &mut headers.if_modified_since
+                }
+                _ if name.eq_ignore_ascii_case("if-unmodified-since") => {
+                    &mut headers.if_unmodified_since
+                }
+                _ if name.eq_ignore_ascii_case("accept-encoding") => &mut headers.accept_encoding,
+                _ if name.eq_ignore_ascii_case("cache-control") => &mut headers.cache_control,
+                _ if name.eq_ignore_ascii_case("range") => &mut headers.range,
+                _ if name.eq_ignore_ascii_case("if-range") => &mut headers.if_range,
+                _ => continue,
+            };
+            *slot = Some(value.as_ref().to_owned());
+        }
+        headers
+    }
+}
+
+/// Response produced by [`Silo::serve`], to be mapped onto a web framework's response type.
+pub struct HttpFileResponse {
+    /// 200, 206 for a byte range, 301 for a redirected alias, 304, 400 for a bundle request
+    /// without paths, 404, 412 for a failed `If-Match` or `If-Unmodified-Since`, 416 for a
+    /// range past the end, or 500 if the file could not be read.
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    /// File contents; `None` unless the status is 200 or 206, or the 404 or 500 has a page
+    /// (see [`Silo::with_not_found_page`]).
+    pub body: Option<ResponseBody>,
+}
+
+/// Body of an [`HttpFileResponse`].
+pub enum ResponseBody {
+    /// The file contents as stored.
+    File(FileReader),
+    /// The contents compressed while they are read, see
+    /// [`Silo::with_dynamic_compression`](crate::Silo::with_dynamic_compression).
+    #[cfg(feature = "compression")]
+    Encoded(Box<dyn Read + Send>),
+    /// The requested byte range of the contents.
+    Range(std::io::Take<FileReader>),
+    /// Several files, see [`Silo::serve_bundle`].
+    Bundle(Box<BundleReader>),
+    /// A page or message held in memory, see [`Silo::with_not_found_page`].
+    Bytes(std::io::Cursor<Vec<u8>>),
+}
+
+impl Read for ResponseBody {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            ResponseBody::File(reader) => reader.read(buf),
+            #[cfg(feature = "compression")]
+            ResponseBody::Encoded(reader) => reader.read(buf),
+            ResponseBody::Range(reader) => reader.read(buf),
+            ResponseBody::Bundle(reader) => reader.read(buf),
+            ResponseBody::Bytes(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl HttpFileResponse {
+    pub(crate) fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// Returns the value of the header `name` (case-insensitive), if set.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl std::fmt::Debug for HttpFileResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpFileResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &self.body.is_some())
+            .finish()
+    }
+}
+
+impl Silo {
+    /// Serves `request_target` (e.g. `"/css/site.css?v=3"`) from this silo.
+    ///
+    /// The target is resolved with [`Silo::get_file_url`], so traversal attempts are 404s.
+    /// Found files get `Content-Type`, `Content-Length`, a strong `ETag` derived from
+    /// [`File::content_hash`] and `Last-Modified`. Conditional headers are evaluated by
+    /// [`conditional::evaluate`]: a matching `If-None-Match` (or, when absent,
+    /// `If-Modified-Since`) yields 304, a failed `If-Match` or `If-Unmodified-Since` 412.
+    /// A single `Range` of a file with a known length is answered with 206 and
+    /// `Content-Range`, unless a non-matching `If-Range` asks for the whole file; a range
+    /// starting past the end is 416. Compressed responses are always whole.
+    ///
+    /// Dynamic files are read in full with [`File::contents_consistent`], so the length, ETag
+    /// and body all describe the same contents even while the file is being rewritten. A file
+    /// that never settles is streamed as-is without `Content-Length` or `ETag`, leaving the
+    /// framework to fall back to chunked transfer. Files compressed on the fly (see
+    /// `Silo::with_dynamic_compression`) are streamed through the encoder instead, with an
+    /// ETag from a separate pass over the file.
+    ///
+    /// With [`AliasOptions::redirect`](crate::AliasOptions::redirect), a request for an alias
+    /// is answered with 301 and a `Location` of the target path (absolute from the silo root,
+    /// query kept) instead of the file.
+    ///
+    /// Headers from [`Silo::with_header_profile`] and [`Silo::with_headers_for`] are added
+    /// once the content type is resolved, before conditional headers are evaluated.
+    ///
+    /// Misses and read failures are answered with the pages set by
+    /// [`Silo::with_not_found_page`] and [`Silo::with_error_page`], if any.
+    pub fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse {
+        if self.alias_options().is_some_and(|options| options.redirect)
+            && let Some(target) =
+                decode_target(request_target).and_then(|path| self.alias_target(&path))
+        {
+            return redirect(target, request_target);
+        }
+        let Some(file) = self.get_file_url(request_target) else {
+            return self.error_response(404);
+        };
+        self.serve_found(&file, headers)
+    }
+
+    /// Serves `file`, found in this silo, with this silo's header rules and dynamic options.
+    pub(crate) fn serve_found(&self, file: &File, headers: &RequestHeaders) -> HttpFileResponse {
+        serve_file(
+            file,
+            headers,
+            self.view.headers.as_deref(),
+            &self.dyn_options,
+        )
+        .unwrap_or_else(|_| self.error_response(500))
+    }
+
+    /// Makes [`Silo::serve`] answer misses (including rejected targets) with the file at
+    /// `path` in this silo, e.g. a branded `404.html`, keeping the 404 status.
+    ///
+    /// The page is sent with its `Content-Type` and `Content-Length` only: no validators,
+    /// header rules or compression, and conditional headers are not evaluated. If the page
+    /// is itself missing or unreadable, the body is a plain-text `404 Not Found` instead;
+    /// the page is never looked up through [`Silo::serve`], so a missing page cannot recurse.
+    ///
+    /// A [`SiloSet`](crate::SiloSet) answers its misses with the page of its
+    /// highest-precedence member that sets one.
+    pub fn with_not_found_page(mut self, path: &str) -> Self {
+        Arc::make_mut(self.view.pages.get_or_insert_default()).not_found =
+            Some(path.replace('\\', "/").into());
+        self
+    }
+
+    /// Makes [`Silo::serve`] answer files that fail to read (status 500) with the file at
+    /// `path` in this silo, e.g. `500.html`, falling back to a plain-text
+    /// `500 Internal Server Error`. See [`Silo::with_not_found_page`] for how pages are sent.
+    pub fn with_error_page(mut self, path: &str) -> Self {
+        Arc::make_mut(self.view.pages.get_or_insert_default()).error =
+            Some(path.replace('\\', "/").into());
+        self
+    }
+
+    /// Whether [`Silo::with_not_found_page`] is set.
+    pub(crate) fn has_not_found_page(&self) -> bool {
+        self.view
+            .pages
+            .as_deref()
+            .is_some_and(|pages| pages.not_found.is_some())
+    }
+
+    /// The response for a 404 or 500: the configured page, plain text if the page cannot be
+    /// read, or no body if there is no page.
+    pub(crate) fn error_response(&self, status: u16) -> HttpFileResponse {
+        let pages = self.view.pages.as_deref();
+        let page = match status {
+            404 => pages.and_then(|pages| pages.not_found.as_deref()),
+            _ => pages.and_then(|pages| pages.error.as_deref()),
+        };
+        let Some(page) = page else {
+            return HttpFileResponse::empty(status);
+        };
+        let found = self.get_file(page).and_then(|file| {
+            let contents = file.contents().ok()?;
+            Some((content_type(&file).to_owned(), contents.into_owned()))
+        });
+        let (content_type, contents) = found.unwrap_or_else(|| {
+            let text = match status {
+                404 => "404 Not Found",
+                _ => "500 Internal Server Error",
+            };
+            ("text/plain; charset=utf-8".to_owned(), text.into())
+        });
+        HttpFileResponse {
+            status,
+            headers: vec![
+                ("Content-Type", content_type),
+                ("Content-Length", contents.len().to_string()),
+            ],
+            body: Some(ResponseBody::Bytes(std::io::Cursor::new(contents))),
+        }
+    }
+
+    /// Looks up the file for a URL path or request target such as `/img/a%20b.png?v=1`.
+    ///
+    /// The query and fragment are dropped and the path is percent-decoded exactly once. The
+    /// decoded path is then split on both `/` and `\`, and any `.` or `..` segment (e.g. from
+    /// `%2e%2e%2f` or `..%5c`) or NUL byte rejects the lookup, as does malformed escaping.
+    /// Use this instead of [`Silo::get_file`] for untrusted URL input.
+    pub fn get_file_url(&self, url_path: &str) -> Option<File> {
+        self.get_file(&decode_target(url_path)?)
+    }
+}
+
+/// Pages set by [`Silo::with_not_found_page`] and [`Silo::with_error_page`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub(crate) struct ErrorPages {
+    not_found: Option<Box<str>>,
+    error: Option<Box<str>>,
+}
+
+fn redirect(target: &str, request_target: &str) -> HttpFileResponse {
+    let without_fragment = request_target.split('#').next().unwrap_or_default();
+    let query = without_fragment
+        .find('?')
+        .map_or("", |start| &without_fragment[start..]);
+    HttpFileResponse {
+        status: 301,
+        headers: vec![(
+            "Location",
+            format!("/{}{query}", crate::percent_encode_path(target)),
+        )],
+        body: None,
+    }
+}
+
+pub(crate) fn serve_file(
+    file: &File,
+    request: &RequestHeaders,
+    rules: Option<&HeaderRules>,
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))] options: &DynOptions,
+) -> Result<HttpFileResponse, Error> {
+    let meta = file.meta()?;
+    let mut extra = rules.map_or_else(Vec::new, |rules| {
+        rules.resolve(&file.path().to_string_lossy())
+    });
+    let content_type = match extra
+        .iter()
+        .position(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+    {
+        Some(at) => extra.remove(at).1,
+        None => content_type(file).to_owned(),
+    };
+    #[cfg(feature = "compression")]
+    let (coding, vary) = negotiate_compression(file, request, options, &content_type, meta.size);
+    #[cfg(feature = "compression")]
+    let streamed = coding.is_some();
+    #[cfg(not(feature = "compression"))]
+    let streamed = false;
+    // `(hash, length)` of the body, or `None` if a dynamic file would not hold still.
+    let (known, body) = if file.absolute_path().is_none() || streamed {
+        (Some((file.content_hash()?, meta.size)), file.reader()?)
+    } else {
+        match file.contents_consistent() {
+            Ok(bytes) => {
+                let bytes = bytes.into_owned();
+                let known = (ContentHasher::hash(&bytes), bytes.len());
+                (
+                    Some(known),
+                    FileReader::Consistent(std::io::Cursor::new(bytes)),
+                )
+            }
+            Err(Error::Unstable { .. }) => (None, file.reader()?),
+            Err(err) => return Err(err),
+        }
+    };
+    #[cfg(not(feature = "compression"))]
+    let vary = false;
+    #[cfg(feature = "compression")]
+    let etag = known.map(|(hash, _)| match &coding {
+        Some(codec) => format!("\"{hash:016x}-{}\"", codec.name()),
+        None => format!("\"{hash:016x}\""),
+    });
+    #[cfg(not(feature = "compression"))]
+    let etag = known.map(|(hash, _)| format!("\"{hash:016x}\""));
+    let conditionals = RequestConditionals {
+        if_match: request.if_match.clone(),
+        if_none_match: request.if_none_match.clone(),
+        if_modified_since: request.if_modified_since.clone(),
+        if_unmodified_since: request.if_unmodified_since.clone(),
+        if_range: request.if_range.clone(),
+    };
+    let validators = FileConditionalMeta {
+        etag: etag.clone(),
+        last_modified: Some(meta.modified),
+    };
+    let mut headers: Vec<_> = etag.map(|etag| ("ETag", etag)).into_iter().collect();
+    headers.push(("Last-Modified", http_date(meta.modified)));
+    if vary {
+        headers.push(("Vary", "Accept-Encoding".to_owned()));
+    }
+    headers.extend(extra);
+    let honor_range = match conditional::evaluate(&conditionals, &validators) {
+        ConditionalResult::Serve { honor_range } => honor_range,
+        ConditionalResult::NotModified => {
+            return Ok(HttpFileResponse {
+                status: 304,
+                headers,
+                body: None,
+            });
+        }
+        ConditionalResult::PreconditionFailed => return Ok(HttpFileResponse::empty(412)),
+    };
+    headers.push(("Content-Type", content_type));
+    #[cfg(feature = "compression")]
+    if let Some(codec) = coding {
+        headers.push(("Content-Encoding", codec.name().to_owned()));
+        return Ok(HttpFileResponse {
+            status: 200,
+            headers,
+            body: Some(ResponseBody::Encoded(codec.compress_stream(Box::new(body)))),
+        });
+    }
+    let Some((_, length)) = known else {
+        return Ok(HttpFileResponse {
+            status: 200,
+            headers,
+            body: Some(ResponseBody::File(body)),
+        });
+    };
+    headers.push(("Accept-Ranges", "bytes".to_owned()));
+    match request
+        .range
+        .as_deref()
+        .filter(|_| honor_range)
+        .and_then(|range| parse_range(range, length))
+    {
+        Some(ByteRange::Satisfiable(range)) => {
+            let mut body = body;
+            body.seek(SeekFrom::Start(range.start as u64))?;
+            headers.push((
+                "Content-Range",
+                format!("bytes {}-{}/{length}", range.start, range.end - 1),
+            ));
+            headers.push(("Content-Length", range.len().to_string()));
+            Ok(HttpFileResponse {
+                status: 206,
+                headers,
+                body: Some(ResponseBody::Range(body.take(range.len() as u64))),
+            })
+        }
+        Some(ByteRange::Unsatisfiable) => Ok(HttpFileResponse {
+            status: 416,
+            headers: vec![("Content-Range", format!("bytes */{length}"))],
+            body: None,
+        }),
+        None => {
+            headers.push(("Content-Length", length.to_string()));
+            Ok(HttpFileResponse {
+                status: 200,
+                headers,
+                body: Some(ResponseBody::File(body)),
+            })
+        }
+    }
+}
+
+enum ByteRange {
+    Satisfiable(Range<usize>),
+    Unsatisfiable,
+}
+
+/// Parses a `Range` header for a body of `length` bytes. `None` means the header is to be
+/// ignored: malformed, not in bytes, or a list of several ranges.
+#[deny(clippy::indexing_slicing)]
+fn parse_range(value: &str, length: usize) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix("bytes=")?.trim();
+    if spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+    let range = if first.is_empty() {
+        let suffix: usize = last.parse().ok()?;
+        length.saturating_sub(suffix)..length
+    } else {
+        let start: usize = first.parse().ok()?;
+        let end = match last {
+            "" => length,
+            last => {
+                let last: usize = last.parse().ok()?;
+                if last < start {
+                    return None;
+                }
+                last.saturating_add(1).min(length)
+            }
+        };
+        start..end
+    };
+    Some(if range.is_empty() {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Satisfiable(range)
+    })
+}
+
+/// The codec to compress `file` with, if any, and whether the response varies by
+/// `Accept-Encoding` at all. Dynamic files follow their own options, in-memory files those of
+/// the silo serving them (`options`).
+#[cfg(feature = "compression")]
+fn negotiate_compression(
+    file: &File,
+    request: &RequestHeaders,
+    options: &DynOptions,
+    content_type: &str,
+    size: usize,
+) -> (Option<std::sync::Arc<dyn crate::Codec>>, bool) {
+    let config = match &file.inner {
+        crate::FileKind::Dynamic(dyn_file) => &dyn_file.options.compression,
+        crate::FileKind::Memory(_) => match &options.compression {
+            Some(config) if config.in_memory => &options.compression,
+            _ => return (None, false),
+        },
+        crate::FileKind::Embed(_) => return (None, false),
+    };
+    let Some(config) = config else {
+        return (None, false);
+    };
+    if !crate::compression::is_compressible(content_type, &file.path().to_string_lossy()) {
+        return (None, false);
+    }
+    let opted_out = request.cache_control.as_deref().is_some_and(|value| {
+        value
+            .split(',')
+            .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+    });
+    if size < config.min_size || opted_out {
+        return (None, true);
+    }
+    let codec = request
+        .accept_encoding
+        .as_deref()
+        .and_then(|accept| crate::compression::negotiate(accept, config));
+    (codec, true)
+}
+
+pub(crate) fn content_type(file: &File) -> &'static str {
+    #[cfg(feature = "mime")]
+    let guessed = file.mime_type_or_sniff();
+    #[cfg(not(feature = "mime"))]
+    let guessed = file.sniff_mime();
+    guessed.unwrap_or("application/octet-stream")
+}
+
+/// Strips query and fragment, percent-decodes and validates the path of a request target.
+#[deny(clippy::indexing_slicing)]
+pub(crate) fn decode_target(target: &str) -> Option<String> {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let mut bytes = path.bytes();
+    let mut decoded = Vec::with_capacity(path.len());
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let high = char::from(bytes.next()?).to_digit(16)?;
+            let low = char::from(bytes.next()?).to_digit(16)?;
+            decoded.push((high * 16 + low) as u8);
+        } else {
+            decoded.push(byte);
+        }
+    }
+    let decoded = String::from_utf8(decoded).ok()?;
+    let mut segments = Vec::new();
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" => {}
+            "." | ".." => return None,
+            _ if segment.contains('\0') => return None,
+            _ => segments.push(segment),
+        }
+    }
+    let mut path = segments.join("/");
+    if decoded.ends_with('/') && !path.is_empty() {
+        path.push('/');
+    }
+    Some(path)
+}
+
+/// Formats seconds since the UNIX epoch as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+fn http_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days + 4).rem_euclid(7) as usize],
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// Inverse of `conditional::days_from_civil`: `(year, month, day)` of a day count since the epoch.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
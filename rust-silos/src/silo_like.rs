@@ -0,0 +1,232 @@
+//! [`SiloLike`]: the read-only surface shared by [`Silo`] and [`SiloSet`], so helpers can be
+//! written once against `&impl SiloLike` or `&dyn SiloLike`.
+
+use crate::serve::{self, HttpFileResponse, RequestHeaders};
+use crate::{Error, File, Silo, SiloSet, SiloSnapshot};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// File and byte counts returned by [`SiloLike::stats`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SiloStats {
+    pub files: usize,
+    /// Sum of the sizes reported by [`File::meta`].
+    pub bytes: u64,
+}
+
+/// Read-only access to a silo or a set of silos.
+///
+/// Implemented by [`Silo`], [`SiloSet`], [`SiloSnapshot`], and references, boxes and `Arc`s of them. The trait
+/// is sealed and object safe:
+///
+/// ```ignore
+/// fn render(source: &dyn SiloLike) -> Result<String, Error> { ... }
+/// render(&silo)?;
+/// render(&set)?;
+/// ```
+pub trait SiloLike: private::Sealed {
+    /// Looks up the file at the relative `path`; for a set, the highest-precedence one.
+    fn get_file(&self, path: &str) -> Option<File>;
+
+    /// Iterates the visible files. For a set this is the override view
+    /// ([`SiloSet::iter_override`]), unlike the inherent [`SiloSet::iter`].
+    fn iter(&self) -> Box<dyn Iterator<Item = File> + '_>;
+
+    /// Returns `true` if a file exists at `path`.
+    fn contains(&self, path: &str) -> bool {
+        self.get_file(path).is_some()
+    }
+
+    /// Iterates the visible files whose relative path matches `pattern`: `*` and `?` match
+    /// within a path segment, `**` matches any number of segments.
+    fn glob<'a>(&'a self, pattern: &'a str) -> Box<dyn Iterator<Item = File> + 'a> {
+        Box::new(
+            self.iter().filter(move |file| {
+                crate::search::glob_match(pattern, &file.path().to_string_lossy())
+            }),
+        )
+    }
+
+    /// Returns the contents of the file at `path`, or [`Error::NotFound`].
+    fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        self.get_file(path).ok_or(Error::NotFound)?.contents()
+    }
+
+    /// Counts the visible files and their total size.
+    fn stats(&self) -> Result<SiloStats, Error> {
+        self.iter().try_fold(SiloStats::default(), |stats, file| {
+            Ok(SiloStats {
+                files: stats.files + 1,
+                bytes: stats.bytes + file.meta()?.size as u64,
+            })
+        })
+    }
+
+    /// Looks up the file for a URL path, see [`Silo::get_file_url`].
+    fn get_file_url(&self, url_path: &str) -> Option<File> {
+        self.get_file(&serve::decode_target(url_path)?)
+    }
+
+    /// Serves `request_target`, see [`Silo::serve`]. Files are served with the header rules
+    /// and dynamic options of the silo they come from.
+    fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse;
+}
+
+impl private::Sealed for Silo {}
+
+impl SiloLike for Silo {
+    fn get_file(&self, path: &str) -> Option<File> {
+        Silo::get_file(self, path)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+        Silo::iter(self)
+    }
+
+    fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        self.get_file_content(path)
+    }
+
+    // Strict about walk limits: a truncated walk would undercount.
+    fn stats(&self) -> Result<SiloStats, Error> {
+        self.try_iter()
+            .try_fold(SiloStats::default(), |stats, file| {
+                Ok(SiloStats {
+                    files: stats.files + 1,
+                    bytes: stats.bytes + file?.meta()?.size as u64,
+                })
+            })
+    }
+
+    fn get_file_url(&self, url_path: &str) -> Option<File> {
+        Silo::get_file_url(self, url_path)
+    }
+
+    // Keeps alias redirects, which only a single silo knows about.
+    fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse {
+        Silo::serve(self, request_target, headers)
+    }
+}
+
+impl private::Sealed for SiloSet {}
+
+impl SiloLike for SiloSet {
+    fn get_file(&self, path: &str) -> Option<File> {
+        SiloSet::get_file(self, path)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+        Box::new(self.iter_override())
+    }
+
+    // Goes through the set so its observer sees the read.
+    fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        self.get_file_content(path)
+    }
+
+    // Serves with the header rules, options and error page of the member that has the file,
+    // and reports the bytes of a 200 or 206 body to the observer. Misses get the not-found
+    // page of the highest-precedence member that sets one.
+    fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse {
+        let not_found = || match self
+            .silos
+            .iter()
+            .rev()
+            .find(|silo| silo.has_not_found_page())
+        {
+            Some(silo) => silo.error_response(404),
+            None => HttpFileResponse::empty(404),
+        };
+        let Some(path) = serve::decode_target(request_target) else {
+            return not_found();
+        };
+        let Some((at, file)) = self.lookup(&path) else {
+            return not_found();
+        };
+        let response = self.silos[at].serve_found(&file, headers);
+        if let Some(observer) = &self.observer
+            && matches!(response.status, 200 | 206)
+            && response.body.is_some()
+            && let Some(bytes) = response
+                .header("Content-Length")
+                .and_then(|length| length.parse().ok())
+                .or_else(|| file.meta().ok().map(|meta| meta.size))
+        {
+            observer.on_read(&path, bytes);
+        }
+        response
+    }
+}
+
+impl private::Sealed for SiloSnapshot {}
+
+impl SiloLike for SiloSnapshot {
+    fn get_file(&self, path: &str) -> Option<File> {
+        SiloSnapshot::get_file(self, path)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+        SiloSnapshot::iter(self)
+    }
+
+    fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        SiloSnapshot::read(self, path)
+    }
+
+    fn stats(&self) -> Result<SiloStats, Error> {
+        SiloSnapshot::stats(self)
+    }
+
+    fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse {
+        let Some(file) = self.get_file_url(request_target) else {
+            return HttpFileResponse::empty(404);
+        };
+        self.silo().serve_found(&file, headers)
+    }
+}
+
+macro_rules! forward_silo_like {
+    ($($wrapper:ty),*) => {$(
+        impl<T: SiloLike + ?Sized> private::Sealed for $wrapper {}
+
+        impl<T: SiloLike + ?Sized> SiloLike for $wrapper {
+            fn get_file(&self, path: &str) -> Option<File> {
+                (**self).get_file(path)
+            }
+
+            fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+                (**self).iter()
+            }
+
+            fn contains(&self, path: &str) -> bool {
+                (**self).contains(path)
+            }
+
+            fn glob<'a>(&'a self, pattern: &'a str) -> Box<dyn Iterator<Item = File> + 'a> {
+                (**self).glob(pattern)
+            }
+
+            fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+                (**self).read(path)
+            }
+
+            fn stats(&self) -> Result<SiloStats, Error> {
+                (**self).stats()
+            }
+
+            fn get_file_url(&self, url_path: &str) -> Option<File> {
+                (**self).get_file_url(url_path)
+            }
+
+            fn serve(&self, request_target: &str, headers: &RequestHeaders) -> HttpFileResponse {
+                (**self).serve(request_target, headers)
+            }
+        }
+    )*};
+}
+
+forward_silo_like!(&T, Box<T>, Arc<T>);
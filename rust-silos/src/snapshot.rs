@@ -0,0 +1,175 @@
+//! Point-in-time file lists for dynamic silos, see [`Silo::snapshot`].
+
+use crate::{
+    Error, File, FileMeta, Manifest, ManifestEntry, RouteEntry, Silo, SiloLike, SiloStats,
+};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// The files of a silo as they were when [`Silo::snapshot`] was called.
+///
+/// Lookups and iteration only ever see the captured list: files added afterwards are
+/// invisible, and files deleted afterwards are still listed but fail to read with
+/// [`Error::Stale`]. Contents are read when asked for, so a file rewritten in place yields
+/// its new contents. Cloning is cheap.
+#[derive(Debug, Clone)]
+pub struct SiloSnapshot {
+    silo: Silo,
+    /// Captured files sorted by relative path; `None` for silos whose contents cannot change.
+    files: Option<Arc<[(File, FileMeta)]>>,
+}
+
+impl Silo {
+    /// Captures the current file list, with each file's metadata, so that iteration and
+    /// manifests see one consistent set of files while the directory changes underneath.
+    ///
+    /// Dynamic silos are walked once, eagerly; files that vanish during the walk are left
+    /// out. Embedded and in-memory silos cannot change, so their snapshot is just the silo.
+    pub fn snapshot(&self) -> SiloSnapshot {
+        let files = self.is_dynamic().then(|| {
+            let mut files: Vec<(File, FileMeta)> = self
+                .iter()
+                .filter_map(|file| {
+                    let meta = file.meta().ok()?;
+                    Some((file, meta))
+                })
+                .collect();
+            files.sort_by(|a, b| a.0.path().cmp(b.0.path()));
+            files.into()
+        });
+        SiloSnapshot {
+            silo: self.clone(),
+            files,
+        }
+    }
+}
+
+impl SiloSnapshot {
+    /// The silo this snapshot was taken from.
+    pub fn silo(&self) -> &Silo {
+        &self.silo
+    }
+
+    /// Number of captured files.
+    pub fn len(&self) -> usize {
+        match &self.files {
+            Some(files) => files.len(),
+            None => self.silo.iter().count(),
+        }
+    }
+
+    /// Returns `true` if no files were captured.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates the captured files, in path order for dynamic silos.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = File> + '_> {
+        match &self.files {
+            Some(files) => Box::new(files.iter().map(|(file, _)| file.clone())),
+            None => self.silo.iter(),
+        }
+    }
+
+    fn captured(&self, path: &std::path::Path) -> Option<&(File, FileMeta)> {
+        let files = self.files.as_ref()?;
+        files
+            .binary_search_by(|(file, _)| file.path().cmp(path))
+            .ok()
+            .map(|at| &files[at])
+    }
+
+    /// Looks up `path` among the captured files. Index files and aliases resolve, and
+    /// rooted, UNC and (on Windows) drive paths are rejected, as in [`Silo::get_file`], as
+    /// long as the file they resolve to was captured.
+    pub fn get_file(&self, path: &str) -> Option<File> {
+        if self.files.is_none() {
+            return self.silo.get_file(path);
+        }
+        if crate::is_rejected_lookup(path) || path.starts_with(['/', '\\']) {
+            return None;
+        }
+        if let Some((file, _)) = self.captured(std::path::Path::new(path)) {
+            return Some(file.clone());
+        }
+        let resolved = self.silo.get_file(path)?;
+        self.captured(resolved.path()).map(|(file, _)| file.clone())
+    }
+
+    /// Metadata of the file at `path` as captured, without touching the disk.
+    pub fn meta(&self, path: &str) -> Option<FileMeta> {
+        let file = self.get_file(path)?;
+        match self.captured(file.path()) {
+            Some((_, meta)) => Some(*meta),
+            None => file.meta().ok(),
+        }
+    }
+
+    /// Returns the current contents of the captured file at `path`.
+    ///
+    /// Fails with [`Error::NotFound`] if the file was not captured, and with [`Error::Stale`]
+    /// if it was but has since been deleted.
+    pub fn read(&self, path: &str) -> Result<Cow<'static, [u8]>, Error> {
+        let file = self.get_file(path).ok_or(Error::NotFound)?;
+        file.contents().map_err(|err| stale(&file, err))
+    }
+
+    /// Like [`Silo::manifest`], over the captured files and with their captured size and
+    /// mtime. Fails with [`Error::Stale`] if a captured file was deleted before it was hashed.
+    pub fn manifest(&self) -> Result<Manifest, Error> {
+        let Some(files) = &self.files else {
+            return self.silo.manifest();
+        };
+        let mut entries = files
+            .iter()
+            .map(|(file, meta)| {
+                Ok(ManifestEntry {
+                    path: file.path().to_string_lossy().into_owned(),
+                    size: meta.size,
+                    modified: meta.modified,
+                    hash: Some(file.content_hash().map_err(|err| stale(file, err))?),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Manifest {
+            entries,
+            build_id: self.silo.build_id(),
+        })
+    }
+
+    /// Counts the captured files and sums their captured sizes, see [`SiloLike::stats`].
+    pub fn stats(&self) -> Result<SiloStats, Error> {
+        let Some(files) = &self.files else {
+            return SiloLike::stats(&self.silo);
+        };
+        Ok(SiloStats {
+            files: files.len(),
+            bytes: files.iter().map(|(_, meta)| meta.size as u64).sum(),
+        })
+    }
+
+    /// Like [`Silo::routes`], over the captured files.
+    pub fn routes(&self, prefix: &str) -> Result<Vec<RouteEntry>, Error> {
+        let mut routes = self
+            .iter()
+            .map(|file| file.route(prefix).map_err(|err| stale(&file, err)))
+            .collect::<Result<Vec<_>, _>>()?;
+        routes.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(routes)
+    }
+}
+
+/// Turns "no such file" for a captured dynamic file into [`Error::Stale`].
+fn stale(file: &File, err: Error) -> Error {
+    match err {
+        Error::IoError { source }
+            if source.kind() == std::io::ErrorKind::NotFound && file.absolute_path().is_some() =>
+        {
+            Error::Stale {
+                path: file.path().to_string_lossy().into_owned(),
+            }
+        }
+        err => err,
+    }
+}
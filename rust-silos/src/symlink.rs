@@ -0,0 +1,66 @@
+//! Which symlinks dynamic silos follow, see [`Silo::with_symlink_policy`].
+
+use crate::Silo;
+use std::path::Path;
+
+/// How dynamic and static silos treat symlinks under their root, for lookups, iteration
+/// and directory listings alike.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum SymlinkPolicy {
+    /// Refuse any path that passes through a symlink, whether the file itself or a
+    /// directory on the way to it.
+    Deny,
+    /// Follow symlinks whose canonical target stays inside the canonical root.
+    #[default]
+    AllowWithinRoot,
+    /// Follow every symlink, even out of the root, and walk into symlinked directories.
+    /// Lookup paths themselves still cannot climb out of the root with `..`.
+    AllowAll,
+}
+
+impl SymlinkPolicy {
+    /// Whether the file or directory at `lexical`, a path under `root_canon` that
+    /// canonicalizes to `canon`, may be served.
+    pub(crate) fn permits(self, root_canon: &Path, lexical: &Path, canon: &Path) -> bool {
+        match self {
+            SymlinkPolicy::Deny => !has_symlink_component(root_canon, lexical),
+            SymlinkPolicy::AllowWithinRoot => canon.starts_with(root_canon),
+            SymlinkPolicy::AllowAll => true,
+        }
+    }
+
+    /// Whether a symlink met while walking or listing may be served as a file.
+    pub(crate) fn permits_symlink_file(self, path: &Path, root_canon: &Path) -> bool {
+        match self {
+            SymlinkPolicy::Deny => false,
+            SymlinkPolicy::AllowWithinRoot => crate::is_contained_symlink_file(path, root_canon),
+            SymlinkPolicy::AllowAll => path.is_file(),
+        }
+    }
+}
+
+/// Returns `true` if any component of `path` below `root` is a symlink, or cannot be
+/// inspected.
+fn has_symlink_component(root: &Path, path: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(root) else {
+        return true;
+    };
+    let mut current = root.to_path_buf();
+    rel.components().any(|component| {
+        current.push(component);
+        std::fs::symlink_metadata(&current).map_or(true, |meta| meta.file_type().is_symlink())
+    })
+}
+
+impl Silo {
+    /// Sets which symlinks under the root this silo follows; [`SymlinkPolicy::AllowWithinRoot`]
+    /// unless configured. Embedded and in-memory silos resolve symlinks at build time, so
+    /// this has no effect on them until [`Silo::into_dynamic`].
+    ///
+    /// `embed_silo!` sets [`SymlinkPolicy::Deny`], or [`SymlinkPolicy::AllowAll`] with
+    /// `follow_symlinks = true`, so its dynamic form lists the same files it embeds.
+    pub const fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.dyn_options.symlink_policy = policy;
+        self
+    }
+}
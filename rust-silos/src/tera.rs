@@ -0,0 +1,278 @@
+//! Tera integration: register templates from a [`Silo`](crate::Silo) or [`SiloSet`] and reload
+//! the ones that changed.
+
+use crate::{Error, File, SiloLike, SiloSet};
+use ::tera::Tera;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Registers every file of `source` (for a set, its override view) as a template, named by its
+/// relative path.
+pub fn load_templates(tera: &mut Tera, source: &(impl SiloLike + ?Sized)) -> Result<(), Error> {
+    let templates = source
+        .iter()
+        .map(|file| Ok((template_name(&file), read_template(&file)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    tera.add_raw_templates(templates)?;
+    Ok(())
+}
+
+/// Registers the files of `set`'s override view whose relative path matches `glob` (as in
+/// [`SiloLike::glob`]) as templates, without blocking the async runtime.
+///
+/// The set is walked on tokio's blocking pool, and the templates are read concurrently with
+/// [`File::contents_async`], a bounded number at a time, before being registered in one go.
+#[cfg(feature = "tokio")]
+pub async fn load_async(tera: &mut Tera, set: &SiloSet, glob: &str) -> Result<(), Error> {
+    let (set, glob) = (set.clone(), glob.to_owned());
+    let files = crate::async_io::blocking(move || {
+        set.iter_override()
+            .filter(|file| crate::search::glob_match(&glob, &template_name(file)))
+            .collect::<Vec<_>>()
+    })
+    .await?;
+    let names: Vec<String> = files.iter().map(template_name).collect();
+    tera.add_raw_templates(read_templates_async(names, files).await?)?;
+    Ok(())
+}
+
+/// Summary of what a [`Reloader::reload`] call registered or removed.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReloadReport {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl ReloadReport {
+    /// Returns `true` if nothing was added, changed or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Identity of a loaded template, used to detect changes without reading contents.
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Stamp {
+    /// Embedded templates never change for the lifetime of the process.
+    Embedded,
+    Dynamic {
+        path: PathBuf,
+        size: u64,
+        modified: SystemTime,
+    },
+}
+
+impl Stamp {
+    fn of(file: &File) -> Result<Self, Error> {
+        match file.absolute_path() {
+            None => Ok(Stamp::Embedded),
+            Some(path) => {
+                let meta = std::fs::metadata(path)?;
+                Ok(Stamp::Dynamic {
+                    path: path.to_path_buf(),
+                    size: meta.len(),
+                    modified: meta.modified()?,
+                })
+            }
+        }
+    }
+}
+
+/// Tracks the templates loaded from a [`SiloSet`] and re-registers only what changed.
+///
+/// Only dynamic members are ever re-read: embedded templates are registered once and
+/// left alone on subsequent reloads.
+#[derive(Debug, Clone)]
+pub struct Reloader {
+    set: SiloSet,
+    loaded: Option<HashMap<String, Stamp>>,
+    /// Modification times of dynamic directories that contain templates (and dynamic roots),
+    /// so added files can be noticed without walking.
+    dirs: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl Reloader {
+    /// Creates a reloader over the given set. Nothing is loaded until [`Reloader::reload`].
+    pub fn new(set: SiloSet) -> Self {
+        Self {
+            set,
+            loaded: None,
+            dirs: HashMap::new(),
+        }
+    }
+
+    /// Returns the underlying set.
+    pub fn silo_set(&self) -> &SiloSet {
+        &self.set
+    }
+
+    /// Cheaply checks whether a reload would change anything.
+    ///
+    /// This only stats the dynamic templates loaded so far and the directories containing
+    /// them; it never walks the silos or reads contents. Returns `true` before the first reload.
+    pub fn needs_reload(&self) -> bool {
+        let Some(loaded) = &self.loaded else {
+            return true;
+        };
+        let template_changed = loaded.values().any(|stamp| match stamp {
+            Stamp::Embedded => false,
+            Stamp::Dynamic {
+                path,
+                size,
+                modified,
+            } => match std::fs::metadata(path) {
+                Ok(meta) => meta.len() != *size || meta.modified().ok() != Some(*modified),
+                Err(_) => true,
+            },
+        });
+        template_changed
+            || self
+                .dirs
+                .iter()
+                .any(|(dir, mtime)| dir_mtime(dir) != *mtime)
+    }
+
+    /// Walks the override view and registers added or changed templates into `tera`,
+    /// removing templates that no longer exist.
+    pub fn reload(&mut self, tera: &mut Tera) -> Result<ReloadReport, Error> {
+        let previous = self.loaded.take().unwrap_or_default();
+        let pending = scan(&self.set, &previous)?;
+        let templates = pending
+            .to_read
+            .iter()
+            .map(|(name, file)| Ok((name.clone(), read_template(file)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.apply(tera, pending, templates)
+    }
+
+    /// Like [`Reloader::reload`], without blocking the async runtime: the walk and stats run
+    /// on tokio's blocking pool and changed templates are read with [`File::contents_async`].
+    #[cfg(feature = "tokio")]
+    pub async fn reload_async(&mut self, tera: &mut Tera) -> Result<ReloadReport, Error> {
+        let previous = self.loaded.take().unwrap_or_default();
+        let set = self.set.clone();
+        let mut pending = crate::async_io::blocking(move || scan(&set, &previous)).await??;
+        let (names, files) = std::mem::take(&mut pending.to_read).into_iter().unzip();
+        let templates = read_templates_async(names, files).await?;
+        self.apply(tera, pending, templates)
+    }
+
+    /// Registers the templates read for `pending`, drops the removed ones and records the
+    /// new state.
+    fn apply(
+        &mut self,
+        tera: &mut Tera,
+        pending: Pending,
+        templates: Vec<(String, String)>,
+    ) -> Result<ReloadReport, Error> {
+        let Pending {
+            current,
+            dirs,
+            mut report,
+            to_read: _,
+        } = pending;
+        for name in &report.removed {
+            tera.templates.remove(name);
+        }
+
+        if templates.is_empty() {
+            if !report.removed.is_empty() {
+                tera.build_inheritance_chains()?;
+            }
+        } else {
+            tera.add_raw_templates(templates)?;
+        }
+
+        report.added.sort();
+        report.changed.sort();
+        report.removed.sort();
+        self.loaded = Some(current);
+        self.dirs = dirs;
+        Ok(report)
+    }
+}
+
+/// What a reload found: the new stamps, the templates to (re-)read, and the report.
+struct Pending {
+    current: HashMap<String, Stamp>,
+    dirs: HashMap<PathBuf, Option<SystemTime>>,
+    report: ReloadReport,
+    to_read: Vec<(String, File)>,
+}
+
+/// Walks the override view of `set` and compares it against the `previous` stamps, without
+/// reading any contents.
+fn scan(set: &SiloSet, previous: &HashMap<String, Stamp>) -> Result<Pending, Error> {
+    let mut current = HashMap::new();
+    let mut dirs = HashMap::new();
+    let mut report = ReloadReport::default();
+    let mut to_read = Vec::new();
+
+    for silo in &set.silos {
+        if let Some(root) = silo.dynamic_root() {
+            dirs.insert(root.to_path_buf(), dir_mtime(root));
+        }
+    }
+
+    for file in set.iter_override() {
+        let name = template_name(&file);
+        let stamp = Stamp::of(&file)?;
+        if let Stamp::Dynamic { path, .. } = &stamp
+            && let Some(parent) = path.parent()
+        {
+            dirs.entry(parent.to_path_buf())
+                .or_insert_with(|| dir_mtime(parent));
+        }
+        match previous.get(&name) {
+            Some(prev) if *prev == stamp => {}
+            Some(_) => {
+                report.changed.push(name.clone());
+                to_read.push((name.clone(), file));
+            }
+            None => {
+                report.added.push(name.clone());
+                to_read.push((name.clone(), file));
+            }
+        }
+        current.insert(name, stamp);
+    }
+
+    for name in previous.keys() {
+        if !current.contains_key(name) {
+            report.removed.push(name.clone());
+        }
+    }
+    Ok(Pending {
+        current,
+        dirs,
+        report,
+        to_read,
+    })
+}
+
+fn template_name(file: &File) -> String {
+    file.path().to_string_lossy().into_owned()
+}
+
+fn read_template(file: &File) -> Result<String, Error> {
+    Ok(file.text()?.into_owned())
+}
+
+#[cfg(feature = "tokio")]
+async fn read_templates_async(
+    names: Vec<String>,
+    files: Vec<File>,
+) -> Result<Vec<(String, String)>, Error> {
+    let contents = crate::async_io::read_all(files).await?;
+    names
+        .into_iter()
+        .zip(contents)
+        .map(|(name, bytes)| Ok((name, String::from_utf8(bytes.into_owned())?)))
+        .collect()
+}
+
+fn dir_mtime(dir: &Path) -> Option<SystemTime> {
+    std::fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,83 @@
+//! Unix permission bits of files, and extraction that keeps them, see [`File::unix_mode`].
+
+use crate::{Digest, Error, File, FileKind, HashAlg, Silo};
+use std::path::{Path, PathBuf};
+
+/// What [`File::extract_to`] wrote for one file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExtractReport {
+    /// Path of the file within its silo.
+    pub path: PathBuf,
+    /// Number of bytes written.
+    pub bytes: u64,
+    /// Digest of the bytes written, computed while writing them.
+    pub digest: Digest,
+}
+
+impl File {
+    /// The Unix permission bits (`st_mode & 0o7777`) of this file: recorded at embed time
+    /// for embedded files, read from the filesystem for dynamic ones. `None` for in-memory
+    /// files, for files embedded on a platform without permission bits or without a recorded
+    /// mode (see [`EmbedEntry::mode`](crate::EmbedEntry::mode)), and for dynamic files off Unix.
+    pub fn unix_mode(&self) -> Option<u32> {
+        match &self.inner {
+            FileKind::Embed(embed) => Some(embed.inner.mode).filter(|&mode| mode != 0),
+            #[cfg(unix)]
+            FileKind::Dynamic(dyn_file) => {
+                use std::os::unix::fs::PermissionsExt;
+                let meta = std::fs::metadata(dyn_file.absolute_path()).ok()?;
+                Some(meta.permissions().mode() & 0o7777)
+            }
+            _ => None,
+        }
+    }
+
+    /// Writes the contents to a new or truncated file at `dest` in one pass, reporting the
+    /// number of bytes written and their digest with `alg` (see [`File::reader_with_digest`]).
+    /// On Unix, the executable bits of [`File::unix_mode`] are added to the new file's
+    /// permissions, so extracted scripts stay runnable; other bits are left to the umask.
+    /// [`File::copy_to`] writes to any writer and cannot restore them.
+    pub fn extract_to(&self, dest: impl AsRef<Path>, alg: HashAlg) -> Result<ExtractReport, Error> {
+        let mut out = std::fs::File::create(dest.as_ref())?;
+        let (mut reader, handle) = self.reader_with_digest(alg)?;
+        let bytes = std::io::copy(&mut reader, &mut out)?;
+        #[cfg(unix)]
+        if let Some(executable) = self
+            .unix_mode()
+            .map(|mode| mode & 0o111)
+            .filter(|&bits| bits != 0)
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = out.metadata()?.permissions();
+            permissions.set_mode(permissions.mode() | executable);
+            out.set_permissions(permissions)?;
+        }
+        Ok(ExtractReport {
+            path: self.path().to_path_buf(),
+            bytes,
+            digest: handle.finalize()?,
+        })
+    }
+}
+
+impl Silo {
+    /// Extracts every file under the directory `dest` with [`File::extract_to`], creating
+    /// subdirectories as needed, and returns a report per file in iteration order. Stops at
+    /// the first file that cannot be read or written.
+    pub fn extract_to(
+        &self,
+        dest: impl AsRef<Path>,
+        alg: HashAlg,
+    ) -> Result<Vec<ExtractReport>, Error> {
+        let dest = dest.as_ref();
+        let mut reports = Vec::new();
+        for file in self.iter() {
+            let target = dest.join(file.path());
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            reports.push(file.extract_to(target, alg)?);
+        }
+        Ok(reports)
+    }
+}
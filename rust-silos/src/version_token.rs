@@ -0,0 +1,71 @@
+//! A cheap token for the whole merged view of a [`SiloSet`], for cache busting.
+
+use crate::{ContentHasher, File, HttpFileResponse, Silo, SiloSet};
+
+/// Identifies the contents of a [`SiloSet`] at one point in time, see
+/// [`SiloSet::version_token`]. Displays as 16 lowercase hex digits.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct VersionToken(pub u64);
+
+impl std::fmt::Display for VersionToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl SiloSet {
+    /// Computes a token that changes whenever a file of any layer is added, removed or
+    /// changed, e.g. for an `X-Assets-Version` header or a `?v=` cache-buster.
+    ///
+    /// Embedded and in-memory files contribute their precomputed content hashes. Dynamic
+    /// files contribute their path, size and modification time (with sub-second precision
+    /// where the filesystem has it), so nothing is read; a rewrite that keeps both the size
+    /// and the mtime goes unnoticed.
+    pub fn version_token(&self) -> VersionToken {
+        let mut hasher = ContentHasher::new();
+        for (layer, silo) in self.silos.iter().enumerate() {
+            hasher.update(&(layer as u64).to_le_bytes());
+            hash_silo(&mut hasher, silo);
+        }
+        VersionToken(hasher.finish())
+    }
+
+    /// Returns `true` if the set no longer matches `prev`.
+    pub fn changed_since(&self, prev: &VersionToken) -> bool {
+        self.version_token() != *prev
+    }
+}
+
+fn hash_silo(hasher: &mut ContentHasher, silo: &Silo) {
+    let mut files: Vec<File> = silo.iter().collect();
+    // Directory walks have no fixed order.
+    files.sort_by(|a, b| a.path().cmp(b.path()));
+    for file in files {
+        hasher.update(file.path().to_string_lossy().as_bytes());
+        hasher.update(&[0]);
+        match file.absolute_path() {
+            None => hasher.update(&file.content_hash().unwrap_or_default().to_le_bytes()),
+            Some(path) => {
+                let stamp = std::fs::metadata(path).ok().and_then(|meta| {
+                    let modified = meta
+                        .modified()
+                        .ok()?
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .ok()?;
+                    Some((meta.len(), modified.as_nanos()))
+                });
+                let (size, modified) = stamp.unwrap_or((u64::MAX, 0));
+                hasher.update(&size.to_le_bytes());
+                hasher.update(&modified.to_le_bytes());
+            }
+        }
+    }
+}
+
+impl HttpFileResponse {
+    /// Adds an `X-Assets-Version` header carrying `token`.
+    pub fn with_version(mut self, token: VersionToken) -> Self {
+        self.headers.push(("X-Assets-Version", token.to_string()));
+        self
+    }
+}
@@ -0,0 +1,118 @@
+//! Versioned content packs: pick the newest pack directory that satisfies a version
+//! requirement, e.g. `packs/v3/`, `packs/v4/` next to the binary.
+
+use crate::{Error, Silo, SiloSet};
+use semver::{Version, VersionReq};
+use std::path::Path;
+
+/// Manifest inside a pack directory whose `version` overrides the directory name.
+const MANIFEST: &str = "pack.toml";
+
+impl Silo {
+    /// Loads the subdirectory of `root` with the highest version matching `constraint` as a
+    /// dynamic silo named after the directory.
+    ///
+    /// A directory's version comes from the `version` key of its `pack.toml` if present, else
+    /// from its name: `v3`, `3.1` and `v3.1.0` are read as 3.0.0, 3.1.0 and 3.1.0. Directories
+    /// with neither are ignored. Versions are compared as semver, so `v10` beats `v9`.
+    ///
+    /// Returns [`Error::NoMatchingVersion`] listing every version found if none matches, and
+    /// fails on an unreadable `pack.toml` or a malformed `version` in one.
+    pub fn latest_versioned(
+        root: &Path,
+        constraint: &VersionReq,
+    ) -> Result<(Version, Silo), Error> {
+        let mut candidates = Vec::new();
+        for entry in std::fs::read_dir(root)? {
+            let entry = entry?;
+            let path = entry.path();
+            let (Some(name), Some(dir)) =
+                (entry.file_name().to_str().map(str::to_owned), path.to_str())
+            else {
+                continue;
+            };
+            if name.starts_with('.') || !path.is_dir() {
+                continue;
+            }
+            if let Some(version) = pack_version(&path, &name)? {
+                candidates.push((version, name, dir.to_owned()));
+            }
+        }
+        // Ties (`v3` next to `3.0.0`) go to the last name, so the choice does not depend on
+        // directory listing order.
+        candidates.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        match candidates
+            .iter()
+            .rev()
+            .find(|(version, ..)| constraint.matches(version))
+        {
+            Some((version, name, dir)) => Ok((version.clone(), Silo::new(dir).with_name(name))),
+            None => Err(Error::NoMatchingVersion {
+                constraint: constraint.clone(),
+                candidates: candidates
+                    .into_iter()
+                    .map(|(version, ..)| version)
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl SiloSet {
+    /// Stacks the pack chosen by [`Silo::latest_versioned`] over `base` (e.g. embedded
+    /// defaults), returning its version with the two-layer set.
+    pub fn latest_versioned_with_base(
+        base: Silo,
+        root: &Path,
+        constraint: &VersionReq,
+    ) -> Result<(Version, SiloSet), Error> {
+        let (version, pack) = Silo::latest_versioned(root, constraint)?;
+        Ok((version, SiloSet::new(vec![base, pack])))
+    }
+}
+
+/// Version of the pack directory at `path`: its manifest's `version`, else its name.
+fn pack_version(path: &Path, name: &str) -> Result<Option<Version>, Error> {
+    let manifest = match std::fs::read_to_string(path.join(MANIFEST)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(parse_dir_version(name)),
+        Err(e) => return Err(e.into()),
+    };
+    let table: toml::Table = toml::from_str(&manifest)?;
+    match table.get("version") {
+        None => Ok(parse_dir_version(name)),
+        Some(toml::Value::String(version)) => {
+            Version::parse(version)
+                .map(Some)
+                .map_err(|e| Error::PackError {
+                    reason: format!("{name}/{MANIFEST}: invalid version {version:?}: {e}"),
+                })
+        }
+        Some(other) => Err(Error::PackError {
+            reason: format!(
+                "{name}/{MANIFEST}: version must be a string, found {}",
+                other.type_str()
+            ),
+        }),
+    }
+}
+
+/// Reads `v3`, `3.1` or `v3.1.0-beta` as a version, filling in missing minor and patch
+/// numbers with zero.
+fn parse_dir_version(name: &str) -> Option<Version> {
+    let name = name.strip_prefix(['v', 'V']).unwrap_or(name);
+    let (core, suffix) = match name.find(['-', '+']) {
+        Some(at) => name.split_at(at),
+        None => (name, ""),
+    };
+    let parts: Vec<&str> = core.split('.').collect();
+    if parts.len() > 3
+        || parts
+            .iter()
+            .any(|part| part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+    let padded = (parts.len()..3).fold(core.to_owned(), |version, _| version + ".0");
+    Version::parse(&format!("{padded}{suffix}")).ok()
+}
@@ -0,0 +1,149 @@
+//! Read-only [`vfs::FileSystem`] over a [`SiloSet`], so silo contents can be
+//! handed to code written against the `vfs` crate.
+//!
+//! `vfs` paths are absolute (`"/a/b.txt"`, `""` for the root); the leading slash is dropped
+//! and the remainder is looked up in the override view of the set. Directories are whatever
+//! the layers report through [`Silo::read_dir`](crate::Silo::read_dir), merged across layers.
+//! Every write operation fails with [`VfsErrorKind::NotSupported`].
+
+use crate::{Entry, Error, File, SiloSet};
+use ::vfs::error::VfsErrorKind;
+use ::vfs::{FileSystem, SeekAndRead, SeekAndWrite, VfsError, VfsFileType, VfsMetadata, VfsResult};
+use std::collections::BTreeSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A [`SiloSet`] exposed as a read-only `vfs` filesystem.
+///
+/// ```ignore
+/// let root: vfs::VfsPath = rust_silos::vfs::SiloFS(set).into();
+/// let text = root.join("templates/base.html")?.read_to_string()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SiloFS(pub SiloSet);
+
+impl SiloFS {
+    fn file(&self, path: &str) -> Option<File> {
+        let key = relative(path);
+        if key.is_empty() {
+            return None;
+        }
+        self.0.get_file(key)
+    }
+
+    /// Names of the immediate children of `path`, merged across layers; `None` if no layer
+    /// has anything under it.
+    fn children(&self, path: &str) -> Option<BTreeSet<String>> {
+        let dir = relative(path);
+        let names: BTreeSet<String> = self
+            .0
+            .silos
+            .iter()
+            .flat_map(|silo| silo.read_dir(dir))
+            .map(|entry| match entry {
+                Entry::File(file) => file_name(&file.path().to_string_lossy()).to_owned(),
+                Entry::Dir(dir) => file_name(&dir).to_owned(),
+            })
+            .collect();
+        (dir.is_empty() || !names.is_empty()).then_some(names)
+    }
+}
+
+fn relative(path: &str) -> &str {
+    path.trim_start_matches('/')
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+// `VfsPath` attaches the path to errors itself, so these carry only the kind.
+fn not_found() -> VfsError {
+    VfsErrorKind::FileNotFound.into()
+}
+
+fn not_supported<T>() -> VfsResult<T> {
+    Err(VfsErrorKind::NotSupported.into())
+}
+
+fn vfs_error(err: Error) -> VfsError {
+    match err {
+        Error::NotFound | Error::IsADirectory { .. } => not_found(),
+        Error::PermissionDenied { .. } => {
+            VfsErrorKind::IoError(std::io::ErrorKind::PermissionDenied.into()).into()
+        }
+        Error::IoError { source } => VfsErrorKind::IoError(source).into(),
+        err => VfsErrorKind::Other(err.to_string()).into(),
+    }
+}
+
+impl FileSystem for SiloFS {
+    fn read_dir(&self, path: &str) -> VfsResult<Box<dyn Iterator<Item = String> + Send>> {
+        let names = self.children(path).ok_or_else(not_found)?;
+        Ok(Box::new(names.into_iter()))
+    }
+
+    fn create_dir(&self, _path: &str) -> VfsResult<()> {
+        not_supported()
+    }
+
+    fn open_file(&self, path: &str) -> VfsResult<Box<dyn SeekAndRead + Send>> {
+        let file = self.0.open(relative(path)).map_err(vfs_error)?;
+        Ok(Box::new(file))
+    }
+
+    fn create_file(&self, _path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        not_supported()
+    }
+
+    fn append_file(&self, _path: &str) -> VfsResult<Box<dyn SeekAndWrite + Send>> {
+        not_supported()
+    }
+
+    fn metadata(&self, path: &str) -> VfsResult<VfsMetadata> {
+        if let Some(file) = self.file(path) {
+            let meta = file.meta().map_err(vfs_error)?;
+            return Ok(VfsMetadata {
+                file_type: VfsFileType::File,
+                len: meta.size as u64,
+                created: None,
+                modified: (meta.modified > 0)
+                    .then(|| UNIX_EPOCH + Duration::from_secs(meta.modified)),
+                accessed: None,
+            });
+        }
+        if self.children(path).is_some() {
+            return Ok(VfsMetadata {
+                file_type: VfsFileType::Directory,
+                len: 0,
+                created: None,
+                modified: None,
+                accessed: None,
+            });
+        }
+        Err(not_found())
+    }
+
+    fn set_creation_time(&self, _path: &str, _time: SystemTime) -> VfsResult<()> {
+        not_supported()
+    }
+
+    fn set_modification_time(&self, _path: &str, _time: SystemTime) -> VfsResult<()> {
+        not_supported()
+    }
+
+    fn set_access_time(&self, _path: &str, _time: SystemTime) -> VfsResult<()> {
+        not_supported()
+    }
+
+    fn exists(&self, path: &str) -> VfsResult<bool> {
+        Ok(self.file(path).is_some() || self.children(path).is_some())
+    }
+
+    fn remove_file(&self, _path: &str) -> VfsResult<()> {
+        not_supported()
+    }
+
+    fn remove_dir(&self, _path: &str) -> VfsResult<()> {
+        not_supported()
+    }
+}
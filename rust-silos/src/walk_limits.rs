@@ -0,0 +1,104 @@
+//! Limits on directory walks of dynamic silos, see [`Silo::with_walk_limits`].
+
+use crate::{Error, File, Silo};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Bounds on a walk of a dynamic silo's directory tree. `None` means unbounded.
+///
+/// Exceeding any bound stops the walk. [`Silo::iter`] then simply ends early, while
+/// [`Silo::try_iter`] and [`SiloLike::stats`](crate::SiloLike::stats) report
+/// [`Error::LimitExceeded`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct WalkLimits {
+    /// Most files walked, before view filters apply.
+    pub max_files: Option<usize>,
+    /// Deepest level walked; 1 is the root directory's own entries.
+    pub max_depth: Option<usize>,
+    /// Longest time a single walk may take.
+    pub max_duration: Option<Duration>,
+}
+
+impl WalkLimits {
+    /// No bounds; what [`Silo::iter`] uses unless limits are configured.
+    pub const UNLIMITED: Self = Self {
+        max_files: None,
+        max_depth: None,
+        max_duration: None,
+    };
+
+    /// Bounds used by [`Silo::prewarm`] and `Silo::build_phf_at_runtime` when none are
+    /// configured: a million files, 64 levels, one minute.
+    pub const GENEROUS: Self = Self {
+        max_files: Some(1_000_000),
+        max_depth: Some(64),
+        max_duration: Some(Duration::from_secs(60)),
+    };
+
+    pub(crate) fn is_unlimited(&self) -> bool {
+        *self == Self::UNLIMITED
+    }
+}
+
+/// The bound a walk exceeded, see [`Error::LimitExceeded`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LimitKind {
+    /// [`WalkLimits::max_files`]; the limit is a file count.
+    Files,
+    /// [`WalkLimits::max_depth`]; the limit is a depth.
+    Depth,
+    /// [`WalkLimits::max_duration`]; the limit is in milliseconds.
+    Duration,
+}
+
+impl std::fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            LimitKind::Files => "file count",
+            LimitKind::Depth => "depth",
+            LimitKind::Duration => "duration (ms)",
+        })
+    }
+}
+
+/// Where a walk records the bound it exceeded.
+pub(crate) type Breach = Arc<OnceLock<(LimitKind, u64)>>;
+
+impl Silo {
+    /// Creates a dynamic silo whose walks are bounded by `limits`, so pointing it at a huge
+    /// directory by mistake cannot hang iteration.
+    pub fn new_with_limits(path: &str, limits: WalkLimits) -> Self {
+        Self::new(path).with_walk_limits(limits)
+    }
+
+    /// Bounds every walk of this silo's directory by `limits`. Embedded and in-memory silos
+    /// are never walked, so this has no effect on them.
+    pub fn with_walk_limits(mut self, limits: WalkLimits) -> Self {
+        self.dyn_options.walk_limits = Some(limits);
+        self
+    }
+
+    /// Like [`Silo::iter`], but yields [`Error::LimitExceeded`] as the last item if the walk
+    /// was stopped by the configured [`WalkLimits`].
+    pub fn try_iter(&self) -> impl Iterator<Item = Result<File, Error>> + '_ {
+        self.try_iter_with(self.dyn_options.walk_limits.unwrap_or_default())
+    }
+
+    pub(crate) fn try_iter_with(
+        &self,
+        limits: WalkLimits,
+    ) -> impl Iterator<Item = Result<File, Error>> + '_ {
+        let breach = Breach::default();
+        let files = self.iter_limited(limits, Some(breach.clone())).map(Ok);
+        files.chain(
+            std::iter::once_with(move || breach.get().copied())
+                .flatten()
+                .map(|(which, limit)| Err(Error::LimitExceeded { which, limit })),
+        )
+    }
+
+    /// The configured limits, or `default` for walks that must not run unbounded.
+    pub(crate) fn walk_limits_or(&self, default: WalkLimits) -> WalkLimits {
+        self.dyn_options.walk_limits.unwrap_or(default)
+    }
+}
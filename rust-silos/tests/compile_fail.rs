@@ -0,0 +1,152 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Checks the binary `bin` of the fixture crate in `tests/ui` with `env` set for the
+/// compiler, returning whether it compiled and the diagnostics, one `file:line:col: error:`
+/// message per entry.
+///
+/// The cases are a crate of their own so that `embed_silo!` paths resolve against the
+/// committed fixtures next to them.
+fn check(bin: &str, env: &[(&str, &str)]) -> (bool, Vec<String>) {
+    let output = Command::new(env!("CARGO"))
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui"))
+        .args([
+            "check",
+            "--offline",
+            "--quiet",
+            "--message-format=short",
+            "--bin",
+            bin,
+        ])
+        .env(
+            "CARGO_TARGET_DIR",
+            Path::new(env!("CARGO_TARGET_TMPDIR")).join("ui"),
+        )
+        .env_remove("RUST_SILOS_MAX_TOTAL")
+        .envs(env.iter().copied())
+        .output()
+        .unwrap();
+    let mut errors: Vec<String> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        match errors.last_mut() {
+            _ if line.starts_with("src/") => errors.push(line.to_owned()),
+            // Continuation of a multi-line message; cargo's own summary follows the last one.
+            Some(last) if !line.starts_with("error: could not compile") => {
+                last.push('\n');
+                last.push_str(line.trim_start());
+            }
+            _ => {}
+        }
+    }
+    (output.status.success(), errors)
+}
+
+/// Tests that embeds over the `RUST_SILOS_MAX_TOTAL` or `max_total_size` budget fail to
+/// compile with a message naming the call, the size and the limit.
+#[test]
+fn test_embed_budget_ui() {
+    let budget = [("RUST_SILOS_MAX_TOTAL", "50B")];
+    assert_eq!(
+        check("over_budget_env", &budget),
+        (
+            false,
+            vec![
+                "src/bin/over_budget_env.rs:2:19: error: embed_silo!(\"assets\") would embed 60 \
+                 bytes, over the limit of 50 bytes set by RUST_SILOS_MAX_TOTAL=50B; add \
+                 `allow_over_budget = true` to this call to opt out"
+                    .to_owned()
+            ]
+        )
+    );
+    assert_eq!(
+        check("over_budget_call", &budget),
+        (
+            false,
+            vec![
+                "src/bin/over_budget_call.rs:2:19: error: embed_silo!(\"assets\") would embed 60 \
+                 bytes, over its max_total_size of 10 bytes"
+                    .to_owned()
+            ]
+        )
+    );
+    assert_eq!(check("allow_over_budget", &budget), (true, Vec::new()));
+}
+
+/// Tests that `embed_silo_enum!` rejects an empty directory instead of generating an enum
+/// without variants.
+#[test]
+fn test_embed_silo_enum_empty_ui() {
+    // Git does not keep empty directories, so the fixture is created here.
+    std::fs::create_dir_all(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui/empty")).unwrap();
+    assert_eq!(
+        check("empty_enum", &[]),
+        (
+            false,
+            vec![
+                "src/bin/empty_enum.rs:1:36: error: embed_silo_enum!: empty has no files to name \
+                 variants after"
+                    .to_owned()
+            ]
+        )
+    );
+}
+
+/// Tests that `deny_containing` fails the build with one error listing each offending file
+/// and the patterns it contains.
+#[test]
+fn test_deny_containing_ui() {
+    assert_eq!(
+        check("deny_containing", &[]),
+        (
+            false,
+            vec![
+                "src/bin/deny_containing.rs:2:67: error: embed_silo!: 1 file(s) contain patterns \
+                 denied by deny_containing:\nnotes.txt: \"DO NOT DISTRIBUTE\""
+                    .to_owned()
+            ]
+        )
+    );
+}
+
+/// Tests that `RUST_SILOS_EMIT_EXPANSION=1` makes `emit_expansion` write the generated code,
+/// as parseable Rust, to the path relative to the calling crate, and that nothing is written
+/// without it.
+#[test]
+fn test_emit_expansion_ui() {
+    let ui = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/ui");
+    let out = ui.join("target/audit/assets.rs");
+    // Cargo does not track the variable, so the binary is made stale for each check.
+    let touch = || {
+        std::fs::File::options()
+            .write(true)
+            .open(ui.join("src/bin/emit_expansion.rs"))
+            .unwrap()
+            .set_modified(std::time::SystemTime::now())
+            .unwrap();
+    };
+    let _ = std::fs::remove_file(&out);
+    touch();
+    assert_eq!(check("emit_expansion", &[]), (true, Vec::new()));
+    assert!(!out.exists());
+
+    for value in ["0", "1"] {
+        touch();
+        assert_eq!(
+            check("emit_expansion", &[("RUST_SILOS_EMIT_EXPANSION", value)]),
+            (true, Vec::new())
+        );
+        assert_eq!(out.exists(), value == "1", "{value}");
+    }
+    let rendered = std::fs::read_to_string(&out).unwrap();
+    assert!(rendered.starts_with("// Expansion of `embed_silo!`, written because"));
+    assert!(rendered.contains("// Embedded files: 1\n"));
+    let file = syn::parse_file(&rendered).unwrap();
+    assert!(!file.items.is_empty());
+    assert!(rendered.contains("page.txt"));
+    // Written through a temporary file renamed into place, which is gone afterwards.
+    let leftovers: Vec<_> = std::fs::read_dir(out.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(leftovers, ["assets.rs"]);
+}
@@ -0,0 +1,4 @@
+/* This library is free software; you can redistribute it and/or modify it under the
+ * terms of the GNU Lesser General Public License as published by the Free Software
+ * Foundation. */
+int answer(void) { return 42; }
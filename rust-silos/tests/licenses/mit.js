@@ -0,0 +1,7 @@
+/*
+ * Copyright (c) Example
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software, to deal in the Software without restriction.
+ */
+export const answer = 42;
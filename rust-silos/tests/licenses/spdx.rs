@@ -0,0 +1,2 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+pub const ANSWER: u32 = 42;
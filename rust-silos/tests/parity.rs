@@ -0,0 +1,207 @@
+//! Randomized invariant tests for dynamic silos over generated directory trees.
+//!
+//! Trees are produced by a small seeded generator (so failures are reproducible from the
+//! seed) with unicode names, spaces, dots, empty files, extensionless files and nesting up
+//! to six levels deep.
+
+use rust_silos::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Minimal xorshift generator; deterministic per seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+const STEMS: &[&str] = &[
+    "a",
+    "data",
+    "my file",
+    "ünïcödé",
+    "日本",
+    "x.y",
+    ".hidden",
+    "emoji😀",
+    "CAPS",
+    "tab-less",
+];
+const EXTS: &[&str] = &["", ".txt", ".tar.gz", ".", ".HTML", ".json"];
+
+/// Generates a random tree under `root`, returning relative path -> contents.
+fn generate_tree(root: &Path, seed: u64) -> BTreeMap<String, Vec<u8>> {
+    let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1);
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![String::new()];
+    for _ in 0..40 {
+        let parent = dirs[rng.below(dirs.len() as u64) as usize].clone();
+        let depth = parent.matches('/').count() + usize::from(!parent.is_empty());
+        let name = format!("{}{}", rng.pick(STEMS), rng.below(100));
+        let rel = if parent.is_empty() {
+            name
+        } else {
+            format!("{parent}/{name}")
+        };
+        if depth < 6 && rng.below(3) == 0 {
+            std::fs::create_dir_all(root.join(&rel)).unwrap();
+            dirs.push(rel);
+            continue;
+        }
+        let rel = format!("{rel}{}", rng.pick(EXTS));
+        if files.contains_key(&rel) || dirs.contains(&rel) {
+            continue;
+        }
+        let size = match rng.below(10) {
+            0 => 0,
+            1 => rng.below(1 << 20) as usize,
+            _ => rng.below(4096) as usize,
+        };
+        let contents: Vec<u8> = (0..size).map(|_| rng.next() as u8).collect();
+        std::fs::write(root.join(&rel), &contents).unwrap();
+        files.insert(rel, contents);
+    }
+    files
+}
+
+/// Recursively collects file paths through `read_dir`.
+fn walk_read_dir(silo: &Silo, dir: &str, out: &mut BTreeSet<String>) {
+    for entry in silo.read_dir(dir) {
+        match entry {
+            Entry::File(file) => {
+                out.insert(file.path().to_str().unwrap().to_owned());
+            }
+            Entry::Dir(sub) => walk_read_dir(silo, &sub, out),
+        }
+    }
+}
+
+/// Tests lookup, iteration, counting, scoping, glob and listing invariants over many
+/// generated trees.
+#[test]
+fn test_dynamic_silo_invariants_over_random_trees() {
+    for seed in 1..=8u64 {
+        let tmp = tempfile::tempdir().unwrap();
+        let expected = generate_tree(tmp.path(), seed);
+        let silo = Silo::new(tmp.path().to_str().unwrap());
+
+        let iterated: BTreeSet<String> = silo
+            .iter()
+            .map(|f| f.path().to_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(
+            silo.iter().count(),
+            expected.len(),
+            "seed {seed}: count mismatch"
+        );
+        assert_eq!(
+            iterated,
+            expected.keys().cloned().collect(),
+            "seed {seed}: iter mismatch"
+        );
+
+        for path in &iterated {
+            assert!(
+                !path.split('/').any(|c| c == ".." || c == "."),
+                "seed {seed}: {path}"
+            );
+            assert!(!path.contains('\\'), "seed {seed}: {path}");
+            let file = silo
+                .get_file(path)
+                .unwrap_or_else(|| panic!("seed {seed}: {path} not found"));
+            assert_eq!(file.path().to_str().unwrap(), path);
+            assert_eq!(
+                file.contents().unwrap().as_ref(),
+                expected[path].as_slice(),
+                "seed {seed}: {path}"
+            );
+            assert_eq!(file.meta().unwrap().size, expected[path].len());
+        }
+
+        let mut listed = BTreeSet::new();
+        walk_read_dir(&silo, "", &mut listed);
+        assert_eq!(listed, iterated, "seed {seed}: read_dir mismatch");
+
+        assert_eq!(silo.snapshot().len(), expected.len(), "seed {seed}: len");
+        assert_eq!(
+            SiloLike::stats(&silo).unwrap().files,
+            expected.len(),
+            "seed {seed}: stats"
+        );
+        assert_eq!(paths(SiloLike::glob(&silo, "**")), iterated, "seed {seed}");
+
+        let dirs: BTreeSet<&str> = iterated
+            .iter()
+            .flat_map(|path| path.match_indices('/').map(|(at, _)| &path[..at]))
+            .collect();
+        for dir in dirs {
+            let under: BTreeSet<String> = iterated
+                .iter()
+                .filter_map(|path| path.strip_prefix(&format!("{dir}/")))
+                .map(str::to_owned)
+                .collect();
+            let scoped = silo.clone().scope(dir);
+            assert_eq!(paths(scoped.iter()), under, "seed {seed}: scope {dir}");
+            for path in &under {
+                assert_eq!(
+                    scoped.get_file(path).unwrap().contents().unwrap(),
+                    silo.get_file(&format!("{dir}/{path}"))
+                        .unwrap()
+                        .contents()
+                        .unwrap(),
+                    "seed {seed}: scope {dir}: {path}"
+                );
+            }
+            assert!(silo.has_any(dir), "seed {seed}: has_any {dir}");
+
+            let glob = |pattern: String| {
+                paths(SiloLike::glob(&silo, &pattern))
+                    .into_iter()
+                    .map(|path| path[dir.len() + 1..].to_owned())
+                    .collect::<BTreeSet<_>>()
+            };
+            assert_eq!(glob(format!("{dir}/**")), under, "seed {seed}: glob {dir}");
+
+            let mut listed = BTreeSet::new();
+            walk_read_dir(&silo, dir, &mut listed);
+            assert_eq!(
+                listed,
+                under.iter().map(|path| format!("{dir}/{path}")).collect(),
+                "seed {seed}: read_dir {dir}"
+            );
+            let children: BTreeSet<String> = silo
+                .read_dir(dir)
+                .filter_map(|entry| match entry {
+                    Entry::File(file) => Some(file.path().to_str().unwrap().to_owned()),
+                    Entry::Dir(_) => None,
+                })
+                .map(|path| path[dir.len() + 1..].to_owned())
+                .collect();
+            assert_eq!(
+                glob(format!("{dir}/*")),
+                children,
+                "seed {seed}: glob and read_dir of {dir}"
+            );
+        }
+    }
+}
+
+/// Relative paths of `files`.
+fn paths(files: impl Iterator<Item = File>) -> BTreeSet<String> {
+    files
+        .map(|file| file.path().to_str().unwrap().to_owned())
+        .collect()
+}
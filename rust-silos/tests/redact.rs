@@ -0,0 +1,43 @@
+//! Checks that `redact_root = true` keeps the build machine's source path out of the binary.
+//!
+//! Lives in its own test binary: every non-redacted embedded silo stores its absolute root,
+//! which would show up in the scan.
+
+use rust_silos::*;
+
+static ASSETS: Silo = embed_silo!("tests/data", force = true, redact_root = true);
+
+/// The redacted silo still serves files, but its absolute root appears nowhere in the test binary.
+#[test]
+fn test_redacted_root_not_in_binary() {
+    assert!(ASSETS.is_embedded());
+    assert!(ASSETS.get_file("alpha.txt").is_some());
+    // Assembled at runtime so the needle itself is not a literal in the binary.
+    let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .canonicalize()
+        .unwrap()
+        .join("tests")
+        .join("data");
+    let needle = root.to_str().unwrap().as_bytes().to_vec();
+    let binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    assert!(
+        !binary
+            .windows(needle.len())
+            .any(|window| window == needle.as_slice())
+    );
+}
+
+/// `into_dynamic` and, in debug builds, `auto_dynamic` report the missing root.
+#[test]
+fn test_redacted_root_into_dynamic() {
+    assert!(matches!(
+        ASSETS.clone().into_dynamic(),
+        Err(Error::RootRedacted)
+    ));
+    let auto = ASSETS.clone().auto_dynamic();
+    if cfg!(debug_assertions) {
+        assert!(matches!(auto, Err(Error::RootRedacted)));
+    } else {
+        assert!(auto.unwrap().is_embedded());
+    }
+}
@@ -3,6 +3,8 @@ use rust_silos_macros::embed_silo;
 use std::collections::HashSet;
 use std::io::Read;
 
+mod testutil;
+
 /// Tests that an embedded silo can retrieve a known file by path.
 #[test]
 fn test_embed_silo_get_file() {
@@ -100,7 +102,7 @@ fn test_file_absolute_path_embed() {
 #[test]
 fn test_silo_from_embedded_and_path() {
     let embed = embed_silo!("tests/data", force=true);
-    let dyns = embed.clone().into_dynamic();
+    let dyns = embed.clone().into_dynamic().unwrap();
     assert!(embed.is_embedded());
     assert!(dyns.is_dynamic());
 }
@@ -109,7 +111,7 @@ fn test_silo_from_embedded_and_path() {
 #[test]
 fn test_silo_auto_dynamic() {
     let embed = embed_silo!("tests/data");
-    let auto = embed.auto_dynamic();
+    let auto = embed.auto_dynamic().unwrap();
     assert!(auto.is_dynamic() || auto.is_embedded());
 }
 
@@ -142,6 +144,44 @@ fn test_silo_set_iter_override() {
     assert!(!files.is_empty());
 }
 
+/// Tests that resolve_with_source reports the winning layer by name or position.
+#[test]
+fn test_silo_set_resolve_with_source() {
+    let mut set = SiloSet::new(vec![Silo::from_static("tests/data")]);
+    set.push_named("overrides", Silo::from_static("tests/data/override"));
+    assert_eq!(set.layer_names(), ["layer-0", "overrides"]);
+
+    let (file, layer) = set.resolve_with_source("alpha.txt").unwrap();
+    assert_eq!(layer, "overrides");
+    assert!(
+        file.absolute_path()
+            .unwrap()
+            .ends_with("override/alpha.txt")
+    );
+    let (_, layer) = set.resolve_with_source("beta.txt").unwrap();
+    assert_eq!(layer, "layer-0");
+    assert!(set.resolve_with_source("missing.txt").is_none());
+}
+
+/// Tests that iter_overlay and iter_all_deduplicated match iter_override exactly.
+#[test]
+fn test_silo_set_overlay_aliases() {
+    let set = SiloSet::new(vec![
+        Silo::from_static("tests/data"),
+        Silo::from_static("tests/data/override"),
+    ]);
+    let describe = |files: Vec<File>| -> Vec<_> {
+        files
+            .iter()
+            .map(|f| (f.path().to_path_buf(), f.contents().unwrap().into_owned()))
+            .collect()
+    };
+    let overridden = describe(set.iter_override().collect());
+    assert_eq!(describe(set.iter_overlay().collect()), overridden);
+    assert_eq!(describe(set.iter_all_deduplicated().collect()), overridden);
+    assert_eq!(overridden.len(), 4);
+}
+
 /// Tests that embedded and dynamic silos yield the same files and contents.
 #[test]
 fn test_embed_vs_dyn_parity() {
@@ -271,3 +311,5490 @@ fn test_lookup_blocks_traversal() {
     assert!(silo.get_file("ok.txt").is_some());
     assert!(silo.get_file("../outside.txt").is_none());
 }
+
+/// Tests that routes are sorted, mounted under the prefix and percent-encoded.
+#[test]
+fn test_silo_routes_urls() {
+    let silo = embed_silo!("tests/data", force = true);
+    let routes = silo.routes("/assets/").unwrap();
+    let urls: Vec<_> = routes.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "/assets/alpha.txt",
+            "/assets/beta.txt",
+            "/assets/override/alpha.txt",
+            "/assets/subdir/gamma.txt"
+        ]
+    );
+    assert_eq!(routes[0].size, 19);
+}
+
+/// Tests that SiloSet::routes uses the override view.
+#[test]
+fn test_silo_set_routes_override() {
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::from_static("tests/data"),
+    ]);
+    let routes = set.routes("").unwrap();
+    assert_eq!(routes.len(), 4);
+    assert_eq!(routes[0].url, "/alpha.txt");
+    assert!(!set.get_file("alpha.txt").unwrap().is_embedded());
+}
+
+/// Tests a stable JSON snapshot of the route manifest for `tests/data`.
+#[cfg(all(feature = "serde", feature = "mime", feature = "hash"))]
+#[test]
+fn test_silo_routes_json_snapshot() {
+    let silo = embed_silo!("tests/data", force = true);
+    let json = serde_json::to_string(&silo.routes("/static").unwrap()).unwrap();
+    let expected = concat!(
+        r#"[{"url":"/static/alpha.txt","path":"alpha.txt","size":19,"content_type":"text/plain","hash":"b945ec0d3895cf17e25a165ebb6cce03f37ff70f8d96931c286e3438df08f3c0"},"#,
+        r#"{"url":"/static/beta.txt","path":"beta.txt","size":18,"content_type":"text/plain","hash":"68aff8968ef4f932111707a0021bf69c5aa0f449494c703ddd8c9edf9fe3d98e"},"#,
+        r#"{"url":"/static/override/alpha.txt","path":"override/alpha.txt","size":23,"content_type":"text/plain","hash":"b57acdd8c99226e2789eb15319d316e312f79bec341e550634f944ec51b409c2"},"#,
+        r#"{"url":"/static/subdir/gamma.txt","path":"subdir/gamma.txt","size":19,"content_type":"text/plain","hash":"37e1866a6e46eb9cf807acfefc926ebed004d76ed98e9e2e0bdd27ff7bb9bd60"}]"#,
+    );
+    assert_eq!(json, expected);
+}
+
+/// Tests that all fixture files fall into the lowest (< 1 KiB) histogram bucket.
+#[test]
+fn test_silo_size_histogram_default() {
+    let silo = embed_silo!("tests/data", force = true);
+    let histogram = silo.size_histogram_default();
+    assert_eq!(histogram[0], (0, 4));
+    assert_eq!(histogram[1], (1024, 0));
+    assert_eq!(histogram.last().unwrap().0, 1 << 20);
+    assert_eq!(histogram.iter().map(|(_, c)| c).sum::<usize>(), 4);
+}
+
+/// Tests custom bucket boundaries, including the open-ended last bucket.
+#[test]
+fn test_silo_size_histogram_buckets() {
+    let silo = Silo::from_static("tests/data");
+    assert_eq!(
+        silo.size_histogram(&[0, 19, 20]),
+        vec![(0, 1), (19, 2), (20, 1)]
+    );
+}
+
+/// Tests that directory-style lookups resolve to the configured index file.
+#[test]
+fn test_silo_with_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(tmp.path().join("docs")).unwrap();
+    std::fs::write(tmp.path().join("index.html"), b"root").unwrap();
+    std::fs::write(tmp.path().join("docs/index.html"), b"docs").unwrap();
+    std::fs::write(tmp.path().join("docs/page.html"), b"page").unwrap();
+
+    let plain = Silo::new(tmp.path().to_str().unwrap());
+    assert!(plain.get_file("docs/").is_none());
+
+    let silo = plain.with_default_index();
+    assert_eq!(
+        silo.get_file("docs").unwrap().path().to_str().unwrap(),
+        "docs/index.html"
+    );
+    assert_eq!(
+        silo.get_file("docs/").unwrap().path().to_str().unwrap(),
+        "docs/index.html"
+    );
+    assert_eq!(
+        silo.get_file("").unwrap().path().to_str().unwrap(),
+        "index.html"
+    );
+    assert_eq!(
+        silo.get_file("docs/page.html")
+            .unwrap()
+            .path()
+            .to_str()
+            .unwrap(),
+        "docs/page.html"
+    );
+    assert!(silo.get_file("missing/").is_none());
+}
+
+/// Tests that the index configuration survives conversion to dynamic mode.
+#[test]
+fn test_silo_with_index_embedded() {
+    let silo = embed_silo!("tests/data", force = true).with_index("gamma.txt");
+    assert_eq!(
+        silo.get_file("subdir").unwrap().path().to_str().unwrap(),
+        "subdir/gamma.txt"
+    );
+    let dynamic = silo.into_dynamic().unwrap();
+    assert_eq!(
+        dynamic
+            .get_file("subdir/")
+            .unwrap()
+            .path()
+            .to_str()
+            .unwrap(),
+        "subdir/gamma.txt"
+    );
+}
+
+/// Tests that generated embed code is checked against the current EmbedEntry layout.
+#[test]
+fn test_silo_abi_matches_generated_code() {
+    const _: () = assert!(SILO_ABI == 5);
+    let silo = embed_silo!("tests/data", force = true);
+    assert!(silo.get_file("alpha.txt").is_some());
+}
+
+/// Tests that symlinked files are embedded under their own keys when following symlinks.
+#[cfg(unix)]
+#[test]
+fn test_embed_follow_symlinks() {
+    let silo = embed_silo!("tests/links", force = true, follow_symlinks = true);
+    let mut paths: Vec<_> = silo
+        .iter()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, ["logo-dark.svg", "logo.svg", "nested/logo.svg"]);
+    for path in &paths {
+        let mut buf = String::new();
+        silo.get_file(path)
+            .unwrap()
+            .reader()
+            .unwrap()
+            .read_to_string(&mut buf)
+            .unwrap();
+        assert_eq!(buf, "logo\n");
+        assert!(silo.get_file(path).unwrap().symlink_target().is_none());
+    }
+
+    let plain = embed_silo!("tests/links", force = true);
+    let plain_paths: Vec<_> = plain
+        .iter()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(plain_paths, ["logo.svg"]);
+}
+
+/// Tests that the dynamic forms of an embedded tree with symlinks, from `into_dynamic` and
+/// from a debug build, list the same files as the embedded silo, with and without
+/// `follow_symlinks`.
+#[cfg(unix)]
+#[test]
+fn test_embed_symlink_parity() {
+    let paths = |silo: &Silo| {
+        let mut paths: Vec<_> = silo
+            .iter()
+            .map(|f| f.path().to_str().unwrap().to_owned())
+            .collect();
+        paths.sort();
+        paths
+    };
+    for (embedded, debug) in [
+        (
+            embed_silo!("tests/links", force = true),
+            embed_silo!("tests/links"),
+        ),
+        (
+            embed_silo!("tests/links", force = true, follow_symlinks = true),
+            embed_silo!("tests/links", follow_symlinks = true),
+        ),
+    ] {
+        let expected = paths(&embedded);
+        let dynamic = embedded.clone().into_dynamic().unwrap();
+        assert!(dynamic.is_dynamic());
+        for silo in [&dynamic, &debug] {
+            assert_eq!(paths(silo), expected);
+            assert_eq!(
+                silo.get_file("logo-dark.svg").is_some(),
+                embedded.get_file("logo-dark.svg").is_some()
+            );
+        }
+    }
+}
+
+/// Tests that dynamic symlinks keep their own path and report their resolved target.
+#[cfg(unix)]
+#[test]
+fn test_dyn_symlink_identity() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("root");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("logo.svg"), b"logo").unwrap();
+    std::os::unix::fs::symlink("logo.svg", root.join("logo-dark.svg")).unwrap();
+    std::fs::write(tmp.path().join("secret.txt"), b"secret").unwrap();
+    std::os::unix::fs::symlink(tmp.path().join("secret.txt"), root.join("escape.txt")).unwrap();
+
+    let silo = Silo::new(root.to_str().unwrap());
+    let link = silo.get_file("logo-dark.svg").unwrap();
+    assert_eq!(link.path().to_str().unwrap(), "logo-dark.svg");
+    assert_eq!(
+        link.symlink_target().unwrap(),
+        root.join("logo.svg").canonicalize().unwrap()
+    );
+    assert!(
+        silo.get_file("logo.svg")
+            .unwrap()
+            .symlink_target()
+            .is_none()
+    );
+    assert!(silo.get_file("escape.txt").is_none());
+
+    let mut paths: Vec<_> = silo
+        .iter()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    paths.sort();
+    assert_eq!(paths, ["logo-dark.svg", "logo.svg"]);
+}
+
+/// Tests that get_file_content borrows embedded bytes and owns dynamic bytes.
+#[test]
+fn test_get_file_content_cow() {
+    use std::borrow::Cow;
+    let embed = embed_silo!("tests/data", force = true);
+    let dyns = Silo::from_static("tests/data");
+    let embedded = embed.get_file_content("alpha.txt");
+    let dynamic = dyns.get_file_content("alpha.txt");
+    assert!(matches!(embedded, Ok(Cow::Borrowed(_))));
+    assert!(matches!(dynamic, Ok(Cow::Owned(_))));
+    assert_eq!(embedded.unwrap(), dynamic.unwrap());
+    assert!(matches!(
+        embed.get_file_content("missing.txt"),
+        Err(Error::NotFound)
+    ));
+}
+
+/// Tests that get_text_content decodes UTF-8 for both variants.
+#[test]
+fn test_get_text_content_cow() {
+    use std::borrow::Cow;
+    let embed = embed_silo!("tests/data", force = true);
+    let dyns = Silo::from_static("tests/data");
+    let embedded = embed.get_text_content("subdir/gamma.txt");
+    assert!(matches!(embedded, Ok(Cow::Borrowed(_))));
+    assert_eq!(
+        embedded.unwrap(),
+        dyns.get_text_content("subdir/gamma.txt").unwrap()
+    );
+}
+
+/// Tests that suggest returns the nearest paths for a typo, closest first.
+#[test]
+fn test_silo_suggest() {
+    let silo = embed_silo!("tests/data", force = true);
+    assert_eq!(silo.suggest("aplha.txt", 1), vec!["alpha.txt".to_owned()]);
+    assert_eq!(
+        silo.suggest("subdir/gama.txt", 3),
+        vec!["subdir/gamma.txt".to_owned()]
+    );
+    assert_eq!(
+        silo.suggest("alpha.txt", 2),
+        vec!["alpha.txt".to_owned(), "beta.txt".to_owned()]
+    );
+    assert!(silo.suggest("completely-unrelated.bin", 3).is_empty());
+    assert!(
+        Silo::from_static("tests/data")
+            .suggest("bta.txt", 0)
+            .is_empty()
+    );
+}
+
+/// Tests that skip_unreadable embeds readable files and skips a broken symlink.
+#[cfg(unix)]
+#[test]
+#[allow(deprecated)]
+fn test_embed_skip_unreadable() {
+    let silo = embed_silo!(
+        "tests/broken",
+        force = true,
+        follow_symlinks = true,
+        skip_unreadable = true
+    );
+    let paths: Vec<_> = silo
+        .iter()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(paths, ["ok.txt"]);
+}
+
+/// Tests that concatenated chunks equal the file contents for both variants.
+#[test]
+fn test_file_stream_chunks() {
+    for silo in [
+        embed_silo!("tests/data", force = true),
+        Silo::from_static("tests/data"),
+    ] {
+        let file = silo.get_file("override/alpha.txt").unwrap();
+        let chunks: Vec<Vec<u8>> = file.stream_chunks(5).collect::<Result<_, _>>().unwrap();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|c| c.len() <= 5));
+        assert_eq!(chunks.concat(), file.contents().unwrap().as_ref());
+    }
+}
+
+/// Tests that borrowed chunks are only available for embedded files.
+#[test]
+fn test_file_stream_chunks_borrowed() {
+    let embed = embed_silo!("tests/data", force = true)
+        .get_file("alpha.txt")
+        .unwrap();
+    let chunks: Vec<&'static [u8]> = embed.stream_chunks_borrowed(4).unwrap().collect();
+    assert_eq!(chunks.concat(), embed.contents().unwrap().as_ref());
+    let dynamic = Silo::from_static("tests/data")
+        .get_file("alpha.txt")
+        .unwrap();
+    assert!(dynamic.stream_chunks_borrowed(4).is_none());
+}
+
+/// Tests that a zero chunk size panics.
+#[test]
+#[should_panic(expected = "chunk_size must be non-zero")]
+fn test_file_stream_chunks_zero_panics() {
+    let file = Silo::from_static("tests/data")
+        .get_file("alpha.txt")
+        .unwrap();
+    let _ = file.stream_chunks(0);
+}
+
+fn entry_names(entries: impl Iterator<Item = Entry>) -> Vec<String> {
+    entries
+        .map(|entry| match entry {
+            Entry::File(file) => file.path().to_str().unwrap().to_owned(),
+            Entry::Dir(dir) => format!("{dir}/"),
+        })
+        .collect()
+}
+
+/// Tests shallow listing of the root and a subdirectory for both variants.
+#[test]
+fn test_silo_read_dir() {
+    for silo in [
+        embed_silo!("tests/data", force = true),
+        Silo::from_static("tests/data"),
+    ] {
+        assert_eq!(
+            entry_names(silo.read_dir("")),
+            ["alpha.txt", "beta.txt", "override/", "subdir/"]
+        );
+        assert_eq!(entry_names(silo.read_dir("subdir/")), ["subdir/gamma.txt"]);
+        assert!(entry_names(silo.read_dir("missing")).is_empty());
+        assert!(entry_names(silo.read_dir("../")).is_empty());
+    }
+}
+
+/// Tests magic-byte sniffing for extensionless files.
+#[test]
+fn test_file_sniff_mime() {
+    let tmp = tempfile::tempdir().unwrap();
+    let samples: &[(&str, &[u8], Option<&str>)] = &[
+        ("png", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", Some("image/png")),
+        ("jpeg", b"\xff\xd8\xff\xe0\0\x10JFIF", Some("image/jpeg")),
+        ("gif", b"GIF89a\x01\0\x01\0", Some("image/gif")),
+        ("pdf", b"%PDF-1.7\n", Some("application/pdf")),
+        ("gzip", b"\x1f\x8b\x08\0", Some("application/gzip")),
+        ("zip", b"PK\x03\x04\x14\0", Some("application/zip")),
+        ("text", "héllo\nwörld\t!".as_bytes(), Some("text/plain")),
+        ("binary", b"\0\x01\x02\x03", None),
+        ("empty", b"", None),
+    ];
+    for (name, bytes, _) in samples {
+        std::fs::write(tmp.path().join(name), bytes).unwrap();
+    }
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    for (name, _, expected) in samples {
+        assert_eq!(
+            silo.get_file(name).unwrap().sniff_mime(),
+            *expected,
+            "{name}"
+        );
+    }
+    let embedded = embed_silo!("tests/data", force = true)
+        .get_file("alpha.txt")
+        .unwrap();
+    assert_eq!(embedded.sniff_mime(), Some("text/plain"));
+}
+
+/// Tests that a multi-byte character cut by the sniff prefix still counts as text.
+#[test]
+fn test_file_sniff_mime_truncated_utf8() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut text = "a".repeat(511).into_bytes();
+    text.extend_from_slice("é".as_bytes());
+    std::fs::write(tmp.path().join("notes"), &text).unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    assert_eq!(
+        silo.get_file("notes").unwrap().sniff_mime(),
+        Some("text/plain")
+    );
+}
+
+/// Tests that mime_type_or_sniff prefers the extension and falls back to sniffing.
+#[cfg(feature = "mime")]
+#[test]
+fn test_file_mime_type_or_sniff() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("logo"), b"\x89PNG\r\n\x1a\n").unwrap();
+    std::fs::write(tmp.path().join("data.json"), b"\x89PNG\r\n\x1a\n").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    assert_eq!(silo.get_file("logo").unwrap().mime_type(), None);
+    assert_eq!(
+        silo.get_file("logo").unwrap().mime_type_or_sniff(),
+        Some("image/png")
+    );
+    assert_eq!(
+        silo.get_file("data.json").unwrap().mime_type_or_sniff(),
+        Some("application/json")
+    );
+}
+
+/// Tests the UTF-8 path accessors and constructors (unwrap-free path handling).
+#[cfg(feature = "camino")]
+#[test]
+fn test_camino_paths() {
+    use camino::Utf8Path;
+    let embed = embed_silo!("tests/data", force = true);
+    let file = embed.get_file("subdir/gamma.txt").unwrap();
+    assert_eq!(file.path_utf8(), "subdir/gamma.txt");
+    assert_eq!(file.path_utf8().extension(), Some("txt"));
+    assert!(file.absolute_path_utf8().is_none());
+
+    let silo = Silo::from_utf8_path(Utf8Path::new("tests/data"));
+    let file = silo.get_file("alpha.txt").unwrap();
+    assert_eq!(file.path_utf8().file_name(), Some("alpha.txt"));
+    assert!(
+        file.absolute_path_utf8()
+            .unwrap()
+            .ends_with("tests/data/alpha.txt")
+    );
+
+    let set = SiloSet::from_utf8_paths([
+        Utf8Path::new("tests/data/subdir"),
+        Utf8Path::new("tests/data"),
+    ]);
+    assert_eq!(set.get_file("gamma.txt").unwrap().path_utf8(), "gamma.txt");
+}
+
+/// Tests that a runtime-built PHF silo mirrors the dynamic silo it was built from.
+#[cfg(feature = "runtime-phf")]
+#[test]
+fn test_build_phf_at_runtime() {
+    let dynamic = Silo::from_static("tests/data");
+    let memory = dynamic.build_phf_at_runtime().unwrap();
+    let mut expected: Vec<_> = dynamic.iter().map(|f| f.path().to_path_buf()).collect();
+    let mut actual: Vec<_> = memory.iter().map(|f| f.path().to_path_buf()).collect();
+    expected.sort();
+    actual.sort();
+    assert_eq!(actual, expected);
+
+    for path in &expected {
+        let path = path.to_str().unwrap();
+        let from_disk = dynamic.get_file(path).unwrap();
+        let from_memory = memory.get_file(path).unwrap();
+        assert!(from_memory.absolute_path().is_none());
+        assert_eq!(
+            from_memory.contents().unwrap(),
+            from_disk.contents().unwrap()
+        );
+        assert_eq!(
+            from_memory.meta().unwrap().size,
+            from_disk.meta().unwrap().size
+        );
+        let mut buf = String::new();
+        from_memory
+            .reader()
+            .unwrap()
+            .read_to_string(&mut buf)
+            .unwrap();
+        assert_eq!(buf.as_bytes(), &*from_disk.contents().unwrap());
+    }
+    assert!(memory.get_file("missing.txt").is_none());
+    assert!(memory.get_file("subdir").is_none());
+}
+
+/// Tests that concurrent reads of one cold file through a coalescing silo open it once.
+#[test]
+fn test_coalescing_shares_one_read() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+
+    let opens = Arc::new(AtomicUsize::new(0));
+    let counter = opens.clone();
+    let silo = Silo::from_static("tests/data").with_coalescing_reader(move |path| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        std::fs::read(path)
+    });
+
+    let threads = 64;
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let silo = silo.clone();
+            let barrier = barrier.clone();
+            std::thread::spawn(move || {
+                let file = silo.get_file("alpha.txt").unwrap();
+                barrier.wait();
+                file.shared_contents().unwrap()
+            })
+        })
+        .collect();
+    let results: Vec<Arc<[u8]>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(opens.load(Ordering::SeqCst), 1);
+    assert!(results.iter().all(|bytes| Arc::ptr_eq(bytes, &results[0])));
+    assert_eq!(
+        &*results[0],
+        &*std::fs::read("tests/data/alpha.txt").unwrap()
+    );
+
+    // Completed reads are evicted, so a later read goes back to disk.
+    let file = silo
+        .iter()
+        .find(|f| f.path() == std::path::Path::new("alpha.txt"))
+        .unwrap();
+    assert_eq!(file.contents().unwrap().as_ref(), &*results[0]);
+    assert_eq!(opens.load(Ordering::SeqCst), 2);
+    assert!(silo.get_file("missing.txt").is_none());
+}
+
+/// Tests that embedded_map exposes the PHF map only for embedded silos.
+#[test]
+fn test_embedded_map() {
+    let embed = embed_silo!("tests/data", force = true);
+    let map = embed.embedded_map().unwrap();
+    assert_eq!(map.len(), embed.iter().count());
+    let entry = map.get("subdir/gamma.txt").unwrap();
+    assert_eq!(
+        entry.contents,
+        &*embed
+            .get_file("subdir/gamma.txt")
+            .unwrap()
+            .contents()
+            .unwrap()
+    );
+    assert!(Silo::from_static("tests/data").embedded_map().is_none());
+    assert!(embed.into_dynamic().unwrap().embedded_map().is_none());
+}
+
+/// Tests that non-UTF-8 text is transcoded identically at embed time and in dynamic mode.
+#[test]
+fn test_transcode_utf8() {
+    let embed = embed_silo!("tests/encodings", force = true, transcode = "utf8");
+    let dynamic = embed_silo!("tests/encodings", transcode = "utf8");
+    assert!(embed.is_embedded());
+    // Without `force`, release builds embed too.
+    if cfg!(debug_assertions) {
+        assert!(dynamic.is_dynamic());
+    }
+    let expected = [
+        ("latin1.txt", Some("windows-1252")),
+        ("utf16le.txt", Some("UTF-16LE")),
+        ("utf16be.csv", Some("UTF-16BE")),
+        ("bom.md", Some("UTF-8")),
+        ("plain.txt", None),
+    ];
+    for silo in [&embed, &dynamic] {
+        for (path, encoding) in expected {
+            let file = silo.get_file(path).unwrap();
+            assert_eq!(file.text().unwrap(), "café – naïve\n", "{path}");
+            assert_eq!(file.original_encoding(), encoding, "{path}");
+            assert_eq!(file.meta().unwrap().size, "café – naïve\n".len(), "{path}");
+            let mut text = String::new();
+            file.reader().unwrap().read_to_string(&mut text).unwrap();
+            assert_eq!(text, "café – naïve\n", "{path}");
+        }
+        let binary = silo.get_file("latin1.bin").unwrap();
+        assert_eq!(
+            binary.contents().unwrap().as_ref(),
+            &*std::fs::read("tests/encodings/latin1.bin").unwrap()
+        );
+        assert_eq!(binary.original_encoding(), None);
+    }
+}
+
+/// Tests the runtime failure policy for text files with an unrecognizable encoding.
+#[test]
+fn test_transcoding_failure_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("garbled.txt"), b"\xff\x00\x01\x02\x03").unwrap();
+    let root = tmp.path().to_str().unwrap();
+    let transcoding = |on_failure| Transcoding {
+        extensions: &["txt"],
+        on_failure,
+    };
+
+    let strict = Silo::new(root).with_transcoding(transcoding(TranscodeFailure::Error));
+    let err = strict
+        .get_file("garbled.txt")
+        .unwrap()
+        .contents()
+        .unwrap_err();
+    assert!(matches!(err, Error::TranscodeError { ref path } if path == "garbled.txt"));
+
+    let lenient = Silo::new(root).with_transcoding(transcoding(TranscodeFailure::Skip));
+    let file = lenient.get_file("garbled.txt").unwrap();
+    assert_eq!(file.contents().unwrap().as_ref(), b"\xff\x00\x01\x02\x03");
+    assert_eq!(file.original_encoding(), None);
+}
+
+/// Tests that as_embed_ref gives direct access to embedded entries.
+#[test]
+fn test_as_embed_ref() {
+    let embed = embed_silo!("tests/data", force = true);
+    let map = embed.as_embed_ref().unwrap();
+    assert_eq!(map.get("alpha.txt").unwrap().path, "alpha.txt");
+    assert!(Silo::from_static("tests/data").as_embed_ref().is_none());
+}
+
+/// Tests sorted iteration by path, by custom comparator, by size and by modification time.
+#[test]
+fn test_iter_sorted_by() {
+    let silo = Silo::from_static("tests/data");
+    let size = |file: &File| file.meta().unwrap().size;
+    let paths: Vec<_> = silo
+        .iter_sorted()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "alpha.txt",
+            "beta.txt",
+            "override/alpha.txt",
+            "subdir/gamma.txt"
+        ]
+    );
+
+    let largest = silo.iter().map(|f| size(&f)).max().unwrap();
+    let first = silo
+        .iter_sorted_by(|a, b| size(b).cmp(&size(a)))
+        .next()
+        .unwrap();
+    assert_eq!(size(&first), largest);
+
+    let sizes: Vec<_> = silo.iter_sorted_by_size().map(|f| size(&f)).collect();
+    assert!(sizes.is_sorted());
+    assert_eq!(sizes.len(), 4);
+    let modified: Vec<_> = silo
+        .iter_sorted_by_modified()
+        .map(|f| f.meta().unwrap().modified)
+        .collect();
+    assert!(modified.is_sorted());
+}
+
+/// Tests silo equality by backend identity and SiloSet::dedup keeping precedence.
+#[test]
+fn test_silo_eq_and_dedup() {
+    let embed = embed_silo!("tests/data", force = true);
+    let other_embed = embed_silo!("tests/data/subdir", force = true);
+    assert_eq!(embed, embed.clone());
+    assert_ne!(embed, other_embed);
+    assert_eq!(Silo::from_static("tests/data"), Silo::new("tests/data"));
+    assert_ne!(Silo::new("tests/data"), Silo::new("tests/data/subdir"));
+    assert_ne!(
+        Silo::new("tests/data"),
+        Silo::new("tests/data").with_default_index()
+    );
+    assert_ne!(embed, embed.clone().into_dynamic().unwrap());
+
+    let base = Silo::new("tests/data");
+    let overrides = Silo::new("tests/data/override");
+    let mut set = SiloSet::new(vec![
+        base.clone(),
+        overrides.clone(),
+        base.clone(),
+        overrides.clone(),
+    ]);
+    set.dedup();
+    assert_eq!(set, SiloSet::new(vec![base, overrides]));
+    assert_eq!(set.iter().count(), 5);
+    assert!(
+        set.get_file("alpha.txt")
+            .unwrap()
+            .absolute_path()
+            .unwrap()
+            .ends_with("override/alpha.txt")
+    );
+}
+
+/// Silos over the same root with different symlink policies, walk limits or key
+/// normalization are distinct, so `SiloSet::dedup` never drops one with other semantics.
+#[test]
+fn test_dedup_keeps_distinct_policies() {
+    let strict = Silo::new("tests/data").with_symlink_policy(SymlinkPolicy::Deny);
+    let permissive = Silo::new("tests/data").with_symlink_policy(SymlinkPolicy::AllowAll);
+    assert_ne!(strict, permissive);
+    assert_eq!(strict, strict.clone());
+    let mut set = SiloSet::new(vec![permissive.clone(), strict.clone()]);
+    set.dedup();
+    assert_eq!(set, SiloSet::new(vec![permissive, strict]));
+
+    let limited = Silo::new("tests/data").with_walk_limits(WalkLimits {
+        max_files: Some(1),
+        ..WalkLimits::UNLIMITED
+    });
+    assert_ne!(Silo::new("tests/data"), limited);
+    let mut set = SiloSet::new(vec![Silo::new("tests/data"), limited]);
+    set.dedup();
+    assert_eq!(set.silos.len(), 2);
+
+    let lowercase = Silo::new("tests/data").with_key_normalization(KeyNormalization::Lowercase);
+    assert_ne!(Silo::new("tests/data"), lowercase);
+    assert_eq!(lowercase, lowercase.clone());
+    let mut set = SiloSet::new(vec![lowercase, Silo::new("tests/data")]);
+    set.dedup();
+    assert_eq!(set.silos.len(), 2);
+}
+
+/// Tests that a scoped silo resolves, iterates and lists paths relative to the subtree.
+#[test]
+fn test_silo_scope() {
+    let embed = embed_silo!("tests/data", force = true);
+    for silo in [
+        embed.clone().scope("subdir"),
+        Silo::new("tests/data").scope("./subdir/"),
+    ] {
+        let file = silo.get_file("gamma.txt").unwrap();
+        assert_eq!(file.path().to_str().unwrap(), "gamma.txt");
+        assert_eq!(
+            file.text().unwrap(),
+            std::fs::read_to_string("tests/data/subdir/gamma.txt").unwrap()
+        );
+        assert!(silo.get_file("alpha.txt").is_none());
+        assert!(silo.get_file("../alpha.txt").is_none());
+        let paths: Vec<_> = silo
+            .iter()
+            .map(|f| f.path().to_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(paths, ["gamma.txt"]);
+        let listed: Vec<_> = silo
+            .read_dir("")
+            .map(|e| match e {
+                Entry::File(f) => f.path().to_str().unwrap().to_owned(),
+                Entry::Dir(d) => d,
+            })
+            .collect();
+        assert_eq!(listed, ["gamma.txt"]);
+    }
+
+    assert_eq!(
+        embed.clone().scope("override/../subdir"),
+        embed.clone().scope("subdir")
+    );
+    assert_eq!(
+        embed.clone().scope("subdir").scope(".."),
+        embed.clone().scope("subdir")
+    );
+    assert!(
+        embed
+            .clone()
+            .scope("sub")
+            .get_file("dir/gamma.txt")
+            .is_none()
+    );
+    let dynamic = embed.scope("subdir").into_dynamic().unwrap();
+    assert!(dynamic.is_dynamic());
+    assert_eq!(
+        dynamic
+            .get_file("gamma.txt")
+            .unwrap()
+            .path()
+            .to_str()
+            .unwrap(),
+        "gamma.txt"
+    );
+}
+
+/// Tests composing SiloSet::scoped and SiloSet::filtered while preserving overlay precedence.
+#[test]
+fn test_silo_set_scoped_and_filtered() {
+    let tmp = tempfile::tempdir().unwrap();
+    let write = |rel: &str, text: &str| {
+        let path = tmp.path().join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    };
+    write("base/templates/base.html", "base");
+    write("base/templates/page.html", "base page");
+    write("base/templates/style.css", "css");
+    write("base/readme.txt", "readme");
+    write("theme/templates/page.html", "theme page");
+    write("theme/templates/partials/nav.html", "nav");
+
+    let mut set = SiloSet::new(vec![Silo::new(tmp.path().join("base").to_str().unwrap())]);
+    set.push_named(
+        "theme",
+        Silo::new(tmp.path().join("theme").to_str().unwrap()),
+    );
+    let templates = set
+        .scoped("templates")
+        .filtered(|f| f.extension() == Some("html"));
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates.layer_names(), ["layer-0", "theme"]);
+
+    assert_eq!(
+        templates.get_file("page.html").unwrap().text().unwrap(),
+        "theme page"
+    );
+    assert_eq!(
+        templates.get_file("base.html").unwrap().text().unwrap(),
+        "base"
+    );
+    assert!(templates.get_file("style.css").is_none());
+    assert!(templates.get_file("readme.txt").is_none());
+    let (_, layer) = templates.resolve_with_source("partials/nav.html").unwrap();
+    assert_eq!(layer, "theme");
+
+    let mut visible: Vec<_> = templates
+        .iter_override()
+        .map(|f| f.path().to_str().unwrap().to_owned())
+        .collect();
+    visible.sort();
+    assert_eq!(visible, ["base.html", "page.html", "partials/nav.html"]);
+
+    let partials = templates.scoped("partials");
+    assert_eq!(
+        partials.get_file("nav.html").unwrap().text().unwrap(),
+        "nav"
+    );
+    assert_eq!(partials.iter_override().count(), 1);
+}
+
+/// Tests that buffered_reader supports line reads for embedded and dynamic files alike.
+#[test]
+fn test_buffered_reader_lines() {
+    use std::io::BufRead;
+    let tmp = tempfile::tempdir().unwrap();
+    let text: String = (0..1000).map(|i| format!("line {i}\n")).collect();
+    std::fs::write(tmp.path().join("lines.txt"), &text).unwrap();
+    let dynamic = Silo::new(tmp.path().to_str().unwrap())
+        .get_file("lines.txt")
+        .unwrap();
+    let reader = dynamic.buffered_reader(64).unwrap();
+    assert!(matches!(reader, BufferedFileReader::Dynamic(_)));
+    let lines: Vec<String> = reader.lines().map(Result::unwrap).collect();
+    assert_eq!(lines.len(), 1000);
+    assert_eq!(lines[999], "line 999");
+
+    let embed = embed_silo!("tests/data", force = true);
+    let file = embed.get_file("alpha.txt").unwrap();
+    let mut line = String::new();
+    let mut reader = file.buffered_reader(8 * 1024).unwrap();
+    assert!(matches!(reader, BufferedFileReader::Embed(_)));
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(
+        line,
+        file.text().unwrap().lines().next().unwrap().to_owned() + "\n"
+    );
+}
+
+/// Tests that prewarm counts files for dynamic silos and is a no-op count for embedded ones.
+#[test]
+fn test_prewarm() {
+    let dynamic = Silo::from_static("tests/data");
+    assert_eq!(dynamic.prewarm().unwrap(), dynamic.iter().count());
+    let embed = embed_silo!("tests/data", force = true);
+    assert_eq!(embed.prewarm().unwrap(), 4);
+    assert_eq!(Silo::new("tests/does-not-exist").prewarm().unwrap(), 0);
+}
+
+/// Tests that a SiloSet observer sees hits with the serving layer, misses and reads.
+#[test]
+fn test_silo_set_observer() {
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl SiloObserver for Recorder {
+        fn on_hit(&self, path: &str, silo_index: usize, embedded: bool) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("hit {path} {silo_index} {embedded}"));
+        }
+        fn on_miss(&self, path: &str) {
+            self.0.lock().unwrap().push(format!("miss {path}"));
+        }
+        fn on_read(&self, path: &str, bytes: usize) {
+            self.0.lock().unwrap().push(format!("read {path} {bytes}"));
+        }
+    }
+
+    let recorder = Arc::new(Recorder::default());
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::from_static("tests/data/override"),
+    ])
+    .with_observer(recorder.clone());
+    assert!(set.get_file("beta.txt").is_some());
+    assert!(set.get_file("nope.txt").is_none());
+    let alpha = set.get_file_content("alpha.txt").unwrap();
+    let beta = set.get_text_content("beta.txt").unwrap();
+    assert!(set.get_file_content("nope.txt").is_err());
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [
+            "hit beta.txt 0 true".to_owned(),
+            "miss nope.txt".to_owned(),
+            "hit alpha.txt 1 false".to_owned(),
+            format!("read alpha.txt {}", alpha.len()),
+            "hit beta.txt 0 true".to_owned(),
+            format!("read beta.txt {}", beta.len()),
+            "miss nope.txt".to_owned(),
+        ]
+    );
+
+    recorder.0.lock().unwrap().clear();
+    let full = set.serve("/alpha.txt", &RequestHeaders::new());
+    let range: RequestHeaders = [("Range", "bytes=0-1")].into_iter().collect();
+    assert_eq!(set.serve("/alpha.txt", &range).status, 206);
+    let revalidate: RequestHeaders = [("If-None-Match", full.header("ETag").unwrap())]
+        .into_iter()
+        .collect();
+    assert_eq!(set.serve("/alpha.txt", &revalidate).status, 304);
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        [
+            "hit alpha.txt 1 false".to_owned(),
+            format!("read alpha.txt {}", alpha.len()),
+            "hit alpha.txt 1 false".to_owned(),
+            "read alpha.txt 2".to_owned(),
+            "hit alpha.txt 1 false".to_owned(),
+        ]
+    );
+}
+
+/// Tests that get_file_with_exts tries extensions in order and returns the first hit.
+#[test]
+fn test_get_file_with_exts() {
+    let silo = Silo::from_static("tests/data");
+    let file = silo
+        .get_file_with_exts("subdir/gamma", &[".html", "md", "txt"])
+        .unwrap();
+    assert_eq!(file.path().to_str().unwrap(), "subdir/gamma.txt");
+    assert!(
+        silo.get_file_with_exts("subdir/gamma", &["html", "htm"])
+            .is_none()
+    );
+    assert!(silo.get_file_with_exts("alpha", &[]).is_none());
+    let embed = embed_silo!("tests/data", force = true);
+    assert_eq!(
+        embed
+            .get_file_with_exts("beta", &["txt"])
+            .unwrap()
+            .path()
+            .to_str()
+            .unwrap(),
+        "beta.txt"
+    );
+}
+
+/// Tests merging two silos with a resolver that keeps the larger file.
+#[test]
+fn test_merge_with() {
+    let base = Silo::from_static("tests/data");
+    let overrides = embed_silo!("tests/data/override", force = true);
+    let size = |file: &File| file.meta().unwrap().size;
+    let merged = base
+        .merge_with(&overrides, |left, right| {
+            if size(left) > size(right) {
+                MergeDecision::TakeLeft
+            } else {
+                MergeDecision::TakeRight
+            }
+        })
+        .unwrap();
+    assert!(!merged.is_dynamic() && !merged.is_embedded());
+    assert_eq!(merged.iter().count(), 4);
+    // override/alpha.txt (23 bytes) beats alpha.txt (19 bytes).
+    assert_eq!(
+        merged.get_text_content("alpha.txt").unwrap(),
+        std::fs::read_to_string("tests/data/override/alpha.txt").unwrap()
+    );
+    assert_eq!(
+        merged.get_text_content("beta.txt").unwrap(),
+        std::fs::read_to_string("tests/data/beta.txt").unwrap()
+    );
+    assert!(merged.get_file("subdir/gamma.txt").is_some());
+
+    let joined = base
+        .merge_with(&overrides, |left, right| {
+            let mut bytes = left.contents().unwrap().into_owned();
+            bytes.extend_from_slice(&right.contents().unwrap());
+            MergeDecision::TakeBytes(bytes)
+        })
+        .unwrap();
+    let alpha = joined.get_file("alpha.txt").unwrap();
+    assert_eq!(alpha.meta().unwrap().size, 19 + 23);
+    assert!(joined.get_file("missing.txt").is_none());
+}
+
+/// `into_dynamic` converts embedded silos that kept their source directory.
+#[test]
+fn test_into_dynamic_checked() {
+    let silo = embed_silo!("tests/data", force = true)
+        .into_dynamic()
+        .unwrap();
+    assert!(silo.is_dynamic());
+    assert!(silo.get_file("alpha.txt").is_some());
+    assert!(Silo::new("tests/data").into_dynamic().unwrap().is_dynamic());
+}
+
+/// `modified_latest` is the newest file timestamp, and `None` for an empty silo.
+#[test]
+fn test_modified_latest() {
+    let dir = tempfile::tempdir().unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap());
+    assert_eq!(silo.modified_latest(), None);
+    std::fs::write(dir.path().join("old.txt"), "old").unwrap();
+    std::fs::write(dir.path().join("new.txt"), "new").unwrap();
+    let old = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+    std::fs::File::options()
+        .write(true)
+        .open(dir.path().join("old.txt"))
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    let newest = silo
+        .iter()
+        .filter_map(|f| f.meta().ok())
+        .map(|m| m.modified)
+        .max()
+        .unwrap();
+    let latest = silo.modified_latest().unwrap();
+    assert_eq!(
+        latest
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        newest
+    );
+    assert!(latest > old);
+
+    let embed = embed_silo!("tests/data", force = true);
+    let newest = embed
+        .iter()
+        .map(|f| f.meta().unwrap().modified)
+        .max()
+        .unwrap();
+    assert_eq!(
+        embed.modified_latest(),
+        Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(newest))
+    );
+}
+
+/// `auto_manifest` deserializes the first candidate found at the silo root.
+#[cfg(feature = "json")]
+#[test]
+fn test_auto_manifest() {
+    let silo = embed_silo!("tests/manifest", force = true);
+    let result = silo.auto_manifest::<serde_json::Value>().unwrap().unwrap();
+    assert_eq!(result["version"], "1.0");
+    let assets: serde_json::Value = silo
+        .auto_manifest_with_candidates(&["missing.json", "assets.json"])
+        .unwrap()
+        .unwrap();
+    assert_eq!(assets["version"], "assets");
+    assert!(
+        embed_silo!("tests/data", force = true)
+            .auto_manifest::<serde_json::Value>()
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        silo.auto_manifest_with_candidates::<serde_json::Value>(&[])
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// `manifest.toml` is parsed as TOML and takes precedence over `assets.json`.
+#[cfg(all(feature = "json", feature = "toml"))]
+#[test]
+fn test_auto_manifest_toml() {
+    let silo = Silo::new("tests/manifest_toml");
+    let result: serde_json::Value = silo.auto_manifest().unwrap().unwrap();
+    assert_eq!(result["version"], "toml");
+}
+
+/// Malformed manifests surface as a parse error.
+#[cfg(feature = "json")]
+#[test]
+fn test_auto_manifest_invalid() {
+    let silo = Silo::new("tests/data");
+    let err = silo
+        .auto_manifest_with_candidates::<serde_json::Value>(&["alpha.txt"])
+        .unwrap_err();
+    assert!(matches!(err, Error::JsonError { .. }));
+}
+
+/// A tenant overrides one file and inherits the rest from the default layer.
+#[test]
+fn test_tenant_view() {
+    for silo in [
+        embed_silo!("tests/tenants", force = true),
+        Silo::new("tests/tenants"),
+    ] {
+        let view = silo.tenant_view("acme", "tenants", "default");
+        assert_eq!(
+            view.get_file("logo.txt").unwrap().text().unwrap(),
+            "acme logo\n"
+        );
+        assert_eq!(
+            view.get_file("style.css").unwrap().text().unwrap(),
+            "default style\n"
+        );
+        let (_, source) = view.resolve_with_source("logo.txt").unwrap();
+        assert_eq!(source, "acme");
+        assert_eq!(view.layer_names(), ["default", "acme"]);
+        assert!(view.get_file("secret.txt").is_none());
+
+        let unknown = silo.tenant_view("initech", "tenants", "default");
+        assert_eq!(
+            unknown.get_file("logo.txt").unwrap().text().unwrap(),
+            "default logo\n"
+        );
+    }
+}
+
+/// Tenant names that are not a single path component never reach other tenants' files.
+#[test]
+fn test_tenant_view_traversal() {
+    for silo in [
+        embed_silo!("tests/tenants", force = true),
+        Silo::new("tests/tenants"),
+    ] {
+        for tenant in [
+            "../tenants/globex",
+            "acme/../globex",
+            "..",
+            ".",
+            "",
+            "..\\globex",
+            "../../",
+        ] {
+            let view = silo.tenant_view(tenant, "tenants", "default");
+            assert_eq!(view.len(), 1, "{tenant}");
+            assert!(view.get_file("secret.txt").is_none(), "{tenant}");
+            assert_eq!(
+                view.get_file("logo.txt").unwrap().text().unwrap(),
+                "default logo\n"
+            );
+        }
+        let view = silo.tenant_view("acme", "tenants", "default");
+        assert!(view.get_file("../globex/secret.txt").is_none());
+        assert!(view.get_file("../tenants/globex/secret.txt").is_none());
+    }
+}
+
+/// Embed-time content hashes equal the runtime hashes of the same files on disk.
+#[test]
+fn test_content_hash_matches_across_backends() {
+    let embed = embed_silo!("tests/data", force = true);
+    let dynamic = embed.clone().into_dynamic().unwrap();
+    assert!(embed.iter().count() > 0);
+    for file in embed.iter() {
+        let on_disk = dynamic.get_file(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(
+            file.content_hash().unwrap(),
+            on_disk.content_hash().unwrap(),
+            "{}",
+            file.path().display()
+        );
+    }
+    let transcoded = embed_silo!("tests/encodings", force = true, transcode = "utf8");
+    let transcoding = Transcoding {
+        extensions: &["txt", "csv", "md"],
+        on_failure: TranscodeFailure::Skip,
+    };
+    let dynamic = Silo::new("tests/encodings").with_transcoding(transcoding);
+    let latin1 = transcoded.get_file("latin1.txt").unwrap();
+    assert_eq!(
+        latin1.content_hash().unwrap(),
+        ContentHasher::hash(&latin1.contents().unwrap())
+    );
+    for file in transcoded.iter() {
+        let on_disk = dynamic.get_file(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(
+            file.content_hash().unwrap(),
+            on_disk.content_hash().unwrap(),
+            "{}",
+            file.path().display()
+        );
+    }
+}
+
+/// `ContentHasher` is 64-bit FNV-1a and independent of how the input is chunked.
+#[test]
+fn test_content_hasher_stable() {
+    assert_eq!(ContentHasher::hash(b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(ContentHasher::hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    let mut hasher = ContentHasher::new();
+    hasher.update(b"alpha ");
+    hasher.update(b"file content\n");
+    assert_eq!(
+        hasher.finish(),
+        ContentHasher::hash(b"alpha file content\n")
+    );
+    let memory = Silo::new("tests/data")
+        .merge_with(&Silo::new("tests/data/subdir"), |_, _| {
+            MergeDecision::TakeLeft
+        })
+        .unwrap();
+    assert_eq!(
+        memory
+            .get_file("alpha.txt")
+            .unwrap()
+            .content_hash()
+            .unwrap(),
+        hasher.finish()
+    );
+}
+
+/// `diff_manifest` reports added, removed and modified files by content hash.
+#[test]
+fn test_manifest_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("same.txt"), "same").unwrap();
+    std::fs::write(dir.path().join("edit.txt"), "before").unwrap();
+    std::fs::write(dir.path().join("gone.txt"), "gone").unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap());
+    let older = silo.manifest().unwrap();
+    assert_eq!(
+        older
+            .entries
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<_>>(),
+        ["edit.txt", "gone.txt", "same.txt"]
+    );
+    assert!(silo.diff_manifest(&older).unwrap().is_empty());
+
+    std::fs::write(dir.path().join("edit.txt"), "after!!").unwrap();
+    std::fs::remove_file(dir.path().join("gone.txt")).unwrap();
+    std::fs::write(dir.path().join("new.txt"), "new").unwrap();
+    let diff = silo.diff_manifest(&older).unwrap();
+    assert_eq!(
+        diff.added
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<_>>(),
+        ["new.txt"]
+    );
+    assert_eq!(
+        diff.removed
+            .iter()
+            .map(|e| e.path.as_str())
+            .collect::<Vec<_>>(),
+        ["gone.txt"]
+    );
+    assert_eq!(diff.changed.len(), 1);
+    let change = &diff.changed[0];
+    assert_eq!(
+        (
+            change.path.as_str(),
+            change.old_size,
+            change.new_size,
+            change.by_hash
+        ),
+        ("edit.txt", 6, 7, true)
+    );
+    let text = diff.to_string();
+    assert_eq!(text.lines().count(), 3);
+    assert!(text.contains("+ new.txt (3 bytes)"));
+    assert!(text.contains("- gone.txt"));
+    assert!(text.contains("~ edit.txt: size 6 -> 7"));
+}
+
+/// Without hashes, manifests fall back to size and mtime and flag the lower confidence.
+#[test]
+fn test_manifest_diff_without_hashes() {
+    let entry = |path: &str, size, modified, hash| ManifestEntry {
+        path: path.into(),
+        size,
+        modified,
+        hash,
+    };
+    let older = Manifest {
+        entries: vec![entry("a.txt", 1, 10, None), entry("b.txt", 2, 10, Some(7))],
+        build_id: None,
+    };
+    let newer = Manifest {
+        entries: vec![
+            entry("a.txt", 1, 20, Some(1)),
+            entry("b.txt", 2, 30, Some(7)),
+        ],
+        build_id: None,
+    };
+    let diff = newer.diff(&older);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].path, "a.txt");
+    assert!(!diff.changed[0].by_hash);
+    assert_eq!(
+        diff.to_string(),
+        "~ a.txt: size 1 -> 1, modified +10s (compared by size and mtime)\n"
+    );
+}
+
+/// Manifests and diffs survive a JSON round trip.
+#[cfg(feature = "serde")]
+#[test]
+fn test_manifest_serde_round_trip() {
+    let silo = embed_silo!("tests/data", force = true);
+    let manifest = silo.manifest().unwrap();
+    let json = serde_json::to_string(&manifest).unwrap();
+    let older: Manifest = serde_json::from_str(&json).unwrap();
+    assert_eq!(older, manifest);
+    let mut edited = older.clone();
+    edited.entries[0].hash = Some(0);
+    let diff = silo.diff_manifest(&edited).unwrap();
+    assert_eq!(diff.changed.len(), 1);
+    let diff_json = serde_json::to_string(&diff).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ManifestDiff>(&diff_json).unwrap(),
+        diff
+    );
+    let legacy: Manifest =
+        serde_json::from_str(r#"{"entries":[{"path":"a","size":1,"modified":2}]}"#).unwrap();
+    assert_eq!(legacy.entries[0].hash, None);
+}
+
+/// `serve` returns 200 with headers and body, 404 for missing files and rejected targets.
+#[test]
+fn test_serve_basic() {
+    let silo = embed_silo!("tests/data", force = true);
+    let mut response = silo.serve("/alpha.txt?v=3#top", &RequestHeaders::new());
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("content-type"), Some("text/plain"));
+    assert_eq!(response.header("Content-Length"), Some("19"));
+    let etag = silo.get_file("alpha.txt").unwrap().content_hash().unwrap();
+    assert_eq!(response.header("ETag").unwrap(), format!("\"{etag:016x}\""));
+    let mut body = String::new();
+    response
+        .body
+        .take()
+        .unwrap()
+        .read_to_string(&mut body)
+        .unwrap();
+    assert_eq!(body, "alpha file content\n");
+    assert_eq!(
+        silo.serve("/subdir/gamma%2Etxt", &RequestHeaders::new())
+            .status,
+        200
+    );
+
+    for target in [
+        "/missing.txt",
+        "/subdir/../alpha.txt",
+        "/./alpha.txt",
+        "/%2e%2e/Cargo.toml",
+        "/%zz",
+        "/alpha.txt%00",
+    ] {
+        let response = silo.serve(target, &RequestHeaders::new());
+        assert_eq!(response.status, 404, "{target}");
+        assert!(response.body.is_none());
+    }
+    let dynamic = Silo::new("tests/data");
+    assert_eq!(
+        dynamic
+            .serve("/..%2FCargo.toml", &RequestHeaders::new())
+            .status,
+        404
+    );
+    assert_eq!(
+        dynamic.serve("/alpha.txt", &RequestHeaders::new()).status,
+        200
+    );
+}
+
+/// Error pages keep the 404/500 status, carry no validators, and fall back to plain text
+/// when the page itself is missing.
+#[test]
+fn test_serve_error_pages() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("404.html"), b"<h1>lost</h1>").unwrap();
+    std::fs::write(tmp.path().join("500.html"), b"<h1>broken</h1>").unwrap();
+    std::fs::write(tmp.path().join("garbled.txt"), b"\xff\x00\x01\x02\x03").unwrap();
+    let root = tmp.path().to_str().unwrap();
+    let transcoding = Transcoding {
+        extensions: &["txt"],
+        on_failure: TranscodeFailure::Error,
+    };
+    let body = |response: HttpFileResponse| {
+        assert!(response.header("ETag").is_none());
+        assert!(response.header("Last-Modified").is_none());
+        let mut body = String::new();
+        response
+            .body
+            .expect("error page body")
+            .read_to_string(&mut body)
+            .unwrap();
+        body
+    };
+
+    let silo = Silo::new(root)
+        .with_transcoding(transcoding)
+        .with_not_found_page("404.html")
+        .with_error_page("500.html");
+    let revalidate: RequestHeaders = [("If-None-Match", "*")].into_iter().collect();
+    for target in ["/missing.txt", "/%2e%2e/secret"] {
+        let response = silo.serve(target, &revalidate);
+        assert_eq!(response.status, 404, "{target}");
+        assert_eq!(response.header("Content-Length"), Some("13"));
+        assert_eq!(body(response), "<h1>lost</h1>");
+    }
+    let response = silo.serve("/garbled.txt", &RequestHeaders::new());
+    assert_eq!(response.status, 500);
+    assert_eq!(body(response), "<h1>broken</h1>");
+    assert_eq!(silo.serve("/404.html", &RequestHeaders::new()).status, 200);
+
+    let missing_pages = Silo::new(root)
+        .with_transcoding(transcoding)
+        .with_not_found_page("nope/404.html")
+        .with_error_page("nope/500.html");
+    let response = missing_pages.serve("/missing.txt", &RequestHeaders::new());
+    assert_eq!(response.status, 404);
+    assert_eq!(
+        response.header("Content-Type"),
+        Some("text/plain; charset=utf-8")
+    );
+    assert_eq!(body(response), "404 Not Found");
+    let response = missing_pages.serve("/garbled.txt", &RequestHeaders::new());
+    assert_eq!(response.status, 500);
+    assert_eq!(body(response), "500 Internal Server Error");
+
+    let plain = Silo::new(root).with_transcoding(transcoding);
+    assert!(
+        plain
+            .serve("/missing.txt", &RequestHeaders::new())
+            .body
+            .is_none()
+    );
+    assert_ne!(plain, silo);
+
+    // A set answers misses with its highest-precedence not-found page, and only reports
+    // bytes read for files actually served.
+    #[derive(Default)]
+    struct Reads(std::sync::Mutex<Vec<String>>);
+    impl SiloObserver for Reads {
+        fn on_read(&self, path: &str, bytes: usize) {
+            self.0.lock().unwrap().push(format!("{path} {bytes}"));
+        }
+    }
+    let reads = std::sync::Arc::new(Reads::default());
+    let set = SiloSet::new(vec![missing_pages, plain, silo]).with_observer(reads.clone());
+    let response = set.serve("/missing.txt", &RequestHeaders::new());
+    assert_eq!(response.status, 404);
+    assert_eq!(body(response), "<h1>lost</h1>");
+    assert!(
+        set.serve("/%2e%2e/secret", &RequestHeaders::new())
+            .body
+            .is_some()
+    );
+    let response = set.serve("/garbled.txt", &RequestHeaders::new());
+    assert_eq!(response.status, 500);
+    assert_eq!(body(response), "<h1>broken</h1>");
+    assert_eq!(set.serve("/404.html", &RequestHeaders::new()).status, 200);
+    assert_eq!(*reads.0.lock().unwrap(), ["404.html 13"]);
+}
+
+/// `HeaderProfile::WebDefaults` gives wasm, fonts and service workers their headers, custom
+/// rules override it in declaration order, and 304s keep the extra headers.
+#[test]
+fn test_header_profiles() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join("fonts")).unwrap();
+    std::fs::write(tmp.path().join("app.wasm"), b"\0asm\x01\0\0\0").unwrap();
+    std::fs::write(tmp.path().join("fonts/inter.woff2"), b"wOF2").unwrap();
+    std::fs::write(tmp.path().join("sw.js"), b"self.skipWaiting();").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap())
+        .with_header_profile(HeaderProfile::WebDefaults)
+        .with_headers_for(
+            "**/*.woff2",
+            &[("Cache-Control", "max-age=31536000, immutable")],
+        );
+    let served = |target: &str| {
+        let response = silo.serve(target, &RequestHeaders::new());
+        assert_eq!(response.status, 200, "{target}");
+        response
+            .headers
+            .into_iter()
+            .filter(|(name, _)| !matches!(*name, "ETag" | "Last-Modified"))
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect::<Vec<_>>()
+    };
+    let expected = |headers: &[(&str, &str)]| {
+        headers
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        served("/app.wasm"),
+        expected(&[
+            ("Content-Type", "application/wasm"),
+            ("Accept-Ranges", "bytes"),
+            ("Content-Length", "8")
+        ])
+    );
+    assert_eq!(
+        served("/fonts/inter.woff2"),
+        expected(&[
+            ("Access-Control-Allow-Origin", "*"),
+            ("Cache-Control", "max-age=31536000, immutable"),
+            ("Content-Type", "font/woff2"),
+            ("Accept-Ranges", "bytes"),
+            ("Content-Length", "4"),
+        ])
+    );
+    assert_eq!(
+        served("/sw.js"),
+        expected(&[
+            ("Cache-Control", "no-cache"),
+            ("Service-Worker-Allowed", "/"),
+            ("Content-Type", "text/javascript"),
+            ("Accept-Ranges", "bytes"),
+            ("Content-Length", "19"),
+        ])
+    );
+
+    let later = silo
+        .clone()
+        .with_headers_for("sw.js", &[("cache-control", "no-store")]);
+    let etag = later
+        .serve("/sw.js", &RequestHeaders::new())
+        .header("ETag")
+        .unwrap()
+        .to_owned();
+    let mut conditional = RequestHeaders::new();
+    conditional.if_none_match = Some(etag);
+    let not_modified = later.serve("/sw.js", &conditional);
+    assert_eq!(not_modified.status, 304);
+    assert_eq!(not_modified.header("Cache-Control"), Some("no-store"));
+    assert_eq!(not_modified.header("Service-Worker-Allowed"), Some("/"));
+    assert_eq!(not_modified.header("Content-Type"), None);
+    assert_eq!(
+        Silo::new(tmp.path().to_str().unwrap())
+            .serve("/sw.js", &RequestHeaders::new())
+            .header("Service-Worker-Allowed"),
+        None
+    );
+    assert_ne!(silo, later);
+
+    // Through a set, each file gets the rules of the member that has it.
+    let base = tempfile::tempdir().unwrap();
+    std::fs::write(base.path().join("base.woff2"), b"wOF2").unwrap();
+    let set = SiloSet::new(vec![Silo::new(base.path().to_str().unwrap()), silo.clone()]);
+    let response = set.serve("/fonts/inter.woff2", &RequestHeaders::new());
+    assert_eq!(response.header("Access-Control-Allow-Origin"), Some("*"));
+    assert_eq!(
+        response.header("Cache-Control"),
+        Some("max-age=31536000, immutable")
+    );
+    assert_eq!(
+        set.serve("/app.wasm", &RequestHeaders::new())
+            .header("Content-Type"),
+        Some("application/wasm")
+    );
+    assert_eq!(
+        set.serve("/base.woff2", &RequestHeaders::new())
+            .header("Access-Control-Allow-Origin"),
+        None
+    );
+    assert_eq!(
+        set.serve("/missing.txt", &RequestHeaders::new()).status,
+        404
+    );
+}
+
+/// `as_embedded` yields `'static` keys and entries only for embedded silos, so indexes built
+/// from them can live in a `OnceLock`; `to_lookup_map` owns its keys otherwise.
+#[test]
+fn test_embedded_silo_ref() {
+    static INDEX: std::sync::OnceLock<
+        std::collections::HashMap<&'static str, &'static EmbedEntry>,
+    > = std::sync::OnceLock::new();
+    let index = INDEX.get_or_init(|| {
+        let silo = embed_silo!("tests/data", force = true);
+        silo.as_embedded().unwrap().iter().collect()
+    });
+    let mut keys: Vec<_> = index.keys().copied().collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "alpha.txt",
+            "beta.txt",
+            "override/alpha.txt",
+            "subdir/gamma.txt"
+        ]
+    );
+    assert_eq!(
+        index["beta.txt"].bytes(),
+        embed_silo!("tests/data", force = true)
+            .get_file("beta.txt")
+            .unwrap()
+            .contents()
+            .unwrap()
+            .as_ref()
+    );
+
+    let scoped = embed_silo!("tests/data", force = true).scope("override");
+    let embedded = scoped.as_embedded().unwrap();
+    assert_eq!(
+        embedded.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+        ["alpha.txt"]
+    );
+    assert_eq!(
+        embedded.get("alpha.txt").unwrap().path,
+        "override/alpha.txt"
+    );
+    assert!(embedded.get("beta.txt").is_none());
+    let LookupMap::Embedded(map) = scoped.to_lookup_map() else {
+        panic!("embedded silos give 'static keys")
+    };
+    assert_eq!(map["alpha.txt"].path(), std::path::Path::new("alpha.txt"));
+
+    let dynamic = Silo::new("tests/data");
+    assert!(dynamic.as_embedded().is_none());
+    let lookup = dynamic.to_lookup_map();
+    assert!(matches!(lookup, LookupMap::Owned(_)));
+    assert_eq!(lookup.len(), 4);
+    assert!(
+        lookup
+            .get("subdir/gamma.txt")
+            .unwrap()
+            .absolute_path()
+            .is_some()
+    );
+    assert!(lookup.get("subdir").is_none());
+}
+
+/// Reads racing a writer never see a mixed file, and served lengths always match the body.
+#[test]
+fn test_contents_consistent_under_mutation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("asset.js");
+    let short = vec![b'a'; 1000];
+    let long = vec![b'b'; 64 * 1024];
+    std::fs::write(&path, &short).unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap());
+    let file = silo.get_file("asset.js").unwrap();
+
+    let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let writer = {
+        let (done, path, short, long) = (done.clone(), path.clone(), short.clone(), long.clone());
+        std::thread::spawn(move || {
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                std::fs::write(&path, &long).unwrap();
+                std::fs::write(&path, &short).unwrap();
+            }
+        })
+    };
+    // An empty file is a real (truncated) state of the file, not a torn read.
+    let valid =
+        |bytes: &[u8]| bytes.is_empty() || bytes == short.as_slice() || bytes == long.as_slice();
+    for _ in 0..200 {
+        match file.contents_consistent() {
+            Ok(bytes) => assert!(valid(&bytes), "torn read of {} bytes", bytes.len()),
+            Err(Error::Unstable { attempts, .. }) => assert_eq!(attempts, CONSISTENT_READ_ATTEMPTS),
+            Err(err) => panic!("{err}"),
+        }
+        let mut response = silo.serve("/asset.js", &RequestHeaders::new());
+        assert_eq!(response.status, 200);
+        if let Some(length) = response.header("Content-Length").map(str::to_owned) {
+            let mut body = Vec::new();
+            response
+                .body
+                .take()
+                .unwrap()
+                .read_to_end(&mut body)
+                .unwrap();
+            assert_eq!(length, body.len().to_string());
+            assert!(valid(&body));
+        } else {
+            assert!(response.header("ETag").is_none());
+        }
+    }
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    writer.join().unwrap();
+}
+
+/// Conditional headers yield 304; `If-None-Match` takes precedence over `If-Modified-Since`.
+#[test]
+fn test_serve_conditional() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("page.html");
+    std::fs::write(&path, "<p>hi</p>").unwrap();
+    let stamp = std::time::UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(stamp)
+        .unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap());
+
+    let response = silo.serve("/page.html", &RequestHeaders::new());
+    assert_eq!(response.status, 200);
+    assert_eq!(
+        response.header("Last-Modified"),
+        Some("Sun, 06 Nov 1994 08:49:37 GMT")
+    );
+    let etag = response.header("ETag").unwrap().to_owned();
+
+    let headers: RequestHeaders = [("If-None-Match", format!("\"other\", W/{etag}"))]
+        .into_iter()
+        .collect();
+    let response = silo.serve("/page.html", &headers);
+    assert_eq!(response.status, 304);
+    assert!(response.body.is_none());
+    assert_eq!(response.header("ETag"), Some(etag.as_str()));
+
+    let since = |date: &str| RequestHeaders {
+        if_modified_since: Some(date.into()),
+        ..RequestHeaders::new()
+    };
+    assert_eq!(
+        silo.serve("/page.html", &since("Sun, 06 Nov 1994 08:49:37 GMT"))
+            .status,
+        304
+    );
+    assert_eq!(
+        silo.serve("/page.html", &since("Mon, 01 Jan 2024 00:00:00 GMT"))
+            .status,
+        304
+    );
+    assert_eq!(
+        silo.serve("/page.html", &since("Sun, 06 Nov 1994 08:49:36 GMT"))
+            .status,
+        200
+    );
+    assert_eq!(silo.serve("/page.html", &since("not a date")).status, 200);
+
+    let both = RequestHeaders {
+        if_none_match: Some("\"other\"".into()),
+        if_modified_since: Some("Mon, 01 Jan 2024 00:00:00 GMT".into()),
+        ..RequestHeaders::new()
+    };
+    assert_eq!(silo.serve("/page.html", &both).status, 200);
+
+    let headers: RequestHeaders = [("if-match", "\"other\"")].into_iter().collect();
+    assert_eq!(silo.serve("/page.html", &headers).status, 412);
+    let headers: RequestHeaders = [("If-Match", etag.as_str())].into_iter().collect();
+    assert_eq!(silo.serve("/page.html", &headers).status, 200);
+    let headers: RequestHeaders = [("If-Unmodified-Since", "Sun, 06 Nov 1994 08:49:36 GMT")]
+        .into_iter()
+        .collect();
+    assert_eq!(silo.serve("/page.html", &headers).status, 412);
+}
+
+/// Dynamic compression streams gzip/deflate bodies that decode to the file, and skips PNGs.
+#[cfg(feature = "compression")]
+#[test]
+fn test_serve_dynamic_compression() {
+    let dir = tempfile::tempdir().unwrap();
+    let text = "line of compressible text\n".repeat(200);
+    std::fs::write(dir.path().join("big.txt"), &text).unwrap();
+    std::fs::write(dir.path().join("small.txt"), "tiny").unwrap();
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    png.extend(std::iter::repeat_n(0u8, 4096));
+    std::fs::write(dir.path().join("image.png"), &png).unwrap();
+    std::fs::write(dir.path().join("big.txt.gz"), &text).unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap())
+        .with_dynamic_compression(CompressionConfig::default());
+    let request = |pairs: &[(&str, &str)]| pairs.iter().copied().collect::<RequestHeaders>();
+    let body = |response: &mut HttpFileResponse| {
+        let mut bytes = Vec::new();
+        response
+            .body
+            .take()
+            .unwrap()
+            .read_to_end(&mut bytes)
+            .unwrap();
+        bytes
+    };
+
+    let mut response = silo.serve(
+        "/big.txt",
+        &request(&[("Accept-Encoding", "br;q=1.0, gzip;q=0.8")]),
+    );
+    assert_eq!(response.header("Content-Encoding"), Some("gzip"));
+    assert_eq!(response.header("Vary"), Some("Accept-Encoding"));
+    assert!(response.header("Content-Length").is_none());
+    let gzip_etag = response.header("ETag").unwrap().to_owned();
+    let compressed = body(&mut response);
+    assert!(compressed.len() < text.len());
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text);
+    let revalidate = request(&[("Accept-Encoding", "gzip"), ("If-None-Match", &gzip_etag)]);
+    assert_eq!(silo.serve("/big.txt", &revalidate).status, 304);
+
+    let mut response = silo.serve(
+        "/big.txt",
+        &request(&[("Accept-Encoding", "gzip;q=0, deflate")]),
+    );
+    assert_eq!(response.header("Content-Encoding"), Some("deflate"));
+    let mut decoded = String::new();
+    flate2::read::ZlibDecoder::new(body(&mut response).as_slice())
+        .read_to_string(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text);
+
+    for (path, headers) in [
+        ("/big.txt", request(&[])),
+        (
+            "/big.txt",
+            request(&[
+                ("Accept-Encoding", "gzip"),
+                ("Cache-Control", "no-cache, no-transform"),
+            ]),
+        ),
+        ("/small.txt", request(&[("Accept-Encoding", "gzip")])),
+        ("/image.png", request(&[("Accept-Encoding", "gzip")])),
+        ("/big.txt.gz", request(&[("Accept-Encoding", "gzip")])),
+    ] {
+        let mut response = silo.serve(path, &headers);
+        assert!(response.header("Content-Encoding").is_none(), "{path}");
+        let length: usize = response.header("Content-Length").unwrap().parse().unwrap();
+        assert_eq!(body(&mut response).len(), length, "{path}");
+    }
+    assert!(
+        silo.serve("/image.png", &request(&[]))
+            .header("Vary")
+            .is_none()
+    );
+
+    let embedded =
+        embed_silo!("tests/data", force = true).with_dynamic_compression(CompressionConfig {
+            min_size: 0,
+            level: 9,
+            ..Default::default()
+        });
+    assert!(
+        embedded
+            .serve("/alpha.txt", &request(&[("Accept-Encoding", "gzip")]))
+            .header("Content-Encoding")
+            .is_none()
+    );
+}
+
+/// A user codec that XORs every byte with a key, so it encodes and decodes alike.
+struct Xor(u8);
+
+impl Codec for Xor {
+    fn name(&self) -> &'static str {
+        "xor"
+    }
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        data.iter().map(|byte| byte ^ self.0).collect()
+    }
+    fn decompress_stream(&self, mut reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        Box::new(std::io::Cursor::new(self.compress(&data)))
+    }
+}
+
+/// A user codec that run-length encodes bytes as (count, byte) pairs.
+struct Rle;
+
+impl Codec for Rle {
+    fn name(&self) -> &'static str {
+        "rle"
+    }
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for run in data.chunk_by(|a, b| a == b) {
+            for chunk in run.chunks(255) {
+                out.extend([chunk.len() as u8, chunk[0]]);
+            }
+        }
+        out
+    }
+    fn decompress_stream(&self, mut reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        let decoded = data
+            .chunks(2)
+            .flat_map(|pair| std::iter::repeat_n(pair[1], pair[0] as usize))
+            .collect::<Vec<_>>();
+        Box::new(std::io::Cursor::new(decoded))
+    }
+}
+
+/// Writes `notes.txt`, stored XOR-encoded, into a temporary directory.
+fn xor_encoded_dir(text: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("notes.txt"),
+        Xor(0x5a).compress(text.as_bytes()),
+    )
+    .unwrap();
+    dir
+}
+
+/// A user codec decodes stored files into a memory silo, encodes them back, and compresses
+/// pack entries, which load only when the codec is passed in.
+#[test]
+fn test_custom_codec_memory_and_pack() {
+    use std::sync::Arc;
+
+    let xor: Arc<dyn Codec> = Arc::new(Xor(0x5a));
+    let text = "plain text stored xor-encoded\n".repeat(10);
+    let dir = xor_encoded_dir(&text);
+    let stored = Silo::new(dir.path().to_str().unwrap());
+    let silo = stored.decompressed(xor.clone()).unwrap();
+    assert_eq!(silo.get_file("notes.txt").unwrap().text().unwrap(), text);
+    let reencoded = silo.compressed(xor.clone()).unwrap();
+    assert_eq!(
+        reencoded.get_file("notes.txt").unwrap().contents().unwrap(),
+        stored.get_file("notes.txt").unwrap().contents().unwrap()
+    );
+    assert_eq!(format!("{:?}", xor), r#"Codec("xor")"#);
+
+    let rle: Arc<dyn Codec> = Arc::new(Rle);
+    let runs = tempfile::tempdir().unwrap();
+    std::fs::write(runs.path().join("runs.txt"), "a".repeat(1000)).unwrap();
+    std::fs::write(runs.path().join("plain.txt"), "abc").unwrap();
+    let options = rust_silos::pack::PackOptions {
+        compress: true,
+        codec: Some(rle.clone()),
+        ..Default::default()
+    };
+    let pack =
+        rust_silos::pack::pack_silo(&Silo::new(runs.path().to_str().unwrap()), &options).unwrap();
+    let pack: &'static [u8] = Box::leak(pack.into_boxed_slice());
+    assert!(pack.len() < 200, "{}", pack.len());
+    match Silo::from_pack_bytes(pack) {
+        Err(Error::PackError { reason }) => {
+            assert!(reason.contains("unknown codec rle"), "{reason}")
+        }
+        other => panic!("expected a pack error, got {other:?}"),
+    }
+    let packed = Silo::from_pack_bytes_with_codecs(pack, &[xor, rle]).unwrap();
+    assert_eq!(
+        packed.get_file("runs.txt").unwrap().text().unwrap(),
+        "a".repeat(1000)
+    );
+    assert_eq!(packed.get_file("plain.txt").unwrap().text().unwrap(), "abc");
+}
+
+/// A user codec encodes responses from a memory silo once in-memory compression is enabled.
+#[cfg(feature = "compression")]
+#[test]
+fn test_custom_codec_serve() {
+    use std::sync::Arc;
+
+    let xor: Arc<dyn Codec> = Arc::new(Xor(0x5a));
+    let text = "plain text stored xor-encoded\n".repeat(10);
+    let dir = xor_encoded_dir(&text);
+    let config = CompressionConfig {
+        min_size: 0,
+        codecs: vec![xor.clone()],
+        ..Default::default()
+    };
+    let decoded = Silo::new(dir.path().to_str().unwrap())
+        .decompressed(xor.clone())
+        .unwrap();
+    let request = |accept: &str| {
+        [("Accept-Encoding", accept)]
+            .into_iter()
+            .collect::<RequestHeaders>()
+    };
+    let plain = decoded.clone().with_dynamic_compression(config.clone());
+    let response = plain.serve("/notes.txt", &request("xor"));
+    assert_eq!(response.header("Content-Encoding"), None);
+    assert_eq!(response.header("Vary"), None);
+
+    let silo = decoded.with_dynamic_compression(CompressionConfig {
+        in_memory: true,
+        ..config
+    });
+    let mut response = silo.serve("/notes.txt", &request("gzip, xor"));
+    assert_eq!(response.header("Content-Encoding"), Some("xor"));
+    assert!(response.header("ETag").unwrap().ends_with("-xor\""));
+    let mut decoded = Vec::new();
+    xor.decompress_stream(Box::new(response.body.take().unwrap()))
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, text.as_bytes());
+
+    assert_eq!(
+        silo.serve("/notes.txt", &request("gzip"))
+            .header("Content-Encoding"),
+        Some("gzip")
+    );
+    assert_eq!(
+        silo.serve("/notes.txt", &request("*"))
+            .header("Content-Encoding"),
+        Some("gzip")
+    );
+    assert_eq!(
+        silo.serve("/notes.txt", &request("xor;q=0"))
+            .header("Content-Encoding"),
+        None
+    );
+    let set = SiloSet::new(vec![Silo::new(dir.path().to_str().unwrap()), silo.clone()]);
+    assert_eq!(
+        set.serve("/notes.txt", &request("xor"))
+            .header("Content-Encoding"),
+        Some("xor")
+    );
+    assert_eq!(
+        silo.snapshot()
+            .serve("/notes.txt", &request("xor"))
+            .header("Content-Encoding"),
+        Some("xor")
+    );
+    assert_eq!(
+        Gzip(6)
+            .decompress(&Gzip(6).compress(b"round trip"))
+            .unwrap(),
+        b"round trip"
+    );
+}
+
+/// Strong and weak entity tag comparison follows the table in RFC 7232 section 2.3.2.
+#[test]
+fn test_conditional_etag_comparison() {
+    use rust_silos::conditional::EntityTag;
+    let cases = [
+        (r#"W/"1""#, r#"W/"1""#, false, true),
+        (r#"W/"1""#, r#"W/"2""#, false, false),
+        (r#"W/"1""#, r#""1""#, false, true),
+        (r#""1""#, r#""1""#, true, true),
+    ];
+    for (a, b, strong, weak) in cases {
+        let (a, b) = (EntityTag::parse(a).unwrap(), EntityTag::parse(b).unwrap());
+        assert_eq!(a.strong_eq(&b), strong, "{a} {b}");
+        assert_eq!(a.weak_eq(&b), weak, "{a} {b}");
+    }
+    let list = EntityTag::parse_list(r#" "xyzzy", W/"r2d2,xxxx" ,"c3piozzzz""#).unwrap();
+    let tags: Vec<String> = list.iter().map(ToString::to_string).collect();
+    assert_eq!(tags, [r#""xyzzy""#, r#"W/"r2d2,xxxx""#, r#""c3piozzzz""#]);
+    assert_eq!(EntityTag::parse_list("").unwrap(), []);
+    for malformed in [r#""unterminated"#, "xyzzy", r#""a" "b""#, "\"sp ace\""] {
+        assert!(EntityTag::parse_list(malformed).is_none(), "{malformed}");
+    }
+}
+
+/// `conditional::evaluate` applies the RFC 7232 precedence rules and `If-Range`.
+#[test]
+fn test_conditional_evaluate() {
+    use ConditionalResult::{NotModified, PreconditionFailed, Serve};
+    use rust_silos::conditional::{
+        ConditionalResult, FileConditionalMeta, RequestConditionals, evaluate,
+    };
+    const SERVE: ConditionalResult = Serve { honor_range: true };
+    const FULL: ConditionalResult = Serve { honor_range: false };
+    const DATE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+    const EARLIER: &str = "Sun, 06 Nov 1994 08:49:36 GMT";
+    const LATER: &str = "Mon, 07 Nov 1994 08:49:37 GMT";
+    let file = FileConditionalMeta {
+        etag: Some(r#""xyzzy""#.into()),
+        last_modified: Some(784_111_777),
+    };
+    let weak_file = FileConditionalMeta {
+        etag: Some(r#"W/"xyzzy""#.into()),
+        ..file.clone()
+    };
+
+    type Case = (
+        &'static str,
+        fn(&mut RequestConditionals),
+        ConditionalResult,
+    );
+    let cases: [Case; 20] = [
+        ("no conditionals", |_| {}, SERVE),
+        (
+            "if-match hit",
+            |h| h.if_match = Some(r#""a", "xyzzy""#.into()),
+            SERVE,
+        ),
+        (
+            "if-match miss",
+            |h| h.if_match = Some(r#""a", "b""#.into()),
+            PreconditionFailed,
+        ),
+        ("if-match star", |h| h.if_match = Some("*".into()), SERVE),
+        (
+            "if-match weak tag",
+            |h| h.if_match = Some(r#"W/"xyzzy""#.into()),
+            PreconditionFailed,
+        ),
+        (
+            "if-unmodified-since later",
+            |h| h.if_unmodified_since = Some(LATER.into()),
+            SERVE,
+        ),
+        (
+            "if-unmodified-since earlier",
+            |h| h.if_unmodified_since = Some(EARLIER.into()),
+            PreconditionFailed,
+        ),
+        (
+            "if-match overrides if-unmodified-since",
+            |h| {
+                h.if_match = Some(r#""xyzzy""#.into());
+                h.if_unmodified_since = Some(EARLIER.into());
+            },
+            SERVE,
+        ),
+        (
+            "if-none-match hit",
+            |h| h.if_none_match = Some(r#""a", W/"xyzzy""#.into()),
+            NotModified,
+        ),
+        (
+            "if-none-match miss",
+            |h| h.if_none_match = Some(r#""a""#.into()),
+            SERVE,
+        ),
+        (
+            "if-none-match star",
+            |h| h.if_none_match = Some("*".into()),
+            NotModified,
+        ),
+        (
+            "if-none-match malformed",
+            |h| h.if_none_match = Some("xyzzy".into()),
+            SERVE,
+        ),
+        (
+            "if-modified-since equal",
+            |h| h.if_modified_since = Some(DATE.into()),
+            NotModified,
+        ),
+        (
+            "if-modified-since earlier",
+            |h| h.if_modified_since = Some(EARLIER.into()),
+            SERVE,
+        ),
+        (
+            "if-modified-since invalid",
+            |h| h.if_modified_since = Some("yesterday".into()),
+            SERVE,
+        ),
+        (
+            "if-none-match overrides if-modified-since",
+            |h| {
+                h.if_none_match = Some(r#""a""#.into());
+                h.if_modified_since = Some(LATER.into());
+            },
+            SERVE,
+        ),
+        (
+            "precondition failure beats not modified",
+            |h| {
+                h.if_match = Some(r#""a""#.into());
+                h.if_none_match = Some(r#""xyzzy""#.into());
+            },
+            PreconditionFailed,
+        ),
+        (
+            "if-range tag hit",
+            |h| h.if_range = Some(r#""xyzzy""#.into()),
+            SERVE,
+        ),
+        (
+            "if-range tag miss",
+            |h| h.if_range = Some(r#""a""#.into()),
+            FULL,
+        ),
+        ("if-range date", |h| h.if_range = Some(LATER.into()), FULL),
+    ];
+    for (name, setup, expected) in cases {
+        let mut headers = RequestConditionals::default();
+        setup(&mut headers);
+        assert_eq!(evaluate(&headers, &file), expected, "{name}");
+    }
+
+    let if_range = |value: &str| RequestConditionals {
+        if_range: Some(value.into()),
+        ..Default::default()
+    };
+    assert_eq!(evaluate(&if_range(DATE), &file), SERVE);
+    // If-Range requires a strong match, so a weak validator never satisfies it.
+    assert_eq!(evaluate(&if_range(r#"W/"xyzzy""#), &weak_file), FULL);
+    let none_match = RequestConditionals {
+        if_none_match: Some(r#""xyzzy""#.into()),
+        ..Default::default()
+    };
+    assert_eq!(evaluate(&none_match, &weak_file), NotModified);
+    let no_validators = FileConditionalMeta::default();
+    assert_eq!(evaluate(&none_match, &no_validators), SERVE);
+    assert_eq!(
+        evaluate(
+            &RequestConditionals {
+                if_match: Some("*".into()),
+                ..Default::default()
+            },
+            &no_validators
+        ),
+        SERVE
+    );
+}
+
+/// `find_bytes` finds needles that span a chunk boundary of a streamed dynamic file.
+#[test]
+fn test_find_bytes_chunk_boundary() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut log = vec![b'.'; 100];
+    log[6..12].copy_from_slice(b"MARKER"); // spans the boundary at 8
+    log[62..68].copy_from_slice(b"marker"); // spans the boundary at 64
+    log[90..96].copy_from_slice(b"MARKER");
+    std::fs::write(dir.path().join("app.log"), &log).unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "MARKER").unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap());
+    let opts = SearchOptions {
+        chunk_size: 8,
+        path_glob: Some("*.log".into()),
+        ..SearchOptions::default()
+    };
+
+    let offsets = |opts: SearchOptions| -> Vec<u64> {
+        silo.find_bytes(b"MARKER", opts)
+            .map(|m| m.unwrap().offset)
+            .collect()
+    };
+    assert_eq!(offsets(opts.clone()), [6, 90]);
+    assert_eq!(
+        offsets(SearchOptions {
+            ascii_case_insensitive: true,
+            ..opts.clone()
+        }),
+        [6, 62, 90]
+    );
+    assert_eq!(
+        offsets(SearchOptions {
+            max_matches_per_file: Some(1),
+            ..opts.clone()
+        }),
+        [6]
+    );
+    let all: Vec<Match> = silo
+        .find_bytes(b"MARKER", SearchOptions::default())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(all.len(), 3);
+    assert!(all.contains(&Match {
+        path: "notes.txt".into(),
+        offset: 0
+    }));
+    assert_eq!(silo.find_bytes(b"", SearchOptions::default()).count(), 0);
+}
+
+/// Streamed and in-memory scans report the same non-overlapping matches.
+#[test]
+fn test_find_bytes_streaming_matches_in_memory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.bin"), b"aaaaabaaab").unwrap();
+    let dynamic = Silo::new(dir.path().to_str().unwrap());
+    let memory = dynamic
+        .merge_with(&Silo::new("tests/data/subdir"), |_, _| {
+            MergeDecision::TakeLeft
+        })
+        .unwrap();
+    for needle in [&b"aa"[..], b"aab", b"b", b"aaaaabaaab", b"aaaaabaaabX"] {
+        let expected: Vec<u64> = memory
+            .find_bytes(
+                needle,
+                SearchOptions {
+                    path_glob: Some("a.bin".into()),
+                    ..SearchOptions::default()
+                },
+            )
+            .map(|m| m.unwrap().offset)
+            .collect();
+        for chunk_size in 1..12 {
+            let opts = SearchOptions {
+                chunk_size,
+                ..SearchOptions::default()
+            };
+            let found: Vec<u64> = dynamic
+                .find_bytes(needle, opts)
+                .map(|m| m.unwrap().offset)
+                .collect();
+            assert_eq!(found, expected, "needle {needle:?}, chunk {chunk_size}");
+        }
+    }
+    let found: Vec<u64> = memory
+        .find_bytes(
+            b"aa",
+            SearchOptions {
+                path_glob: Some("a.bin".into()),
+                ..SearchOptions::default()
+            },
+        )
+        .map(|m| m.unwrap().offset)
+        .collect();
+    assert_eq!(found, [0, 2, 6]);
+}
+
+/// Path globs match `*` within a segment and `**` across segments.
+#[test]
+fn test_find_bytes_glob() {
+    let silo = embed_silo!("tests/data", force = true);
+    let paths = |glob: &str| -> Vec<String> {
+        let mut paths: Vec<String> = silo
+            .find_bytes(
+                b"content",
+                SearchOptions {
+                    path_glob: Some(glob.into()),
+                    ..SearchOptions::default()
+                },
+            )
+            .map(|m| m.unwrap().path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(paths("*.txt"), ["alpha.txt", "beta.txt"]);
+    assert_eq!(paths("subdir/*"), ["subdir/gamma.txt"]);
+    assert!(paths("**/gamma.txt").contains(&"subdir/gamma.txt".to_owned()));
+    assert_eq!(paths("?lpha.*"), ["alpha.txt"]);
+    assert!(paths("nothing/**").is_empty());
+}
+
+/// `into_temp_file` writes the contents to a named temporary file that is removed on drop.
+#[cfg(feature = "tempfile")]
+#[test]
+fn test_into_temp_file() {
+    let silo = embed_silo!("tests/data", force = true);
+    let file = silo.get_file("alpha.txt").unwrap();
+    let temp = file.into_temp_file().unwrap();
+    assert_eq!(temp.path().extension().unwrap(), "txt");
+    let mut bytes = Vec::new();
+    std::fs::File::open(temp.path())
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    assert_eq!(bytes, file.contents().unwrap().as_ref());
+    let path = temp.path().to_path_buf();
+    drop(temp);
+    assert!(!path.exists());
+
+    let dynamic = Silo::new("tests/data")
+        .get_file("subdir/gamma.txt")
+        .unwrap();
+    assert_eq!(
+        std::fs::read(dynamic.into_temp_file().unwrap().path()).unwrap(),
+        b"gamma file content\n"
+    );
+}
+
+/// `with_temp_path` passes a readable path to the closure and cleans up afterwards.
+#[cfg(feature = "tempfile")]
+#[test]
+fn test_with_temp_path() {
+    let file = embed_silo!("tests/data", force = true)
+        .get_file("beta.txt")
+        .unwrap();
+    let (path, text) = file
+        .with_temp_path(|path| (path.to_path_buf(), std::fs::read_to_string(path).unwrap()))
+        .unwrap();
+    assert_eq!(text, "beta file content\n");
+    assert!(!path.exists());
+}
+
+/// `write_back` replaces a dynamic file in place, keeping its permissions and leaving no temp file.
+#[test]
+fn test_write_back() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("config.toml"), "old = 1\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(
+            tmp.path().join("config.toml"),
+            std::fs::Permissions::from_mode(0o640),
+        )
+        .unwrap();
+    }
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    let file = silo.get_file("config.toml").unwrap();
+    let edited = file.text().unwrap().replace("old", "new");
+    file.write_back(edited.as_bytes()).unwrap();
+    assert_eq!(
+        silo.get_file("config.toml").unwrap().text().unwrap(),
+        "new = 1\n"
+    );
+    assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(tmp.path().join("config.toml"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+}
+
+/// `write_back` refuses embedded files.
+#[test]
+fn test_write_back_embedded_is_read_only() {
+    let file = embed_silo!("tests/data", force = true)
+        .get_file("alpha.txt")
+        .unwrap();
+    assert!(matches!(file.write_back(b"x"), Err(Error::ReadOnly)));
+}
+
+/// A touched file with identical contents is reported as a metadata change, without hashing.
+#[test]
+fn test_has_changed_touched() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("asset.css");
+    std::fs::write(&path, "body {}").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    let snapshot = silo
+        .get_file("asset.css")
+        .unwrap()
+        .snapshot_with_hash()
+        .unwrap();
+    assert!(snapshot.hash.is_some());
+    let file = silo.get_file("asset.css").unwrap();
+    assert_eq!(file.has_changed(&snapshot).unwrap(), Changed::No);
+
+    let touched = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(touched)
+        .unwrap();
+    assert_eq!(file.has_changed(&snapshot).unwrap(), Changed::Metadata);
+}
+
+/// A same-size edit that keeps the mtime is only caught when content verification is requested.
+#[test]
+fn test_has_changed_same_size_content() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("asset.css");
+    std::fs::write(&path, "aaaa").unwrap();
+    let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+    let file = Silo::new(tmp.path().to_str().unwrap())
+        .get_file("asset.css")
+        .unwrap();
+    let snapshot = file.snapshot_with_hash().unwrap();
+
+    std::fs::write(&path, "bbbb").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(mtime)
+        .unwrap();
+    assert_eq!(file.has_changed(&snapshot).unwrap(), Changed::No);
+    let verify = ChangeOptions {
+        verify_content: true,
+    };
+    assert_eq!(
+        file.has_changed_with(&snapshot, verify.clone()).unwrap(),
+        Changed::Content
+    );
+
+    // Without metadata in the snapshot, the hash decides.
+    let hash_only = FileSnapshot {
+        size: None,
+        modified: None,
+        ..snapshot
+    };
+    assert_eq!(file.has_changed(&hash_only).unwrap(), Changed::Content);
+    assert_eq!(
+        file.has_changed(&file.snapshot_with_hash().unwrap())
+            .unwrap(),
+        Changed::No
+    );
+}
+
+/// Embedded snapshots carry the baked-in hash and compare equal to themselves.
+#[test]
+fn test_snapshot_embedded() {
+    let file = embed_silo!("tests/data", force = true)
+        .get_file("alpha.txt")
+        .unwrap();
+    let snapshot = file.snapshot();
+    assert_eq!(snapshot.hash, Some(file.content_hash().unwrap()));
+    assert_eq!(
+        file.has_changed_with(
+            &snapshot,
+            ChangeOptions {
+                verify_content: true
+            }
+        )
+        .unwrap(),
+        Changed::No
+    );
+    let edited = FileSnapshot {
+        hash: Some(0),
+        ..snapshot
+    };
+    assert_eq!(
+        file.has_changed_with(
+            &edited,
+            ChangeOptions {
+                verify_content: true
+            }
+        )
+        .unwrap(),
+        Changed::Content
+    );
+    #[cfg(feature = "json")]
+    {
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FileSnapshot>(&json).unwrap(),
+            snapshot
+        );
+    }
+}
+
+/// `has_any` reports whether any file exists under a directory prefix.
+#[test]
+fn test_has_any() {
+    let embed = embed_silo!("tests/data", force = true);
+    let memory = embed
+        .transform(|f| f.contents().ok().map(|c| c.into_owned()))
+        .unwrap();
+    for silo in [embed, memory, Silo::new("tests/data")] {
+        assert!(silo.has_any(""));
+        assert!(silo.has_any("subdir"));
+        assert!(silo.has_any("subdir/"));
+        assert!(!silo.has_any("sub"));
+        assert!(!silo.has_any("missing"));
+        assert!(!silo.has_any("alpha.txt"));
+        assert!(
+            !silo
+                .clone()
+                .filtered(|f| f.path().extension().is_some_and(|e| e == "md"))
+                .has_any("subdir")
+        );
+    }
+    let empty = tempfile::tempdir().unwrap();
+    std::fs::create_dir(empty.path().join("plugins")).unwrap();
+    assert!(!Silo::new(empty.path().to_str().unwrap()).has_any("plugins"));
+}
+
+/// `zip_with` pairs every file of two silos over the same directory, in path order.
+#[test]
+fn test_zip_with() {
+    let embed = embed_silo!("tests/data", force = true);
+    let dynamic = Silo::new("tests/data");
+    let pairs: Vec<(File, File)> = embed.zip_with(&dynamic).collect();
+    assert_eq!(pairs.len(), embed.iter().count());
+    let paths: Vec<&std::path::Path> = pairs.iter().map(|(left, _)| left.path()).collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+    for (left, right) in &pairs {
+        assert!(left.is_embedded() && !right.is_embedded());
+        assert_eq!(left.path(), right.path());
+        assert_eq!(left.contents().unwrap(), right.contents().unwrap());
+    }
+    assert!(
+        embed
+            .zip_with_full(&dynamic)
+            .all(|(left, right)| left.is_some() && right.is_some())
+    );
+}
+
+/// `zip_with_full` keeps paths present on only one side, with `None` for the other.
+#[test]
+fn test_zip_with_full() {
+    let root = Silo::new("tests/data");
+    let sub = Silo::new("tests/data/override");
+    let pairs: Vec<(Option<String>, Option<String>)> = root
+        .zip_with_full(&sub)
+        .map(|(l, r)| {
+            (
+                l.map(|f| f.path().display().to_string()),
+                r.map(|f| f.path().display().to_string()),
+            )
+        })
+        .collect();
+    assert!(pairs.contains(&(Some("alpha.txt".into()), Some("alpha.txt".into()))));
+    assert!(pairs.contains(&(Some("beta.txt".into()), None)));
+    assert!(pairs.contains(&(Some("override/alpha.txt".into()), None)));
+    assert_eq!(root.zip_with(&sub).count(), 1);
+    assert_eq!(
+        sub.zip_with_full(&root)
+            .filter(|(l, _)| l.is_none())
+            .count(),
+        pairs.len() - 1
+    );
+}
+
+/// `discover` loads each pack directory as a named layer and reports stray entries.
+#[test]
+fn test_silo_set_discover() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for (pack, file, text) in [
+        ("beta", "alpha.txt", "from beta"),
+        ("alpha", "alpha.txt", "from alpha"),
+        ("alpha", "only.txt", "only"),
+    ] {
+        std::fs::create_dir_all(root.join(pack)).unwrap();
+        std::fs::write(root.join(pack).join(file), text).unwrap();
+    }
+    std::fs::create_dir(root.join("empty")).unwrap();
+    std::fs::write(root.join("stray.txt"), "not a pack").unwrap();
+
+    let discovery = SiloSet::discover(root).unwrap();
+    assert_eq!(discovery.set.layer_names(), ["alpha", "beta", "empty"]);
+    assert_eq!(discovery.skipped, [root.join("stray.txt")]);
+    assert_eq!(
+        discovery.set.get_file("alpha.txt").unwrap().text().unwrap(),
+        "from beta"
+    );
+    assert_eq!(
+        discovery.set.get_file("only.txt").unwrap().text().unwrap(),
+        "only"
+    );
+
+    let based = SiloSet::discover_with_base(
+        embed_silo!("tests/data", force = true).with_name("base"),
+        root,
+    )
+    .unwrap();
+    assert_eq!(based.set.layer_names(), ["base", "alpha", "beta", "empty"]);
+    assert_eq!(
+        based.set.get_file("alpha.txt").unwrap().text().unwrap(),
+        "from beta"
+    );
+    assert_eq!(
+        based.set.get_file("beta.txt").unwrap().text().unwrap(),
+        "beta file content\n"
+    );
+
+    assert!(SiloSet::discover(&root.join("missing")).is_err());
+}
+
+/// Packs are ordered by `order.txt`, then numeric prefix, then name.
+#[test]
+fn test_silo_set_discover_order() {
+    let dir = tempfile::tempdir().unwrap();
+    for pack in ["zeta", "10-late", "2-early", "alpha", "pinned"] {
+        std::fs::create_dir(dir.path().join(pack)).unwrap();
+    }
+    let discovery = SiloSet::discover(dir.path()).unwrap();
+    assert_eq!(
+        discovery.set.layer_names(),
+        ["2-early", "10-late", "alpha", "pinned", "zeta"]
+    );
+
+    std::fs::write(
+        dir.path().join("order.txt"),
+        "# lowest first\nzeta\n\nmissing\npinned\n",
+    )
+    .unwrap();
+    let discovery = SiloSet::discover(dir.path()).unwrap();
+    assert_eq!(
+        discovery.set.layer_names(),
+        ["zeta", "pinned", "2-early", "10-late", "alpha"]
+    );
+    assert!(discovery.skipped.is_empty());
+}
+
+/// The forwarded `read_to_end`/`read_to_string`/`read_exact` fast paths return the same bytes
+/// as plain reads, including after a partial read and when appending to a non-empty buffer.
+#[test]
+fn test_file_reader_read_to_end() {
+    for silo in [
+        embed_silo!("tests/data", force = true),
+        Silo::new("tests/data"),
+    ] {
+        let file = silo.get_file("alpha.txt").unwrap();
+        let mut reader = file.reader().unwrap();
+        let mut head = [0u8; 6];
+        reader.read_exact(&mut head).unwrap();
+        assert_eq!(&head, b"alpha ");
+        let mut rest = b"prefix:".to_vec();
+        assert_eq!(reader.read_to_end(&mut rest).unwrap(), 13);
+        assert_eq!(rest, b"prefix:file content\n");
+        let mut text = String::new();
+        file.reader().unwrap().read_to_string(&mut text).unwrap();
+        assert_eq!(text, "alpha file content\n");
+        assert!(reader.read_exact(&mut head).is_err());
+    }
+}
+
+/// `read_file_as_str_static` borrows embedded text from the binary and is `None` otherwise.
+#[test]
+fn test_read_file_as_str_static() {
+    let embed = embed_silo!("tests/data", force = true);
+    let text: &'static str = embed.read_file_as_str_static("alpha.txt").unwrap().unwrap();
+    assert_eq!(text, "alpha file content\n");
+    let entry = embed.embedded_map().unwrap().get("alpha.txt").unwrap();
+    assert_eq!(text.as_ptr(), entry.contents.as_ptr());
+    assert!(embed.read_file_as_str_static("missing.txt").is_none());
+    assert_eq!(
+        embed
+            .clone()
+            .scope("subdir")
+            .read_file_as_str_static("gamma.txt")
+            .unwrap()
+            .unwrap(),
+        "gamma file content\n"
+    );
+    assert!(
+        Silo::new("tests/data")
+            .read_file_as_str_static("alpha.txt")
+            .is_none()
+    );
+
+    let encodings = embed_silo!("tests/encodings", force = true);
+    assert!(
+        encodings
+            .read_file_as_str_static("latin1.txt")
+            .unwrap()
+            .is_err()
+    );
+}
+
+/// `by_extension` groups files case-insensitively, with `""` for files without an extension,
+/// and agrees between the embedded index and the dynamic scan.
+#[test]
+fn test_by_extension() {
+    let group = |silo: &Silo, ext: &str| -> Vec<String> {
+        let mut paths: Vec<String> = silo
+            .by_extension(ext)
+            .map(|f| f.path().to_string_lossy().into_owned())
+            .collect();
+        paths.sort();
+        paths
+    };
+    let embed = embed_silo!("tests/extensions", force = true);
+    let dynamic = Silo::new("tests/extensions");
+    for silo in [&embed, &dynamic] {
+        assert_eq!(
+            group(silo, "wasm"),
+            ["app.WASM", "lib.wasm", "sub/deep.Wasm"]
+        );
+        assert_eq!(group(silo, ".WASM"), group(silo, "wasm"));
+        assert_eq!(group(silo, ""), [".hidden", "README"]);
+        assert_eq!(group(silo, "gz"), ["archive.tar.gz"]);
+        assert!(group(silo, "tar.gz").is_empty());
+        assert!(group(silo, "png").is_empty());
+        let scoped = silo.clone().scope("sub");
+        assert_eq!(group(&scoped, "wasm"), ["deep.Wasm"]);
+        let filtered = silo.clone().filtered(|f| !f.path().starts_with("sub"));
+        assert_eq!(group(&filtered, "wasm"), ["app.WASM", "lib.wasm"]);
+    }
+    assert!(embed.by_extension("woff2").all(|f| f.is_embedded()));
+}
+
+/// `get_file_url` rejects traversal that only appears after percent-decoding.
+#[test]
+fn test_get_file_url_encoded_traversal() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("public")).unwrap();
+    std::fs::write(dir.path().join("public/page.html"), "page").unwrap();
+    std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+    let public = dir.path().join("public");
+    for silo in [
+        Silo::new(public.to_str().unwrap()),
+        embed_silo!("tests/data", force = true),
+    ] {
+        for target in [
+            "/%2e%2e/secret.txt",
+            "/%2E%2E%2Fsecret.txt",
+            "/..%2fsecret.txt",
+            "/..%5csecret.txt",
+            "/%2e%2e%5csecret.txt",
+            "/sub/%2e%2e/%2e%2e/secret.txt",
+            "/%252e%252e%252fsecret.txt",
+            "/.%2e/secret.txt",
+            "/%2e/page.html",
+            "/page.html%00.txt",
+            "/%",
+            "/%2",
+        ] {
+            assert!(silo.get_file_url(target).is_none(), "{target}");
+        }
+    }
+    let silo = Silo::new(public.to_str().unwrap());
+    assert_eq!(
+        silo.get_file_url("/page.html?x=1").unwrap().text().unwrap(),
+        "page"
+    );
+    assert_eq!(
+        silo.get_file_url("page%2Ehtml").unwrap().text().unwrap(),
+        "page"
+    );
+    let embed = embed_silo!("tests/data", force = true);
+    assert!(embed.get_file_url("/subdir%2Fgamma.txt").is_some());
+    assert!(embed.get_file_url("/subdir%5Cgamma.txt").is_some());
+}
+
+/// `retain_files` hides rejected files from lookups and iteration until exclusions are cleared.
+#[test]
+fn test_retain_files() {
+    for mut silo in [
+        Silo::new("tests/data"),
+        embed_silo!("tests/data", force = true),
+    ] {
+        let total = silo.iter().count();
+        let untouched = silo.clone();
+        assert_eq!(
+            silo.retain_files(|f| f.path() != std::path::Path::new("alpha.txt")),
+            1
+        );
+        assert!(silo.get_file("alpha.txt").is_none());
+        assert!(silo.get_file("./alpha.txt").is_none());
+        assert!(silo.get_file("override/alpha.txt").is_some());
+        assert_eq!(silo.iter().count(), total - 1);
+        assert!(
+            !silo.read_dir("").any(
+                |e| matches!(e, Entry::File(f) if f.path() == std::path::Path::new("alpha.txt"))
+            )
+        );
+        assert!(untouched.get_file("alpha.txt").is_some());
+        assert_ne!(silo, untouched);
+
+        assert_eq!(silo.retain_files(|f| !f.path().starts_with("subdir")), 1);
+        assert!(silo.clone().scope("subdir").get_file("gamma.txt").is_none());
+        assert!(
+            silo.clone()
+                .into_dynamic()
+                .unwrap()
+                .get_file("alpha.txt")
+                .is_none()
+        );
+        assert_eq!(silo.retain_files(|_| true), 0);
+
+        silo.clear_exclusions();
+        assert!(silo.get_file("alpha.txt").is_some());
+        assert_eq!(silo.iter().count(), total);
+        assert_eq!(silo, untouched);
+    }
+}
+
+/// Random-access conformance checks every backend must pass for a file with contents `expected`.
+fn assert_random_access(file: &File, expected: &[u8]) {
+    use std::io::{Seek, SeekFrom};
+    let len = expected.len() as u64;
+    assert!(len >= 4, "fixture too short");
+    // Independent readers.
+    let mut first = file.reader().unwrap();
+    let mut second = file.reader().unwrap();
+    let mut head = [0u8; 2];
+    first.read_exact(&mut head).unwrap();
+    let mut all = Vec::new();
+    second.read_to_end(&mut all).unwrap();
+    assert_eq!(all, expected);
+    let mut rest = Vec::new();
+    first.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, &expected[2..]);
+    // Seeking within one reader.
+    assert_eq!(first.seek(SeekFrom::Start(1)).unwrap(), 1);
+    let mut byte = [0u8; 1];
+    first.read_exact(&mut byte).unwrap();
+    assert_eq!(byte[0], expected[1]);
+    assert_eq!(first.seek(SeekFrom::End(-1)).unwrap(), len - 1);
+    first.read_exact(&mut byte).unwrap();
+    assert_eq!(byte[0], expected[expected.len() - 1]);
+    assert_eq!(first.seek(SeekFrom::Current(-2)).unwrap(), len - 2);
+    let mut buffered = file.buffered_reader(2).unwrap();
+    buffered.seek(SeekFrom::Start(len - 3)).unwrap();
+    let mut tail = Vec::new();
+    buffered.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, &expected[expected.len() - 3..]);
+    // Ranges, clamped to the length.
+    assert_eq!(file.read_range(0..len).unwrap(), expected);
+    assert_eq!(file.read_range(1..3).unwrap(), &expected[1..3]);
+    assert_eq!(
+        file.read_range(len - 2..len + 10).unwrap(),
+        &expected[expected.len() - 2..]
+    );
+    assert!(file.read_range(len + 1..len + 5).unwrap().is_empty());
+    assert!(file.read_range(3..3).unwrap().is_empty());
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = file.read_range(3..1).unwrap();
+    assert!(reversed.is_empty());
+}
+
+/// Embedded, dynamic, in-memory and transcoded files all pass the random-access checks.
+#[test]
+fn test_random_access_conformance() {
+    let expected = b"alpha file content\n";
+    let embed = embed_silo!("tests/data", force = true);
+    let dynamic = Silo::new("tests/data");
+    let memory = dynamic
+        .merge_with(&Silo::new("tests/data/subdir"), |_, _| {
+            MergeDecision::TakeLeft
+        })
+        .unwrap();
+    for silo in [&embed, &dynamic, &memory] {
+        assert_random_access(&silo.get_file("alpha.txt").unwrap(), expected);
+    }
+    let utf8 = "café – naïve\n".as_bytes();
+    let transcoding = Transcoding {
+        extensions: &["txt"],
+        on_failure: TranscodeFailure::Error,
+    };
+    let transcoded = Silo::new("tests/encodings").with_transcoding(transcoding);
+    assert_random_access(&transcoded.get_file("latin1.txt").unwrap(), utf8);
+    let embedded = embed_silo!("tests/encodings", force = true, transcode = "utf8");
+    assert_random_access(&embedded.get_file("latin1.txt").unwrap(), utf8);
+}
+
+/// `iter_with_hint` yields the same files as `iter`, with a lower bound counting down.
+#[test]
+fn test_iter_with_hint() {
+    for silo in [
+        Silo::new("tests/data"),
+        embed_silo!("tests/data", force = true),
+        Silo::new("tests/data").scope("subdir"),
+    ] {
+        let total = silo.iter().count();
+        let mut iter = silo.iter_with_hint();
+        assert_eq!(iter.size_hint().0, total);
+        iter.next().unwrap();
+        assert_eq!(iter.size_hint().0, total - 1);
+        let files: Vec<File> = silo.iter_with_hint().collect();
+        assert!(files.capacity() >= total);
+        let mut hinted: Vec<_> = files.iter().map(|f| f.path().to_path_buf()).collect();
+        let mut plain: Vec<_> = silo.iter().map(|f| f.path().to_path_buf()).collect();
+        hinted.sort();
+        plain.sort();
+        assert_eq!(hinted, plain);
+    }
+}
+
+/// Relative paths use `/` on every platform and backend; `path_native` uses the OS separator.
+#[test]
+fn test_paths_use_forward_slash() {
+    let embed = embed_silo!("tests/data", force = true);
+    let dynamic = Silo::new("tests/data");
+    let memory = dynamic
+        .merge_with(&Silo::new("tests/data/subdir"), |_, _| {
+            MergeDecision::TakeLeft
+        })
+        .unwrap();
+    let scoped = Silo::new("tests").scope("data");
+    for silo in [&embed, &dynamic, &memory, &scoped] {
+        let paths: Vec<String> = silo
+            .iter()
+            .map(|f| f.path().to_str().unwrap().to_owned())
+            .collect();
+        assert!(paths.iter().all(|p| !p.contains('\\')), "{paths:?}");
+        assert!(paths.contains(&"subdir/gamma.txt".to_owned()), "{paths:?}");
+        let file = silo.get_file("subdir/gamma.txt").unwrap();
+        assert_eq!(file.path().to_str().unwrap(), "subdir/gamma.txt");
+        let native: std::path::PathBuf = ["subdir", "gamma.txt"].iter().collect();
+        assert_eq!(file.path_native(), native);
+    }
+    let lookup = dynamic.get_file("subdir\\gamma.txt").unwrap();
+    assert_eq!(lookup.path().to_str().unwrap(), "subdir/gamma.txt");
+}
+
+#[cfg(any(feature = "json", feature = "toml"))]
+#[derive(Debug, ::serde::Serialize, ::serde::Deserialize)]
+struct SiloRefConfig {
+    title: String,
+    banner: rust_silos::serde::SiloRef<String>,
+    raw: rust_silos::serde::SiloRef<Vec<u8>>,
+    lazy: rust_silos::serde::SiloRef<File>,
+}
+
+/// `SiloRef` fields resolve to file contents and serialize back to their paths (JSON).
+#[cfg(feature = "json")]
+#[test]
+fn test_silo_ref_json_round_trip() {
+    use ::serde::de::DeserializeSeed;
+    use rust_silos::serde::{SiloSeed, with_silo};
+    let json = r#"{"title":"t","banner":"alpha.txt","raw":"beta.txt","lazy":"subdir/gamma.txt"}"#;
+    for silo in [
+        embed_silo!("tests/data", force = true),
+        Silo::new("tests/data"),
+    ] {
+        let config: SiloRefConfig = with_silo(&silo, || serde_json::from_str(json)).unwrap();
+        assert_eq!(
+            config.banner.as_str(),
+            silo.get_file("alpha.txt").unwrap().text().unwrap()
+        );
+        assert_eq!(config.raw.path(), "beta.txt");
+        assert_eq!(
+            *config.raw,
+            silo.get_file_content("beta.txt").unwrap().into_owned()
+        );
+        assert_eq!(
+            config.lazy.value().path(),
+            std::path::Path::new("subdir/gamma.txt")
+        );
+        assert_eq!(serde_json::to_string(&config).unwrap(), json);
+
+        let mut de = serde_json::Deserializer::from_str(json);
+        let seeded: SiloRefConfig = SiloSeed::new(&silo).deserialize(&mut de).unwrap();
+        assert_eq!(seeded.banner.value(), config.banner.value());
+    }
+}
+
+/// `SiloRef` fields resolve the same way through the TOML front-end.
+#[cfg(feature = "toml")]
+#[test]
+fn test_silo_ref_toml_round_trip() {
+    let source =
+        "title = \"t\"\nbanner = \"alpha.txt\"\nraw = \"beta.txt\"\nlazy = \"subdir/gamma.txt\"\n";
+    let silo = Silo::new("tests/data");
+    let config: SiloRefConfig =
+        rust_silos::serde::with_silo(&silo, || toml::from_str(source)).unwrap();
+    assert_eq!(
+        config.banner.as_str(),
+        silo.get_file("alpha.txt").unwrap().text().unwrap()
+    );
+    assert_eq!(
+        config.lazy.text().unwrap(),
+        silo.get_file("subdir/gamma.txt").unwrap().text().unwrap()
+    );
+    assert_eq!(toml::to_string(&config).unwrap(), source);
+}
+
+/// A missing path is a deserialization error naming the path, and through `SiloSeed` the
+/// field, as is resolving outside `with_silo`.
+#[cfg(feature = "json")]
+#[test]
+fn test_silo_ref_errors() {
+    use ::serde::de::DeserializeSeed;
+    use rust_silos::serde::{SiloRef, SiloSeed};
+    let json = r#"{"title":"t","banner":"missing.txt","raw":"beta.txt","lazy":"alpha.txt"}"#;
+    let silo = Silo::new("tests/data");
+    let err = rust_silos::serde::with_silo(&silo, || serde_json::from_str::<SiloRefConfig>(json))
+        .unwrap_err();
+    assert!(err.to_string().contains("missing.txt"), "{err}");
+    let err = serde_json::from_str::<SiloRefConfig>(json).unwrap_err();
+    assert!(err.to_string().contains("with_silo"), "{err}");
+
+    let mut de = serde_json::Deserializer::from_str(json);
+    let err = SiloSeed::<SiloRefConfig>::new(&silo)
+        .deserialize(&mut de)
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("silo path `missing.txt` not found (field `banner`)"),
+        "{err}"
+    );
+
+    #[derive(Debug, ::serde::Deserialize)]
+    struct Server {
+        #[allow(dead_code)]
+        tls_cert: SiloRef<Vec<u8>>,
+    }
+    #[derive(Debug, ::serde::Deserialize)]
+    struct Servers {
+        #[allow(dead_code)]
+        servers: Vec<Server>,
+    }
+    let json = r#"{"servers":[{"tls_cert":"alpha.txt"},{"tls_cert":"certs/dev.pem"}]}"#;
+    let mut de = serde_json::Deserializer::from_str(json);
+    let err = SiloSeed::<Servers>::new(&silo)
+        .deserialize(&mut de)
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("`certs/dev.pem` not found (field `servers[1].tls_cert`)"),
+        "{err}"
+    );
+}
+
+/// An owned silo iterates on another thread, yielding the same files as `iter`.
+#[test]
+fn test_silo_into_iter() {
+    let dynamic = Silo::new("tests/data");
+    let memory = dynamic
+        .merge_with(&Silo::new("tests/tenants"), |_, _| MergeDecision::TakeLeft)
+        .unwrap();
+    let silos = [
+        dynamic.clone(),
+        embed_silo!("tests/data", force = true),
+        embed_silo!("tests/data", force = true).scope("subdir"),
+        dynamic.filtered(|file| file.path().extension().is_some_and(|ext| ext == "txt")),
+        memory.clone(),
+        memory.scope("default"),
+    ];
+    for silo in silos {
+        let mut expected: Vec<_> = silo.iter().map(|f| f.path().to_path_buf()).collect();
+        expected.sort();
+        let mut owned = std::thread::spawn(move || {
+            silo.into_iter()
+                .map(|f| f.path().to_path_buf())
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+        owned.sort();
+        assert!(!owned.is_empty());
+        assert_eq!(owned, expected);
+    }
+    let borrowed = Silo::new("tests/data");
+    assert_eq!((&borrowed).into_iter().count(), borrowed.iter().count());
+}
+
+/// Packs `dir` into a temporary file and loads it back, leaking the bytes as `include_bytes!` would.
+fn load_pack(
+    dir: &str,
+    options: &rust_silos::pack::PackOptions,
+) -> (Silo, rust_silos::pack::PackSummary) {
+    let out = tempfile::tempdir().unwrap();
+    let path = out.path().join("assets.pack");
+    let summary = rust_silos::pack::pack_dir(std::path::Path::new(dir), &path, options).unwrap();
+    let bytes: &'static [u8] = Box::leak(std::fs::read(&path).unwrap().into_boxed_slice());
+    assert_eq!(bytes.len() as u64, summary.pack_bytes);
+    (Silo::from_pack_bytes(bytes).unwrap(), summary)
+}
+
+fn assert_pack_parity(packed: &Silo, dir: &str) {
+    let source = Silo::new(dir);
+    let mut expected: Vec<_> = source
+        .iter()
+        .map(|f| f.path().to_string_lossy().into_owned())
+        .collect();
+    expected.sort();
+    let listed: Vec<_> = packed
+        .iter()
+        .map(|f| f.path().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(listed, expected);
+    for path in &expected {
+        let (a, b) = (
+            source.get_file(path).unwrap(),
+            packed.get_file(path).unwrap(),
+        );
+        assert_eq!(a.contents().unwrap(), b.contents().unwrap(), "{path}");
+        assert_eq!(
+            a.meta().unwrap().modified,
+            b.meta().unwrap().modified,
+            "{path}"
+        );
+        assert_eq!(
+            a.content_hash().unwrap(),
+            b.content_hash().unwrap(),
+            "{path}"
+        );
+    }
+    assert!(packed.get_file("missing.txt").is_none());
+}
+
+/// A pack lists and reads exactly what the directory-backed silo does.
+#[test]
+fn test_pack_parity() {
+    for dir in ["tests/data", "tests/extensions", "tests/tenants"] {
+        let (packed, summary) = load_pack(dir, &Default::default());
+        assert_eq!(summary.files, Silo::new(dir).iter().count());
+        assert_eq!(summary.original_bytes, summary.stored_bytes);
+        assert_pack_parity(&packed, dir);
+        let hashed = rust_silos::pack::PackOptions {
+            hashes: true,
+            ..Default::default()
+        };
+        assert_pack_parity(&load_pack(dir, &hashed).0, dir);
+    }
+    let (packed, _) = load_pack("tests/data", &Default::default());
+    let scoped = packed.scope("subdir");
+    assert_eq!(
+        scoped.get_file("gamma.txt").unwrap().text().unwrap(),
+        Silo::new("tests/data/subdir")
+            .get_file("gamma.txt")
+            .unwrap()
+            .text()
+            .unwrap()
+    );
+}
+
+/// Compressed packs round-trip; compression is only used where it saves space.
+#[cfg(feature = "deflate")]
+#[test]
+fn test_pack_deflate() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("repetitive.txt"), "silo ".repeat(1000)).unwrap();
+    std::fs::write(dir.path().join("tiny.txt"), "x").unwrap();
+    let root = dir.path().to_str().unwrap();
+    let options = rust_silos::pack::PackOptions {
+        compress: true,
+        hashes: true,
+        ..Default::default()
+    };
+    let (packed, summary) = load_pack(root, &options);
+    assert_eq!(summary.original_bytes, 5001);
+    assert!(summary.stored_bytes < 200, "{summary:?}");
+    assert_pack_parity(&packed, root);
+}
+
+/// Packing with compression fails cleanly without the `deflate` feature.
+#[cfg(not(feature = "deflate"))]
+#[test]
+fn test_pack_deflate_requires_feature() {
+    let options = rust_silos::pack::PackOptions {
+        compress: true,
+        ..Default::default()
+    };
+    let err = rust_silos::pack::pack_silo(&Silo::new("tests/data"), &options).unwrap_err();
+    assert!(matches!(err, Error::PackError { .. }));
+}
+
+/// Bad magic, unknown versions, truncation and flipped bits are all rejected.
+#[test]
+fn test_pack_corruption() {
+    let pack = rust_silos::pack::pack_silo(&Silo::new("tests/data"), &Default::default()).unwrap();
+    let load = |bytes: Vec<u8>| Silo::from_pack_bytes(Box::leak(bytes.into_boxed_slice()));
+    assert!(load(pack.clone()).is_ok());
+
+    let reason = |bytes: Vec<u8>| match load(bytes) {
+        Err(Error::PackError { reason }) => reason,
+        other => panic!("expected a pack error, got {other:?}"),
+    };
+    let mut bad_magic = pack.clone();
+    bad_magic[0] = b'X';
+    assert!(reason(bad_magic).contains("magic"));
+    let mut newer = pack.clone();
+    newer[8] = rust_silos::pack::PACK_VERSION as u8 + 1;
+    assert!(reason(newer).contains("version"));
+    assert!(reason(pack[..pack.len() - 1].to_vec()).contains("checksum"));
+    assert!(reason(pack[..20].to_vec()).contains("magic"));
+    for i in [40, pack.len() / 2, pack.len() - 1] {
+        let mut flipped = pack.clone();
+        flipped[i] ^= 0x20;
+        assert!(reason(flipped).contains("checksum"), "byte {i}");
+    }
+}
+
+/// `checksum_files` hashes every listed file with SHA-256, identically across backends.
+#[cfg(feature = "hash")]
+#[test]
+fn test_checksum_files() {
+    use sha2::{Digest, Sha256};
+    let embedded = embed_silo!("tests/data", force = true);
+    let sums = embedded.checksum_files().unwrap();
+    let expected: [u8; 32] = Sha256::digest(b"alpha file content\n").into();
+    assert_eq!(sums["alpha.txt"], expected);
+    let paths: HashSet<String> = embedded
+        .iter()
+        .map(|f| f.path().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(sums.keys().cloned().collect::<HashSet<_>>(), paths);
+    assert_eq!(Silo::new("tests/data").checksum_files().unwrap(), sums);
+
+    let hex = embedded.checksum_files_hex().unwrap();
+    assert_eq!(
+        hex["alpha.txt"],
+        "b945ec0d3895cf17e25a165ebb6cce03f37ff70f8d96931c286e3438df08f3c0"
+    );
+    assert_eq!(hex.len(), sums.len());
+}
+
+/// `audit` reports detected license headers per file; scanning does not change what is embedded.
+#[test]
+fn test_embed_license_audit() {
+    let audited = embed_silo!(
+        "tests/licenses",
+        force = true,
+        deny_containing = ["DO NOT DISTRIBUTE"],
+        audit = "tests/licenses.audit.json"
+    );
+    let plain = embed_silo!("tests/licenses", force = true);
+    assert_eq!(audited.iter().count(), plain.iter().count());
+    for file in plain.iter() {
+        let path = file.path().to_str().unwrap();
+        assert_eq!(
+            audited.get_file(path).unwrap().contents().unwrap(),
+            file.contents().unwrap()
+        );
+    }
+
+    let report = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/licenses.audit.json"
+    ))
+    .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["root"], "tests/licenses");
+    let licenses = |path: &str| {
+        let files = report["files"].as_array().unwrap();
+        let entry = files.iter().find(|entry| entry["path"] == path).unwrap();
+        entry["licenses"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l.as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(licenses("mit.js"), ["MIT"]);
+    assert_eq!(licenses("lgpl.c"), ["LGPL"]);
+    assert_eq!(licenses("spdx.rs"), ["Apache-2.0 OR MIT"]);
+    assert_eq!(licenses("site.css"), ["MPL-2.0"]);
+    assert_eq!(licenses("blob.bin"), ["MPL-2.0"]);
+    assert!(licenses("plain.txt").is_empty());
+}
+
+/// `latest_versioned` picks the highest semver-compatible pack, honouring `pack.toml`.
+#[cfg(feature = "semver")]
+#[test]
+fn test_latest_versioned() {
+    use semver::{Version, VersionReq};
+    let tmp = tempfile::tempdir().unwrap();
+    for (dir, marker) in [
+        ("v3", "3"),
+        ("v9", "9"),
+        ("v10", "10"),
+        ("4.2", "4.2"),
+        ("notes", "-"),
+        ("current", "11"),
+    ] {
+        std::fs::create_dir(tmp.path().join(dir)).unwrap();
+        std::fs::write(tmp.path().join(dir).join("which.txt"), marker).unwrap();
+    }
+    std::fs::write(
+        tmp.path().join("current/pack.toml"),
+        "version = \"11.0.0-beta.1\"\n",
+    )
+    .unwrap();
+    std::fs::write(tmp.path().join("v12.txt"), "not a directory").unwrap();
+    let latest = |req: &str| Silo::latest_versioned(tmp.path(), &VersionReq::parse(req).unwrap());
+
+    let (version, silo) = latest(">=3").unwrap();
+    assert_eq!(version, Version::new(10, 0, 0));
+    assert_eq!(silo.get_file("which.txt").unwrap().text().unwrap(), "10");
+    assert_eq!(silo.name(), Some("v10"));
+    assert_eq!(latest("^9").unwrap().0, Version::new(9, 0, 0));
+    assert_eq!(latest("~4").unwrap().0, Version::new(4, 2, 0));
+    assert_eq!(
+        latest(">=11.0.0-beta").unwrap().0,
+        Version::parse("11.0.0-beta.1").unwrap()
+    );
+
+    match latest("^20") {
+        Err(Error::NoMatchingVersion { candidates, .. }) => {
+            let found: Vec<String> = candidates.iter().map(ToString::to_string).collect();
+            assert_eq!(
+                found,
+                ["3.0.0", "4.2.0", "9.0.0", "10.0.0", "11.0.0-beta.1"]
+            );
+        }
+        other => panic!("expected NoMatchingVersion, got {other:?}"),
+    }
+
+    let base = embed_silo!("tests/data", force = true);
+    let (_, set) =
+        SiloSet::latest_versioned_with_base(base, tmp.path(), &VersionReq::parse("<10").unwrap())
+            .unwrap();
+    assert_eq!(set.get_file("which.txt").unwrap().text().unwrap(), "9");
+    assert!(set.get_file("alpha.txt").is_some());
+
+    std::fs::write(
+        tmp.path().join("current/pack.toml"),
+        "version = \"eleven\"\n",
+    )
+    .unwrap();
+    assert!(matches!(latest(">=3"), Err(Error::PackError { .. })));
+}
+
+/// `base64 = true` embeds text literals that decode to the same bytes, sizes and hashes.
+#[test]
+fn test_embed_base64() {
+    let encoded = embed_silo!("tests/base64", force = true, base64 = true);
+    let raw = embed_silo!("tests/base64", force = true);
+    assert_eq!(encoded.iter().count(), 5);
+    for file in raw.iter() {
+        let path = file.path().to_str().unwrap();
+        let decoded = encoded.get_file(path).unwrap();
+        assert_eq!(
+            decoded.contents().unwrap(),
+            file.contents().unwrap(),
+            "{path}"
+        );
+        assert_eq!(
+            decoded.meta().unwrap().size,
+            file.meta().unwrap().size,
+            "{path}"
+        );
+        assert_eq!(
+            decoded.content_hash().unwrap(),
+            file.content_hash().unwrap(),
+            "{path}"
+        );
+        let mut read = Vec::new();
+        decoded.reader().unwrap().read_to_end(&mut read).unwrap();
+        assert_eq!(read, *file.contents().unwrap(), "{path}");
+    }
+    assert!(matches!(
+        encoded
+            .get_file("all_bytes.bin")
+            .unwrap()
+            .contents()
+            .unwrap(),
+        std::borrow::Cow::Borrowed(_)
+    ));
+    assert_eq!(
+        encoded.read_file_as_str_static("three.txt"),
+        Some(Ok("abc"))
+    );
+}
+
+/// Aliases resolve one level to their target and keep the requested path.
+#[test]
+fn test_silo_aliases() {
+    let alias = |a: &str, t: &str| (a.to_owned(), t.to_owned());
+    for silo in [
+        Silo::new("tests/data"),
+        embed_silo!("tests/data", force = true),
+    ] {
+        let plain_count = silo.iter().count();
+        let silo = silo.with_aliases([
+            alias("old/alpha.txt", "alpha.txt"),
+            alias("gamma.txt", "subdir/gamma.txt"),
+        ]);
+        let file = silo.get_file("old/alpha.txt").unwrap();
+        assert_eq!(file.path(), std::path::Path::new("alpha.txt"));
+        assert_eq!(file.requested_path(), std::path::Path::new("old/alpha.txt"));
+        assert_eq!(
+            file.text().unwrap(),
+            silo.get_file("alpha.txt").unwrap().text().unwrap()
+        );
+        assert_eq!(
+            silo.get_file("alpha.txt").unwrap().requested_path(),
+            std::path::Path::new("alpha.txt")
+        );
+        assert_eq!(silo.alias_target("gamma.txt"), Some("subdir/gamma.txt"));
+        assert_eq!(
+            silo.get_file("old\\alpha.txt").unwrap().path(),
+            std::path::Path::new("alpha.txt")
+        );
+        assert_eq!(silo.iter().count(), plain_count);
+
+        let listed = silo.clone().with_alias_options(AliasOptions {
+            include_in_iter: true,
+            ..Default::default()
+        });
+        let requested: HashSet<String> = listed
+            .iter()
+            .map(|f| f.requested_path().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(requested.len(), plain_count + 2);
+        assert!(requested.contains("old/alpha.txt") && requested.contains("gamma.txt"));
+        assert_eq!(listed.clone().into_iter().count(), plain_count + 2);
+
+        let scoped = silo
+            .with_aliases([alias("subdir/old.txt", "subdir/gamma.txt")])
+            .scope("subdir");
+        assert_eq!(
+            scoped.get_file("old.txt").unwrap().path(),
+            std::path::Path::new("gamma.txt")
+        );
+    }
+}
+
+/// Alias chains and cycles are rejected when the aliases are set.
+#[test]
+fn test_silo_alias_cycles_rejected() {
+    let pairs = |list: &[(&str, &str)]| {
+        list.iter()
+            .map(|(a, t)| (a.to_string(), t.to_string()))
+            .collect::<Vec<_>>()
+    };
+    let silo = Silo::new("tests/data");
+    for invalid in [
+        pairs(&[("a", "b"), ("b", "a")]),
+        pairs(&[("a", "a")]),
+        pairs(&[("x", "y"), ("y", "alpha.txt")]),
+    ] {
+        assert!(matches!(
+            silo.clone().try_with_aliases(invalid),
+            Err(Error::InvalidAlias { .. })
+        ));
+    }
+    assert!(
+        silo.try_with_aliases(pairs(&[("x", "alpha.txt"), ("y", "alpha.txt")]))
+            .is_ok()
+    );
+}
+
+/// With `redirect`, `serve` answers aliases with a 301 to the target.
+#[test]
+fn test_serve_alias_redirect() {
+    let silo = Silo::new("tests/data")
+        .with_aliases([("css/site.css".to_owned(), "subdir/gamma.txt".to_owned())]);
+    assert_eq!(
+        silo.serve("/css/site.css", &RequestHeaders::new()).status,
+        200
+    );
+    let silo = silo.with_alias_options(AliasOptions {
+        redirect: true,
+        ..Default::default()
+    });
+    let response = silo.serve("/css/site.css?v=2#top", &RequestHeaders::new());
+    assert_eq!(response.status, 301);
+    assert_eq!(response.header("location"), Some("/subdir/gamma.txt?v=2"));
+    assert!(response.body.is_none());
+    assert_eq!(silo.serve("/alpha.txt", &RequestHeaders::new()).status, 200);
+}
+
+/// `transform` rewrites contents and drops files, producing an in-memory silo.
+#[test]
+fn test_silo_transform() {
+    let silo = Silo::new("tests/data");
+    let upper = silo
+        .transform(|file| {
+            let path = file.path().to_string_lossy();
+            (!path.starts_with("override/"))
+                .then(|| file.text().unwrap().to_uppercase().into_bytes())
+        })
+        .unwrap();
+    assert!(upper.get_file("override/alpha.txt").is_none());
+    assert_eq!(upper.iter().count(), silo.iter().count() - 1);
+    let alpha = upper.get_file("alpha.txt").unwrap();
+    assert_eq!(
+        alpha.text().unwrap(),
+        silo.get_file("alpha.txt")
+            .unwrap()
+            .text()
+            .unwrap()
+            .to_uppercase()
+    );
+    assert_eq!(
+        alpha.meta().unwrap().modified,
+        silo.get_file("alpha.txt").unwrap().meta().unwrap().modified
+    );
+    assert_eq!(
+        upper
+            .get_file("subdir/gamma.txt")
+            .unwrap()
+            .meta()
+            .unwrap()
+            .size,
+        silo.get_file("subdir/gamma.txt")
+            .unwrap()
+            .meta()
+            .unwrap()
+            .size
+    );
+    assert_eq!(silo.transform(|_| None).unwrap().iter().count(), 0);
+}
+
+/// Checks `case` against a silo, naming the backend on failure.
+fn check_path_case(silo: &Silo, backend: &str, case: &testutil::PathCase) {
+    let found = silo
+        .get_file(&case.input)
+        .map(|file| file.path().to_string_lossy().into_owned());
+    match case.expect {
+        testutil::Expect::Rejected => {
+            assert_eq!(found, None, "{backend}: {:?} ({})", case.input, case.why)
+        }
+        testutil::Expect::Resolves(path) => assert_eq!(
+            found.as_deref(),
+            Some(path),
+            "{backend}: {:?} ({})",
+            case.input,
+            case.why
+        ),
+    }
+}
+
+/// Hostile and Windows-style inputs behave identically on every backend and platform.
+#[test]
+fn test_path_equivalence_matrix() {
+    let backends = [
+        ("dynamic", Silo::new("tests/data")),
+        ("embedded", embed_silo!("tests/data", force = true)),
+        (
+            "memory",
+            Silo::new("tests/data")
+                .transform(|file| file.contents().ok().map(|c| c.into_owned()))
+                .unwrap(),
+        ),
+    ];
+    for case in testutil::data_cases() {
+        for (backend, silo) in &backends {
+            check_path_case(silo, backend, &case);
+        }
+        let set = SiloSet::new(backends.iter().map(|(_, silo)| silo.clone()).collect());
+        let expected = match case.expect {
+            testutil::Expect::Rejected => None,
+            testutil::Expect::Resolves(path) => Some(std::path::PathBuf::from(path)),
+        };
+        assert_eq!(
+            set.get_file(&case.input).map(|f| f.path().to_path_buf()),
+            expected,
+            "set: {:?}",
+            case.input
+        );
+    }
+}
+
+/// Windows device names are rejected on Windows and ordinary files elsewhere.
+#[test]
+fn test_reserved_device_names() {
+    let dir = tempfile::tempdir().unwrap();
+    testutil::create_reserved_name_files(dir.path());
+    let silo = Silo::new(dir.path().to_str().unwrap());
+    for case in testutil::reserved_name_cases() {
+        check_path_case(&silo, "dynamic", &case);
+    }
+}
+
+/// Names starting like a drive are rejected on Windows and ordinary files elsewhere, where
+/// every listed file is found again by its path.
+#[test]
+fn test_drive_like_names() {
+    let dir = tempfile::tempdir().unwrap();
+    testutil::create_drive_name_files(dir.path());
+    let dynamic = Silo::new(dir.path().to_str().unwrap());
+    let memory = dynamic
+        .transform(|file| file.contents().ok().map(|c| c.into_owned()))
+        .unwrap();
+    for (backend, silo) in [("dynamic", &dynamic), ("memory", &memory)] {
+        for case in testutil::drive_name_cases() {
+            check_path_case(silo, backend, &case);
+        }
+        for file in silo.iter() {
+            let path = file.path().to_str().unwrap();
+            assert!(silo.get_file(path).is_some(), "{backend}: {path}");
+        }
+    }
+}
+
+/// Dynamic lookups follow the filesystem's case sensitivity; embedded keys are always exact.
+#[test]
+fn test_lookup_case_sensitivity() {
+    let case_insensitive_fs = std::path::Path::new("tests/data/ALPHA.TXT").exists();
+    assert_eq!(
+        Silo::new("tests/data").get_file("ALPHA.TXT").is_some(),
+        case_insensitive_fs
+    );
+    assert!(
+        embed_silo!("tests/data", force = true)
+            .get_file("ALPHA.TXT")
+            .is_none()
+    );
+}
+
+/// Misses are remembered for the TTL, so files created meanwhile appear only once it expires.
+#[test]
+fn test_negative_cache_ttl() {
+    let dir = tempfile::tempdir().unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap())
+        .with_negative_cache(16, std::time::Duration::from_millis(200));
+    assert!(silo.get_file("late.txt").is_none());
+    std::fs::write(dir.path().join("late.txt"), "late").unwrap();
+    assert!(silo.get_file("late.txt").is_none());
+    assert!(silo.clone().get_file("late.txt").is_none());
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    assert_eq!(silo.get_file("late.txt").unwrap().text().unwrap(), "late");
+
+    assert!(silo.get_file("later.txt").is_none());
+    std::fs::write(dir.path().join("later.txt"), "later").unwrap();
+    silo.clear_negative_cache();
+    assert!(silo.get_file("later.txt").is_some());
+}
+
+/// The cache holds at most `capacity` misses, evicting the oldest, and scopes do not collide.
+#[test]
+fn test_negative_cache_capacity() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let silo = Silo::new(dir.path().to_str().unwrap())
+        .with_negative_cache(2, std::time::Duration::from_secs(3600));
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        assert!(silo.get_file(name).is_none());
+    }
+    for name in ["a.txt", "c.txt"] {
+        std::fs::write(dir.path().join(name), name).unwrap();
+    }
+    assert!(silo.get_file("a.txt").is_some(), "oldest miss was evicted");
+    assert!(
+        silo.get_file("c.txt").is_none(),
+        "recent miss is still cached"
+    );
+
+    std::fs::write(dir.path().join("sub/b.txt"), "b").unwrap();
+    assert!(silo.get_file("b.txt").is_none());
+    assert!(silo.clone().scope("sub").get_file("b.txt").is_some());
+}
+
+fn visible_paths(source: &impl SiloLike) -> Vec<String> {
+    let mut paths: Vec<String> = source
+        .iter()
+        .map(|file| file.path().to_string_lossy().into_owned())
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn alpha_text(source: &dyn SiloLike) -> String {
+    String::from_utf8(source.read("alpha.txt").unwrap().into_owned()).unwrap()
+}
+
+/// Tests that helpers written against SiloLike accept both a Silo and a SiloSet, statically and through dyn.
+#[test]
+fn test_silo_like_silo_and_set() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "overlay").unwrap();
+    let silo = embed_silo!("tests/data", force = true);
+    let set = SiloSet::new(vec![silo.clone(), Silo::new(tmp.path().to_str().unwrap())]);
+
+    let expected = [
+        "alpha.txt",
+        "beta.txt",
+        "override/alpha.txt",
+        "subdir/gamma.txt",
+    ];
+    assert_eq!(visible_paths(&silo), expected);
+    assert_eq!(visible_paths(&set), expected);
+    assert_eq!(visible_paths(&std::sync::Arc::new(set.clone())), expected);
+
+    assert_eq!(alpha_text(&silo), "alpha file content\n");
+    assert_eq!(alpha_text(&set), "overlay");
+    assert!(matches!(set.read("missing.txt"), Err(Error::NotFound)));
+
+    let sources: [&dyn SiloLike; 2] = [&silo, &set];
+    for source in sources {
+        assert!(source.contains("subdir/gamma.txt"));
+        assert!(!source.contains("subdir"));
+        let mut globbed: Vec<_> = source
+            .glob("**/alpha.txt")
+            .map(|file| file.path().to_string_lossy().into_owned())
+            .collect();
+        globbed.sort();
+        assert_eq!(globbed, ["alpha.txt", "override/alpha.txt"]);
+        assert_eq!(source.stats().unwrap().files, 4);
+        assert_eq!(
+            source.serve("/beta.txt", &RequestHeaders::new()).status,
+            200
+        );
+        assert_eq!(
+            source.serve("/../beta.txt", &RequestHeaders::new()).status,
+            404
+        );
+    }
+    let silo_bytes = silo.stats().unwrap().bytes;
+    assert_eq!(
+        set.stats().unwrap().bytes,
+        silo_bytes - silo.get_file("alpha.txt").unwrap().meta().unwrap().size as u64 + 7
+    );
+}
+
+/// Tests that a snapshot of a dynamic silo keeps its file list while the directory changes.
+#[test]
+fn test_silo_snapshot_dynamic() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("a.txt"), "aa").unwrap();
+    std::fs::create_dir(tmp.path().join("sub")).unwrap();
+    std::fs::write(tmp.path().join("sub/b.txt"), "bbb").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    let snapshot = silo.snapshot();
+    let before = snapshot.manifest().unwrap();
+
+    std::fs::write(tmp.path().join("c.txt"), "new").unwrap();
+    std::fs::remove_file(tmp.path().join("sub/b.txt")).unwrap();
+
+    let paths: Vec<_> = snapshot
+        .iter()
+        .map(|file| file.path().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(paths, ["a.txt", "sub/b.txt"]);
+    assert_eq!(snapshot.len(), 2);
+    assert!(snapshot.get_file("c.txt").is_none());
+    assert!(silo.get_file("c.txt").is_some());
+    for rooted in ["/a.txt", "\\a.txt", "//a.txt", "C:a.txt"] {
+        assert!(silo.get_file(rooted).is_none(), "{rooted}");
+        assert!(snapshot.get_file(rooted).is_none(), "{rooted}");
+    }
+    assert_eq!(snapshot.meta("sub/b.txt").unwrap().size, 3);
+    assert_eq!(snapshot.read("a.txt").unwrap().as_ref(), b"aa");
+    assert!(
+        matches!(snapshot.read("sub/b.txt"), Err(Error::Stale { path }) if path == "sub/b.txt")
+    );
+    assert!(matches!(snapshot.read("c.txt"), Err(Error::NotFound)));
+    assert!(matches!(snapshot.manifest(), Err(Error::Stale { .. })));
+    assert_eq!(snapshot.stats().unwrap(), SiloStats { files: 2, bytes: 5 });
+    assert_eq!(before.entries.len(), 2);
+    let diff = silo.snapshot().manifest().unwrap().diff(&before);
+    assert_eq!(
+        diff.removed
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect::<Vec<_>>(),
+        ["sub/b.txt"]
+    );
+}
+
+/// Tests that a snapshot of an embedded silo sees the same files as the silo.
+#[test]
+fn test_silo_snapshot_embedded() {
+    let silo = embed_silo!("tests/data", force = true);
+    let snapshot = silo.snapshot();
+    assert_eq!(snapshot.len(), silo.iter().count());
+    assert_eq!(
+        snapshot.read("alpha.txt").unwrap().as_ref(),
+        b"alpha file content\n"
+    );
+    assert_eq!(snapshot.manifest().unwrap(), silo.manifest().unwrap());
+    assert_eq!(
+        SiloLike::stats(&snapshot).unwrap(),
+        SiloLike::stats(&silo).unwrap()
+    );
+}
+
+/// `cache = true` embeds exactly what a plain expansion embeds.
+#[test]
+fn test_embed_cache_matches_plain() {
+    let cached = embed_silo!("tests/data", force = true, cache = true);
+    let plain = embed_silo!("tests/data", force = true);
+    assert_eq!(cached.manifest().unwrap(), plain.manifest().unwrap());
+}
+
+/// `open` returns a seekable reader with metadata, and the same errors for embedded and dynamic silos.
+#[test]
+fn test_open_embedded_dynamic_parity() {
+    use std::io::{Seek, SeekFrom};
+    let embedded = embed_silo!("tests/data", force = true);
+    let dynamic = embedded.clone().into_dynamic().unwrap();
+    for silo in [&embedded, &dynamic] {
+        let mut file = silo.open("subdir/gamma.txt").unwrap();
+        assert_eq!(file.path, "subdir/gamma.txt");
+        assert_eq!(
+            file.meta.size,
+            silo.get_file("subdir/gamma.txt")
+                .unwrap()
+                .meta()
+                .unwrap()
+                .size
+        );
+        file.seek(SeekFrom::Start(6)).unwrap();
+        let mut rest = String::new();
+        file.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "file content\n");
+
+        assert!(
+            matches!(silo.open("subdir"), Err(Error::IsADirectory { path }) if path == "subdir")
+        );
+        assert!(matches!(
+            silo.open("subdir/"),
+            Err(Error::IsADirectory { .. })
+        ));
+        assert!(matches!(silo.open(""), Err(Error::IsADirectory { .. })));
+        assert!(matches!(silo.open("missing.txt"), Err(Error::NotFound)));
+        assert!(matches!(silo.open("subdir/missing"), Err(Error::NotFound)));
+    }
+    let set = SiloSet::new(vec![embedded, dynamic]);
+    assert_eq!(set.open("alpha.txt").unwrap().path, "alpha.txt");
+    assert!(matches!(
+        set.open("override"),
+        Err(Error::IsADirectory { .. })
+    ));
+}
+
+/// `open` reports unreadable dynamic files as PermissionDenied.
+#[cfg(unix)]
+#[test]
+fn test_open_permission_denied() {
+    use std::os::unix::fs::PermissionsExt;
+    let tmp = tempfile::tempdir().unwrap();
+    let secret = tmp.path().join("secret.txt");
+    std::fs::write(&secret, "x").unwrap();
+    std::fs::set_permissions(&secret, std::fs::Permissions::from_mode(0o000)).unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    // Privileged users can read anything; there is nothing to check then.
+    if std::fs::File::open(&secret).is_ok() {
+        return;
+    }
+    assert!(
+        matches!(silo.open("secret.txt"), Err(Error::PermissionDenied { path }) if path == "secret.txt")
+    );
+}
+
+/// `select` spreads keys over the variants in proportion to their weights.
+#[test]
+fn test_select_weighted_distribution() {
+    let silo = embed_silo!("tests/data", force = true);
+    let candidates = [("alpha.txt", 1), ("beta.txt", 3), ("subdir/gamma.txt", 6)];
+    let keys = 100_000u64;
+    let mut counts = std::collections::HashMap::new();
+    for key in 0..keys {
+        let file = silo.select(&candidates, key).unwrap();
+        *counts
+            .entry(file.path().to_string_lossy().into_owned())
+            .or_insert(0u64) += 1;
+    }
+    // Chi-square with 2 degrees of freedom; 13.8 is the 0.1% critical value.
+    let chi_square: f64 = candidates
+        .iter()
+        .map(|(path, weight)| {
+            let expected = keys as f64 * f64::from(*weight) / 10.0;
+            let observed = counts[*path] as f64;
+            (observed - expected).powi(2) / expected
+        })
+        .sum();
+    assert!(chi_square < 13.8, "chi-square {chi_square} for {counts:?}");
+}
+
+/// `select` is deterministic, skips missing candidates and follows overlays in a set.
+#[test]
+fn test_select_deterministic_and_missing() {
+    let silo = embed_silo!("tests/data", force = true);
+    let candidates = [("alpha.txt", 1), ("missing.txt", 5), ("beta.txt", 1)];
+    for key in [0, 1, 42, u64::MAX] {
+        let first = silo.select(&candidates, key).unwrap();
+        assert_eq!(silo.select(&candidates, key).unwrap(), first);
+        assert_ne!(first.path(), std::path::Path::new("missing.txt"));
+    }
+    let picked: HashSet<_> = (0..1000)
+        .map(|key| silo.select(&candidates, key).unwrap().path().to_owned())
+        .collect();
+    assert_eq!(picked.len(), 2);
+    assert!(silo.select(&[("missing.txt", 1)], 7).is_none());
+    assert!(silo.select(&[], 7).is_none());
+    assert_eq!(
+        silo.select(&[("missing.txt", 1), ("beta.txt", 0), ("alpha.txt", 0)], 7)
+            .unwrap()
+            .path(),
+        std::path::Path::new("beta.txt")
+    );
+
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "overlay").unwrap();
+    let set = SiloSet::new(vec![silo.clone(), Silo::new(tmp.path().to_str().unwrap())]);
+    let key = (0..)
+        .find(|&key| {
+            silo.select(&candidates, key).unwrap().path() == std::path::Path::new("alpha.txt")
+        })
+        .unwrap();
+    assert_eq!(
+        set.select(&candidates, key)
+            .unwrap()
+            .contents()
+            .unwrap()
+            .as_ref(),
+        b"overlay"
+    );
+}
+
+/// `report_json` lists byte-identical files and files over `size_budget`.
+#[test]
+fn test_embed_report_json() {
+    let silo = embed_silo!(
+        "tests/extensions",
+        force = true,
+        report_json = "target/test-report.json",
+        size_budget = 4
+    );
+    assert!(silo.get_file("lib.wasm").is_some());
+    let report = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/target/test-report.json"
+    ))
+    .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(
+        report["duplicates"],
+        serde_json::json!([["app.WASM", "lib.wasm", "sub/deep.Wasm"]])
+    );
+    assert_eq!(report["totals"]["files"], silo.iter().count());
+    assert_eq!(report["totals"]["duplicate_files"], 2);
+    assert_eq!(report["size_budget"], 4);
+}
+
+/// The version token of a set changes when an overlay file changes, appears or disappears.
+#[test]
+fn test_silo_set_version_token() {
+    let tmp = tempfile::tempdir().unwrap();
+    let overlay = tmp.path().join("alpha.txt");
+    std::fs::write(&overlay, "one").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let token = set.version_token();
+    assert_eq!(set.version_token(), token);
+    assert!(!set.changed_since(&token));
+    assert_eq!(token.to_string().len(), 16);
+
+    std::fs::write(&overlay, "three").unwrap();
+    assert!(set.changed_since(&token));
+    let resized = set.version_token();
+
+    std::fs::write(tmp.path().join("new.txt"), "n").unwrap();
+    assert!(set.changed_since(&resized));
+    let added = set.version_token();
+    std::fs::remove_file(tmp.path().join("new.txt")).unwrap();
+    assert!(set.changed_since(&added));
+
+    let file = std::fs::File::options().write(true).open(&overlay).unwrap();
+    file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(5))
+        .unwrap();
+    assert!(set.changed_since(&resized));
+
+    let response = set
+        .serve("/beta.txt", &RequestHeaders::new())
+        .with_version(set.version_token());
+    assert_eq!(
+        response.header("x-assets-version"),
+        Some(set.version_token().to_string().as_str())
+    );
+}
+
+fn include_silo(files: &[(&str, &str)]) -> (tempfile::TempDir, Silo) {
+    let tmp = tempfile::tempdir().unwrap();
+    for (path, contents) in files {
+        let path = tmp.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    (tmp, silo)
+}
+
+/// `render_includes` splices nested includes and handles missing ones as configured.
+#[test]
+fn test_render_includes_nested_and_missing() {
+    let (_tmp, silo) = include_silo(&[
+        (
+            "page.html",
+            "<body><!-- include: partials/nav.html --><main/></body>",
+        ),
+        (
+            "partials/nav.html",
+            "<nav><!-- include:partials/logo.html--></nav>",
+        ),
+        ("partials/logo.html", "LOGO"),
+        ("broken.html", "a<!-- include: nope.html -->b"),
+    ]);
+    let opts = IncludeOptions::default();
+    assert_eq!(
+        silo.render_includes("page.html", &opts).unwrap(),
+        "<body><nav>LOGO</nav><main/></body>"
+    );
+    assert!(matches!(
+        silo.render_includes("broken.html", &opts),
+        Err(Error::MissingInclude { path, included_from }) if path == "nope.html" && included_from == "broken.html"
+    ));
+    let keep = IncludeOptions {
+        missing: MissingInclude::Keep,
+        ..IncludeOptions::default()
+    };
+    assert_eq!(
+        silo.render_includes("broken.html", &keep).unwrap(),
+        "a<!-- include: nope.html -->b"
+    );
+    let empty = IncludeOptions {
+        missing: MissingInclude::Empty,
+        ..IncludeOptions::default()
+    };
+    assert_eq!(silo.render_includes("broken.html", &empty).unwrap(), "ab");
+    let shallow = IncludeOptions {
+        max_depth: 1,
+        ..IncludeOptions::default()
+    };
+    assert!(
+        matches!(silo.render_includes("page.html", &shallow), Err(Error::IncludeTooDeep { path, max_depth: 1 }) if path == "partials/logo.html")
+    );
+    let custom = IncludeOptions {
+        open: "{{> ".to_owned(),
+        close: " }}".to_owned(),
+        ..IncludeOptions::default()
+    };
+    let (_tmp2, other) =
+        include_silo(&[("a.txt", "[{{> b.txt }}] {{> unterminated"), ("b.txt", "B")]);
+    assert_eq!(
+        other.render_includes("a.txt", &custom).unwrap(),
+        "[B] {{> unterminated"
+    );
+    assert!(matches!(
+        silo.render_includes("missing.html", &opts),
+        Err(Error::NotFound)
+    ));
+}
+
+/// `render_includes` names the files of an include cycle.
+#[test]
+fn test_render_includes_cycle() {
+    let (_tmp, silo) = include_silo(&[
+        ("a.html", "<!-- include: b.html -->"),
+        ("b.html", "<!-- include: c.html -->"),
+        ("c.html", "<!-- include: b.html -->"),
+    ]);
+    let err = silo
+        .render_includes("a.html", &IncludeOptions::default())
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::IncludeCycle { chain } if chain == &["b.html", "c.html", "b.html"])
+    );
+    assert_eq!(err.to_string(), "Include cycle: b.html -> c.html -> b.html");
+}
+
+/// Partials included through a set come from the highest layer that has them.
+#[test]
+fn test_render_includes_overlay_partial() {
+    let (_base_dir, base) = include_silo(&[
+        ("page.html", "[<!-- include: nav.html -->]"),
+        ("nav.html", "base nav"),
+    ]);
+    let (_theme_dir, theme) = include_silo(&[("nav.html", "theme nav")]);
+    let set = SiloSet::new(vec![base.clone(), theme]);
+    assert_eq!(
+        base.render_includes("page.html", &IncludeOptions::default())
+            .unwrap(),
+        "[base nav]"
+    );
+    assert_eq!(
+        set.render_includes("page.html", &IncludeOptions::default())
+            .unwrap(),
+        "[theme nav]"
+    );
+}
+
+/// The multi-directory form layers the directories the same way embedded and from disk.
+#[test]
+fn test_embed_multi_root_parity() {
+    let embedded = embed_silo!(["tests/overlay/base", "tests/overlay/theme"], force = true);
+    let dynamic = embed_silo!(["tests/overlay/base", "tests/overlay/theme"], force = false);
+    assert!(embedded.silos.iter().all(Silo::is_embedded));
+    // Without `force`, release builds embed too.
+    if cfg!(debug_assertions) {
+        assert!(dynamic.silos.iter().all(Silo::is_dynamic));
+    }
+    for path in ["logo.txt", "partials/nav.html", "base.txt", "theme.css"] {
+        let (embedded, dynamic) = (
+            embedded.get_file(path).unwrap(),
+            dynamic.get_file(path).unwrap(),
+        );
+        assert_eq!(
+            embedded.contents().unwrap(),
+            dynamic.contents().unwrap(),
+            "{path}"
+        );
+    }
+    assert_eq!(
+        embedded.get_text_content("logo.txt").unwrap(),
+        "theme logo\n"
+    );
+    assert_eq!(embedded.iter_override().count(), 4);
+    assert_eq!(dynamic.iter_override().count(), 4);
+}
+
+/// `deviations` reports files added on disk and files that shadow the embedded baseline.
+#[test]
+fn test_silo_set_deviations() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "operator edit").unwrap();
+    std::fs::write(
+        tmp.path().join("beta.txt"),
+        std::fs::read("tests/data/beta.txt").unwrap(),
+    )
+    .unwrap();
+    std::fs::write(tmp.path().join("extra.txt"), "new").unwrap();
+    std::fs::create_dir(tmp.path().join("subdir")).unwrap();
+    std::fs::write(tmp.path().join("subdir/gamma.txt"), "GAMMA FILE CONTENT\n").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let deviations = set.deviations(0).unwrap();
+    assert_eq!(
+        deviations,
+        vec![
+            Deviation {
+                path: "alpha.txt".into(),
+                kind: DeviationKind::ShadowsEmbedded {
+                    size_differs: true,
+                    hash_differs: true
+                }
+            },
+            Deviation {
+                path: "beta.txt".into(),
+                kind: DeviationKind::ShadowsEmbedded {
+                    size_differs: false,
+                    hash_differs: false
+                }
+            },
+            Deviation {
+                path: "extra.txt".into(),
+                kind: DeviationKind::AddedOnDisk
+            },
+            Deviation {
+                path: "subdir/gamma.txt".into(),
+                kind: DeviationKind::ShadowsEmbedded {
+                    size_differs: false,
+                    hash_differs: true
+                }
+            },
+        ]
+    );
+    assert!(set.deviations(1).unwrap().is_empty());
+    assert!(matches!(set.deviations(2), Err(Error::NotFound)));
+}
+
+/// Deviations serialize for diagnostics bundles.
+#[cfg(feature = "serde")]
+#[test]
+fn test_silo_set_deviations_serde() {
+    let deviation = Deviation {
+        path: "a.txt".into(),
+        kind: DeviationKind::ShadowsEmbedded {
+            size_differs: false,
+            hash_differs: true,
+        },
+    };
+    let json = serde_json::to_string(&deviation).unwrap();
+    assert_eq!(
+        json,
+        r#"{"path":"a.txt","kind":{"ShadowsEmbedded":{"size_differs":false,"hash_differs":true}}}"#
+    );
+    assert_eq!(serde_json::from_str::<Deviation>(&json).unwrap(), deviation);
+}
+
+/// Builds a tree `width` files wide at each of `depth` nested levels.
+fn deep_wide_tree(depth: usize, width: usize) -> tempfile::TempDir {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut dir = tmp.path().to_path_buf();
+    for level in 0..depth {
+        for i in 0..width {
+            std::fs::write(dir.join(format!("f{i}.txt")), "x").unwrap();
+        }
+        dir = dir.join(format!("d{level}"));
+        std::fs::create_dir(&dir).unwrap();
+    }
+    tmp
+}
+
+/// Tests that walk limits stop dynamic walks, quietly for `iter` and with an error for `try_iter` and `stats`.
+#[test]
+fn test_walk_limits() {
+    let tmp = deep_wide_tree(8, 5);
+    let root = tmp.path().to_str().unwrap();
+    assert_eq!(Silo::new(root).iter().count(), 40);
+    assert_eq!(Silo::new(root).try_iter().filter(Result::is_err).count(), 0);
+
+    let by_files = Silo::new_with_limits(
+        root,
+        WalkLimits {
+            max_files: Some(12),
+            ..WalkLimits::default()
+        },
+    );
+    assert_eq!(by_files.iter().count(), 12);
+    assert_eq!(by_files.clone().into_iter().count(), 12);
+    let results: Vec<_> = by_files.try_iter().collect();
+    assert_eq!(results.len(), 13);
+    assert!(matches!(
+        results.last(),
+        Some(Err(Error::LimitExceeded {
+            which: LimitKind::Files,
+            limit: 12
+        }))
+    ));
+    assert!(matches!(
+        SiloLike::stats(&by_files),
+        Err(Error::LimitExceeded {
+            which: LimitKind::Files,
+            ..
+        })
+    ));
+    assert!(matches!(
+        by_files.prewarm(),
+        Err(Error::LimitExceeded { .. })
+    ));
+
+    let by_depth = Silo::new_with_limits(
+        root,
+        WalkLimits {
+            max_depth: Some(3),
+            ..WalkLimits::default()
+        },
+    );
+    assert!(
+        by_depth
+            .iter()
+            .all(|file| file.path().components().count() <= 3)
+    );
+    assert!(matches!(
+        by_depth.try_iter().last(),
+        Some(Err(Error::LimitExceeded {
+            which: LimitKind::Depth,
+            limit: 3
+        }))
+    ));
+
+    let by_time = Silo::new(root).with_walk_limits(WalkLimits {
+        max_duration: Some(std::time::Duration::ZERO),
+        ..WalkLimits::default()
+    });
+    assert!(matches!(
+        by_time.try_iter().last(),
+        Some(Err(Error::LimitExceeded {
+            which: LimitKind::Duration,
+            limit: 0
+        }))
+    ));
+
+    let roomy = Silo::new_with_limits(
+        root,
+        WalkLimits {
+            max_files: Some(40),
+            max_depth: Some(8),
+            max_duration: None,
+        },
+    );
+    assert_eq!(
+        SiloLike::stats(&roomy).unwrap(),
+        SiloStats {
+            files: 40,
+            bytes: 40
+        }
+    );
+    assert_eq!(roomy.prewarm().unwrap(), 40);
+}
+
+/// Tests that `serve` answers a single byte range with 206 and honors `If-Range`.
+#[test]
+fn test_serve_byte_ranges() {
+    let silo = embed_silo!("tests/data", force = true);
+    let request = |pairs: &[(&str, &str)]| pairs.iter().copied().collect::<RequestHeaders>();
+    let body = |response: HttpFileResponse| {
+        let mut bytes = Vec::new();
+        response.body.unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    };
+    let whole = silo.serve("/alpha.txt", &RequestHeaders::new());
+    assert_eq!(whole.header("Accept-Ranges"), Some("bytes"));
+    let etag = whole.header("ETag").unwrap().to_owned();
+
+    let response = silo.serve("/alpha.txt", &request(&[("Range", "bytes=0-4")]));
+    assert_eq!(response.status, 206);
+    assert_eq!(response.header("Content-Range"), Some("bytes 0-4/19"));
+    assert_eq!(response.header("Content-Length"), Some("5"));
+    assert_eq!(body(response), b"alpha");
+    assert_eq!(
+        body(silo.serve("/alpha.txt", &request(&[("Range", "bytes=-8")]))),
+        b"content\n"
+    );
+    assert_eq!(
+        body(silo.serve("/alpha.txt", &request(&[("Range", "bytes=15-")]))),
+        b"ent\n"
+    );
+    assert_eq!(
+        body(silo.serve("/alpha.txt", &request(&[("Range", "bytes=6-100")]))),
+        b"file content\n"
+    );
+
+    let response = silo.serve("/alpha.txt", &request(&[("Range", "bytes=19-")]));
+    assert_eq!(response.status, 416);
+    assert_eq!(response.header("Content-Range"), Some("bytes */19"));
+    assert_eq!(
+        silo.serve("/alpha.txt", &request(&[("Range", "bytes=0-1,4-5")]))
+            .status,
+        200
+    );
+    assert_eq!(
+        silo.serve("/alpha.txt", &request(&[("Range", "items=0-1")]))
+            .status,
+        200
+    );
+    assert_eq!(
+        silo.serve(
+            "/alpha.txt",
+            &request(&[("Range", "bytes=0-4"), ("If-Range", &etag)])
+        )
+        .status,
+        206
+    );
+    assert_eq!(
+        silo.serve(
+            "/alpha.txt",
+            &request(&[("Range", "bytes=0-4"), ("If-Range", "\"stale\"")])
+        )
+        .status,
+        200
+    );
+}
+
+/// Tests that digests computed while reading match precomputed vectors for embedded and dynamic files.
+#[test]
+fn test_reader_with_digest() {
+    let embedded = embed_silo!("tests/data", force = true);
+    let dynamic = Silo::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data"));
+    for silo in [&embedded, &dynamic] {
+        let file = silo.get_file("alpha.txt").unwrap();
+        let (mut reader, handle) = file.reader_with_digest(HashAlg::Fnv64).unwrap();
+        let mut first = [0u8; 5];
+        reader.read_exact(&mut first).unwrap();
+        assert!(matches!(handle.finalize(), Err(Error::DigestIncomplete)));
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(
+            handle.finalize().unwrap(),
+            Digest::Fnv64(0xe777_64da_4dba_cb88)
+        );
+        assert_eq!(
+            handle.finalize().unwrap(),
+            Digest::Fnv64(file.content_hash().unwrap())
+        );
+
+        let mut out = Vec::new();
+        let (copied, digest) = file.copy_to(&mut out, HashAlg::Fnv64).unwrap();
+        assert_eq!(
+            (copied, out.as_slice()),
+            (19, b"alpha file content\n".as_slice())
+        );
+        assert_eq!(digest.to_string(), "e77764da4dbacb88");
+        #[cfg(feature = "hash")]
+        {
+            let (_, digest) = file.copy_to(&mut std::io::sink(), HashAlg::Sha256).unwrap();
+            assert_eq!(
+                digest.to_string(),
+                "b945ec0d3895cf17e25a165ebb6cce03f37ff70f8d96931c286e3438df08f3c0"
+            );
+            assert_eq!(digest, Digest::Sha256(file.hash().unwrap()));
+        }
+    }
+}
+
+/// Tests each symlink policy against an escaping link, a within-root link and a symlinked directory.
+#[cfg(unix)]
+#[test]
+fn test_symlink_policy() {
+    use std::os::unix::fs::symlink;
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("root");
+    std::fs::create_dir_all(root.join("real")).unwrap();
+    std::fs::write(root.join("plain.txt"), "plain").unwrap();
+    std::fs::write(root.join("real/inner.txt"), "inner").unwrap();
+    std::fs::write(tmp.path().join("secret.txt"), "secret").unwrap();
+    symlink(tmp.path().join("secret.txt"), root.join("escape.txt")).unwrap();
+    symlink("plain.txt", root.join("within.txt")).unwrap();
+    symlink("real", root.join("linked")).unwrap();
+    std::fs::create_dir(tmp.path().join("outside")).unwrap();
+    std::fs::write(tmp.path().join("outside/far.txt"), "far").unwrap();
+    symlink(tmp.path().join("outside"), root.join("away")).unwrap();
+
+    let silo = |policy| Silo::new(root.to_str().unwrap()).with_symlink_policy(policy);
+    let found = |silo: &Silo| {
+        [
+            "plain.txt",
+            "escape.txt",
+            "within.txt",
+            "linked/inner.txt",
+            "away/far.txt",
+        ]
+        .map(|path| silo.get_file(path).is_some())
+    };
+    let walked = |silo: &Silo| {
+        let mut paths: Vec<_> = silo
+            .iter()
+            .map(|file| file.path().to_str().unwrap().to_owned())
+            .collect();
+        paths.sort();
+        paths
+    };
+
+    let within = silo(SymlinkPolicy::AllowWithinRoot);
+    assert_eq!(SymlinkPolicy::default(), SymlinkPolicy::AllowWithinRoot);
+    assert_eq!(found(&within), [true, false, true, true, false]);
+    assert_eq!(
+        walked(&within),
+        ["plain.txt", "real/inner.txt", "within.txt"]
+    );
+    assert_eq!(
+        within
+            .get_file("linked/inner.txt")
+            .unwrap()
+            .contents()
+            .unwrap()
+            .as_ref(),
+        b"inner"
+    );
+    assert!(within.read_dir("away").next().is_none());
+
+    let deny = silo(SymlinkPolicy::Deny);
+    assert_eq!(found(&deny), [true, false, false, false, false]);
+    assert_eq!(walked(&deny), ["plain.txt", "real/inner.txt"]);
+    assert!(deny.get_file("real/inner.txt").is_some());
+    assert!(deny.read_dir("linked").next().is_none());
+    assert!(!deny.read_dir("").any(
+        |entry| matches!(entry, Entry::File(file) if file.path().to_str() == Some("within.txt"))
+    ));
+
+    let all = silo(SymlinkPolicy::AllowAll);
+    assert_eq!(found(&all), [true, true, true, true, true]);
+    // Symlinked directories are walked too, as `embed_silo!(.., follow_symlinks = true)` does.
+    assert_eq!(
+        walked(&all),
+        [
+            "away/far.txt",
+            "escape.txt",
+            "linked/inner.txt",
+            "plain.txt",
+            "real/inner.txt",
+            "within.txt"
+        ]
+    );
+    assert_eq!(
+        all.get_file("escape.txt")
+            .unwrap()
+            .contents()
+            .unwrap()
+            .as_ref(),
+        b"secret"
+    );
+    assert!(all.get_file("../secret.txt").is_none());
+    assert!(all.get_file("real/../../secret.txt").is_none());
+}
+
+/// Tests that `build_id` stamps embedded silos, stably with `deterministic`, and not dynamic ones.
+#[test]
+fn test_embed_build_id() {
+    let first = embed_silo!(
+        "tests/data",
+        force = true,
+        build_id = true,
+        deterministic = true
+    );
+    let second = embed_silo!(
+        "tests/data",
+        force = true,
+        build_id = true,
+        deterministic = true
+    );
+    let id = first.build_id().unwrap();
+    assert_eq!(second.build_id(), Some(id));
+    assert_eq!(id.to_string().len(), 32);
+    assert_eq!(id.to_string().parse::<BuildId>(), Ok(id));
+
+    let random = embed_silo!("tests/data", force = true, build_id = true);
+    assert!(random.build_id().is_some_and(|other| other != id));
+    assert!(embed_silo!("tests/data", force = true).build_id().is_none());
+    assert!(Silo::new("tests/data").build_id().is_none());
+
+    assert_eq!(first.manifest().unwrap().build_id, Some(id));
+    let response = first
+        .serve("/alpha.txt", &RequestHeaders::new())
+        .with_build_id(first.build_id());
+    assert_eq!(
+        response.header("X-Asset-Build"),
+        Some(id.to_string().as_str())
+    );
+}
+
+/// Tests that the build id is written into, and read back from, the manifest JSON.
+#[cfg(feature = "serde")]
+#[test]
+fn test_manifest_build_id_json() {
+    let silo = embed_silo!(
+        "tests/data",
+        force = true,
+        build_id = true,
+        deterministic = true
+    );
+    let manifest = silo.manifest().unwrap();
+    let json: serde_json::Value = serde_json::to_value(&manifest).unwrap();
+    assert_eq!(json["build_id"], silo.build_id().unwrap().to_string());
+    assert_eq!(serde_json::from_value::<Manifest>(json).unwrap(), manifest);
+    let dynamic = serde_json::to_value(Silo::new("tests/data").manifest().unwrap()).unwrap();
+    assert!(dynamic.get("build_id").is_none());
+}
+
+/// Tests bulk loading with a file that fails to read in the middle of iteration.
+#[test]
+fn test_for_each_contents() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("a.txt"), "first").unwrap();
+    std::fs::write(tmp.path().join("garbled.txt"), b"\xff\x00\x01\x02\x03").unwrap();
+    std::fs::write(tmp.path().join("z.txt"), "last").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap()).with_transcoding(Transcoding {
+        extensions: &["txt"],
+        on_failure: TranscodeFailure::Error,
+    });
+
+    let results: Vec<_> = silo.iter_contents().collect();
+    assert_eq!(results.len(), 3);
+    let mut loaded: Vec<(String, Vec<u8>)> = results
+        .iter()
+        .filter_map(|result| result.as_ref().ok())
+        .map(|(file, contents)| {
+            (
+                file.path().to_string_lossy().into_owned(),
+                contents.to_vec(),
+            )
+        })
+        .collect();
+    loaded.sort();
+    assert_eq!(
+        loaded,
+        [
+            ("a.txt".to_owned(), b"first".to_vec()),
+            ("z.txt".to_owned(), b"last".to_vec())
+        ]
+    );
+    assert!(matches!(
+        results.iter().find(|result| result.is_err()),
+        Some(Err(Error::TranscodeError { .. }))
+    ));
+
+    let mut seen = Vec::new();
+    let err = silo
+        .for_each_contents_with(ReadErrors::Collect, |file, contents| {
+            seen.push((
+                file.path().to_string_lossy().into_owned(),
+                contents.to_vec(),
+            ));
+            Ok::<_, std::fmt::Error>(())
+        })
+        .unwrap_err();
+    seen.sort();
+    assert_eq!(seen, loaded);
+    let IterError::Read(failures) = err else {
+        panic!("expected read errors")
+    };
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, "garbled.txt");
+    assert!(matches!(failures[0].1, Error::TranscodeError { .. }));
+
+    let mut visited = 0;
+    let err = silo.for_each_contents(|_, _| {
+        visited += 1;
+        Ok::<_, std::fmt::Error>(())
+    });
+    assert!(matches!(err, Err(IterError::Read(ref failures)) if failures.len() == 1));
+    assert!(visited < 3);
+
+    let embedded = embed_silo!("tests/data", force = true);
+    let alpha = embedded.read("alpha.txt").unwrap();
+    let err = embedded.for_each_contents(|file, contents| {
+        if file.path() == std::path::Path::new("alpha.txt") {
+            assert_eq!(contents, alpha.as_ref());
+            return Err("rejected");
+        }
+        Ok(())
+    });
+    assert!(
+        matches!(err, Err(IterError::Callback { ref path, error: "rejected" }) if path == "alpha.txt")
+    );
+    let mut total = 0;
+    embedded
+        .for_each_contents(|_, contents| {
+            total += contents.len() as u64;
+            Ok::<_, std::fmt::Error>(())
+        })
+        .unwrap();
+    assert_eq!(total, embedded.stats().unwrap().bytes);
+}
+
+embed_silo_enum!(
+    /// The files of `tests/data`.
+    DataFile,
+    "tests/data",
+    nested = "flatten"
+);
+
+/// Tests the enum generated for a directory: variants, paths, order and file lookup.
+#[test]
+fn test_embed_silo_enum() {
+    assert_eq!(
+        DataFile::ALL,
+        [
+            DataFile::Alpha,
+            DataFile::Beta,
+            DataFile::OverrideAlpha,
+            DataFile::SubdirGamma
+        ]
+    );
+    assert_eq!(DataFile::SubdirGamma.path(), "subdir/gamma.txt");
+    assert!(DataFile::Alpha < DataFile::SubdirGamma);
+    let paths: HashSet<_> = DataFile::ALL.iter().map(DataFile::path).collect();
+    assert_eq!(paths.len(), 4);
+
+    for silo in [
+        embed_silo!("tests/data", force = true),
+        Silo::new("tests/data"),
+    ] {
+        for variant in DataFile::ALL {
+            assert_eq!(
+                variant.file(&silo).path(),
+                std::path::Path::new(variant.path())
+            );
+        }
+        assert_eq!(
+            DataFile::Beta.file(&silo).contents().unwrap(),
+            silo.read("beta.txt").unwrap()
+        );
+    }
+}
+
+/// Splits a length-prefixed bundle into its files and missing paths.
+fn parse_length_prefixed(mut bytes: &[u8]) -> (Vec<(String, Vec<u8>)>, Vec<String>) {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize) -> &'a [u8] {
+        let (head, tail) = bytes.split_at(n);
+        *bytes = tail;
+        head
+    }
+    let u32_at =
+        |bytes: &mut &[u8]| u32::from_le_bytes(take(bytes, 4).try_into().unwrap()) as usize;
+    let mut files = Vec::new();
+    loop {
+        let len = u32_at(&mut bytes);
+        if len == 0 {
+            break;
+        }
+        let path = String::from_utf8(take(&mut bytes, len).to_vec()).unwrap();
+        let size = u64::from_le_bytes(take(&mut bytes, 8).try_into().unwrap()) as usize;
+        files.push((path, take(&mut bytes, size).to_vec()));
+    }
+    let missing = (0..u32_at(&mut bytes))
+        .map(|_| {
+            let len = u32_at(&mut bytes);
+            String::from_utf8(take(&mut bytes, len).to_vec()).unwrap()
+        })
+        .collect();
+    assert!(bytes.is_empty());
+    (files, missing)
+}
+
+/// Splits a multipart bundle into `(Content-Location, body)` parts, checking each
+/// `Content-Length`.
+fn parse_multipart(bytes: &[u8], content_type: &str) -> Vec<(String, Vec<u8>)> {
+    let boundary = format!("--{}", content_type.split_once("boundary=").unwrap().1);
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    let (parts, epilogue) = text.rsplit_once(&format!("{boundary}--\r\n")).unwrap();
+    assert!(epilogue.is_empty());
+    parts
+        .split(&boundary)
+        .skip(1)
+        .map(|part| {
+            let (head, body) = part
+                .strip_prefix("\r\n")
+                .unwrap()
+                .split_once("\r\n\r\n")
+                .unwrap();
+            let body = body.strip_suffix("\r\n").unwrap();
+            let header = |name: &str| {
+                head.lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .unwrap()
+                    .to_owned()
+            };
+            assert_eq!(
+                header("Content-Length: ").parse::<usize>().unwrap(),
+                body.len()
+            );
+            (header("Content-Location: "), body.as_bytes().to_vec())
+        })
+        .collect()
+}
+
+/// Tests that bundles stream the requested files and report missing paths in both formats.
+#[test]
+fn test_bundle_write() {
+    let silo = embed_silo!("tests/data", force = true);
+    let alpha = silo.read("alpha.txt").unwrap().to_vec();
+    let gamma = silo.read("subdir/gamma.txt").unwrap().to_vec();
+    let paths = ["alpha.txt", "nope.txt", "subdir/gamma.txt", "subdir"];
+
+    let mut out = Vec::new();
+    let report =
+        bundle::write(&mut out, &silo, paths, bundle::BundleFormat::LengthPrefixed).unwrap();
+    assert_eq!(report.files, 2);
+    assert_eq!(report.missing, ["nope.txt", "subdir"]);
+    assert_eq!(report.content_type, "application/octet-stream");
+    let (files, missing) = parse_length_prefixed(&out);
+    assert_eq!(
+        files,
+        [
+            ("alpha.txt".to_owned(), alpha.clone()),
+            ("subdir/gamma.txt".to_owned(), gamma.clone())
+        ]
+    );
+    assert_eq!(missing, report.missing);
+
+    let mut out = Vec::new();
+    let report = bundle::write(
+        &mut out,
+        &Silo::new("tests/data"),
+        paths,
+        bundle::BundleFormat::Multipart,
+    )
+    .unwrap();
+    let parts = parse_multipart(&out, &report.content_type);
+    assert_eq!(
+        parts,
+        [
+            ("alpha.txt".to_owned(), alpha),
+            ("subdir/gamma.txt".to_owned(), gamma),
+            ("missing".to_owned(), b"nope.txt\nsubdir\n".to_vec()),
+        ]
+    );
+
+    let mut out = Vec::new();
+    let report = bundle::write(
+        &mut out,
+        &silo,
+        Vec::<String>::new(),
+        bundle::BundleFormat::LengthPrefixed,
+    )
+    .unwrap();
+    assert_eq!((report.files, out), (0, vec![0; 8]));
+}
+
+/// Tests bundle requests from the query and a POSTed body, including rejected paths.
+#[test]
+fn test_serve_bundle() {
+    let silo = embed_silo!("tests/data", force = true);
+    let body = |response: HttpFileResponse| {
+        let mut bytes = Vec::new();
+        response.body.unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    };
+
+    let response = silo.serve_bundle(
+        "/bundle?path=alpha.txt&v=2&path=..%2FCargo.toml#top",
+        Some("subdir/gamma.txt\n\nbeta.txt\n"),
+        bundle::BundleFormat::Multipart,
+    );
+    assert_eq!(response.status, 200);
+    let content_type = response.header("Content-Type").unwrap().to_owned();
+    assert!(content_type.starts_with("multipart/mixed; boundary="));
+    assert!(response.header("Content-Length").is_none());
+    let locations: Vec<_> = parse_multipart(&body(response), &content_type)
+        .into_iter()
+        .map(|(location, body)| (location, String::from_utf8(body).unwrap()))
+        .collect();
+    let names: Vec<_> = locations
+        .iter()
+        .map(|(location, _)| location.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["alpha.txt", "subdir/gamma.txt", "beta.txt", "missing"]
+    );
+    assert_eq!(locations[3].1, "..%252FCargo.toml\n");
+
+    let response = silo.serve_bundle(
+        "/bundle?path=a%20b.txt&path=beta.txt",
+        None,
+        bundle::BundleFormat::LengthPrefixed,
+    );
+    let (files, missing) = parse_length_prefixed(&body(response));
+    assert_eq!(
+        files,
+        [(
+            "beta.txt".to_owned(),
+            silo.read("beta.txt").unwrap().to_vec()
+        )]
+    );
+    assert_eq!(missing, ["a%20b.txt"]);
+
+    assert_eq!(
+        silo.serve_bundle("/bundle", Some("\n"), bundle::BundleFormat::Multipart)
+            .status,
+        400
+    );
+    let many = "path=alpha.txt&".repeat(bundle::MAX_PATHS + 1);
+    assert_eq!(
+        silo.serve_bundle(
+            &format!("/bundle?{many}"),
+            None,
+            bundle::BundleFormat::Multipart
+        )
+        .status,
+        400
+    );
+}
+
+/// Tests lowercase key normalization on disk and embedded, including collision detection.
+#[test]
+fn test_key_normalization_lowercase() {
+    let dynamic = Silo::new("tests/keys").with_key_normalization(KeyNormalization::Lowercase);
+    let file = dynamic.get_file("notes/readme.md").unwrap();
+    assert_eq!(file.path(), std::path::Path::new("notes/readme.md"));
+    assert_eq!(file.contents().unwrap().as_ref(), b"notes");
+    assert!(dynamic.get_file("NOTES/Readme.MD").is_some());
+    assert!(
+        Silo::new("tests/keys")
+            .get_file("notes/readme.md")
+            .is_none()
+    );
+    assert!(
+        dynamic
+            .iter()
+            .any(|file| file.path() == std::path::Path::new("notes/readme.md"))
+    );
+
+    let embedded = embed_silo!("tests/keys", force = true, key_normalization = "lowercase");
+    assert_eq!(embedded.key_normalization(), KeyNormalization::Lowercase);
+    assert_eq!(
+        embedded.get_file("Notes/README.md").unwrap().path(),
+        std::path::Path::new("notes/readme.md")
+    );
+
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("Logo.png"), "a").unwrap();
+    std::fs::write(tmp.path().join("logo.PNG"), "b").unwrap();
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    let err = silo
+        .clone()
+        .try_with_key_normalization(KeyNormalization::Lowercase)
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::KeyCollision { ref key, ref paths } if key == "logo.png" && paths == &["Logo.png", "logo.PNG"])
+    );
+    assert!(
+        silo.clone()
+            .with_key_normalization(KeyNormalization::Lowercase)
+            .get_file("LOGO.png")
+            .is_none()
+    );
+    assert!(
+        silo.try_with_key_normalization(KeyNormalization::None)
+            .is_ok()
+    );
+}
+
+/// Tests that a file named in decomposed form (NFD, as macOS writes it) is found by its
+/// composed (NFC) name, on disk and embedded.
+#[cfg(feature = "unicode")]
+#[test]
+fn test_key_normalization_nfc() {
+    let composed = "caf\u{e9}.txt";
+    let decomposed = "cafe\u{301}.txt";
+    assert!(Silo::new("tests/keys").get_file(composed).is_none());
+
+    let dynamic = Silo::new("tests/keys")
+        .try_with_key_normalization(KeyNormalization::Nfc)
+        .unwrap();
+    for silo in [
+        dynamic,
+        embed_silo!("tests/keys", force = true, key_normalization = "nfc"),
+    ] {
+        for path in [composed, decomposed] {
+            let file = silo.get_file(path).unwrap();
+            assert_eq!(file.path(), std::path::Path::new(composed));
+            assert_eq!(file.contents().unwrap().as_ref(), b"decomposed");
+        }
+        let paths: Vec<_> = silo
+            .iter()
+            .map(|file| file.path().to_string_lossy().into_owned())
+            .collect();
+        assert!(paths.contains(&composed.to_owned()) && !paths.contains(&decomposed.to_owned()));
+    }
+    let both = embed_silo!(
+        "tests/keys",
+        force = true,
+        key_normalization = "nfc_lowercase"
+    );
+    assert_eq!(
+        both.get_file("CAF\u{c9}.txt").unwrap().path(),
+        std::path::Path::new(composed)
+    );
+}
+
+/// Tests compacting a set into one silo: winners, lazy versus preloaded reads, and staleness.
+#[test]
+fn test_silo_set_compact() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "overlay").unwrap();
+    std::fs::write(tmp.path().join("extra.txt"), "extra").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+
+    let lazy = set.compact().unwrap();
+    let preloaded = set.compact_with(CompactOptions { preload: true }).unwrap();
+    assert_eq!(lazy.compacted_from(), Some(set.version_token()));
+    assert!(set.silos[0].compacted_from().is_none());
+    let mut expected: Vec<_> = set
+        .iter_override()
+        .map(|file| file.path().to_path_buf())
+        .collect();
+    expected.sort();
+    for silo in [&lazy, &preloaded] {
+        let paths: Vec<_> = silo.iter().map(|file| file.path().to_path_buf()).collect();
+        assert_eq!(paths, expected);
+        assert_eq!(silo.read("alpha.txt").unwrap().as_ref(), b"overlay");
+        assert_eq!(
+            silo.read("subdir/gamma.txt").unwrap(),
+            set.silos[0].read("subdir/gamma.txt").unwrap()
+        );
+        assert!(silo.get_file("beta.txt").unwrap().absolute_path().is_none());
+        assert_eq!(
+            entry_names(silo.read_dir("")),
+            ["alpha.txt", "beta.txt", "extra.txt", "override/", "subdir/"]
+        );
+    }
+    assert!(
+        lazy.get_file("alpha.txt")
+            .unwrap()
+            .absolute_path()
+            .is_some()
+    );
+    assert!(
+        preloaded
+            .get_file("alpha.txt")
+            .unwrap()
+            .absolute_path()
+            .is_none()
+    );
+
+    std::fs::write(tmp.path().join("alpha.txt"), "rewritten").unwrap();
+    std::fs::write(tmp.path().join("later.txt"), "later").unwrap();
+    assert_eq!(lazy.read("alpha.txt").unwrap().as_ref(), b"rewritten");
+    assert_eq!(preloaded.read("alpha.txt").unwrap().as_ref(), b"overlay");
+    assert!(lazy.get_file("later.txt").is_none());
+    assert!(set.changed_since(&lazy.compacted_from().unwrap()));
+}
+
+/// A cascade skips missing directories and resolves each path from its highest layer.
+#[test]
+fn test_config_cascade_layers() {
+    let user = tempfile::tempdir().unwrap();
+    let defaults = tempfile::tempdir().unwrap();
+    std::fs::write(user.path().join("config.toml"), "from = \"user\"\n").unwrap();
+    std::fs::write(defaults.path().join("config.toml"), "from = \"defaults\"\n").unwrap();
+    std::fs::write(defaults.path().join("only-defaults.toml"), "").unwrap();
+
+    let cascade = cascade::ConfigCascade::new()
+        .dir_opt(Some(user.path()))
+        .dir(user.path().join("missing"))
+        .dir_opt(None::<&str>)
+        .xdg("rust-silos-test-app-that-does-not-exist")
+        .embedded(Silo::new(defaults.path().to_str().unwrap()));
+    assert_eq!(
+        cascade.first("config.toml").unwrap().text().unwrap(),
+        "from = \"user\"\n"
+    );
+    assert!(cascade.first("only-defaults.toml").is_some());
+    assert!(cascade.first("absent.toml").is_none());
+
+    let set = cascade.build();
+    assert_eq!(
+        set.get_file("config.toml").unwrap().text().unwrap(),
+        "from = \"user\"\n"
+    );
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.iter_override().count(), 2);
+    assert!(cascade::ConfigCascade::new().first("config.toml").is_none());
+}
+
+/// The per-user config directory follows XDG on Unix and the native conventions on macOS
+/// and Windows.
+#[test]
+fn test_config_dir_platforms() {
+    use cascade::Platform;
+    use std::path::PathBuf;
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.into())
+        }
+    };
+
+    assert_eq!(
+        Platform::Unix.config_dir(env(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/home/me")])),
+        Some(PathBuf::from("/xdg"))
+    );
+    assert_eq!(
+        Platform::Unix.config_dir(env(&[
+            ("XDG_CONFIG_HOME", "relative"),
+            ("HOME", "/home/me")
+        ])),
+        Some(PathBuf::from("/home/me/.config"))
+    );
+    assert_eq!(
+        Platform::Unix.config_dir(env(&[("XDG_CONFIG_HOME", ""), ("HOME", "/home/me")])),
+        Some(PathBuf::from("/home/me/.config"))
+    );
+    assert_eq!(Platform::Unix.config_dir(env(&[])), None);
+    assert_eq!(
+        Platform::MacOs.config_dir(env(&[("XDG_CONFIG_HOME", "/xdg"), ("HOME", "/Users/me")])),
+        Some(
+            PathBuf::from("/Users/me")
+                .join("Library")
+                .join("Application Support")
+        )
+    );
+    assert_eq!(Platform::MacOs.config_dir(env(&[])), None);
+    assert_eq!(
+        Platform::Windows.config_dir(env(&[
+            ("APPDATA", r"C:\Users\me\AppData\Roaming"),
+            ("USERPROFILE", r"C:\Users\other")
+        ])),
+        Some(PathBuf::from(r"C:\Users\me\AppData\Roaming"))
+    );
+    assert_eq!(
+        Platform::Windows.config_dir(env(&[("USERPROFILE", r"C:\Users\me")])),
+        Some(
+            PathBuf::from(r"C:\Users\me")
+                .join("AppData")
+                .join("Roaming")
+        )
+    );
+    assert_eq!(
+        Platform::Windows.config_dir(env(&[("HOME", "/home/me")])),
+        None
+    );
+}
+
+/// `merged_toml` merges tables key by key from the lowest layer up, while scalars, arrays
+/// and mismatched kinds take the higher layer's value.
+#[cfg(feature = "toml")]
+#[test]
+fn test_config_cascade_merged_toml() {
+    let layers: Vec<_> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+    let [user, xdg, defaults] = [0, 1, 2].map(|i| layers[i].path());
+    std::fs::write(
+        defaults.join("config.toml"),
+        r#"
+name = "defaults"
+ports = [80, 443]
+[server]
+host = "localhost"
+timeout = 30
+[server.tls]
+enabled = false
+cert = "default.pem"
+[logging]
+level = "info"
+[features]
+beta = true
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        xdg.join("config.toml"),
+        r#"
+ports = [8080]
+[server]
+timeout = 60
+[server.tls]
+enabled = true
+[logging]
+file = "app.log"
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        user.join("config.toml"),
+        r#"
+name = "user"
+features = "none"
+[server.tls]
+cert = "mine.pem"
+[extra]
+key = 1
+"#,
+    )
+    .unwrap();
+    let cascade = cascade::ConfigCascade::new()
+        .dir(user)
+        .dir(xdg)
+        .embedded(Silo::new(defaults.to_str().unwrap()));
+
+    let merged: toml::Table = cascade.merged_toml("config.toml").unwrap().unwrap();
+    let expected: toml::Table = toml::from_str(
+        r#"
+name = "user"
+ports = [8080]
+features = "none"
+[server]
+host = "localhost"
+timeout = 60
+[server.tls]
+enabled = true
+cert = "mine.pem"
+[logging]
+level = "info"
+file = "app.log"
+[extra]
+key = 1
+"#,
+    )
+    .unwrap();
+    assert_eq!(merged, expected);
+
+    std::fs::write(xdg.join("config.toml"), "[server]\ntimeout = \"soon\"\n").unwrap();
+    #[derive(Debug, ::serde::Deserialize)]
+    struct Server {
+        timeout: u32,
+    }
+    #[derive(Debug, ::serde::Deserialize)]
+    struct Config {
+        server: Server,
+    }
+    assert!(matches!(
+        cascade.merged_toml::<Config>("config.toml"),
+        Err(Error::TomlError { .. })
+    ));
+    std::fs::remove_file(xdg.join("config.toml")).unwrap();
+    assert_eq!(
+        cascade
+            .merged_toml::<Config>("config.toml")
+            .unwrap()
+            .unwrap()
+            .server
+            .timeout,
+        30
+    );
+
+    std::fs::write(user.join("config.toml"), "name = ").unwrap();
+    assert!(matches!(
+        cascade.merged_toml::<toml::Table>("config.toml"),
+        Err(Error::TomlError { .. })
+    ));
+    assert!(
+        cascade
+            .merged_toml::<toml::Table>("absent.toml")
+            .unwrap()
+            .is_none()
+    );
+}
+
+/// Embedded and dynamic files report their Unix mode, and `extract_to` keeps the
+/// executable bit and reports the digest of what it wrote.
+#[cfg(unix)]
+#[test]
+fn test_unix_mode_and_extract() {
+    use std::os::unix::fs::PermissionsExt;
+    let embedded = embed_silo!("tests/modes", force = true);
+    let dynamic = Silo::new("tests/modes");
+    for silo in [&embedded, &dynamic] {
+        assert_eq!(
+            silo.get_file("run.sh").unwrap().unix_mode().unwrap() & 0o111,
+            0o111
+        );
+        assert_eq!(
+            silo.get_file("data.txt").unwrap().unix_mode().unwrap() & 0o111,
+            0
+        );
+    }
+
+    let out = tempfile::tempdir().unwrap();
+    let script = out.path().join("run.sh");
+    let run = embedded.get_file("run.sh").unwrap();
+    assert_eq!(
+        run.extract_to(&script, HashAlg::Fnv64).unwrap(),
+        ExtractReport {
+            path: "run.sh".into(),
+            bytes: 22,
+            digest: Digest::Fnv64(run.content_hash().unwrap()),
+        }
+    );
+    assert_eq!(
+        std::fs::read_to_string(&script).unwrap(),
+        "#!/bin/sh\necho helper\n"
+    );
+    assert_eq!(
+        std::fs::metadata(&script).unwrap().permissions().mode() & 0o111,
+        0o111
+    );
+    let data = out.path().join("data.txt");
+    dynamic
+        .get_file("data.txt")
+        .unwrap()
+        .extract_to(&data, HashAlg::Fnv64)
+        .unwrap();
+    assert_eq!(
+        std::fs::metadata(&data).unwrap().permissions().mode() & 0o111,
+        0
+    );
+
+    let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let mut reports = embedded.extract_to(a.path(), HashAlg::Fnv64).unwrap();
+    let mut dynamic_reports = dynamic.extract_to(b.path(), HashAlg::Fnv64).unwrap();
+    reports.sort_by(|x, y| x.path.cmp(&y.path));
+    dynamic_reports.sort_by(|x, y| x.path.cmp(&y.path));
+    assert_eq!(reports.len(), embedded.iter().count());
+    assert_eq!(reports, dynamic_reports);
+    for report in &reports {
+        let written = std::fs::read(a.path().join(&report.path)).unwrap();
+        assert_eq!(report.bytes, written.len() as u64);
+        assert_eq!(report.digest, Digest::Fnv64(ContentHasher::hash(&written)));
+    }
+    assert_eq!(
+        std::fs::metadata(b.path().join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o111,
+        0o111
+    );
+}
+
+/// `top_by_size` keeps the largest files (ties by path) and `modified_since` filters by
+/// mtime, on a silo and on a set's override view.
+#[test]
+fn test_top_by_size_and_modified_since() {
+    use std::time::{Duration, SystemTime};
+    let tmp = tempfile::tempdir().unwrap();
+    let now = SystemTime::now();
+    // File i is 3 * (i + 1) bytes, except that f09 ties with f10 at 33; it is i * 10 minutes old.
+    for i in 0..20u64 {
+        let path = tmp.path().join(format!("f{i:02}.bin"));
+        let size = if i == 9 { 33 } else { 3 * i as usize + 3 };
+        std::fs::write(&path, vec![b'x'; size]).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(now - Duration::from_secs(i * 600))
+            .unwrap();
+    }
+    let silo = Silo::new(tmp.path().to_str().unwrap());
+    let names = |files: Vec<File>| {
+        files
+            .iter()
+            .map(|file| file.path().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(silo.top_by_size(4)),
+        ["f19.bin", "f18.bin", "f17.bin", "f16.bin"]
+    );
+    assert_eq!(
+        names(silo.top_by_size(12))[9..],
+        ["f09.bin", "f10.bin", "f08.bin"]
+    );
+    assert_eq!(silo.top_by_size(100).len(), 20);
+    assert_eq!(
+        names(silo.top_by_size(usize::MAX)),
+        names(silo.top_by_size(20))
+    );
+    assert!(silo.top_by_size(0).is_empty());
+
+    let mut recent = names(
+        silo.modified_since(now - Duration::from_secs(3600) + Duration::from_secs(1))
+            .collect(),
+    );
+    recent.sort();
+    assert_eq!(
+        recent,
+        [
+            "f00.bin", "f01.bin", "f02.bin", "f03.bin", "f04.bin", "f05.bin"
+        ]
+    );
+    assert_eq!(
+        silo.modified_since(now + Duration::from_secs(60)).count(),
+        0
+    );
+
+    let upper = tempfile::tempdir().unwrap();
+    std::fs::write(upper.path().join("f19.bin"), "small").unwrap();
+    let set = SiloSet::new(vec![
+        silo.clone(),
+        Silo::new(upper.path().to_str().unwrap()),
+    ]);
+    assert_eq!(names(set.top_by_size(2)), ["f18.bin", "f17.bin"]);
+    let mut recent = names(
+        set.modified_since(now - Duration::from_secs(1500))
+            .collect(),
+    );
+    recent.sort();
+    assert_eq!(recent, ["f00.bin", "f01.bin", "f02.bin", "f19.bin"]);
+
+    let embedded = embed_silo!("tests/data", force = true);
+    assert_eq!(names(embedded.top_by_size(1)), ["override/alpha.txt"]);
+    assert_eq!(embedded.modified_since(SystemTime::UNIX_EPOCH).count(), 4);
+    assert_eq!(
+        embedded
+            .modified_since(now + Duration::from_secs(60))
+            .count(),
+        0
+    );
+}
+
+/// Lookups, URL decoding, range parsing and path globs never panic on hostile input, and a
+/// glob full of stars stays fast.
+#[test]
+fn test_untrusted_lookup_input_never_panics() {
+    const ALPHABET: &[&str] = &[
+        "a",
+        "/",
+        "\\",
+        ".",
+        "..",
+        "%",
+        "%2",
+        "%2e",
+        "%2F",
+        "%c3%a9",
+        "%ff",
+        "%+f",
+        "\0",
+        "?",
+        "#",
+        "*",
+        "**",
+        "é",
+        "\u{301}",
+        "𝄞",
+        "C:",
+        "//",
+        " ",
+        "alpha.txt",
+        "subdir",
+        "bytes=",
+        "-",
+        ",",
+        "9999999999999999999999",
+    ];
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join("subdir")).unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "alpha").unwrap();
+    std::fs::write(tmp.path().join("subdir/é.txt"), "e").unwrap();
+    let silos = [
+        embed_silo!("tests/data", force = true),
+        embed_silo!("tests/data", force = true)
+            .scope("subdir")
+            .with_key_normalization(KeyNormalization::Lowercase),
+        Silo::new(tmp.path().to_str().unwrap()).with_index("alpha.txt"),
+        Silo::new(tmp.path().to_str().unwrap()).with_key_normalization(KeyNormalization::Lowercase),
+    ];
+    // xorshift64, so every run sees the same inputs.
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..2000 {
+        let len = next() % 8;
+        let input: String = (0..len)
+            .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize])
+            .collect();
+        let mut headers = RequestHeaders::new();
+        headers.range = Some(input.clone());
+        for silo in &silos {
+            let _ = silo.get_file(&input);
+            let _ = silo.get_file_url(&input);
+            let _ = silo.serve(&input, &headers);
+            let _ = silo.serve("/alpha.txt", &headers);
+            let glob = SearchOptions {
+                path_glob: Some(input.clone()),
+                ..SearchOptions::default()
+            };
+            let _ = silo.find_bytes(b"a", glob).count();
+        }
+    }
+    assert!(silos[0].get_file_url("/%+f").is_none());
+
+    let started = std::time::Instant::now();
+    let stars = "*a".repeat(30) + "b";
+    let glob = SearchOptions {
+        path_glob: Some(format!("{stars}/{stars}")),
+        ..SearchOptions::default()
+    };
+    let deep = tempfile::tempdir().unwrap();
+    std::fs::create_dir(deep.path().join("a".repeat(60))).unwrap();
+    std::fs::write(deep.path().join("a".repeat(60)).join("a".repeat(60)), "a").unwrap();
+    assert_eq!(
+        Silo::new(deep.path().to_str().unwrap())
+            .find_bytes(b"a", glob)
+            .count(),
+        0
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
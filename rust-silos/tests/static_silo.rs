@@ -0,0 +1,21 @@
+//! `static_embed_silo!` statics carry their `doc` option as documentation.
+//!
+//! Lives in its own test binary so `missing_docs` can be denied for the whole crate: an
+//! undocumented generated static fails to compile.
+#![deny(missing_docs)]
+
+use rust_silos::*;
+
+static_embed_silo!(pub ASSETS, "tests/data", force = true, doc = "Embedded test assets.");
+static_embed_silo!(PRIVATE, "tests/data/subdir");
+
+/// The generated statics are regular embedded silos.
+#[test]
+fn test_static_embed_silo() {
+    assert!(ASSETS.is_embedded());
+    assert_eq!(
+        ASSETS.get_file("alpha.txt").unwrap().text().unwrap(),
+        "alpha file content\n"
+    );
+    assert!(PRIVATE.iter().count() > 0);
+}
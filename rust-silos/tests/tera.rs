@@ -0,0 +1,177 @@
+#![cfg(feature = "tera")]
+
+use rust_silos::tera::{Reloader, load_templates};
+use rust_silos::*;
+use std::time::{Duration, SystemTime};
+
+fn touch(path: &std::path::Path, contents: &str) {
+    std::fs::write(path, contents).unwrap();
+    // Move the mtime forward explicitly so coarse filesystem timestamps can't hide the change.
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(SystemTime::now() + Duration::from_secs(5))
+        .unwrap();
+}
+
+/// Tests that load_templates registers the override view by relative path.
+#[test]
+fn test_tera_load_templates() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::write(tmp.path().join("alpha.txt"), "overlay {{ name }}").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let mut tera = ::tera::Tera::default();
+    load_templates(&mut tera, &set).unwrap();
+    let mut ctx = ::tera::Context::new();
+    ctx.insert("name", "x");
+    assert_eq!(tera.render("alpha.txt", &ctx).unwrap(), "overlay x");
+    assert!(
+        tera.render("subdir/gamma.txt", &ctx)
+            .unwrap()
+            .contains("gamma file content")
+    );
+}
+
+/// Tests that load_templates also accepts a single silo.
+#[test]
+fn test_tera_load_templates_from_silo() {
+    let silo = embed_silo!("tests/data", force = true);
+    let mut tera = ::tera::Tera::default();
+    load_templates(&mut tera, &silo).unwrap();
+    assert!(
+        tera.render("subdir/gamma.txt", &::tera::Context::new())
+            .unwrap()
+            .contains("gamma file content")
+    );
+}
+
+/// Tests that the reloader only re-registers changed dynamic templates.
+#[test]
+fn test_tera_reloader_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    touch(&tmp.path().join("a.html"), "a1");
+    touch(&tmp.path().join("b.html"), "b1");
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let mut tera = ::tera::Tera::default();
+    let mut reloader = Reloader::new(set);
+    assert!(reloader.needs_reload());
+
+    let report = reloader.reload(&mut tera).unwrap();
+    assert_eq!(report.added.len(), 6);
+    assert!(!reloader.needs_reload());
+    assert!(reloader.reload(&mut tera).unwrap().is_empty());
+
+    touch(&tmp.path().join("b.html"), "b2");
+    assert!(reloader.needs_reload());
+    let report = reloader.reload(&mut tera).unwrap();
+    assert_eq!(report.changed, vec!["b.html".to_owned()]);
+    assert!(report.added.is_empty() && report.removed.is_empty());
+    assert_eq!(
+        tera.render("b.html", &::tera::Context::new()).unwrap(),
+        "b2"
+    );
+
+    std::fs::remove_file(tmp.path().join("a.html")).unwrap();
+    std::fs::create_dir(tmp.path().join("sub")).unwrap();
+    touch(&tmp.path().join("sub/c.html"), "c1");
+    assert!(reloader.needs_reload());
+    let report = reloader.reload(&mut tera).unwrap();
+    assert_eq!(report.added, vec!["sub/c.html".to_owned()]);
+    assert_eq!(report.removed, vec!["a.html".to_owned()]);
+    assert!(tera.get_template("a.html").is_err());
+}
+
+/// Tests that shadowing an embedded template with an overlay file is reported as a change.
+#[test]
+fn test_tera_reloader_overlay_shadows_embedded() {
+    let tmp = tempfile::tempdir().unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let mut tera = ::tera::Tera::default();
+    let mut reloader = Reloader::new(set);
+    reloader.reload(&mut tera).unwrap();
+
+    touch(&tmp.path().join("beta.txt"), "shadowed");
+    assert!(reloader.needs_reload());
+    let report = reloader.reload(&mut tera).unwrap();
+    assert_eq!(report.changed, vec!["beta.txt".to_owned()]);
+    assert_eq!(
+        tera.render("beta.txt", &::tera::Context::new()).unwrap(),
+        "shadowed"
+    );
+}
+
+/// Tests that load_async registers matching templates on a current-thread runtime, where
+/// `block_in_place` would panic.
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tera_load_async() {
+    let tmp = tempfile::tempdir().unwrap();
+    for i in 0..40 {
+        std::fs::write(
+            tmp.path().join(format!("page{i}.html")),
+            format!("page {i} {{{{ name }}}}"),
+        )
+        .unwrap();
+    }
+    std::fs::write(tmp.path().join("notes.txt"), "not a template").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let mut tera = ::tera::Tera::default();
+    rust_silos::tera::load_async(&mut tera, &set, "**/*.html")
+        .await
+        .unwrap();
+    assert_eq!(tera.get_template_names().count(), 40);
+    let mut ctx = ::tera::Context::new();
+    ctx.insert("name", "x");
+    assert_eq!(tera.render("page37.html", &ctx).unwrap(), "page 37 x");
+    assert!(tera.get_template("notes.txt").is_err());
+
+    rust_silos::tera::load_async(&mut tera, &set, "subdir/*")
+        .await
+        .unwrap();
+    assert!(
+        tera.render("subdir/gamma.txt", &ctx)
+            .unwrap()
+            .contains("gamma file content")
+    );
+}
+
+/// Tests that reload_async reports the same changes as reload, on a current-thread runtime.
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tera_reload_async() {
+    let tmp = tempfile::tempdir().unwrap();
+    touch(&tmp.path().join("a.html"), "a1");
+    touch(&tmp.path().join("b.html"), "b1");
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/data", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let mut tera = ::tera::Tera::default();
+    let mut reloader = Reloader::new(set);
+    assert_eq!(
+        reloader.reload_async(&mut tera).await.unwrap().added.len(),
+        6
+    );
+    assert!(reloader.reload_async(&mut tera).await.unwrap().is_empty());
+
+    touch(&tmp.path().join("b.html"), "b2");
+    std::fs::remove_file(tmp.path().join("a.html")).unwrap();
+    let report = reloader.reload_async(&mut tera).await.unwrap();
+    assert_eq!(report.changed, vec!["b.html".to_owned()]);
+    assert_eq!(report.removed, vec!["a.html".to_owned()]);
+    assert_eq!(
+        tera.render("b.html", &::tera::Context::new()).unwrap(),
+        "b2"
+    );
+    assert!(tera.get_template("a.html").is_err());
+}
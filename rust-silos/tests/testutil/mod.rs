@@ -0,0 +1,169 @@
+//! Hostile lookup inputs for path-handling tests, each with the outcome expected on the
+//! platform the tests run on. Inputs are plain strings, so every case is representable (and
+//! exercised) everywhere; where platforms must differ, the difference is in [`Expect`].
+
+/// What looking a path up in a silo over `tests/data` must produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expect {
+    /// Never resolves, on any backend.
+    Rejected,
+    /// Resolves to the file with this relative path.
+    Resolves(&'static str),
+}
+
+/// A lookup input with its expected outcome and the reason for it.
+#[derive(Debug, Clone)]
+pub struct PathCase {
+    pub input: String,
+    pub expect: Expect,
+    pub why: &'static str,
+}
+
+fn case(input: &str, expect: Expect, why: &'static str) -> PathCase {
+    PathCase {
+        input: input.to_owned(),
+        expect,
+        why,
+    }
+}
+
+/// `..` escapes with either separator: rejected everywhere.
+pub fn traversal_cases() -> Vec<PathCase> {
+    let why = "`..` may not climb out of the root, whichever separator spells it";
+    [
+        "../Cargo.toml",
+        "..\\Cargo.toml",
+        "subdir\\..\\..\\Cargo.toml",
+        "subdir/..\\../Cargo.toml",
+    ]
+    .into_iter()
+    .map(|input| case(input, Expect::Rejected, why))
+    .collect()
+}
+
+/// Drive-qualified paths: absolute (or drive-relative) on Windows, so rejected there. On
+/// Unix they merely name a directory or file called `C:`..., which `tests/data` does not
+/// have; see [`drive_name_cases`] for such names that exist.
+pub fn drive_cases() -> Vec<PathCase> {
+    let why = if cfg!(windows) {
+        "drive prefixes are rejected on Windows"
+    } else {
+        "drive-like names are ordinary outside Windows, and missing from tests/data"
+    };
+    ["C:\\evil", "c:/Windows/win.ini", "C:alpha.txt", "z:"]
+        .into_iter()
+        .map(|input| case(input, Expect::Rejected, why))
+        .collect()
+}
+
+/// Files whose names start like a drive, for a directory prepared with
+/// [`create_drive_name_files`]. Windows rejects them (and cannot create them); elsewhere
+/// they are ordinary files, found by the same path `iter()` lists them under.
+pub fn drive_name_cases() -> Vec<PathCase> {
+    DRIVE_NAME_FILES
+        .iter()
+        .map(|&name| {
+            if cfg!(windows) {
+                case(
+                    name,
+                    Expect::Rejected,
+                    "drive paths are rejected on Windows",
+                )
+            } else {
+                case(
+                    name,
+                    Expect::Resolves(name),
+                    "drive-like names are ordinary files outside Windows",
+                )
+            }
+        })
+        .collect()
+}
+
+/// Files named like drive paths, relative and nested.
+pub const DRIVE_NAME_FILES: [&str; 3] = ["a:b.txt", "c:notes", "z:/inner.txt"];
+
+/// Creates [`DRIVE_NAME_FILES`] in `dir` where the platform allows it (not on Windows).
+pub fn create_drive_name_files(dir: &std::path::Path) {
+    if cfg!(windows) {
+        return;
+    }
+    for name in DRIVE_NAME_FILES {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, name).unwrap();
+    }
+}
+
+/// UNC, device-namespace and rooted paths: absolute on Windows (and rooted ones on Unix).
+pub fn unc_cases() -> Vec<PathCase> {
+    let why = "UNC, device and rooted paths never resolve";
+    [
+        "\\\\server\\share\\alpha.txt",
+        "//server/share/alpha.txt",
+        "\\\\?\\C:\\evil",
+        "\\\\.\\pipe\\silo",
+        "/alpha.txt",
+        "\\alpha.txt",
+    ]
+    .into_iter()
+    .map(|input| case(input, Expect::Rejected, why))
+    .collect()
+}
+
+/// Backslashes are accepted as separators on every platform and backend.
+pub fn separator_cases() -> Vec<PathCase> {
+    let why = "`\\` is a separator everywhere, and reported paths always use `/`";
+    vec![case(
+        "subdir\\gamma.txt",
+        Expect::Resolves("subdir/gamma.txt"),
+        why,
+    )]
+}
+
+/// Windows device names, for a directory prepared with [`create_reserved_name_files`].
+/// Windows rejects them (they would open the device); elsewhere they are ordinary files.
+pub fn reserved_name_cases() -> Vec<PathCase> {
+    RESERVED_NAME_FILES
+        .iter()
+        .map(|&name| {
+            if cfg!(windows) {
+                case(
+                    name,
+                    Expect::Rejected,
+                    "reserved device names are rejected on Windows",
+                )
+            } else {
+                case(
+                    name,
+                    Expect::Resolves(name),
+                    "device names are ordinary files outside Windows",
+                )
+            }
+        })
+        .collect()
+}
+
+/// Files named like Windows devices, in varying case and with extensions.
+pub const RESERVED_NAME_FILES: [&str; 4] = ["CON", "nul.txt", "Com1.log", "aux"];
+
+/// Creates [`RESERVED_NAME_FILES`] in `dir` where the platform allows it (not on Windows).
+pub fn create_reserved_name_files(dir: &std::path::Path) {
+    if cfg!(windows) {
+        return;
+    }
+    for name in RESERVED_NAME_FILES {
+        std::fs::write(dir.join(name), name).unwrap();
+    }
+}
+
+/// Every case that applies to a silo over `tests/data`.
+pub fn data_cases() -> Vec<PathCase> {
+    [
+        traversal_cases(),
+        drive_cases(),
+        unc_cases(),
+        separator_cases(),
+    ]
+    .concat()
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let assets = rust_silos::embed_silo!("assets", force = true, allow_over_budget = true);
+    assert!(assets.get_file("page.txt").is_some());
+}
@@ -0,0 +1,8 @@
+fn main() {
+    let assets = rust_silos::embed_silo!(
+        "assets",
+        force = true,
+        emit_expansion = "target/audit/assets.rs"
+    );
+    assert!(assets.get_file("page.txt").is_some());
+}
@@ -0,0 +1,3 @@
+rust_silos::embed_silo_enum!(Icon, "empty");
+
+fn main() {}
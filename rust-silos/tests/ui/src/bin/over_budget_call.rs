@@ -0,0 +1,3 @@
+fn main() {
+    let _assets = rust_silos::embed_silo!("assets", force = true, max_total_size = "10B", allow_over_budget = true);
+}
@@ -0,0 +1,3 @@
+fn main() {
+    let _assets = rust_silos::embed_silo!("assets", force = true);
+}
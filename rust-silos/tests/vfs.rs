@@ -0,0 +1,91 @@
+#![cfg(feature = "vfs")]
+//! Runs the `vfs` crate's read-only conformance suite against `SiloFS`, over the fixture
+//! layout that suite expects (`tests/vfs`), for each backend.
+
+use ::vfs::error::VfsErrorKind;
+use ::vfs::{FileSystem, VfsPath};
+use rust_silos::vfs::SiloFS;
+use rust_silos::*;
+use std::io::Read;
+
+mod embedded {
+    use super::*;
+
+    ::vfs::test_vfs_readonly!(SiloFS(SiloSet::new(vec![embed_silo!(
+        "tests/vfs",
+        force = true
+    )])));
+}
+
+mod dynamic {
+    use super::*;
+
+    ::vfs::test_vfs_readonly!(SiloFS(SiloSet::new(vec![
+        embed_silo!("tests/vfs").into_dynamic().unwrap()
+    ])));
+}
+
+mod layered {
+    use super::*;
+
+    // The lower layer is shadowed for a.txt and contributes no other paths.
+    ::vfs::test_vfs_readonly!({
+        let base = embed_silo!("tests/vfs", force = true)
+            .filtered(|file| file.path().to_string_lossy() == "a.txt")
+            .transform(|_| Some(b"shadowed".to_vec()))
+            .unwrap();
+        SiloFS(SiloSet::new(vec![
+            base,
+            embed_silo!("tests/vfs", force = true),
+        ]))
+    });
+}
+
+/// Tests that every write operation is rejected as unsupported.
+#[test]
+fn test_vfs_writes_not_supported() {
+    let fs = SiloFS(SiloSet::new(vec![embed_silo!("tests/vfs", force = true)]));
+    let unsupported = |result: ::vfs::VfsResult<()>| {
+        assert!(matches!(
+            result.unwrap_err().kind(),
+            VfsErrorKind::NotSupported
+        ));
+    };
+    unsupported(fs.create_dir("/new"));
+    unsupported(fs.create_file("/new.txt").map(|_| ()));
+    unsupported(fs.append_file("/a.txt").map(|_| ()));
+    unsupported(fs.remove_file("/a.txt"));
+    unsupported(fs.remove_dir("/a"));
+    let mut contents = String::new();
+    fs.open_file("/a.txt")
+        .unwrap()
+        .read_to_string(&mut contents)
+        .unwrap();
+    assert_eq!(contents, "a");
+}
+
+/// Tests that files from every layer appear in a merged directory listing.
+#[test]
+fn test_vfs_merges_layers() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(tmp.path().join("a")).unwrap();
+    std::fs::write(tmp.path().join("a/extra.txt"), "extra").unwrap();
+    let set = SiloSet::new(vec![
+        embed_silo!("tests/vfs", force = true),
+        Silo::new(tmp.path().to_str().unwrap()),
+    ]);
+    let root: VfsPath = SiloFS(set).into();
+    let mut names: Vec<String> = root
+        .join("a")
+        .unwrap()
+        .read_dir()
+        .unwrap()
+        .map(|path| path.filename())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["d.txt", "extra.txt", "x"]);
+    assert_eq!(
+        root.join("a/extra.txt").unwrap().read_to_string().unwrap(),
+        "extra"
+    );
+}